@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use azure_core::http::policies::{Policy, PolicyResult};
+use azure_core::http::{Context, Request};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber, info};
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::Context as LayerContext;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Target of the events reporting finished calls.
+const TARGET: &str = "akv::api";
+
+/// Calls kept for the overlay.
+const KEEP: usize = 100;
+
+/// A finished ARM or Key Vault call, for the API overlay (`F12`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ApiCall {
+    pub at: Option<Instant>, // when it finished
+    pub method: String,
+    pub host: String,
+    pub path: String, // without the query, which only holds paging and the API version
+    pub status: Option<u16>, // None = no response (timeout, connection error)
+    pub duration: Duration, // all tries, with the waits between them
+    pub retries: u32,
+}
+
+static CALLS: Mutex<VecDeque<ApiCall>> = Mutex::new(VecDeque::new());
+
+fn calls() -> MutexGuard<'static, VecDeque<ApiCall>> {
+    CALLS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The last calls, newest first.
+pub fn recent() -> Vec<ApiCall> {
+    calls().iter().rev().cloned().collect()
+}
+
+/// Report a finished call as a tracing event; the `layer` keeps it for the overlay, and the
+/// debug log has it too.
+pub fn record(method: &str, url: &str, status: Option<u16>, started: Instant, tries: u32) {
+    let (host, path) = match reqwest::Url::parse(url) {
+        Ok(url) => (
+            url.host_str().unwrap_or_default().to_string(),
+            url.path().to_string(),
+        ),
+        Err(_) => (String::new(), url.to_string()),
+    };
+    info!(
+        target: TARGET,
+        method,
+        host = host.as_str(),
+        path = path.as_str(),
+        status,
+        duration_ms = started.elapsed().as_millis() as u64,
+        retries = tries.saturating_sub(1),
+        "API call"
+    );
+}
+
+/// Send `request` through its client, reporting the call.
+pub async fn send(request: reqwest::RequestBuilder) -> reqwest::Result<reqwest::Response> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    let (method, url) = (request.method().to_string(), request.url().to_string());
+    let started = Instant::now();
+    let result = client.execute(request).await;
+    let status = result.as_ref().ok().map(|r| r.status().as_u16());
+    record(&method, &url, status, started, 1);
+    result
+}
+
+/// Collects the calls reported by `record`.
+pub fn layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    ApiLayer.with_filter(Targets::new().with_target(TARGET, Level::INFO))
+}
+
+struct ApiLayer;
+
+impl<S: Subscriber> Layer<S> for ApiLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let mut call = ApiCall {
+            at: Some(Instant::now()),
+            ..Default::default()
+        };
+        event.record(&mut call);
+        let mut calls = calls();
+        if calls.len() == KEEP {
+            calls.pop_front();
+        }
+        calls.push_back(call);
+    }
+}
+
+impl Visit for ApiCall {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "method" => self.method = value.to_string(),
+            "host" => self.host = value.to_string(),
+            "path" => self.path = value.to_string(),
+            _ => {}
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            "status" => self.status = u16::try_from(value).ok(),
+            "duration_ms" => self.duration = Duration::from_millis(value),
+            "retries" => self.retries = value as u32,
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+/// Requests sent for one SDK call so far, counted by `TryCountPolicy`.
+#[derive(Debug)]
+struct Tries(Arc<AtomicU32>);
+
+/// SDK pipeline policy reporting each call once its retries are over.
+#[derive(Debug)]
+pub struct ApiCallPolicy;
+
+#[async_trait]
+impl Policy for ApiCallPolicy {
+    async fn send(
+        &self,
+        ctx: &Context,
+        request: &mut Request,
+        next: &[Arc<dyn Policy>],
+    ) -> PolicyResult {
+        let tries = Arc::new(AtomicU32::new(0));
+        let ctx = ctx.clone().with_value(Tries(tries.clone()));
+        let (method, url) = (request.method().to_string(), request.url().to_string());
+        let started = Instant::now();
+        let result = next[0].send(&ctx, request, &next[1..]).await;
+        let status = match &result {
+            Ok(response) => Some(u16::from(response.status())),
+            Err(e) => e.http_status().map(u16::from),
+        };
+        record(
+            &method,
+            &url,
+            status,
+            started,
+            tries.load(Ordering::Relaxed),
+        );
+        result
+    }
+}
+
+/// SDK pipeline policy counting the requests of a call: its tries, and the authentication
+/// challenge of a vault's first request.
+#[derive(Debug)]
+pub struct TryCountPolicy;
+
+#[async_trait]
+impl Policy for TryCountPolicy {
+    async fn send(
+        &self,
+        ctx: &Context,
+        request: &mut Request,
+        next: &[Arc<dyn Policy>],
+    ) -> PolicyResult {
+        if let Some(Tries(tries)) = ctx.value::<Tries>() {
+            tries.fetch_add(1, Ordering::Relaxed);
+        }
+        next[0].send(ctx, request, &next[1..]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_vault::MockVault;
+    use crate::store::SecretStore;
+    use tracing_subscriber::Registry;
+    use tracing_subscriber::prelude::*;
+
+    #[tokio::test]
+    async fn test_calls_recorded() {
+        let vault = MockVault::start().await;
+        vault.insert("db-password", "s3cret");
+        let store = vault.store();
+        let subscriber = Registry::default().with(layer());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        store.get("db-password").await.unwrap();
+        vault.fail_next(503);
+        store.get("db-password").await.unwrap();
+        assert!(store.get("api-key").await.is_err());
+
+        let host = reqwest::Url::parse(&vault.uri()).unwrap();
+        let ours: Vec<ApiCall> = recent()
+            .into_iter()
+            .filter(|c| Some(c.host.as_str()) == host.host_str())
+            .collect();
+        assert_eq!(ours.len(), 3);
+        let [missing, retried, first] = &ours[..] else {
+            unreachable!()
+        };
+        assert_eq!(missing.status, Some(404));
+        assert_eq!(missing.path, "/secrets/api-key/");
+        // The 503 was retried
+        assert_eq!((retried.status, retried.retries), (Some(200), 1));
+        // The authentication challenge of the first request
+        assert_eq!(
+            (first.method.as_str(), first.status, first.retries),
+            ("GET", Some(200), 1)
+        );
+    }
+}
@@ -1,82 +1,795 @@
-use std::collections::HashMap;
-use std::convert::TryInto;
-use std::error::Error;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use azure_identity::DeveloperToolsCredential;
-use azure_security_keyvault_secrets::{SecretClient, models::SetSecretParameters};
-use crossterm::event::KeyCode;
+use azure_core::credentials::TokenCredential;
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use fuzzy_matcher::FuzzyMatcher;
 use fuzzy_matcher::skim::SkimMatcherV2;
-use ratatui::widgets::ListState;
+use ratatui::layout::{Position, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::TableState;
 use throbber_widgets_tui::ThrobberState;
-use tokio::sync::mpsc::UnboundedSender;
+use time::OffsetDateTime;
+use tracing::{debug, warn};
 
-use crate::azure::list_secrets_and_cache;
-use crate::model::{AddInputMode, AppEvent, AppScreen, Modal, TokenCache, VaultCacheEntry};
+use crate::audit::{self, Finding};
+use crate::azure::{
+    get_rotation_policy, get_token_then_discover, list_keys, probe_access, secret_activity,
+    set_rotation_policy,
+};
+use crate::cache::{CachedListing, Snapshot};
+use crate::clipboard::ClipboardBackend;
+use crate::columns::{Column, DEFAULT_COLUMNS};
+use crate::config::Config;
+use crate::connstr::ConnStringPicker;
+use crate::credential::{self, CredentialKind};
+use crate::crypto::{self, CryptoField, CryptoForm, CryptoOp};
+use crate::demo;
+use crate::device_code::{self, DeviceCodeCredential};
+use crate::encoding::{
+    FileValue, decode_base64, decoded_text, encode_base64, expand_path, export_statement, hex_dump,
+    read_file_value,
+};
+use crate::error::{AkvError, Category};
+use crate::events::EventSender;
+use crate::forbidden::{self, Need};
+use crate::generator::{GeneratorOptions, generate};
+use crate::highlight::{ValueFormat, detect_format, highlight};
+use crate::hooks::{HookEvent, Hooks};
+use crate::index::{Query, SearchIndex, VaultIndex};
+use crate::json_view::JsonViewer;
+use crate::keymap::{self, Scope};
+use crate::model::{
+    Access, ActivityEntry, AddInputMode, AppEvent, AppScreen, BulkItemStatus, ExpiringSecret,
+    Failure, Identity, KeyInfo, ListingChanges, Modal, Retry, SecretMeta, SignInState, SortKey,
+    Tenant, TokenCache, ValueAction, Vault, VaultCacheEntry,
+};
+use crate::nav::Motion;
+use crate::report::{self, ReportFormat};
+use crate::rotation::RotationForm;
+use crate::state::State;
+use crate::store::{StorePool, list_secrets_and_cache, list_secrets_incremental};
+use crate::tasks::TaskRegistry;
+use crate::textarea::TextArea;
+use crate::theme::theme;
+use crate::toast::{Severity, Toasts};
+use crate::tree::{self, TreeRow};
 
 pub struct App {
     pub screen: AppScreen,
-    pub credential: Arc<DeveloperToolsCredential>,
+    pub credential: Arc<dyn TokenCredential>,
     pub current_vault: Option<(String, String)>, // (name, uri)
-    pub secrets: Vec<String>,
-    pub displayed_secrets: Vec<String>,
+    pub access: Option<Access>, // permission preflight of the current vault, None = unknown
+    pub tasks: TaskRegistry,    // background operations (the `T` panel)
+    pub stores: StorePool,      // Secret store per vault, shared by its requests
+    pub secrets: Arc<Vec<String>>, // sorted listing of the current vault
+    pub displayed_secrets: Arc<Vec<String>>, // the listing itself unless filtered or sorted
+    pub listing_partial: bool,  // more batches of the current vault's listing are on their way
+    pub value_matches: Option<BTreeSet<String>>, // value search (`V`) result filtering the list
     pub selected: usize,
-    pub list_state: ListState,
-    pub message: Option<String>,
+    pub list_state: TableState,
+    pub columns: Vec<Column>, // of the secrets table
+    pub sort: SortKey,
+    pub sort_descending: bool,
+    pub grouped: bool,           // secrets shown as a tree of name prefixes (`z`)
+    pub group_delimiter: String, // splits names into groups, e.g. "--"
+    pub expanded: BTreeSet<String>, // paths of the open groups
+    pub tree: Vec<TreeRow>,      // rows of the grouped list; `selected` indexes these then
+    pub toasts: Toasts,
+    pub last_failure: Option<Failure>, // shown in full with `!`
+    pub retry_at: Option<(Instant, Retry)>, // throttled operation, retried automatically then
+    pub throttled: Option<(Retry, u32)>, // last throttled operation, automatic retries so far
+    pub history: VecDeque<(OffsetDateTime, String)>, // last MESSAGE_HISTORY messages, oldest first
+    pub history_scroll: u16,
+    pub history_back: AppScreen, // screen the history (`m`) was opened from
     pub modal: Option<Modal>,
     pub search_mode: bool,
     pub search_query: String,
+    pub search_at: Option<Instant>, // debounced search of a large vault waiting for typing to pause
+    pub search_generation: u64,     // bumped per search, so stale background results are dropped
     pub throbber_state: ThrobberState,
     pub loading: bool,
-    pub vaults: Vec<(String, String)>,
+    pub vaults: Vec<Vault>,
 
-    pub displayed_vaults: Vec<(String, String)>,
-    pub vault_list_state: ListState,
+    pub displayed_vaults: Vec<Vault>,
+    pub vault_list_state: TableState,
     pub vault_search_mode: bool,
     pub vault_search_query: String,
+    pub list_rows: Rect, // where the vault or secrets list draws its rows (set when drawn)
+    pub last_click: Option<(Instant, usize)>, // time and row of the last click, for double clicks
+    pub pending_g: bool, // first `g` of `gg` pressed
     pub token_cache: Option<TokenCache>, // in-memory token cache (token string stored but not used directly)
     pub vault_secret_cache: HashMap<String, VaultCacheEntry>, // in-memory per-vault cache
+    pub index: SearchIndex,              // names/tags/attributes of every listed vault
     pub secret_value_cache: HashMap<(String, String), String>, // (vault, secret) -> value
     pub welcome_shown_at: Instant,
+    pub marked: BTreeSet<String>, // multi-select marks on the secrets list
+    pub clipboard: ClipboardBackend,
+    pub clipboard_clear_after: Option<Duration>, // None = never clear copied secrets
+    pub clipboard_clear_at: Option<Instant>,     // pending clear for the last copied secret
+    pub clipboard_copied: Option<u64>,           // hash of that secret, to clear only it
+    pub pick_name_only: Option<bool>, // Some = `akv pick` mode (Enter picks instead of copying)
+    pub pick_result: Option<String>,  // printed to stdout after the TUI exits
+    pub quit: bool,
+    pub stdin_value: Option<FileValue>, // value piped into the TUI, used by the next Add
+    pub cache_ttl: Duration,            // cached vault listings older than this are refreshed
+    pub keymap: HashMap<char, char>,    // rebound key -> default key (from [keys] in the config)
+    pub subscriptions: Vec<String>,     // discovery filter (IDs or names); empty = all
+    pub favorites: Vec<String>,         // vaults listed first on the selection screen
+    pub aliases: HashMap<String, String>, // vault name -> display alias
+    pub state: State,                   // favorites/hidden vaults remembered between runs
+    pub state_path: Option<PathBuf>,    // where `state` is saved (None = not saved)
+    pub show_hidden: bool,              // list hidden vaults too (to unhide them)
+    pub profile: Option<String>,        // active config profile, shown in the title bars
+    pub tenant: Option<String>,         // tenant to sign in to (None = the login's home tenant)
+    pub credential_kind: CredentialKind, // re-created per tenant when switching tenants
+    pub device_login: Option<Arc<DeviceCodeCredential>>, // set after a device-code sign-in
+    pub identity: Option<Identity>,     // signed-in principal, from the last ARM token
+    pub offline: bool,                  // no network calls: browse cached listings read-only
+    pub demo: bool,                     // generated vaults and secrets in memory (--demo)
+    pub watch_interval: Duration,       // how often watch mode re-lists the current vault
+    pub watch_next: Option<Instant>,    // Some = watch mode on, next refresh due then
+    pub changes: HashMap<String, ListingChanges>, // last change found per vault (C to review)
+    pub expiry_window: Duration,        // how far ahead the expiry dashboard looks
+    pub expiring: Vec<ExpiringSecret>,  // expiry dashboard rows, soonest first
+    pub expiring_state: TableState,
+    pub audit_referenced: Vec<String>, // name patterns the audit expects to stay enabled
+    pub audit_vault: Option<String>,   // vault the audit view covers, None = all cached vaults
+    pub audit: Vec<Finding>,
+    pub audit_state: TableState,
+    pub keys: Vec<KeyInfo>, // keys of the current vault (keys screen)
+    pub keys_state: TableState,
+    pub focused: bool, // terminal has focus (assumed when focus isn't reported)
+    pub notifications: bool, // desktop notification when background work ends unfocused
+    pub hooks: Hooks,  // user commands run when secrets are copied or changed
+    pub api_overlay: bool, // recent ARM and Key Vault calls shown over the screen (F12)
 }
 
+/// Appended to offline notices.
+pub const OFFLINE_HINT: &str = "press 'o' on the vault list to go online";
+
+/// Appended to notices about what demo mode doesn't simulate.
+pub const DEMO_HINT: &str = "not simulated in demo mode";
+
+/// Vaults with at least this many secrets are searched in the background once typing pauses;
+/// smaller ones on every keystroke.
+const BACKGROUND_SEARCH_MIN: usize = 2000;
+
+/// Pause in typing after which a large vault is searched.
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Rows scrolled per mouse wheel step.
+const SCROLL_ROWS: usize = 3;
+
+/// Longest gap between the clicks of a double click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Wait before retrying a throttled operation; doubled for every further retry.
+const THROTTLE_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Automatic retries of a throttled operation before it's left to `r` in the details (`!`).
+const THROTTLE_RETRIES: u32 = 3;
+
+/// Messages kept for the history screen (`m`).
+pub const MESSAGE_HISTORY: usize = 200;
+
+/// How long a copied secret stays in the clipboard unless overridden.
+pub const DEFAULT_CLIPBOARD_CLEAR: Duration = Duration::from_secs(30);
+
 impl App {
-    pub fn new(credential: Arc<DeveloperToolsCredential>) -> Self {
-        let mut list_state = ListState::default();
+    pub fn new(credential: Arc<dyn TokenCredential>) -> Self {
+        let mut list_state = TableState::default();
         list_state.select(Some(0));
         Self {
             screen: AppScreen::Welcome,
             credential,
             current_vault: None,
-            secrets: Vec::new(),
-            displayed_secrets: Vec::new(),
+            access: None,
+            tasks: TaskRegistry::default(),
+            stores: StorePool::default(),
+            secrets: Arc::default(),
+            displayed_secrets: Arc::default(),
+            listing_partial: false,
+            value_matches: None,
             selected: 0,
             list_state,
-            message: None,
+            columns: DEFAULT_COLUMNS.to_vec(),
+            sort: SortKey::default(),
+            sort_descending: false,
+            grouped: false,
+            group_delimiter: "--".into(),
+            expanded: BTreeSet::new(),
+            tree: Vec::new(),
+            toasts: Toasts::default(),
+            last_failure: None,
+            retry_at: None,
+            throttled: None,
+            history: VecDeque::new(),
+            history_scroll: 0,
+            history_back: AppScreen::VaultSelection,
             modal: None,
             search_mode: false,
             search_query: String::new(),
+            search_at: None,
+            search_generation: 0,
             throbber_state: ThrobberState::default(),
             loading: false,
             vaults: Vec::new(),
 
             displayed_vaults: Vec::new(),
-            vault_list_state: ListState::default(),
+            vault_list_state: TableState::default(),
+            list_rows: Rect::default(),
+            last_click: None,
+            pending_g: false,
             vault_search_mode: false,
             vault_search_query: String::new(),
             token_cache: None,
             vault_secret_cache: HashMap::new(),
+            index: SearchIndex::default(),
             secret_value_cache: HashMap::new(),
             welcome_shown_at: Instant::now(),
+            marked: BTreeSet::new(),
+            clipboard: ClipboardBackend::detect(),
+            clipboard_clear_after: Some(DEFAULT_CLIPBOARD_CLEAR),
+            clipboard_clear_at: None,
+            clipboard_copied: None,
+            pick_name_only: None,
+            pick_result: None,
+            quit: false,
+            stdin_value: None,
+            cache_ttl: Config::default().cache_ttl(),
+            keymap: HashMap::new(),
+            subscriptions: Vec::new(),
+            favorites: Vec::new(),
+            aliases: HashMap::new(),
+            state: State::default(),
+            state_path: None,
+            show_hidden: false,
+            profile: None,
+            tenant: None,
+            credential_kind: CredentialKind::Auto,
+            device_login: None,
+            identity: None,
+            offline: false,
+            demo: false,
+            watch_interval: Config::default().watch_interval(),
+            watch_next: None,
+            changes: HashMap::new(),
+            expiry_window: Config::default().expiry_window(),
+            expiring: Vec::new(),
+            expiring_state: TableState::default(),
+            audit_referenced: Vec::new(),
+            audit_vault: None,
+            audit: Vec::new(),
+            audit_state: TableState::default(),
+            keys: Vec::new(),
+            keys_state: TableState::default(),
+            focused: true,
+            notifications: true,
+            hooks: Hooks::default(),
+            api_overlay: false,
+        }
+    }
+
+    /// Run `task` for the current vault; it is aborted when the vault is left, so a slow
+    /// listing can't report into (or keep running behind) another vault.
+    pub fn spawn_vault_task<F>(&mut self, label: String, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let scope = self.current_vault.as_ref().map(|(name, _)| name.clone());
+        self.tasks.spawn(label, scope, task);
+    }
+
+    /// Write favorites and hidden vaults to the state file (if there is one).
+    pub fn save_state(&self) -> Result<(), String> {
+        match &self.state_path {
+            Some(path) => self.state.save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// The alias of a vault if it has one, otherwise its name.
+    pub fn vault_label<'a>(&'a self, vault: &'a str) -> &'a str {
+        self.aliases.get(vault).map_or(vault, String::as_str)
+    }
+
+    /// What to do when the current vault refuses `need`.
+    pub fn remedy(&self, need: Need) -> String {
+        let vault = self.current_vault.as_ref().map_or("", |(n, _)| n.as_str());
+        vault_remedy(&self.vaults, vault, self.identity.as_ref(), need)
+    }
+
+    /// Replace the discovered vaults, attaching the configured aliases.
+    pub fn set_vaults(&mut self, vaults: Vec<Vault>) {
+        self.vaults = vaults
+            .into_iter()
+            .map(|mut v| {
+                v.alias = self.aliases.get(&v.name).cloned();
+                v
+            })
+            .collect();
+        apply_vault_search(self);
+    }
+
+    pub fn is_hidden(&self, vault: &str) -> bool {
+        self.state.hidden.iter().any(|h| h == vault)
+    }
+
+    /// Remember a vault's secret listing for reopening and searching.
+    pub fn cache_listing(
+        &mut self,
+        vault: &str,
+        secrets: Vec<SecretMeta>,
+        refreshed_at: SystemTime,
+    ) {
+        let mut names: Vec<String> = secrets.iter().map(|s| s.name.clone()).collect();
+        names.sort();
+        self.vault_secret_cache.insert(
+            vault.to_string(),
+            VaultCacheEntry {
+                secrets: Arc::new(names),
+                refreshed_at,
+            },
+        );
+        self.index.update_vault(vault, secrets);
+    }
+
+    /// Show the listings saved by the previous run until discovery and refreshes replace them.
+    pub fn restore_cache(&mut self, snapshot: Snapshot) {
+        if self.vaults.is_empty() {
+            self.set_vaults(snapshot.vaults);
+        }
+        for listing in snapshot.listings {
+            let refreshed_at = UNIX_EPOCH + Duration::from_secs(listing.refreshed_at);
+            self.cache_listing(&listing.vault, listing.secrets, refreshed_at);
+        }
+    }
+
+    /// How long ago a vault's listing was fetched, if it is cached.
+    pub fn cache_age(&self, vault: &str) -> Option<Duration> {
+        let entry = self.vault_secret_cache.get(vault)?;
+        Some(entry.refreshed_at.elapsed().unwrap_or_default())
+    }
+
+    /// Drop the cached listing and values of `vault` (`None` = every vault).
+    pub fn invalidate_cache(&mut self, vault: Option<&str>) {
+        match vault {
+            Some(vault) => {
+                self.vault_secret_cache.remove(vault);
+                self.index.remove_vault(vault);
+                self.secret_value_cache.retain(|(v, _), _| v != vault);
+            }
+            None => {
+                self.vault_secret_cache.clear();
+                self.index.clear();
+                self.secret_value_cache.clear();
+            }
+        }
+    }
+
+    /// Azure couldn't be reached: keep browsing cached data instead of failing, if there is any.
+    pub fn network_failed(&mut self, failure: String) {
+        self.loading = false;
+        if self.vaults.is_empty() && self.vault_secret_cache.is_empty() {
+            self.error(failure);
+            return;
+        }
+        self.offline = true;
+        apply_vault_search(self);
+        self.warn(format!(
+            "{} — offline, showing cached data read-only ({})",
+            failure, OFFLINE_HINT
+        ));
+    }
+
+    /// No credential could sign in, or the token was refused: offer the device-code flow
+    /// instead of failing.
+    pub fn sign_in_required(&mut self, reason: String) {
+        self.loading = false;
+        if self.screen == AppScreen::Welcome {
+            self.screen = AppScreen::VaultSelection;
+        }
+        self.warn("Not signed in".into());
+        self.modal = Some(Modal::SignIn(SignInState::Offer { reason }));
+    }
+
+    /// Show a toast. All but info toasts (progress and status lines) are also kept in the
+    /// message history, so they can be read after they were dismissed.
+    pub fn toast(&mut self, severity: Severity, text: String) {
+        if severity != Severity::Info {
+            if self.history.len() == MESSAGE_HISTORY {
+                self.history.pop_front();
+            }
+            self.history
+                .push_back((OffsetDateTime::now_utc(), text.clone()));
+        }
+        self.toasts.push(severity, text, Instant::now());
+    }
+
+    pub fn info(&mut self, text: String) {
+        self.toast(Severity::Info, text);
+    }
+
+    pub fn success(&mut self, text: String) {
+        self.toast(Severity::Success, text);
+    }
+
+    pub fn warn(&mut self, text: String) {
+        self.toast(Severity::Warning, text);
+    }
+
+    pub fn error(&mut self, text: String) {
+        self.toast(Severity::Error, text);
+    }
+
+    /// Toast the outcome of an operation: its success message, or the error.
+    pub fn report(&mut self, outcome: Result<String, String>) {
+        match outcome {
+            Ok(text) => self.success(text),
+            Err(e) => self.error(e),
+        }
+    }
+
+    /// What to persist on exit: the discovered vaults and every cached listing.
+    pub fn cache_snapshot(&self) -> Snapshot {
+        let mut listings: Vec<CachedListing> = self
+            .vault_secret_cache
+            .iter()
+            .filter_map(|(vault, entry)| {
+                Some(CachedListing {
+                    vault: vault.clone(),
+                    refreshed_at: entry
+                        .refreshed_at
+                        .duration_since(UNIX_EPOCH)
+                        .ok()?
+                        .as_secs(),
+                    secrets: self.index.secrets(vault)?,
+                })
+            })
+            .collect();
+        listings.sort_by(|a, b| a.vault.cmp(&b.vault));
+        Snapshot {
+            vaults: self.vaults.clone(),
+            listings,
         }
     }
 
     pub fn selected_name(&self) -> Option<String> {
+        if self.grouped {
+            let row = self.tree.get(self.selected)?;
+            return row.secret().map(String::from);
+        }
         self.displayed_secrets.get(self.selected).cloned()
     }
 
+    /// "37 / 412 secrets" while searching, otherwise "412 secrets"; a "+" marks a listing
+    /// that is still loading.
+    pub fn secret_count(&self) -> String {
+        let more = if self.listing_partial { "+" } else { "" };
+        if self.search_query.is_empty() && self.value_matches.is_none() {
+            format!("{}{} secrets", self.secrets.len(), more)
+        } else {
+            format!(
+                "{} / {}{} secrets",
+                self.displayed_secrets.len(),
+                self.secrets.len(),
+                more
+            )
+        }
+    }
+
+    /// Rows of the secrets list: one per secret, or the open part of the tree when grouped.
+    pub fn secret_rows(&self) -> usize {
+        if self.grouped {
+            self.tree.len()
+        } else {
+            self.displayed_secrets.len()
+        }
+    }
+
+    /// Select a secret by name, opening the groups it's in.
+    pub fn select_secret(&mut self, name: &str) {
+        let index = if self.grouped {
+            let paths = tree::group_paths(name, &self.group_delimiter);
+            self.expanded.extend(paths.into_iter().map(String::from));
+            self.rebuild_tree();
+            self.tree.iter().position(|r| r.secret() == Some(name))
+        } else {
+            self.displayed_secrets.iter().position(|s| s == name)
+        };
+        if let Some(i) = index {
+            self.selected = i;
+            self.list_state.select(Some(i));
+        }
+    }
+
+    pub fn rebuild_tree(&mut self) {
+        self.tree = if self.grouped {
+            tree::tree_rows(
+                &self.displayed_secrets,
+                &self.group_delimiter,
+                &self.expanded,
+            )
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// Switch between the flat and the grouped list (`z`), keeping the selected secret.
+    pub fn toggle_grouped(&mut self) {
+        let selected = self.selected_name();
+        self.grouped = !self.grouped;
+        self.rebuild_tree();
+        self.selected = 0;
+        self.list_state.select(Some(0));
+        if let Some(name) = selected {
+            self.select_secret(&name);
+        }
+    }
+
+    /// Open the selected group (`l` / →), or go to its first row when it's open already.
+    pub fn expand_selected(&mut self) {
+        match self.tree.get(self.selected) {
+            Some(TreeRow::Group { expanded: true, .. }) => self.move_selection(Motion::Down),
+            Some(TreeRow::Group { .. }) => {
+                self.toggle_selected_group();
+            }
+            _ => {}
+        }
+    }
+
+    /// Close the selected group (`h` / ←), or go to the group the selected row is in.
+    pub fn collapse_selected(&mut self) {
+        match self.tree.get(self.selected) {
+            Some(TreeRow::Group { expanded: true, .. }) => {
+                self.toggle_selected_group();
+            }
+            Some(row) if row.depth() > 0 => {
+                let depth = row.depth();
+                if let Some(parent) = self.tree[..self.selected]
+                    .iter()
+                    .rposition(|r| r.depth() < depth)
+                {
+                    self.selected = parent;
+                    self.list_state.select(Some(parent));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Open (`+`) or close (`-`) every group. The selection stays on its row, or moves to
+    /// the outermost closed group it's in.
+    pub fn expand_all(&mut self, open: bool) {
+        let anchor = self.tree.get(self.selected).map(|r| r.key().to_string());
+        self.expanded = if open {
+            tree::all_group_paths(&self.displayed_secrets, &self.group_delimiter)
+        } else {
+            BTreeSet::new()
+        };
+        self.rebuild_tree();
+        if let Some(anchor) = anchor {
+            let index = self
+                .tree
+                .iter()
+                .position(|r| r.key() == anchor)
+                .or_else(|| {
+                    self.tree
+                        .iter()
+                        .position(|r| r.secret().is_none() && anchor.starts_with(r.key()))
+                });
+            self.selected = index.unwrap_or(0);
+            self.list_state.select(Some(self.selected));
+        }
+    }
+
+    /// Open or close the selected group; false when a secret is selected.
+    pub fn toggle_selected_group(&mut self) -> bool {
+        let Some(TreeRow::Group { path, expanded, .. }) = self.tree.get(self.selected) else {
+            return false;
+        };
+        if *expanded {
+            self.expanded.remove(path);
+        } else {
+            self.expanded.insert(path.clone());
+        }
+        self.rebuild_tree();
+        true
+    }
+
+    /// Toggle the mark on the currently selected secret.
+    pub fn toggle_mark_selected(&mut self) {
+        if let Some(name) = self.selected_name()
+            && !self.marked.remove(&name)
+        {
+            self.marked.insert(name);
+        }
+    }
+
+    /// Mark every secret matching the current filter, or unmark them if all are already marked.
+    pub fn toggle_mark_all_displayed(&mut self) {
+        let all_marked = !self.displayed_secrets.is_empty()
+            && self
+                .displayed_secrets
+                .iter()
+                .all(|s| self.marked.contains(s));
+        if all_marked {
+            for s in self.displayed_secrets.iter() {
+                self.marked.remove(s);
+            }
+        } else {
+            self.marked.extend(self.displayed_secrets.iter().cloned());
+        }
+    }
+
+    /// Order the secrets by the next sort key (`o`), or reverse the order (`O`), keeping the
+    /// selected secret selected.
+    pub fn cycle_sort(&mut self, reverse: bool) {
+        if reverse {
+            self.sort_descending = !self.sort_descending;
+        } else {
+            self.sort = self.sort.next();
+            self.sort_descending = self.sort.descending_first();
+        }
+        let selected = self.selected_name();
+        apply_search(self);
+        if let Some(name) = selected {
+            self.select_secret(&name);
+        }
+    }
+
+    /// Secrets an action should operate on: the marked set if any, otherwise the selection.
+    pub fn action_targets(&self) -> Vec<String> {
+        if self.marked.is_empty() {
+            self.selected_name().into_iter().collect()
+        } else {
+            self.marked.iter().cloned().collect()
+        }
+    }
+
+    fn modal_close_generator(&mut self) {
+        if let Some(Modal::Add { generator, .. }) = &mut self.modal {
+            *generator = None;
+        }
+    }
+
+    /// Copy secret material to the clipboard and schedule it to be wiped.
+    /// Returns the message suffix describing when it will be cleared.
+    pub fn copy_secret(&mut self, text: String) -> Result<String, String> {
+        let copied = clipboard_hash(&text);
+        self.clipboard.set_contents(text)?;
+        self.clipboard_copied = Some(copied);
+        self.arm_clipboard_clear(Instant::now());
+        Ok(match self.clipboard_clear_after {
+            Some(after) => format!(" (clears in {}s)", after.as_secs()),
+            None => String::new(),
+        })
+    }
+
+    /// Copy non-secret text (e.g. names). Any pending clear is dropped since the secret it
+    /// was guarding has just been overwritten.
+    pub fn copy_plain(&mut self, text: String) -> Result<(), String> {
+        self.clipboard.set_contents(text)?;
+        self.clipboard_clear_at = None;
+        self.clipboard_copied = None;
+        Ok(())
+    }
+
+    /// Run the `event` hook for `name` in the current vault, with the value only when the
+    /// hook asks for it.
+    fn run_hook(&self, event: HookEvent, name: &str, value: &str) {
+        if let Some((vault_name, _)) = &self.current_vault {
+            let value = self.hooks.wants_value(event).then_some(value);
+            self.hooks.run(event, vault_name, name, value);
+        }
+    }
+
+    fn arm_clipboard_clear(&mut self, now: Instant) {
+        self.clipboard_clear_at = self.clipboard_clear_after.map(|after| now + after);
+    }
+
+    /// The throttled operation whose backoff is over, if any.
+    pub fn retry_due(&mut self, now: Instant) -> Option<Retry> {
+        match &self.retry_at {
+            Some((at, _)) if now >= *at => self.retry_at.take().map(|(_, retry)| retry),
+            _ => None,
+        }
+    }
+
+    pub fn watch_due(&self, now: Instant) -> bool {
+        self.watch_next.is_some_and(|at| now >= at)
+            && self.screen == AppScreen::Secrets
+            && !self.offline
+    }
+
+    /// Compare a refreshed listing with the one it replaced and report any drift, instead
+    /// of silently swapping the cache.
+    pub fn note_changes(&mut self, vault: &str, previous: &[SecretMeta]) {
+        let Some(current) = self.index.secrets(vault) else {
+            return;
+        };
+        let changes = diff_listings(previous, &current);
+        if changes.is_empty() {
+            return;
+        }
+        self.warn(format!(
+            "'{}' changed since the cached listing: {} (C: review)",
+            self.vault_label(vault),
+            changes.summary()
+        ));
+        self.changes.insert(vault.to_string(), changes);
+    }
+
+    pub fn search_due(&self, now: Instant) -> bool {
+        self.search_at.is_some_and(|at| now >= at)
+    }
+
+    pub fn clipboard_clear_due(&self, now: Instant) -> bool {
+        self.clipboard_clear_at.is_some_and(|at| now >= at)
+    }
+
+    /// Move the selection of the vault list or the secrets list.
+    pub fn move_selection(&mut self, motion: Motion) {
+        let page = self.list_rows.height as usize;
+        match self.screen {
+            AppScreen::VaultSelection if !self.displayed_vaults.is_empty() => {
+                let current = self.vault_list_state.selected().unwrap_or(0);
+                let len = self.displayed_vaults.len();
+                self.vault_list_state
+                    .select(Some(motion.apply(current, len, page)));
+            }
+            AppScreen::Secrets if self.secret_rows() > 0 => {
+                let len = self.secret_rows();
+                self.selected = motion.apply(self.selected, len, page);
+                self.list_state.select(Some(self.selected));
+            }
+            _ => {}
+        }
+    }
+
+    /// The list row at a screen position (vault list or secrets list), if it shows one.
+    fn row_at(&self, column: u16, row: u16) -> Option<usize> {
+        if !self.list_rows.contains(Position::new(column, row)) {
+            return None;
+        }
+        let (offset, len) = match self.screen {
+            AppScreen::VaultSelection => {
+                (self.vault_list_state.offset(), self.displayed_vaults.len())
+            }
+            _ => (self.list_state.offset(), self.secret_rows()),
+        };
+        let index = offset + (row - self.list_rows.y) as usize;
+        (index < len).then_some(index)
+    }
+
+    /// Overwrite the clipboard with empty content and note it in a toast, unless something
+    /// else was copied (in any app) after the secret. Write-only backends (OSC 52) can't
+    /// tell, so they are always cleared.
+    pub fn clear_clipboard(&mut self) {
+        self.clipboard_clear_at = None;
+        let Some(copied) = self.clipboard_copied.take() else {
+            return;
+        };
+        if let Ok(current) = self.clipboard.get_contents()
+            && clipboard_hash(&current) != copied
+        {
+            return;
+        }
+        let cleared = self.clipboard.set_contents(String::new());
+        self.report(
+            cleared
+                .map(|()| "Clipboard cleared".into())
+                .map_err(|e| format!("Failed to clear clipboard: {}", e)),
+        );
+    }
+
     pub fn token_should_refresh(&self) -> bool {
         match &self.token_cache {
             None => true,
@@ -93,46 +806,178 @@ impl App {
     }
 }
 
-/// Apply fuzzy search to produce displayed_secrets
+/// Fingerprint of clipboard text, so the copied secret itself needn't be kept. Trailing line
+/// breaks are ignored since some backends add or strip them on the way back.
+fn clipboard_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.trim_end_matches(['\r', '\n']).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Items where any of `fields` fuzzy-matches `query`, best match first.
+fn fuzzy_rank<T: Clone>(items: &[T], query: &str, fields: fn(&T) -> Vec<&str>) -> Vec<T> {
+    let matcher = SkimMatcherV2::default();
+    let mut results: Vec<(i64, &T)> = items
+        .iter()
+        .filter_map(|item| {
+            fields(item)
+                .into_iter()
+                .filter_map(|field| matcher.fuzzy_match(field, query))
+                .max()
+                .map(|score| (score, item))
+        })
+        .collect();
+    results.sort_by_key(|r| Reverse(r.0));
+    results.into_iter().map(|(_, item)| item.clone()).collect()
+}
+
+/// `secrets` matching `query`, best match first. Once the vault is indexed, the query may also
+/// filter by tags (`tag:env=prod`). Without a query that's the listing itself, not a copy.
+fn rank_secrets(
+    index: Option<&VaultIndex>,
+    secrets: &Arc<Vec<String>>,
+    query: &str,
+) -> Arc<Vec<String>> {
+    if query.is_empty() {
+        return secrets.clone();
+    }
+    Arc::new(match index {
+        Some(index) => index.search(&Query::parse(query)),
+        None => fuzzy_rank(secrets, query, |s| vec![s.as_str()]),
+    })
+}
+
+/// Apply fuzzy search to produce displayed_secrets, right away.
 pub fn apply_search(app: &mut App) {
-    if app.search_query.is_empty() {
-        app.displayed_secrets = app.secrets.clone();
+    app.search_at = None;
+    app.search_generation += 1;
+    let index = app
+        .current_vault
+        .as_ref()
+        .and_then(|(vault, _)| app.index.vault(vault));
+    let ranked = rank_secrets(index.as_deref(), &app.secrets, &app.search_query);
+    show_search_results(app, ranked);
+}
+
+/// Search after the query changed: right away in small vaults; in large ones once typing
+/// pauses and off the event loop (`start_search`), so keystrokes don't wait for the matcher.
+pub fn search_input(app: &mut App, now: Instant) {
+    if app.secrets.len() < BACKGROUND_SEARCH_MIN {
+        apply_search(app);
     } else {
-        let matcher = SkimMatcherV2::default();
-        let mut results: Vec<(i64, &String)> = app
-            .secrets
-            .iter()
-            .filter_map(|s| {
-                matcher
-                    .fuzzy_match(s, &app.search_query)
-                    .map(|score| (score, s))
-            })
-            .collect();
-        results.sort_by(|a, b| b.0.cmp(&a.0));
-        app.displayed_secrets = results.into_iter().map(|(_, s)| s.clone()).collect();
+        app.search_at = Some(now + SEARCH_DEBOUNCE);
     }
+}
+
+/// Rank the secrets for the current query on a blocking thread; answered by
+/// `AppEvent::SearchResults`.
+pub fn start_search(app: &mut App, tx: &EventSender) {
+    app.search_at = None;
+    app.search_generation += 1;
+    let generation = app.search_generation;
+    let index = app
+        .current_vault
+        .as_ref()
+        .and_then(|(vault, _)| app.index.vault(vault));
+    let secrets = app.secrets.clone();
+    let query = app.search_query.clone();
+    let tx = tx.clone();
+    tokio::task::spawn_blocking(move || {
+        let ranked = rank_secrets(index.as_deref(), &secrets, &query);
+        let _ = tx.send(AppEvent::SearchResults(
+            generation,
+            Arc::unwrap_or_clone(ranked),
+        ));
+    });
+}
+
+/// Show the results of a background search, unless another search started since (a newer
+/// query, or a listing update searched right away).
+pub fn search_results(app: &mut App, generation: u64, names: Vec<String>) {
+    if generation == app.search_generation {
+        show_search_results(app, Arc::new(names));
+    }
+}
+
+fn show_search_results(app: &mut App, names: Arc<Vec<String>>) {
+    app.displayed_secrets = names;
+    if let Some(matches) = &app.value_matches {
+        Arc::make_mut(&mut app.displayed_secrets).retain(|name| matches.contains(name));
+    }
+    sort_secrets(app);
+    // Searching opens the groups of every match, so the matches are visible
+    if app.grouped && !app.search_query.is_empty() {
+        app.expanded.extend(tree::all_group_paths(
+            &app.displayed_secrets,
+            &app.group_delimiter,
+        ));
+    }
+    app.rebuild_tree();
     app.selected = 0;
     app.list_state.select(Some(0));
 }
 
-pub fn apply_vault_search(app: &mut App) {
-    if app.vault_search_query.is_empty() {
-        app.displayed_vaults = app.vaults.clone();
+/// Order `displayed_secrets` by `app.sort`. Ascending by name keeps them as they are. Dates
+/// come from the indexed metadata; secrets without one go last in either direction.
+fn sort_secrets(app: &mut App) {
+    let vault = app.current_vault.as_ref().map(|(name, _)| name.as_str());
+    let date = |name: &str| {
+        let meta = vault.and_then(|v| app.index.meta(v, name))?;
+        match app.sort {
+            SortKey::Name => None,
+            SortKey::Updated => meta.updated,
+            SortKey::Expires => meta.expires,
+        }
+    };
+    let descending = app.sort_descending;
+    let mut secrets = std::mem::take(&mut app.displayed_secrets);
+    match app.sort {
+        SortKey::Name if descending => Arc::make_mut(&mut secrets).sort_by(|a, b| b.cmp(a)),
+        SortKey::Name => {}
+        _ => Arc::make_mut(&mut secrets).sort_by(|a, b| match (date(a), date(b)) {
+            (Some(a), Some(b)) if descending => b.cmp(&a),
+            (Some(a), Some(b)) => a.cmp(&b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        }),
+    }
+    app.displayed_secrets = secrets;
+}
+
+/// Vaults matching `query` by name, alias, subscription or resource group. Without a query,
+/// favorites come first, then recently opened vaults, otherwise discovery order.
+fn rank_vaults(app: &App, vaults: &[Vault], query: &str) -> Vec<Vault> {
+    let mut vaults: Vec<Vault> = vaults
+        .iter()
+        .filter(|v| app.show_hidden || !app.is_hidden(&v.name))
+        .cloned()
+        .collect();
+    if query.is_empty() {
+        vaults.sort_by_key(|v| {
+            (
+                !app.favorites.contains(&v.name),
+                app.state.recent_rank(&v.name).unwrap_or(usize::MAX),
+            )
+        });
+        vaults
     } else {
-        let matcher = SkimMatcherV2::default();
-        // We match against the vault name (0th element of tuple)
-        let mut results: Vec<(i64, &(String, String))> = app
-            .vaults
-            .iter()
-            .filter_map(|v| {
-                matcher
-                    .fuzzy_match(&v.0, &app.vault_search_query)
-                    .map(|score| (score, v))
-            })
-            .collect();
-        results.sort_by(|a, b| b.0.cmp(&a.0));
-        app.displayed_vaults = results.into_iter().map(|(_, v)| v.clone()).collect();
+        fuzzy_rank(&vaults, query, |v| {
+            [
+                Some(&v.name),
+                v.alias.as_ref(),
+                v.subscription.as_ref(),
+                v.resource_group.as_ref(),
+            ]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect()
+        })
     }
+}
+
+/// Filter the vault list by name, alias, subscription or resource group.
+pub fn apply_vault_search(app: &mut App) {
+    app.displayed_vaults = rank_vaults(app, &app.vaults, &app.vault_search_query);
     // Reset selection to top of filtered list
     if !app.displayed_vaults.is_empty() {
         app.vault_list_state.select(Some(0));
@@ -141,207 +986,2134 @@ pub fn apply_vault_search(app: &mut App) {
     }
 }
 
-/// Handle modal keys; background tasks clone tx to avoid move errors.
-pub async fn handle_modal_key(
+/// Open the Ctrl+P vault switcher. Before discovery has run (e.g. after `--vault`) it offers
+/// the recently opened vaults.
+pub fn open_vault_switcher(app: &mut App) {
+    app.modal = Some(Modal::VaultSwitcher {
+        query: String::new(),
+        matches: Vec::new(),
+        selected: 0,
+        copy: None,
+    });
+    update_vault_switcher(app);
+}
+
+/// Pick a vault to copy the selected (or marked) secrets into (`Y`), with the switcher.
+pub fn open_copy_to_vault(app: &mut App) {
+    let targets = app.action_targets();
+    if targets.is_empty() {
+        return;
+    }
+    if app.offline {
+        app.warn(format!("Offline: nothing can be copied ({})", OFFLINE_HINT));
+        return;
+    }
+    app.modal = Some(Modal::VaultSwitcher {
+        query: String::new(),
+        matches: Vec::new(),
+        selected: 0,
+        copy: Some(targets),
+    });
+    update_vault_switcher(app);
+}
+
+fn update_vault_switcher(app: &mut App) {
+    let Some(Modal::VaultSwitcher { query, copy, .. }) = &app.modal else {
+        return;
+    };
+    let candidates: Vec<Vault> = if app.vaults.is_empty() {
+        app.state
+            .recent
+            .iter()
+            .map(|r| Vault {
+                name: r.name.clone(),
+                uri: r.uri.clone(),
+                alias: app.aliases.get(&r.name).cloned(),
+                subscription: None,
+                resource_group: None,
+                location: None,
+                properties: None,
+            })
+            .collect()
+    } else {
+        app.vaults.clone()
+    };
+    let mut ranked = rank_vaults(app, &candidates, query);
+    // Secrets are copied elsewhere
+    if copy.is_some()
+        && let Some((current, _)) = &app.current_vault
+    {
+        ranked.retain(|v| v.name != *current);
+    }
+    if let Some(Modal::VaultSwitcher {
+        matches, selected, ..
+    }) = &mut app.modal
+    {
+        *matches = ranked;
+        *selected = 0;
+    }
+}
+
+fn selected_vault_name(app: &App) -> Option<String> {
+    app.vault_list_state
+        .selected()
+        .and_then(|i| app.displayed_vaults.get(i))
+        .map(|v| v.name.clone())
+}
+
+/// Re-apply the vault filter, keeping `name` selected where it ended up.
+fn reselect_vault(app: &mut App, name: &str) {
+    apply_vault_search(app);
+    let index = app.displayed_vaults.iter().position(|v| v.name == name);
+    app.vault_list_state.select(index.or(Some(0)));
+}
+
+/// Show the ARM settings of the selected vault (`i` on the vault list).
+pub fn open_vault_info(app: &mut App) {
+    if let Some(vault) = app
+        .vault_list_state
+        .selected()
+        .and_then(|i| app.displayed_vaults.get(i))
+    {
+        app.modal = Some(Modal::VaultInfo(vault.clone()));
+    }
+}
+
+/// Star or unstar the selected vault and remember the favorites in the state file.
+pub fn toggle_favorite_selected(app: &mut App) {
+    let Some(name) = selected_vault_name(app) else {
+        return;
+    };
+    let starred = if let Some(pos) = app.favorites.iter().position(|f| *f == name) {
+        app.favorites.remove(pos);
+        false
+    } else {
+        app.favorites.push(name.clone());
+        true
+    };
+    reselect_vault(app, &name);
+    app.state.favorites = Some(app.favorites.clone());
+    match app.save_state() {
+        Err(e) => app.error(format!("Favorites not saved: {}", e)),
+        Ok(()) if starred => app.success(format!("★ Added '{}' to favorites", name)),
+        Ok(()) => app.success(format!("Removed '{}' from favorites", name)),
+    }
+}
+
+/// Hide the selected vault (or unhide it while hidden vaults are shown). Hidden vaults are
+/// not preloaded.
+pub fn toggle_hidden_selected(app: &mut App) {
+    let Some(name) = selected_vault_name(app) else {
+        return;
+    };
+    let hidden = if let Some(pos) = app.state.hidden.iter().position(|h| *h == name) {
+        app.state.hidden.remove(pos);
+        false
+    } else {
+        app.state.hidden.push(name.clone());
+        true
+    };
+    reselect_vault(app, &name);
+    match app.save_state() {
+        Err(e) => app.error(format!("Hidden vaults not saved: {}", e)),
+        Ok(()) if hidden => app.success(format!("Hid '{}' (H: show hidden vaults)", name)),
+        Ok(()) => app.success(format!("'{}' is visible again", name)),
+    }
+}
+
+/// Switch to the secrets screen for a vault, using its cached listing when available
+/// (refreshed silently once older than `cache_ttl`) or loading it incrementally.
+pub fn open_vault(
     app: &mut App,
-    code: KeyCode,
-    tx: &UnboundedSender<AppEvent>,
-) -> Result<bool, Box<dyn Error>> {
-    if app.modal.is_none() {
-        return Ok(false);
+    name: String,
+    uri: String,
+    tx: &EventSender,
+) -> Result<(), AkvError> {
+    app.tasks.cancel_scoped();
+    app.current_vault = Some((name.clone(), uri.clone()));
+    app.access = None;
+    app.listing_partial = false;
+    app.value_matches = None;
+    app.marked.clear();
+    app.state.record_recent(&name, &uri);
+    if let Err(e) = app.save_state() {
+        warn!("Cannot remember recent vault: {}", e);
     }
-    match &mut app.modal {
-        Some(Modal::Add {
-            name,
-            value,
-            input_mode,
-        }) => {
-            match code {
-                KeyCode::Esc => {
-                    app.modal = None;
-                }
-                KeyCode::Tab => {
-                    *input_mode = if *input_mode == AddInputMode::Name {
-                        AddInputMode::Value
-                    } else {
-                        AddInputMode::Name
-                    };
-                }
-                KeyCode::Backspace => match input_mode {
-                    AddInputMode::Name => {
-                        name.pop();
-                    }
-                    AddInputMode::Value => {
-                        value.pop();
-                    }
-                },
-                KeyCode::Enter => {
-                    if name.is_empty() {
-                        app.message = Some("Name cannot be empty".into());
-                    } else if let Some((vault_name, vault_uri)) = &app.current_vault {
-                        let secret_name = name.clone();
-                        let secret_value = value.clone();
-                        let vault_name = vault_name.clone();
-                        app.modal = None;
-                        app.loading = true;
-                        app.message = Some("Creating secret...".into());
-                        let tx2 = tx.clone();
-                        let client = SecretClient::new(vault_uri, app.credential.clone(), None)?;
-                        let client_arc = Arc::new(client);
-                        tokio::spawn(async move {
-                            let params = SetSecretParameters {
-                                value: Some(secret_value),
-                                ..Default::default()
-                            };
-                            match params.try_into() {
-                                Ok(p) => match client_arc.set_secret(&secret_name, p, None).await {
-                                    Ok(resp) => {
-                                        let _ = resp.into_body();
-                                        let _ = tx2.send(AppEvent::Message(format!(
-                                            "Secret '{}' created/updated",
-                                            secret_name
-                                        )));
-                                    }
-                                    Err(e) => {
-                                        let _ = tx2.send(AppEvent::Message(format!(
-                                            "Failed to set secret: {}",
-                                            e
-                                        )));
-                                    }
-                                },
-                                Err(e) => {
-                                    let _ = tx2.send(AppEvent::Message(format!(
-                                        "Failed to prepare secret params: {}",
-                                        e
-                                    )));
-                                }
-                            }
-                            // refresh and cache
-                            let _ = list_secrets_and_cache(
-                                client_arc.clone(),
-                                tx2.clone(),
-                                vault_name.clone(),
-                            )
-                            .await;
+    app.screen = AppScreen::Secrets;
+    if app.offline {
+        let cached = app.vault_secret_cache.get(&name).map(|e| e.secrets.clone());
+        app.warn(match &cached {
+            Some(_) => format!("Offline: cached secrets of '{}' (read-only)", name),
+            None => format!(
+                "Offline: no cached secrets for '{}' ({})",
+                name, OFFLINE_HINT
+            ),
+        });
+        app.secrets = cached.unwrap_or_default();
+        apply_search(app);
+        app.loading = false;
+        return Ok(());
+    }
+    probe_vault_access(app, &name, tx);
+    let store = app.stores.get(&uri, &app.credential)?;
+    let tx2 = tx.clone();
+    if let Some(entry) = app.vault_secret_cache.get(&name) {
+        let refreshed_at = entry.refreshed_at;
+        app.secrets = entry.secrets.clone();
+        apply_search(app);
+        app.loading = false;
+        app.info(format!("Using cached secrets for '{}'", name));
+        if refreshed_at.elapsed().unwrap_or_default() > app.cache_ttl {
+            app.spawn_vault_task(format!("Refresh {}", name), async move {
+                let _ = list_secrets_and_cache(store, tx2, name).await;
+            });
+        }
+    } else {
+        app.secrets = Arc::default();
+        apply_search(app);
+        app.loading = true;
+        app.listing_partial = true;
+        app.info("Loading secrets...".into());
+        let remedy = app.remedy(Need::ReadSecrets);
+        app.spawn_vault_task(format!("List {}", name), async move {
+            if let Err(e) = list_secrets_incremental(store, tx2.clone(), name.clone()).await {
+                let message = forbidden::describe("Failed to list secrets", &e, &remedy);
+                let _ = tx2.send(AppEvent::Failed(Failure::new(
+                    format!("List secrets of {}", name),
+                    message,
+                    &e,
+                    Some(Retry::ListSecrets(name)),
+                )));
+            }
+        });
+    }
+    Ok(())
+}
+
+/// What to do when `vault` refuses `need` (see `forbidden::remedy`). Takes fields rather than
+/// the app so it can be used while a modal is borrowed.
+fn vault_remedy(vaults: &[Vault], vault: &str, identity: Option<&Identity>, need: Need) -> String {
+    let props = vaults
+        .iter()
+        .find(|v| v.name == vault)
+        .and_then(|v| v.properties.as_ref());
+    forbidden::remedy(vault, props, identity.map(|i| i.principal.as_str()), need)
+}
+
+/// Find out in the background whether the vault's secrets can be changed
+/// (`AppEvent::AccessProbed`); failures leave the access unknown. Demo vaults are writable.
+fn probe_vault_access(app: &mut App, vault_name: &str, tx: &EventSender) {
+    if app.demo {
+        app.access = Some(Access::ReadWrite);
+        return;
+    }
+    let Some(props) = app
+        .vaults
+        .iter()
+        .find(|v| v.name == vault_name)
+        .and_then(|v| v.properties.clone())
+    else {
+        return;
+    };
+    let vault_name = vault_name.to_string();
+    let tx2 = tx.clone();
+    let cred = app.credential.clone();
+    app.spawn_vault_task(format!("Check access to {}", vault_name), async move {
+        match probe_access(cred, &props).await {
+            Ok(Some(access)) => {
+                let _ = tx2.send(AppEvent::AccessProbed(vault_name, access));
+            }
+            Ok(None) => debug!("Access to '{}' could not be determined", vault_name),
+            Err(e) => debug!("Permission preflight of '{}' failed: {}", vault_name, e),
+        }
+    });
+}
+
+/// List the current vault's secrets again, bypassing the cache.
+pub fn refresh_vault(app: &mut App, tx: &EventSender) -> Result<(), AkvError> {
+    let Some((name, uri)) = app.current_vault.clone() else {
+        app.warn("No vault selected".into());
+        return Ok(());
+    };
+    if app.offline {
+        app.warn(format!("Offline: cannot refresh ({})", OFFLINE_HINT));
+        return Ok(());
+    }
+    app.loading = true;
+    app.listing_partial = true;
+    app.info("Refreshing secrets...".into());
+    let store = app.stores.get(&uri, &app.credential)?;
+    let tx2 = tx.clone();
+    let remedy = app.remedy(Need::ReadSecrets);
+    app.spawn_vault_task(format!("Refresh {}", name), async move {
+        if let Err(e) = list_secrets_incremental(store, tx2.clone(), name.clone()).await {
+            let message = forbidden::describe("Refresh error", &e, &remedy);
+            let _ = tx2.send(AppEvent::Failed(Failure::new(
+                format!("Refresh {}", name),
+                message,
+                &e,
+                Some(Retry::ListSecrets(name)),
+            )));
+        }
+    });
+    Ok(())
+}
+
+/// Turn watch mode on or off for the current vault.
+pub fn toggle_watch(app: &mut App) {
+    if app.watch_next.take().is_some() {
+        app.info("Watch mode off".into());
+    } else {
+        // Highlight only what changes from now on
+        if let Some((name, _)) = &app.current_vault {
+            app.changes.remove(name);
+        }
+        app.watch_next = Some(Instant::now() + app.watch_interval);
+        app.info(format!(
+            "Watching for changes every {}s (w to stop)",
+            app.watch_interval.as_secs()
+        ));
+    }
+}
+
+/// Add a batch of an incremental listing to the current vault's list, keeping the selection.
+/// A refresh lists names that are already there; the full listing follows at the end.
+pub fn merge_listed(app: &mut App, vault: &str, names: Vec<String>) {
+    if app.current_vault.as_ref().map(|(name, _)| name.as_str()) != Some(vault) {
+        return;
+    }
+    let selected = app.selected_name();
+    let secrets = Arc::make_mut(&mut app.secrets);
+    secrets.extend(names);
+    secrets.sort();
+    secrets.dedup();
+    apply_search(app);
+    if let Some(name) = selected {
+        app.select_secret(&name);
+    }
+    app.info(format!("Loading secrets... {} so far", app.secrets.len()));
+}
+
+/// Re-list the current vault for watch mode; changes arrive with `CacheVaultSecrets`.
+pub fn watch_refresh(app: &mut App, tx: &EventSender) -> Result<(), AkvError> {
+    app.watch_next = Some(Instant::now() + app.watch_interval);
+    let Some((name, uri)) = app.current_vault.clone() else {
+        return Ok(());
+    };
+    let store = app.stores.get(&uri, &app.credential)?;
+    let tx2 = tx.clone();
+    app.spawn_vault_task(format!("Watch {}", name), async move {
+        if let Err(e) = list_secrets_and_cache(store, tx2.clone(), name.clone()).await {
+            let _ = tx2.send(AppEvent::Failed(Failure::new(
+                format!("Watch {}", name),
+                format!("Watch refresh failed: {}", e),
+                &e,
+                None,
+            )));
+        }
+    });
+    Ok(())
+}
+
+/// Added, removed and updated secrets between two listings sorted by name.
+fn diff_listings(old: &[SecretMeta], new: &[SecretMeta]) -> ListingChanges {
+    let find = |list: &[SecretMeta], name: &str| {
+        list.binary_search_by(|m| m.name.as_str().cmp(name))
+            .ok()
+            .map(|i| list[i].updated)
+    };
+    let mut changes = ListingChanges::default();
+    for meta in new {
+        match find(old, &meta.name) {
+            None => changes.added.push(meta.name.clone()),
+            Some(updated) if updated != meta.updated => changes.changed.push(meta.name.clone()),
+            Some(_) => {}
+        }
+    }
+    changes.removed = old
+        .iter()
+        .filter(|m| find(new, &m.name).is_none())
+        .map(|m| m.name.clone())
+        .collect();
+    changes
+}
+
+/// Review the changes found by refreshes, per vault.
+pub fn open_changes(app: &mut App) {
+    if app.changes.is_empty() {
+        app.info("No changes found since the cached listings".into());
+        return;
+    }
+    let mut vaults: Vec<&String> = app.changes.keys().collect();
+    vaults.sort();
+    let mut lines = Vec::new();
+    for vault in vaults {
+        let changes = &app.changes[vault];
+        lines.push(Line::styled(
+            format!("{} ({})", app.vault_label(vault), changes.summary()),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        for (names, mark, color) in [
+            (&changes.added, '+', theme().success),
+            (&changes.removed, '-', theme().error),
+            (&changes.changed, '~', theme().warning),
+        ] {
+            lines.extend(
+                names
+                    .iter()
+                    .map(|n| Line::styled(format!("  {} {}", mark, n), Style::default().fg(color))),
+            );
+        }
+        lines.push(Line::default());
+    }
+    app.modal = Some(Modal::Changes { lines, scroll: 0 });
+}
+
+/// Query the vault's audit logs for recent operations on the selected secret (`L`);
+/// `AppEvent::ActivityLoaded` opens the result.
+pub fn open_activity(app: &mut App, tx: &EventSender) {
+    let Some(name) = app.selected_name() else {
+        return;
+    };
+    if app.offline {
+        app.warn(format!(
+            "Offline: cannot query the activity log ({})",
+            OFFLINE_HINT
+        ));
+        return;
+    }
+    if app.demo {
+        app.warn(format!("The activity log is {}", DEMO_HINT));
+        return;
+    }
+    let vault = app.current_vault.as_ref().map(|(n, _)| n);
+    let Some(resource_id) = app
+        .vaults
+        .iter()
+        .find(|v| Some(&v.name) == vault)
+        .and_then(|v| v.properties.as_ref()?.resource_id.clone())
+    else {
+        app.warn(
+            "The activity log needs the vault's resource ID; press v to discover the vaults again"
+                .into(),
+        );
+        return;
+    };
+    app.loading = true;
+    app.info(format!("Querying the activity log of '{}'...", name));
+    let tx2 = tx.clone();
+    let cred = app.credential.clone();
+    app.spawn_vault_task(format!("Activity of {}", name), async move {
+        let _ = tx2.send(match secret_activity(cred, &resource_id, &name).await {
+            Ok(entries) => AppEvent::ActivityLoaded(name, entries),
+            Err(e) => AppEvent::Failed(Failure::new(
+                format!("Query the activity log of '{}'", name),
+                format!("Failed to query the activity log: {}", e),
+                &e,
+                None,
+            )),
+        });
+    });
+}
+
+/// Show the activity log of `name`, newest first.
+pub fn show_activity(app: &mut App, name: String, entries: Vec<ActivityEntry>) {
+    app.loading = false;
+    if entries.is_empty() {
+        app.info(format!(
+            "No activity on '{}' in the last 30 days (audit logs need a diagnostic setting sending AuditEvent to Log Analytics)",
+            name
+        ));
+        return;
+    }
+    app.toasts.dismiss_info();
+    let mut lines = vec![Line::styled(
+        format!(
+            "{:<20} {:<14} {:<40} {:<16} {}",
+            "Time (UTC)", "Operation", "Caller", "IP address", "Result"
+        ),
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    lines.extend(entries.iter().map(|e| {
+        let color = match e.operation.as_str() {
+            "SecretSet" | "SecretRestore" | "SecretUpdate" => theme().warning,
+            "SecretDelete" | "SecretPurge" => theme().error,
+            _ => theme().text,
+        };
+        // 2026-10-01T12:00:00.123Z -> 2026-10-01 12:00:00
+        let time: String = e.time.replacen('T', " ", 1).chars().take(19).collect();
+        Line::styled(
+            format!(
+                "{:<20} {:<14} {:<40} {:<16} {}",
+                time, e.operation, e.caller, e.ip, e.result
+            ),
+            Style::default().fg(color),
+        )
+    }));
+    app.modal = Some(Modal::Activity {
+        name,
+        lines,
+        scroll: 0,
+    });
+}
+
+/// Show the secrets of all cached vaults that expire within `expiry_window` (or already
+/// have), soonest first.
+pub fn open_expiring(app: &mut App) {
+    let horizon = OffsetDateTime::now_utc() + app.expiry_window;
+    let mut expiring: Vec<ExpiringSecret> = app
+        .index
+        .iter()
+        .filter_map(|(vault, meta)| {
+            let expires = meta.expires.filter(|e| *e <= horizon)?;
+            Some(ExpiringSecret {
+                vault: vault.to_string(),
+                name: meta.name.clone(),
+                expires,
+            })
+        })
+        .collect();
+    expiring.sort_by(|a, b| (a.expires, &a.vault, &a.name).cmp(&(b.expires, &b.vault, &b.name)));
+    app.info(format!(
+        "{} secret(s) expire within {} days across {} cached vault(s)",
+        expiring.len(),
+        app.expiry_window.as_secs() / 86400,
+        app.vault_secret_cache.len()
+    ));
+    app.expiring_state
+        .select((!expiring.is_empty()).then_some(0));
+    app.expiring = expiring;
+    app.screen = AppScreen::Expiring;
+}
+
+/// Open the vault of the selected dashboard row, with that secret selected.
+pub fn open_expiring_selected(app: &mut App, tx: &EventSender) -> Result<(), AkvError> {
+    match app
+        .expiring_state
+        .selected()
+        .and_then(|i| app.expiring.get(i))
+        .cloned()
+    {
+        Some(row) => open_secret(app, row.vault, &row.name, tx),
+        None => Ok(()),
+    }
+}
+
+/// Audit the attributes of `vault`'s secrets, or of every cached vault's.
+pub fn open_audit(app: &mut App, vault: Option<String>) {
+    let findings = match &vault {
+        Some(vault) => audit::audit(
+            app.index.iter().filter(|(v, _)| v == vault),
+            &app.audit_referenced,
+        ),
+        None => audit::audit(app.index.iter(), &app.audit_referenced),
+    };
+    let scope = match &vault {
+        Some(vault) => format!("'{}'", vault),
+        None => format!("{} cached vault(s)", app.vault_secret_cache.len()),
+    };
+    app.info(format!(
+        "Audit of {}: {} secret(s) flagged",
+        scope,
+        findings.len()
+    ));
+    app.audit_state.select((!findings.is_empty()).then_some(0));
+    app.audit = findings;
+    app.audit_vault = vault;
+    app.screen = AppScreen::Audit;
+}
+
+/// Show the keys of the current vault, listed in the background (`AppEvent::KeysLoaded`).
+pub fn open_keys(app: &mut App, tx: &EventSender) {
+    let Some((vault_name, vault_uri)) = app.current_vault.clone() else {
+        return;
+    };
+    if app.offline {
+        app.warn(format!("Offline: keys aren't cached ({})", OFFLINE_HINT));
+        return;
+    }
+    if app.demo {
+        app.warn(format!("Keys are {}", DEMO_HINT));
+        return;
+    }
+    app.keys.clear();
+    app.keys_state.select(None);
+    app.screen = AppScreen::Keys;
+    app.loading = true;
+    app.info(format!("Loading keys of '{}'...", vault_name));
+    let tx2 = tx.clone();
+    let cred = app.credential.clone();
+    let remedy = app.remedy(Need::UseKeys);
+    app.spawn_vault_task(format!("List keys of {}", vault_name), async move {
+        let _ = tx2.send(match list_keys(cred, &vault_uri).await {
+            Ok(keys) => AppEvent::KeysLoaded(vault_name, keys),
+            Err(e) => AppEvent::Failed(Failure::new(
+                format!("List keys of {}", vault_name),
+                forbidden::describe("Failed to list keys", &e, &remedy),
+                &e,
+                Some(Retry::ListKeys(vault_name)),
+            )),
+        });
+    });
+}
+
+/// Mouse input on the vault and secrets lists: the wheel scrolls, a click selects a row and
+/// a double click returns Enter, to open the vault or copy the secret like the key does.
+pub fn handle_mouse(app: &mut App, event: MouseEvent, now: Instant) -> Option<KeyCode> {
+    let on_list = matches!(app.screen, AppScreen::VaultSelection | AppScreen::Secrets);
+    if !on_list || app.modal.is_some() || app.search_mode || app.vault_search_mode {
+        return None;
+    }
+    match event.kind {
+        MouseEventKind::ScrollDown => {
+            (0..SCROLL_ROWS).for_each(|_| app.move_selection(Motion::Down))
+        }
+        MouseEventKind::ScrollUp => (0..SCROLL_ROWS).for_each(|_| app.move_selection(Motion::Up)),
+        MouseEventKind::Down(MouseButton::Left) => {
+            let index = app.row_at(event.column, event.row)?;
+            match app.screen {
+                AppScreen::VaultSelection => app.vault_list_state.select(Some(index)),
+                _ => {
+                    app.selected = index;
+                    app.list_state.select(Some(index));
+                }
+            }
+            let double = app
+                .last_click
+                .is_some_and(|(at, row)| row == index && now.duration_since(at) <= DOUBLE_CLICK);
+            app.last_click = (!double).then_some((now, index));
+            if double {
+                return Some(KeyCode::Enter);
+            }
+        }
+        _ => {}
+    }
+    None
+}
+
+/// Show the message history (`m`), newest first.
+pub fn open_history(app: &mut App) {
+    if app.screen != AppScreen::History {
+        app.history_back = app.screen;
+    }
+    app.history_scroll = 0;
+    app.screen = AppScreen::History;
+}
+
+/// List the key bindings of the current screen, then the global and dialog ones (`?`).
+pub fn open_help(app: &mut App) {
+    let mut lines = Vec::new();
+    for section in keymap::help_sections(app.screen) {
+        lines.push(Line::styled(
+            section.title,
+            Style::default()
+                .fg(theme().highlight)
+                .add_modifier(Modifier::BOLD),
+        ));
+        for (keys, description) in section.bindings {
+            let keys = match section.scope {
+                Scope::Dialog => keys.to_string(),
+                _ => keymap::display_keys(keys, &app.keymap),
+            };
+            lines.push(Line::from(format!("  {:<18} {}", keys, description)));
+        }
+        lines.push(Line::default());
+    }
+    app.modal = Some(Modal::Help { lines, scroll: 0 });
+}
+
+/// Show the last failed operation in full (`!`).
+pub fn open_error_details(app: &mut App) {
+    match app.last_failure.clone() {
+        Some(failure) => app.modal = Some(Modal::ErrorDetails { failure, scroll: 0 }),
+        None => app.info("No error to show".into()),
+    }
+}
+
+/// React to a failed background operation by its category: sign in again, back off and retry,
+/// refresh a listing that still shows a deleted secret, or fall back to cached data. Every
+/// failure can be read in full with `!`.
+pub fn failed(app: &mut App, failure: Failure, tx: &EventSender) -> Result<(), AkvError> {
+    warn!("{} failed: {}", failure.operation, failure.chain.join(": "));
+    app.loading = false;
+    app.last_failure = Some(failure.clone());
+    let current = app.current_vault.as_ref().map(|(name, _)| name.clone());
+    match (failure.error.category, failure.retry) {
+        (Category::Auth, _) => app.sign_in_required(failure.error.message),
+        (Category::Network, _) => app.network_failed(failure.message),
+        (Category::Throttled, Some(retry)) => {
+            let attempt = match &app.throttled {
+                Some((last, n)) if *last == retry => n + 1,
+                _ => 0,
+            };
+            if attempt < THROTTLE_RETRIES {
+                let delay = THROTTLE_BACKOFF * 2u32.pow(attempt);
+                app.warn(format!(
+                    "{}; throttled, retrying in {}s",
+                    failure.message,
+                    delay.as_secs()
+                ));
+                app.retry_at = Some((Instant::now() + delay, retry.clone()));
+                app.throttled = Some((retry, attempt));
+            } else {
+                app.throttled = None;
+                app.error(format!("{} (!: details)", failure.message));
+            }
+        }
+        (Category::NotFound, Some(Retry::GetValue(vault, name, _)))
+            if current.as_ref() == Some(&vault) =>
+        {
+            app.error(format!("'{}' no longer exists; refreshing the list", name));
+            refresh_vault(app, tx)?;
+        }
+        _ => app.error(format!("{} (!: details)", failure.message)),
+    }
+    Ok(())
+}
+
+/// Run a failed operation again from the error details popup, or once a throttled one backed
+/// off. Operations on a vault are only retried while that vault is still open.
+pub fn retry(app: &mut App, retry: Retry, tx: &EventSender) -> Result<(), AkvError> {
+    let current = app.current_vault.as_ref().map(|(name, _)| name.clone());
+    match retry {
+        Retry::Discover => start_discovery(app, "Discovering vaults...", tx),
+        Retry::ListSecrets(vault) | Retry::GetValue(vault, ..) | Retry::ListKeys(vault)
+            if current.as_ref() != Some(&vault) =>
+        {
+            app.warn(format!("Open '{}' again to retry", vault));
+        }
+        Retry::ListSecrets(_) => refresh_vault(app, tx)?,
+        Retry::GetValue(_, name, action) => request_secret_value(app, name, action, tx)?,
+        Retry::ListKeys(_) => open_keys(app, tx),
+    }
+    Ok(())
+}
+
+/// Open the crypto dialog for the selected key.
+pub fn open_crypto(app: &mut App) {
+    let Some(key) = app
+        .keys_state
+        .selected()
+        .and_then(|i| app.keys.get(i))
+        .cloned()
+    else {
+        return;
+    };
+    if !key.enabled {
+        app.warn(format!("Key '{}' is disabled", key.name));
+        return;
+    }
+    let name = key.name.clone();
+    match CryptoForm::new(key) {
+        Some(form) => app.modal = Some(Modal::Crypto(form)),
+        None => app.warn(format!(
+            "Key '{}' permits no encrypt, decrypt, sign, verify or wrap operation",
+            name
+        )),
+    }
+}
+
+/// Load the rotation policy of the selected key, then open the editor
+/// (`AppEvent::RotationPolicyLoaded`).
+pub fn open_rotation(app: &mut App, tx: &EventSender) {
+    let (Some(key), Some((_, vault_uri))) = (
+        app.keys_state.selected().and_then(|i| app.keys.get(i)),
+        &app.current_vault,
+    ) else {
+        return;
+    };
+    let key = key.name.clone();
+    let vault_uri = vault_uri.clone();
+    app.loading = true;
+    app.info(format!("Loading the rotation policy of '{}'...", key));
+    let tx2 = tx.clone();
+    let cred = app.credential.clone();
+    let remedy = app.remedy(Need::ManageKeys);
+    tokio::spawn(async move {
+        let _ = tx2.send(match get_rotation_policy(cred, &vault_uri, &key).await {
+            Ok(policy) => AppEvent::RotationPolicyLoaded(key, policy),
+            Err(e) => AppEvent::Failed(Failure::new(
+                format!("Load the rotation policy of {}", key),
+                forbidden::describe("Failed to load the rotation policy", &e, &remedy),
+                &e,
+                None,
+            )),
+        });
+    });
+}
+
+/// Save the policy entered in the rotation dialog, then list the keys again so the
+/// summary is up to date.
+fn save_rotation(app: &mut App, tx: &EventSender) {
+    let remedy = app.remedy(Need::ManageKeys);
+    let (Some(Modal::Rotation(form)), Some((vault_name, vault_uri))) =
+        (&mut app.modal, &app.current_vault)
+    else {
+        return;
+    };
+    let policy = match form.policy() {
+        Ok(policy) => policy,
+        Err(e) => {
+            form.status.error = Some(e);
+            return;
+        }
+    };
+    form.status.pending = true;
+    form.status.error = None;
+    let key = form.key.clone();
+    let (vault_name, vault_uri) = (vault_name.clone(), vault_uri.clone());
+    app.loading = true;
+    let tx2 = tx.clone();
+    let cred = app.credential.clone();
+    let quitting = app.tasks.quitting();
+    let label = format!("Save the rotation policy of {}", key);
+    app.tasks.spawn_write(label, async move {
+        if let Err(e) = set_rotation_policy(cred.clone(), &vault_uri, &key, &policy).await {
+            let _ = tx2.send(AppEvent::SubmitFailed(forbidden::describe(
+                "Failed to save the rotation policy",
+                &e,
+                &remedy,
+            )));
+            return;
+        }
+        let _ = tx2.send(AppEvent::SubmitSucceeded(format!(
+            "Rotation policy of '{}' saved",
+            key
+        )));
+        if !quitting.load(Ordering::Relaxed)
+            && let Ok(keys) = list_keys(cred, &vault_uri).await
+        {
+            let _ = tx2.send(AppEvent::KeysLoaded(vault_name, keys));
+        }
+    });
+}
+
+/// Read the crypto dialog's input (the file, or the clipboard when no file is given) and
+/// run its operation in the background; answered by `AppEvent::CryptoDone`.
+fn run_crypto(app: &mut App, tx: &EventSender) {
+    let (Some(Modal::Crypto(form)), Some((_, vault_uri))) = (&app.modal, &app.current_vault) else {
+        return;
+    };
+    let op = form.op();
+    let key = form.key.clone();
+    let vault_uri = vault_uri.clone();
+    let path = form.input.text().trim().to_string();
+    let signature = form.signature.text();
+
+    let raw = if path.is_empty() {
+        app.clipboard.get_contents().map(String::into_bytes)
+    } else {
+        let path = expand_path(&path);
+        std::fs::read(&path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))
+    };
+    let input = raw.and_then(|raw| {
+        if op.takes_base64() {
+            decode_base64(&String::from_utf8_lossy(&raw))
+        } else {
+            Ok(raw)
+        }
+    });
+    let signature = match op {
+        CryptoOp::Verify if signature.trim().is_empty() => {
+            Err("Enter the base64 signature to verify".to_string())
+        }
+        CryptoOp::Verify => decode_base64(&signature).map(Some),
+        _ => Ok(None),
+    };
+
+    let remedy = app.remedy(Need::UseKeys);
+    let Some(Modal::Crypto(form)) = &mut app.modal else {
+        return;
+    };
+    match (input, signature) {
+        (Ok(input), Ok(signature)) => {
+            form.pending = true;
+            form.result = None;
+            let tx2 = tx.clone();
+            let cred = app.credential.clone();
+            tokio::spawn(async move {
+                let result = crypto::run(cred, &vault_uri, &key, op, input, signature)
+                    .await
+                    .map_err(|e| {
+                        let what = format!("{} failed", op.name());
+                        forbidden::describe(&what, &e, &remedy)
+                    });
+                let _ = tx2.send(AppEvent::CryptoDone(result));
+            });
+        }
+        (Err(e), _) | (_, Err(e)) => form.result = Some(Err(e)),
+    }
+}
+
+/// Ask where to write the metadata report of `vault` (or of every cached vault). Of a vault,
+/// only the marked secrets when there are any.
+pub fn open_export(app: &mut App, vault: Option<String>) {
+    let file = format!(
+        "akv-report-{}-{}.csv",
+        vault.as_deref().unwrap_or("all"),
+        OffsetDateTime::now_utc().date()
+    );
+    let names = (vault.is_some() && !app.marked.is_empty()).then(|| app.action_targets());
+    app.modal = Some(Modal::Export {
+        vault,
+        names,
+        path: TextArea::from_text(&file),
+    });
+}
+
+/// Write the metadata report in the background; answered by `AppEvent::Finished`.
+fn export_report(
+    app: &mut App,
+    vault: Option<String>,
+    names: Option<Vec<String>>,
+    path: PathBuf,
+    tx: &EventSender,
+) {
+    let mut rows: Vec<(String, SecretMeta)> = app
+        .index
+        .iter()
+        .filter(|(v, _)| vault.as_deref().is_none_or(|want| want == *v))
+        .filter(|(_, meta)| {
+            names
+                .as_ref()
+                .is_none_or(|names| names.contains(&meta.name))
+        })
+        .map(|(v, meta)| (v.to_string(), meta.clone()))
+        .collect();
+    rows.sort_by(|a, b| (&a.0, &a.1.name).cmp(&(&b.0, &b.1.name)));
+    let format = ReportFormat::for_path(&path);
+    let tx = tx.clone();
+    let label = format!("Export {}", path.display());
+    let write = tokio::task::spawn_blocking(move || {
+        let _ = tx.send(match std::fs::write(&path, report::render(&rows, format)) {
+            Ok(()) => AppEvent::Finished(format!(
+                "Exported the metadata of {} secret(s) to {} ({})",
+                rows.len(),
+                path.display(),
+                format.name()
+            )),
+            Err(e) => AppEvent::Failed(Failure::new(
+                format!("Export {}", path.display()),
+                format!("Cannot write {}: {}", path.display(), e),
+                &AkvError::other(e.to_string()),
+                None,
+            )),
+        });
+    });
+    app.tasks.spawn_write(label, async move {
+        let _ = write.await;
+    });
+}
+
+/// Copy `names` from the current vault into `target` in the background, one after the other
+/// with their content types; a name the target already has gets a new version. Answered by
+/// `AppEvent::Finished`, after which the target is listed again for the cache.
+fn copy_to_vault(
+    app: &mut App,
+    names: Vec<String>,
+    target: Vault,
+    tx: &EventSender,
+) -> Result<(), AkvError> {
+    let Some((source_name, source_uri)) = app.current_vault.clone() else {
+        app.warn("No vault selected".into());
+        return Ok(());
+    };
+    let source = app.stores.get(&source_uri, &app.credential)?;
+    let dest = app.stores.get(&target.uri, &app.credential)?;
+    let existing: BTreeSet<String> = app
+        .index
+        .secrets(&target.name)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    let secrets: Vec<(String, Option<String>)> = names
+        .into_iter()
+        .map(|name| {
+            let content_type = app
+                .index
+                .meta(&source_name, &name)
+                .and_then(|m| m.content_type.clone());
+            (name, content_type)
+        })
+        .collect();
+    app.info(format!(
+        "Copying {} secret(s) to {}...",
+        secrets.len(),
+        target.name
+    ));
+    let label = format!("Copy {} secret(s) to {}", secrets.len(), target.name);
+    let hooks = app.hooks.clone();
+    let quitting = app.tasks.quitting();
+    let tx = tx.clone();
+    app.tasks.spawn_write(label, async move {
+        let mut failed = Vec::new();
+        for (name, content_type) in &secrets {
+            if quitting.load(Ordering::Relaxed) {
+                break;
+            }
+            let copied = match source.get(name).await {
+                Ok(value) => dest
+                    .set(name, value.clone(), content_type.clone())
+                    .await
+                    .map(|()| value),
+                Err(e) => Err(e),
+            };
+            match copied {
+                Ok(value) => {
+                    let event = if existing.contains(name) {
+                        HookEvent::SecretUpdated
+                    } else {
+                        HookEvent::SecretCreated
+                    };
+                    let value = hooks.wants_value(event).then_some(value.as_str());
+                    hooks.run(event, &target.name, name, value);
+                }
+                Err(e) => {
+                    debug!("Copying '{}' to {} failed: {}", name, target.name, e);
+                    failed.push(name.clone());
+                }
+            }
+        }
+        let copied = secrets.len() - failed.len();
+        let msg = if failed.is_empty() {
+            format!("Copied {} secret(s) to {}", copied, target.name)
+        } else {
+            format!(
+                "Copied {} of {} secret(s) to {} (failed: {})",
+                copied,
+                secrets.len(),
+                target.name,
+                failed.join(", ")
+            )
+        };
+        let _ = tx.send(AppEvent::Finished(msg));
+        if !quitting.load(Ordering::Relaxed) {
+            let _ = list_secrets_and_cache(dest, tx.clone(), target.name.clone()).await;
+        }
+    });
+    Ok(())
+}
+
+/// Ask what to look for in the values of the listed secrets (`V`). Nothing is fetched until
+/// the search is confirmed, as every listed secret costs a request.
+pub fn open_value_search(app: &mut App) {
+    if app.offline {
+        app.warn(format!(
+            "Offline: values can't be searched ({})",
+            OFFLINE_HINT
+        ));
+        return;
+    }
+    if app.displayed_secrets.is_empty() {
+        return;
+    }
+    app.modal = Some(Modal::ValueSearch {
+        needle: TextArea::default(),
+        names: app.displayed_secrets.to_vec(),
+        confirm: false,
+    });
+}
+
+/// Fetch the values of `names` one at a time, within the vault's rate limit, and report those
+/// containing `needle` (ignoring case) with `AppEvent::ValueSearchDone`. Each value is dropped
+/// right after the check; values, the searched text and the matches are never logged.
+fn search_values(
+    app: &mut App,
+    needle: &str,
+    names: Vec<String>,
+    tx: &EventSender,
+) -> Result<(), AkvError> {
+    let Some((vault_name, vault_uri)) = app.current_vault.clone() else {
+        return Ok(());
+    };
+    let store = app.stores.get(&vault_uri, &app.credential)?;
+    let needle = needle.to_lowercase();
+    app.loading = true;
+    app.info(format!(
+        "Searching the values of {} secret(s)...",
+        names.len()
+    ));
+    let tx2 = tx.clone();
+    app.spawn_vault_task(format!("Search values of {}", vault_name), async move {
+        let mut matches = Vec::new();
+        let mut unreadable = 0;
+        for name in names {
+            match store.get(&name).await {
+                Ok(value) if value.to_lowercase().contains(&needle) => matches.push(name),
+                Ok(_) => {}
+                Err(_) => unreadable += 1,
+            }
+        }
+        let _ = tx2.send(AppEvent::ValueSearchDone(vault_name, matches, unreadable));
+    });
+    Ok(())
+}
+
+/// Narrow the list to the secrets whose value matched, until `Esc` clears the filter.
+pub fn show_value_matches(app: &mut App, vault: String, matches: Vec<String>, unreadable: usize) {
+    if app.current_vault.as_ref().map(|(name, _)| name) != Some(&vault) {
+        return;
+    }
+    app.loading = false;
+    let skipped = match unreadable {
+        0 => String::new(),
+        n => format!(" ({} could not be read)", n),
+    };
+    if matches.is_empty() {
+        app.warn(format!("No value contains the text{}", skipped));
+        return;
+    }
+    app.success(format!(
+        "{} secret(s) contain the text{} (Esc: show all)",
+        matches.len(),
+        skipped
+    ));
+    app.value_matches = Some(matches.into_iter().collect());
+    apply_search(app);
+}
+
+/// Show every secret again after a value search.
+pub fn clear_value_search(app: &mut App) {
+    app.value_matches = None;
+    apply_search(app);
+    app.info("Value search cleared".into());
+}
+
+/// Open the vault of the selected audit finding, with that secret selected.
+pub fn open_audit_selected(app: &mut App, tx: &EventSender) -> Result<(), AkvError> {
+    match app
+        .audit_state
+        .selected()
+        .and_then(|i| app.audit.get(i))
+        .cloned()
+    {
+        Some(finding) => open_secret(app, finding.vault, &finding.name, tx),
+        None => Ok(()),
+    }
+}
+
+/// Open a vault by name (from the vault list or recently opened ones) and select `secret`.
+fn open_secret(
+    app: &mut App,
+    vault: String,
+    secret: &str,
+    tx: &EventSender,
+) -> Result<(), AkvError> {
+    let uri = app
+        .vaults
+        .iter()
+        .map(|v| (&v.name, &v.uri))
+        .chain(app.state.recent.iter().map(|r| (&r.name, &r.uri)))
+        .find(|(name, _)| **name == vault)
+        .map(|(_, uri)| uri.clone());
+    let Some(uri) = uri else {
+        app.warn(format!("Vault '{}' is not in the vault list", vault));
+        return Ok(());
+    };
+    open_vault(app, vault, uri, tx)?;
+    app.select_secret(secret);
+    Ok(())
+}
+
+/// "in 12d" / "expired 3d ago"
+pub fn expires_in(expires: OffsetDateTime, now: OffsetDateTime) -> String {
+    if expires < now {
+        format!("expired {} ago", format_age((now - expires).unsigned_abs()))
+    } else {
+        format!("in {}", format_age((expires - now).unsigned_abs()))
+    }
+}
+
+/// Compact age for the cache indicator, e.g. "45s", "12m", "3h", "2d".
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+/// Run `action` on a secret of the current vault, using the value cache when possible and
+/// otherwise fetching in the background (answered by `AppEvent::SecretValueLoaded`).
+pub fn request_secret_value(
+    app: &mut App,
+    name: String,
+    action: ValueAction,
+    tx: &EventSender,
+) -> Result<(), AkvError> {
+    let Some((vault_name, vault_uri)) = app.current_vault.clone() else {
+        app.warn("No vault selected".into());
+        return Ok(());
+    };
+    if let Some(cached_val) = app
+        .secret_value_cache
+        .get(&(vault_name.clone(), name.clone()))
+        .cloned()
+    {
+        apply_value_action(app, &name, cached_val, action, true);
+        return Ok(());
+    }
+    if app.offline {
+        app.warn(format!(
+            "Offline: the value of '{}' isn't cached ({})",
+            name, OFFLINE_HINT
+        ));
+        return Ok(());
+    }
+    app.loading = true;
+    app.info("Fetching secret value...".into());
+    let store = app.stores.get(&vault_uri, &app.credential)?;
+    let tx2 = tx.clone();
+    let remedy = app.remedy(Need::ReadSecrets);
+    tokio::spawn(async move {
+        match store.get(&name).await {
+            Ok(value) => {
+                let _ = tx2.send(AppEvent::SecretValueLoaded(vault_name, name, value, action));
+            }
+            Err(e) => {
+                let message = forbidden::describe("Failed to get secret", &e, &remedy);
+                let _ = tx2.send(AppEvent::Failed(Failure::new(
+                    format!("Get '{}' from {}", name, vault_name),
+                    message,
+                    &e,
+                    Some(Retry::GetValue(vault_name, name, action)),
+                )));
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Apply a value action once the secret value is available.
+pub fn apply_value_action(
+    app: &mut App,
+    name: &str,
+    value: String,
+    action: ValueAction,
+    cached: bool,
+) {
+    let suffix = if cached { " (cached)" } else { "" };
+    match action {
+        ValueAction::Copy => {
+            app.run_hook(HookEvent::Copy, name, &value);
+            let copied = app.copy_secret(value);
+            app.report(
+                copied.map(|clear| {
+                    format!("Secret '{}' copied to clipboard{}{}", name, suffix, clear)
+                }),
+            );
+        }
+        ValueAction::Preview => {
+            let format = detect_format(&value);
+            app.modal = Some(Modal::Preview {
+                name: name.to_string(),
+                format,
+                lines: highlight(&value, format),
+                scroll: 0,
+            });
+        }
+        ValueAction::Pick => {
+            app.pick_result = Some(value);
+            app.quit = true;
+        }
+        ValueAction::CopyExport => {
+            app.run_hook(HookEvent::Copy, name, &value);
+            let copied = app.copy_secret(export_statement(name, &value));
+            app.report(copied.map(|clear| {
+                format!(
+                    "Export statement for '{}' copied to clipboard{}{}",
+                    name, suffix, clear
+                )
+            }));
+        }
+        ValueAction::CopyDecoded => match decode_base64(&value) {
+            Ok(bytes) => match decoded_text(&bytes) {
+                Some(text) => {
+                    app.run_hook(HookEvent::Copy, name, &value);
+                    let copied = app.copy_secret(text);
+                    app.report(copied.map(|clear| {
+                        format!(
+                            "Decoded secret '{}' copied to clipboard{}{}",
+                            name, suffix, clear
+                        )
+                    }));
+                }
+                None => app.warn(format!(
+                    "Decoded value of '{}' is binary ({} bytes); preview it with B",
+                    name,
+                    bytes.len()
+                )),
+            },
+            Err(e) => app.error(e),
+        },
+        ValueAction::PreviewDecoded => match decode_base64(&value) {
+            Ok(bytes) => {
+                let (format, lines) = match decoded_text(&bytes) {
+                    Some(text) => {
+                        let format = detect_format(&text);
+                        (format, highlight(&text, format))
+                    }
+                    None => (
+                        ValueFormat::Plain,
+                        highlight(&hex_dump(&bytes), ValueFormat::Plain),
+                    ),
+                };
+                app.modal = Some(Modal::Preview {
+                    name: format!("{} (base64-decoded, {} bytes)", name, bytes.len()),
+                    format,
+                    lines,
+                    scroll: 0,
+                });
+            }
+            Err(e) => app.error(e),
+        },
+        ValueAction::ConnString => match ConnStringPicker::parse(name, &value) {
+            Some(picker) => app.modal = Some(Modal::ConnString(picker)),
+            None => {
+                app.warn(format!(
+                    "Secret '{}' is not a recognized SQL, Storage or Service Bus connection string",
+                    name
+                ));
+            }
+        },
+        ValueAction::ViewJson => match JsonViewer::parse(name, &value) {
+            Some(viewer) => app.modal = Some(Modal::JsonViewer(viewer)),
+            None => {
+                app.warn(format!("Secret '{}' is not a JSON object or array", name));
+            }
+        },
+    }
+}
+
+/// Validate a secret name against Key Vault's rules: 1-127 characters, ASCII
+/// alphanumerics and hyphens only.
+pub fn validate_secret_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Name cannot be empty".into());
+    }
+    let len = name.chars().count();
+    if len > 127 {
+        return Err(format!("Name is too long ({}/127 characters)", len));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '-'))
+    {
+        return Err(format!(
+            "Invalid character '{}': only letters, digits and '-' are allowed",
+            c
+        ));
+    }
+    Ok(())
+}
+
+/// Insert pasted text (from bracketed paste) into whichever input currently has focus.
+/// Newlines are kept only for the multi-line value fields.
+pub fn handle_paste(app: &mut App, text: &str) {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let single_line = text.replace('\n', "");
+    match &mut app.modal {
+        Some(Modal::Add {
+            name,
+            value,
+            input_mode,
+            generator: None,
+            file_path,
+            ..
+        }) => match (input_mode, file_path) {
+            (AddInputMode::Name, _) => name.insert_str(single_line.trim()),
+            (AddInputMode::File, Some(path)) => path.insert_str(single_line.trim()),
+            _ => value.insert_str(&text),
+        },
+        Some(Modal::Edit { value, .. }) => value.insert_str(&text),
+        Some(Modal::Export { path, .. }) => path.insert_str(single_line.trim()),
+        Some(Modal::ValueSearch {
+            needle,
+            confirm: false,
+            ..
+        }) => needle.insert_str(&single_line),
+        Some(Modal::Rotation(form)) => form.fields[form.focus].insert_str(single_line.trim()),
+        Some(Modal::Crypto(form)) => match form.focus {
+            CryptoField::Signature => form.signature.insert_str(single_line.trim()),
+            _ => form.input.insert_str(single_line.trim()),
+        },
+        Some(Modal::BulkDelete {
+            confirm_input,
+            started: false,
+            ..
+        }) => confirm_input.push_str(&single_line),
+        Some(_) => {}
+        None => {
+            if app.screen == AppScreen::Secrets && app.search_mode {
+                app.search_query.push_str(&single_line);
+                search_input(app, Instant::now());
+            } else if app.screen == AppScreen::VaultSelection && app.vault_search_mode {
+                app.vault_search_query.push_str(&single_line);
+                apply_vault_search(app);
+            }
+        }
+    }
+}
+
+/// Apply an editing/movement key to a text field. Single-line fields ignore Enter and Up/Down.
+fn edit_text_area(ta: &mut TextArea, code: KeyCode, modifiers: KeyModifiers, multiline: bool) {
+    let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+    let alt = modifiers.contains(KeyModifiers::ALT);
+    match code {
+        KeyCode::Left if ctrl || alt => ta.word_left(),
+        KeyCode::Right if ctrl || alt => ta.word_right(),
+        KeyCode::Char('b') if alt => ta.word_left(),
+        KeyCode::Char('f') if alt => ta.word_right(),
+        KeyCode::Backspace if ctrl || alt => ta.delete_word_back(),
+        KeyCode::Char('w') if ctrl => ta.delete_word_back(),
+        KeyCode::Char('a') if ctrl => ta.home(),
+        KeyCode::Char('e') if ctrl => ta.end(),
+        KeyCode::Left => ta.move_left(),
+        KeyCode::Right => ta.move_right(),
+        KeyCode::Home => ta.home(),
+        KeyCode::End => ta.end(),
+        KeyCode::Backspace => ta.backspace(),
+        KeyCode::Delete => ta.delete(),
+        KeyCode::Up if multiline => ta.move_up(),
+        KeyCode::Down if multiline => ta.move_down(),
+        KeyCode::Enter if multiline => ta.insert_newline(),
+        KeyCode::Char(c) if !ctrl && !alt => ta.insert_char(c),
+        _ => {}
+    }
+}
+
+/// Handle modal keys; background tasks clone tx to avoid move errors.
+pub async fn handle_modal_key(
+    app: &mut App,
+    code: KeyCode,
+    modifiers: KeyModifiers,
+    tx: &EventSender,
+) -> Result<bool, AkvError> {
+    if app.modal.is_none() {
+        return Ok(false);
+    }
+    // Ctrl+V pastes the system clipboard into the focused field
+    if modifiers.contains(KeyModifiers::CONTROL) && code == KeyCode::Char('v') {
+        match app.clipboard.get_contents() {
+            Ok(text) => handle_paste(app, &text),
+            Err(e) => app.error(format!("Clipboard read error: {}", e)),
+        }
+        return Ok(true);
+    }
+    match &mut app.modal {
+        Some(Modal::Add {
+            value,
+            generator: Some(opts),
+            ..
+        }) => {
+            match code {
+                KeyCode::Esc => {
+                    app.modal_close_generator();
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    opts.focus = opts.focus.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => {
+                    opts.focus = (opts.focus + 1) % GeneratorOptions::ROWS;
+                }
+                KeyCode::Left | KeyCode::Char('-') => opts.adjust_length(-1),
+                KeyCode::Right | KeyCode::Char('+') => opts.adjust_length(1),
+                KeyCode::Char(' ') => opts.toggle_focused(),
+                KeyCode::Enter => {
+                    let masked = value.is_masked();
+                    *value = TextArea::from_text(&generate(opts));
+                    value.set_masked(masked);
+                    let message = format!("Generated a {}-character value", opts.length);
+                    app.modal_close_generator();
+                    app.info(message);
+                }
+                _ => {}
+            }
+            Ok(true)
+        }
+        Some(Modal::Add {
+            name,
+            value,
+            input_mode,
+            generator,
+            file_path,
+            encode_base64: encode,
+            status,
+        }) => {
+            let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+            // Enter submits from the single-line fields; the multi-line value field needs Ctrl+S.
+            let submit = (ctrl && code == KeyCode::Char('s'))
+                || (code == KeyCode::Enter && *input_mode != AddInputMode::Value);
+            match code {
+                _ if submit && status.pending => {}
+                _ if submit => {
+                    if let Err(e) = validate_secret_name(&name.text()) {
+                        app.error(e);
+                    } else if let Some((vault_name, vault_uri)) = &app.current_vault {
+                        let secret_name = name.text();
+                        let (secret_value, content_type) = match file_path {
+                            Some(path) => match read_file_value(&path.text(), *encode) {
+                                Ok(file) => (file.value, Some(file.content_type)),
+                                Err(e) => {
+                                    status.error = Some(e);
+                                    return Ok(true);
+                                }
+                            },
+                            None if *encode => (encode_base64(&value.text()), None),
+                            None => (value.text(), None),
+                        };
+                        let vault_name = vault_name.clone();
+                        status.pending = true;
+                        status.error = None;
+                        app.loading = true;
+                        let tx2 = tx.clone();
+                        let remedy = vault_remedy(
+                            &app.vaults,
+                            &vault_name,
+                            app.identity.as_ref(),
+                            Need::WriteSecrets,
+                        );
+                        let store = app.stores.get(vault_uri, &app.credential)?;
+                        app.info("Creating secret...".into());
+                        let quitting = app.tasks.quitting();
+                        let label = format!("Set '{}' in {}", secret_name, vault_name);
+                        let hook = if app.secrets.binary_search(&secret_name).is_ok() {
+                            HookEvent::SecretUpdated
+                        } else {
+                            HookEvent::SecretCreated
+                        };
+                        let hooks = app.hooks.clone();
+                        let hook_value = hooks.wants_value(hook).then(|| secret_value.clone());
+                        app.tasks.spawn_write(label, async move {
+                            match store.set(&secret_name, secret_value, content_type).await {
+                                Ok(()) => {
+                                    hooks.run(
+                                        hook,
+                                        &vault_name,
+                                        &secret_name,
+                                        hook_value.as_deref(),
+                                    );
+                                    let _ = tx2.send(AppEvent::SubmitSucceeded(format!(
+                                        "Secret '{}' created/updated",
+                                        secret_name
+                                    )));
+                                }
+                                Err(e) => {
+                                    let _ = tx2.send(AppEvent::SubmitFailed(forbidden::describe(
+                                        "Failed to set secret",
+                                        &e,
+                                        &remedy,
+                                    )));
+                                }
+                            }
+                            // refresh and cache
+                            if !quitting.load(Ordering::Relaxed) {
+                                let _ = list_secrets_and_cache(
+                                    store.clone(),
+                                    tx2.clone(),
+                                    vault_name.clone(),
+                                )
+                                .await;
+                            }
+                        });
+                    } else {
+                        app.warn("No vault selected".into());
+                    }
+                }
+                KeyCode::Char('g') if ctrl && file_path.is_none() => {
+                    *generator = Some(GeneratorOptions::default());
+                }
+                KeyCode::Char('r') if ctrl => {
+                    value.set_masked(!value.is_masked());
+                }
+                KeyCode::Char('b') if ctrl => {
+                    *encode = !*encode;
+                }
+                KeyCode::Char('o') if ctrl => {
+                    // Switch the value source between the text area and a file on disk
+                    if file_path.take().is_some() {
+                        *input_mode = AddInputMode::Value;
+                    } else {
+                        *file_path = Some(TextArea::default());
+                        *input_mode = AddInputMode::File;
+                    }
+                }
+                KeyCode::Esc => {
+                    app.modal = None;
+                }
+                KeyCode::Tab => {
+                    *input_mode = match (&input_mode, &file_path) {
+                        (AddInputMode::Name, Some(_)) => AddInputMode::File,
+                        (AddInputMode::Name, None) => AddInputMode::Value,
+                        _ => AddInputMode::Name,
+                    };
+                }
+                _ => match (input_mode, file_path) {
+                    (AddInputMode::Name, _) => edit_text_area(name, code, modifiers, false),
+                    (AddInputMode::File, Some(path)) => {
+                        edit_text_area(path, code, modifiers, false)
+                    }
+                    _ => edit_text_area(value, code, modifiers, true),
+                },
+            }
+            Ok(true)
+        }
+        Some(Modal::Edit {
+            name,
+            value,
+            encode_base64: encode,
+            status,
+        }) => {
+            let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+            match code {
+                KeyCode::Esc => {
+                    app.modal = None;
+                }
+                KeyCode::Char('r') if ctrl => {
+                    value.set_masked(!value.is_masked());
+                }
+                KeyCode::Char('b') if ctrl => {
+                    *encode = !*encode;
+                }
+                KeyCode::Char('s') if ctrl && status.pending => {}
+                KeyCode::Char('s') if ctrl => {
+                    if let Some((vault_name, vault_uri)) = &app.current_vault {
+                        let store = app.stores.get(vault_uri, &app.credential)?;
+                        let name_clone = name.clone();
+                        let value_clone = if *encode {
+                            encode_base64(&value.text())
+                        } else {
+                            value.text()
+                        };
+                        let vault_name = vault_name.clone();
+                        status.pending = true;
+                        status.error = None;
+                        app.loading = true;
+                        app.info("Updating secret...".into());
+                        let tx2 = tx.clone();
+                        let remedy = vault_remedy(
+                            &app.vaults,
+                            &vault_name,
+                            app.identity.as_ref(),
+                            Need::WriteSecrets,
+                        );
+                        let quitting = app.tasks.quitting();
+                        let label = format!("Update '{}' in {}", name_clone, vault_name);
+                        let hooks = app.hooks.clone();
+                        let hook_value = hooks
+                            .wants_value(HookEvent::SecretUpdated)
+                            .then(|| value_clone.clone());
+                        app.tasks.spawn_write(label, async move {
+                            match store.set(&name_clone, value_clone, None).await {
+                                Ok(()) => {
+                                    hooks.run(
+                                        HookEvent::SecretUpdated,
+                                        &vault_name,
+                                        &name_clone,
+                                        hook_value.as_deref(),
+                                    );
+                                    let _ = tx2.send(AppEvent::SubmitSucceeded(format!(
+                                        "Secret '{}' updated",
+                                        name_clone
+                                    )));
+                                }
+                                Err(e) => {
+                                    let _ = tx2.send(AppEvent::SubmitFailed(forbidden::describe(
+                                        "Failed to update secret",
+                                        &e,
+                                        &remedy,
+                                    )));
+                                }
+                            }
+                            if !quitting.load(Ordering::Relaxed) {
+                                let _ = list_secrets_and_cache(
+                                    store.clone(),
+                                    tx2.clone(),
+                                    vault_name.clone(),
+                                )
+                                .await;
+                            }
+                        });
+                    } else {
+                        app.warn("No vault selected".into());
+                    }
+                }
+                _ => edit_text_area(value, code, modifiers, true),
+            }
+            Ok(true)
+        }
+        Some(Modal::ConfirmDelete { name, status }) => {
+            match code {
+                KeyCode::Char('y') | KeyCode::Char('Y') if status.pending => {}
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    if let Some((vault_name, vault_uri)) = &app.current_vault {
+                        let store = app.stores.get(vault_uri, &app.credential)?;
+                        let name_clone = name.clone();
+                        let vault_name = vault_name.clone();
+                        status.pending = true;
+                        status.error = None;
+                        app.loading = true;
+                        app.info("Deleting secret...".into());
+                        let tx2 = tx.clone();
+                        let remedy = vault_remedy(
+                            &app.vaults,
+                            &vault_name,
+                            app.identity.as_ref(),
+                            Need::WriteSecrets,
+                        );
+                        let quitting = app.tasks.quitting();
+                        let label = format!("Delete '{}' from {}", name_clone, vault_name);
+                        let hooks = app.hooks.clone();
+                        app.tasks.spawn_write(label, async move {
+                            match store.delete(&name_clone).await {
+                                Ok(_) => {
+                                    hooks.run(
+                                        HookEvent::SecretDeleted,
+                                        &vault_name,
+                                        &name_clone,
+                                        None,
+                                    );
+                                    let _ = tx2.send(AppEvent::SubmitSucceeded(format!(
+                                        "Deleted '{}'. (soft-delete)",
+                                        name_clone
+                                    )));
+                                }
+                                Err(e) => {
+                                    let _ = tx2.send(AppEvent::SubmitFailed(forbidden::describe(
+                                        "Failed to delete",
+                                        &e,
+                                        &remedy,
+                                    )));
+                                }
+                            }
+                            if !quitting.load(Ordering::Relaxed) {
+                                let _ = list_secrets_and_cache(
+                                    store.clone(),
+                                    tx2.clone(),
+                                    vault_name.clone(),
+                                )
+                                .await;
+                            }
+                        });
+                    } else {
+                        app.warn("No vault selected".into());
+                        app.modal = None;
+                    }
+                }
+                KeyCode::Esc | KeyCode::Char('n') => {
+                    app.modal = None;
+                }
+                _ => {}
+            }
+            Ok(true)
+        }
+        Some(Modal::JsonViewer(viewer)) => {
+            match code {
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    app.modal = None;
+                }
+                KeyCode::Down | KeyCode::Char('j') => viewer.move_down(),
+                KeyCode::Up | KeyCode::Char('k') => viewer.move_up(),
+                KeyCode::Right | KeyCode::Char('l') => viewer.expand(),
+                KeyCode::Left | KeyCode::Char('h') => viewer.collapse(),
+                KeyCode::Enter | KeyCode::Char('y') => {
+                    // Enter expands containers; on leaves (or with 'y') it copies the node
+                    let is_container = viewer.selected_row().is_some_and(|r| r.is_container);
+                    if code == KeyCode::Enter && is_container {
+                        viewer.expand();
+                    } else if let (Some(row), Some(text)) =
+                        (viewer.selected_row(), viewer.selected_copy_text())
+                    {
+                        let copied = app.copy_secret(text);
+                        app.report(copied.map(|clear| {
+                            format!("Copied '{}' to clipboard{}", row.pointer, clear)
+                        }));
+                    }
+                }
+                _ => {}
+            }
+            Ok(true)
+        }
+        Some(Modal::ConnString(picker)) => {
+            match code {
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    app.modal = None;
+                }
+                KeyCode::Down | KeyCode::Char('j') => picker.move_down(),
+                KeyCode::Up | KeyCode::Char('k') => picker.move_up(),
+                KeyCode::Enter | KeyCode::Char('y') => {
+                    if let Some(part) = picker.selected_part() {
+                        let key = part.key.clone();
+                        let value = part.value.clone();
+                        let copied = app.copy_secret(value);
+                        app.report(
+                            copied.map(|clear| format!("Copied {} to clipboard{}", key, clear)),
+                        );
+                    }
+                }
+                _ => {}
+            }
+            Ok(true)
+        }
+        Some(Modal::Preview { lines, scroll, .. })
+        | Some(Modal::Changes { lines, scroll })
+        | Some(Modal::Activity { lines, scroll, .. })
+        | Some(Modal::Help { lines, scroll }) => {
+            let max = lines.len().saturating_sub(1) as u16;
+            match code {
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    app.modal = None;
+                }
+                KeyCode::Down | KeyCode::Char('j') => *scroll = (*scroll + 1).min(max),
+                KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+                KeyCode::PageDown => *scroll = (*scroll + 10).min(max),
+                KeyCode::PageUp => *scroll = scroll.saturating_sub(10),
+                KeyCode::Char('g') => *scroll = 0,
+                KeyCode::Char('G') => *scroll = max,
+                _ => {}
+            }
+            Ok(true)
+        }
+        Some(Modal::BulkDelete {
+            names,
+            statuses,
+            confirm_input,
+            started,
+            selected,
+        }) => {
+            // Scrolling stops following the deletion; j/k are typed until it starts
+            let current = selected.unwrap_or_else(|| BulkItemStatus::progress(statuses));
+            let last = names.len().saturating_sub(1);
+            let moved = match code {
+                KeyCode::Down => Some((current + 1).min(last)),
+                KeyCode::Char('j') if *started => Some((current + 1).min(last)),
+                KeyCode::Up => Some(current.saturating_sub(1)),
+                KeyCode::Char('k') if *started => Some(current.saturating_sub(1)),
+                KeyCode::PageDown => Some((current + 10).min(last)),
+                KeyCode::PageUp => Some(current.saturating_sub(10)),
+                _ => None,
+            };
+            if moved.is_some() {
+                *selected = moved;
+                return Ok(true);
+            }
+            if *started {
+                // Deletion runs in the background; the modal only reports progress.
+                if matches!(code, KeyCode::Esc | KeyCode::Enter) {
+                    app.modal = None;
+                }
+                return Ok(true);
+            }
+            match code {
+                KeyCode::Esc => {
+                    app.modal = None;
+                }
+                KeyCode::Backspace => {
+                    confirm_input.pop();
+                }
+                KeyCode::Char(c) => {
+                    confirm_input.push(c);
+                }
+                KeyCode::Enter => {
+                    if confirm_input.as_str() != "yes" {
+                        app.warn("Type 'yes' to confirm bulk delete".into());
+                    } else if let Some((vault_name, vault_uri)) = &app.current_vault {
+                        let store = app.stores.get(vault_uri, &app.credential)?;
+                        let names = names.clone();
+                        let vault_name = vault_name.clone();
+                        *started = true;
+                        app.marked.clear();
+                        app.loading = true;
+                        app.info(format!("Deleting {} secrets...", names.len()));
+                        let tx2 = tx.clone();
+                        let quitting = app.tasks.quitting();
+                        let label = format!("Delete {} secrets from {}", names.len(), vault_name);
+                        let hooks = app.hooks.clone();
+                        app.tasks.spawn_write(label, async move {
+                            let (mut deleted, mut failed) = (0, 0);
+                            for name in &names {
+                                // Quitting: the delete in flight finishes, the rest are left
+                                if quitting.load(Ordering::Relaxed) {
+                                    break;
+                                }
+                                let outcome = match store.delete(name).await {
+                                    Ok(_) => {
+                                        hooks.run(
+                                            HookEvent::SecretDeleted,
+                                            &vault_name,
+                                            name,
+                                            None,
+                                        );
+                                        deleted += 1;
+                                        Ok(())
+                                    }
+                                    Err(e) => {
+                                        debug!("Bulk delete of '{}' failed: {}", name, e);
+                                        failed += 1;
+                                        Err(e.to_string())
+                                    }
+                                };
+                                let _ = tx2.send(AppEvent::BulkDeleteItem(name.clone(), outcome));
+                            }
+                            let msg = if deleted == names.len() {
+                                format!("Deleted {} secrets. (soft-delete)", names.len())
+                            } else {
+                                format!(
+                                    "Deleted {} of {} secrets ({} failed)",
+                                    deleted,
+                                    names.len(),
+                                    failed
+                                )
+                            };
+                            let _ = tx2.send(AppEvent::Finished(msg));
+                            if !quitting.load(Ordering::Relaxed) {
+                                let _ = list_secrets_and_cache(
+                                    store.clone(),
+                                    tx2.clone(),
+                                    vault_name.clone(),
+                                )
+                                .await;
+                            }
+                        });
+                    } else {
+                        app.warn("No vault selected".into());
+                        app.modal = None;
+                    }
+                }
+                _ => {}
+            }
+            Ok(true)
+        }
+        Some(Modal::VaultSwitcher {
+            query,
+            matches,
+            selected,
+            copy,
+        }) => {
+            match code {
+                KeyCode::Esc => {
+                    app.modal = None;
+                }
+                KeyCode::Down | KeyCode::Tab => {
+                    *selected = (*selected + 1).min(matches.len().saturating_sub(1));
+                }
+                KeyCode::Up | KeyCode::BackTab => *selected = selected.saturating_sub(1),
+                KeyCode::Enter => {
+                    if let Some(vault) = matches.get(*selected).cloned() {
+                        let copy = copy.take();
+                        app.modal = None;
+                        match copy {
+                            Some(names) => copy_to_vault(app, names, vault, tx)?,
+                            None => {
+                                app.search_query.clear();
+                                open_vault(app, vault.name, vault.uri, tx)?;
+                            }
+                        }
+                    }
+                }
+                KeyCode::Backspace => {
+                    query.pop();
+                    update_vault_switcher(app);
+                }
+                KeyCode::Char(c) => {
+                    query.push(c);
+                    update_vault_switcher(app);
+                }
+                _ => {}
+            }
+            Ok(true)
+        }
+        Some(Modal::Crypto(form)) => {
+            let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+            match code {
+                KeyCode::Esc => app.modal = None,
+                KeyCode::Tab => form.next_focus(),
+                KeyCode::Enter if !form.pending => run_crypto(app, tx),
+                KeyCode::Char('y') if ctrl => {
+                    if let Some(Ok(output)) = form.result.clone() {
+                        let label = form.op().name();
+                        // Decrypted and unwrapped data is secret: clear it like secret values
+                        let copied = if form.op().takes_base64() {
+                            app.copy_secret(output)
+                        } else {
+                            app.copy_plain(output).map(|()| String::new())
+                        };
+                        app.report(
+                            copied.map(|clear| format!("Copied the {} result{}", label, clear)),
+                        );
+                    }
+                }
+                _ => match form.focus {
+                    CryptoField::Operation => match code {
+                        KeyCode::Left | KeyCode::Up | KeyCode::Char('h' | 'k') => form.cycle_op(-1),
+                        KeyCode::Right | KeyCode::Down | KeyCode::Char('l' | 'j') => {
+                            form.cycle_op(1)
+                        }
+                        _ => {}
+                    },
+                    CryptoField::Input => edit_text_area(&mut form.input, code, modifiers, false),
+                    CryptoField::Signature => {
+                        edit_text_area(&mut form.signature, code, modifiers, false)
+                    }
+                },
+            }
+            Ok(true)
+        }
+        Some(Modal::Rotation(form)) => {
+            let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+            let last = RotationForm::LABELS.len() - 1;
+            match code {
+                KeyCode::Esc => app.modal = None,
+                KeyCode::Char('s') if ctrl && !form.status.pending => save_rotation(app, tx),
+                KeyCode::Tab | KeyCode::Down | KeyCode::Enter => {
+                    form.focus = (form.focus + 1).min(last)
+                }
+                KeyCode::BackTab | KeyCode::Up => form.focus = form.focus.saturating_sub(1),
+                _ => edit_text_area(&mut form.fields[form.focus], code, modifiers, false),
+            }
+            Ok(true)
+        }
+        Some(Modal::Export { vault, names, path }) => {
+            match code {
+                KeyCode::Esc => app.modal = None,
+                KeyCode::Enter => {
+                    let target = path.text().trim().to_string();
+                    if target.is_empty() {
+                        app.warn("Enter a file path for the report".into());
+                    } else {
+                        let (vault, names) = (vault.clone(), names.take());
+                        app.modal = None;
+                        export_report(app, vault, names, expand_path(&target), tx);
+                    }
+                }
+                _ => edit_text_area(path, code, modifiers, false),
+            }
+            Ok(true)
+        }
+        Some(Modal::ValueSearch {
+            needle,
+            names,
+            confirm,
+        }) => {
+            match code {
+                KeyCode::Esc => app.modal = None,
+                KeyCode::Enter if !*confirm => {
+                    if needle.text().is_empty() {
+                        app.warn("Enter the text to look for".into());
+                    } else {
+                        *confirm = true;
+                    }
+                }
+                KeyCode::Char('y') if *confirm => {
+                    let needle = needle.text();
+                    let names = std::mem::take(names);
+                    app.modal = None;
+                    search_values(app, &needle, names, tx)?;
+                }
+                KeyCode::Char('n') if *confirm => app.modal = None,
+                _ if *confirm => {}
+                _ => edit_text_area(needle, code, modifiers, false),
+            }
+            Ok(true)
+        }
+        Some(Modal::ErrorDetails { failure, scroll }) => {
+            let max = failure.chain.len() as u16 + 4;
+            match code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('!') => app.modal = None,
+                KeyCode::Down | KeyCode::Char('j') => *scroll = (*scroll + 1).min(max),
+                KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+                KeyCode::Char('c') => {
+                    let report = failure.report();
+                    let copied = app.copy_plain(report);
+                    app.report(copied.map(|()| "Copied the error details to the clipboard".into()));
+                }
+                KeyCode::Char('r') => {
+                    if let Some(op) = failure.retry.clone() {
+                        app.modal = None;
+                        app.last_failure = None;
+                        retry(app, op, tx)?;
+                    }
+                }
+                _ => {}
+            }
+            Ok(true)
+        }
+        Some(Modal::Tasks { selected }) => {
+            let ids: Vec<u64> = app.tasks.list().iter().map(|t| t.id).collect();
+            match code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('T') => app.modal = None,
+                KeyCode::Down | KeyCode::Char('j') => {
+                    *selected = (*selected + 1).min(ids.len().saturating_sub(1));
+                }
+                KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
+                KeyCode::Char('c') | KeyCode::Delete => {
+                    if let Some(&id) = ids.get(*selected) {
+                        let cancelled = app.tasks.cancel(id);
+                        app.info(if cancelled {
+                            "Task cancelled".into()
+                        } else {
+                            "The task has already ended".into()
                         });
-                    } else {
-                        app.message = Some("No vault selected".into());
                     }
                 }
-                KeyCode::Char(c) => match input_mode {
-                    AddInputMode::Name => name.push(c),
-                    AddInputMode::Value => value.push(c),
-                },
                 _ => {}
             }
             Ok(true)
         }
-        Some(Modal::Edit { name, value }) => {
+        Some(Modal::VaultInfo(_)) => {
+            if matches!(
+                code,
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('i')
+            ) {
+                app.modal = None;
+            }
+            Ok(true)
+        }
+        Some(Modal::TenantPicker { tenants, selected }) => {
             match code {
-                KeyCode::Esc => {
+                KeyCode::Esc | KeyCode::Char('q') => {
                     app.modal = None;
                 }
-                KeyCode::Backspace => {
-                    value.pop();
+                KeyCode::Down | KeyCode::Char('j') => {
+                    *selected = (*selected + 1).min(tenants.len().saturating_sub(1));
                 }
+                KeyCode::Up | KeyCode::Char('k') => *selected = selected.saturating_sub(1),
                 KeyCode::Enter => {
-                    if let Some((vault_name, vault_uri)) = &app.current_vault {
-                        let client = SecretClient::new(vault_uri, app.credential.clone(), None)?;
-                        let client_arc = Arc::new(client);
-                        let name_clone = name.clone();
-                        let value_clone = value.clone();
-                        let vault_name = vault_name.clone();
+                    if let Some(tenant) = tenants.get(*selected).cloned() {
                         app.modal = None;
-                        app.loading = true;
-                        app.message = Some("Updating secret...".into());
-                        let tx2 = tx.clone();
-                        tokio::spawn(async move {
-                            let params = SetSecretParameters {
-                                value: Some(value_clone),
-                                ..Default::default()
-                            };
-                            match params.try_into() {
-                                Ok(p) => match client_arc.set_secret(&name_clone, p, None).await {
-                                    Ok(resp) => {
-                                        let _ = resp.into_body();
-                                        let _ = tx2.send(AppEvent::Message(format!(
-                                            "Secret '{}' updated",
-                                            name_clone
-                                        )));
-                                    }
-                                    Err(e) => {
-                                        let _ = tx2.send(AppEvent::Message(format!(
-                                            "Failed to update secret: {}",
-                                            e
-                                        )));
-                                    }
-                                },
-                                Err(e) => {
-                                    let _ = tx2.send(AppEvent::Message(format!(
-                                        "Failed to prepare secret params: {}",
-                                        e
-                                    )));
-                                }
-                            }
-                            let _ = list_secrets_and_cache(
-                                client_arc.clone(),
-                                tx2.clone(),
-                                vault_name.clone(),
-                            )
-                            .await;
-                        });
-                    } else {
-                        app.message = Some("No vault selected".into());
+                        if let Err(e) = switch_tenant(app, &tenant, tx) {
+                            app.error(format!("Cannot switch tenant: {}", e));
+                        }
                     }
                 }
-                KeyCode::Char(c) => {
-                    value.push(c);
-                }
                 _ => {}
             }
             Ok(true)
         }
-        Some(Modal::ConfirmDelete { name }) => {
-            match code {
-                KeyCode::Char('y') | KeyCode::Char('Y') => {
-                    if let Some((vault_name, vault_uri)) = &app.current_vault {
-                        let client = SecretClient::new(vault_uri, app.credential.clone(), None)?;
-                        let client_arc = Arc::new(client);
-                        let name_clone = name.clone();
-                        let vault_name = vault_name.clone();
-                        app.modal = None;
-                        app.loading = true;
-                        app.message = Some("Deleting secret...".into());
-                        let tx2 = tx.clone();
-                        tokio::spawn(async move {
-                            match client_arc.delete_secret(&name_clone, None).await {
-                                Ok(_) => {
-                                    let _ = tx2.send(AppEvent::Message(format!(
-                                        "Deleted '{}'. (soft-delete)",
-                                        name_clone
-                                    )));
-                                }
-                                Err(e) => {
-                                    let _ = tx2.send(AppEvent::Message(format!(
-                                        "Failed to delete: {}",
-                                        e
-                                    )));
-                                }
-                            }
-                            let _ = list_secrets_and_cache(
-                                client_arc.clone(),
-                                tx2.clone(),
-                                vault_name.clone(),
-                            )
-                            .await;
+        Some(Modal::SignIn(state)) => {
+            match (&*state, code) {
+                (_, KeyCode::Esc | KeyCode::Char('q')) => {
+                    app.modal = None;
+                }
+                (SignInState::Offer { .. } | SignInState::Failed(_), KeyCode::Enter)
+                | (SignInState::Offer { .. }, KeyCode::Char('y')) => {
+                    *state = SignInState::Requesting;
+                    let tx2 = tx.clone();
+                    let tenant = app.tenant.clone();
+                    tokio::spawn(async move {
+                        let _ = tx2.send(match device_code::start(tenant.as_deref()).await {
+                            Ok(code) => AppEvent::DeviceCodeIssued(code),
+                            Err(e) => AppEvent::SignInFailed(e),
                         });
-                    } else {
-                        app.message = Some("No vault selected".into());
-                        app.modal = None;
-                    }
+                    });
                 }
-                KeyCode::Esc | KeyCode::Char('n') => {
-                    app.modal = None;
+                (SignInState::Waiting(code), KeyCode::Char('c')) => {
+                    let user_code = code.user_code.clone();
+                    let copied = app.copy_plain(user_code);
+                    app.report(copied.map(|()| "Copied sign-in code to clipboard".into()));
                 }
                 _ => {}
             }
@@ -351,29 +3123,115 @@ pub async fn handle_modal_key(
     }
 }
 
+/// Sign in to `tenant` with the current credential kind (or the device-code login) and
+/// rediscover vaults there. Cached listings belong to the old tenant and are dropped.
+pub fn switch_tenant(app: &mut App, tenant: &Tenant, tx: &EventSender) -> Result<(), AkvError> {
+    app.credential = match &app.device_login {
+        Some(login) => {
+            let login = Arc::new(login.for_tenant(&tenant.id));
+            app.device_login = Some(login.clone());
+            login
+        }
+        None => credential::create(app.credential_kind, Some(&tenant.id))?,
+    };
+    app.stores.clear();
+    app.tenant = Some(tenant.id.clone());
+    app.token_cache = None;
+    app.identity = None;
+    app.vaults.clear();
+    app.displayed_vaults.clear();
+    app.vault_secret_cache.clear();
+    app.index.clear();
+    app.secret_value_cache.clear();
+    app.tasks.cancel_scoped();
+    app.current_vault = None;
+    app.screen = AppScreen::VaultSelection;
+    start_discovery(
+        app,
+        &format!(
+            "Switched to tenant '{}'. Discovering vaults...",
+            tenant.name
+        ),
+        tx,
+    );
+    Ok(())
+}
+
+/// Discover vaults in the background (answered by `TokenCached` + `VaultsLoaded`). When no
+/// credential can sign in, the failure offers the device-code flow instead.
+pub fn start_discovery(app: &mut App, message: &str, tx: &EventSender) {
+    if app.offline {
+        app.loading = false;
+        apply_vault_search(app);
+        app.warn(format!(
+            "Offline: {} cached vault(s) ({})",
+            app.vaults.len(),
+            OFFLINE_HINT
+        ));
+        return;
+    }
+    app.loading = true;
+    app.info(message.into());
+    let tx2 = tx.clone();
+    if app.demo {
+        app.tasks.spawn("Discover vaults", None, async move {
+            tokio::time::sleep(demo::LATENCY * 4).await;
+            let _ = tx2.send(AppEvent::VaultsLoaded(demo::vaults()));
+        });
+        return;
+    }
+    let cred = app.credential.clone();
+    let subscriptions = app.subscriptions.clone();
+    app.tasks.spawn("Discover vaults", None, async move {
+        debug!("Discover task started");
+        match get_token_then_discover(cred, &subscriptions).await {
+            Ok((token_opt, vaults)) => {
+                if let Some((token, fetched_at, ttl)) = token_opt {
+                    let _ = tx2.send(AppEvent::TokenCached(token, fetched_at, ttl));
+                }
+                let _ = tx2.send(AppEvent::VaultsLoaded(vaults));
+            }
+            Err(e) => {
+                let _ = tx2.send(AppEvent::Failed(Failure::new(
+                    "Discover vaults".into(),
+                    format!("Vault discovery failed: {}", e),
+                    &e,
+                    Some(Retry::Discover),
+                )));
+            }
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::store::{MemoryStore, SecretStore};
 
     // Helper to create a dummy credential (we won't actually use it for network calls here)
-    fn create_dummy_credential() -> Arc<DeveloperToolsCredential> {
+    fn create_dummy_credential() -> Arc<dyn TokenCredential> {
         // DeveloperToolsCredential doesn't have a simple no-op constructor that's public and easy to mock without context,
         // but we only need it to satisfy the struct definition.
         // We can use a real one since we won't call methods on it in these tests.
         // In local dev env this might try to actually authorize if used, but we won't use it.
         // new() returns Result, so we unwrap for tests.
-        DeveloperToolsCredential::new(None).expect("Failed to create dummy credential")
+        azure_identity::DeveloperToolsCredential::new(None)
+            .expect("Failed to create dummy credential")
+    }
+
+    fn latest_toast(app: &App) -> Option<&str> {
+        app.toasts.iter().next_back().map(|t| t.text.as_str())
     }
 
     #[test]
     fn test_apply_search_filtering() {
         let mut app = App::new(create_dummy_credential());
-        app.secrets = vec![
+        app.secrets = Arc::new(vec![
             "production-db-password".to_string(),
             "staging-db-password".to_string(),
             "api-key-google".to_string(),
             "api-key-aws".to_string(),
-        ];
+        ]);
 
         // 1. Search for "db"
         app.search_query = "db".to_string();
@@ -406,6 +3264,680 @@ mod tests {
         assert_eq!(app.displayed_secrets.len(), 4);
     }
 
+    #[test]
+    fn test_cache_invalidation_and_age() {
+        let mut app = App::new(create_dummy_credential());
+        let secret = |name: &str| SecretMeta {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let an_hour_ago = SystemTime::now() - Duration::from_secs(3600);
+        app.cache_listing("kv-a", vec![secret("one")], an_hour_ago);
+        app.cache_listing("kv-b", vec![secret("two")], SystemTime::now());
+        for vault in ["kv-a", "kv-b"] {
+            app.secret_value_cache
+                .insert((vault.to_string(), "one".to_string()), "v".to_string());
+        }
+        assert_eq!(app.cache_age("kv-a").map(format_age), Some("1h".into()));
+        assert_eq!(format_age(Duration::from_secs(59)), "59s");
+        assert_eq!(format_age(Duration::from_secs(12 * 60 + 5)), "12m");
+        assert_eq!(format_age(Duration::from_secs(3 * 86400)), "3d");
+
+        app.invalidate_cache(Some("kv-a"));
+        assert_eq!(app.cache_age("kv-a"), None);
+        assert!(app.index.secrets("kv-a").is_none());
+        assert!(app.cache_age("kv-b").is_some());
+        assert_eq!(app.secret_value_cache.len(), 1);
+
+        app.invalidate_cache(None);
+        assert!(app.vault_secret_cache.is_empty() && app.secret_value_cache.is_empty());
+    }
+
+    #[test]
+    fn test_expiring_dashboard() {
+        let mut app = App::new(create_dummy_credential());
+        let now = OffsetDateTime::now_utc();
+        let secret = |name: &str, days: Option<i64>| SecretMeta {
+            name: name.to_string(),
+            expires: days.map(|d| now + time::Duration::days(d)),
+            ..Default::default()
+        };
+        app.cache_listing(
+            "kv-a",
+            vec![
+                secret("far", Some(90)),
+                secret("soon", Some(10)),
+                secret("never", None),
+            ],
+            SystemTime::now(),
+        );
+        app.cache_listing(
+            "kv-b",
+            vec![secret("expired", Some(-3)), secret("tomorrow", Some(1))],
+            SystemTime::now(),
+        );
+
+        open_expiring(&mut app);
+        assert_eq!(app.screen, AppScreen::Expiring);
+        let names: Vec<&str> = app.expiring.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["expired", "tomorrow", "soon"]);
+        assert_eq!(app.expiring[2].vault, "kv-a");
+        assert_eq!(
+            expires_in(now - time::Duration::days(3), now),
+            "expired 3d ago"
+        );
+        assert_eq!(expires_in(now + time::Duration::hours(5), now), "in 5h");
+    }
+
+    #[test]
+    fn test_listing_changes() {
+        let mut app = App::new(create_dummy_credential());
+        let listing = |names: &[(&str, i64)]| {
+            names
+                .iter()
+                .map(|(n, updated)| SecretMeta {
+                    name: n.to_string(),
+                    updated: time::OffsetDateTime::from_unix_timestamp(*updated).ok(),
+                    ..Default::default()
+                })
+                .collect()
+        };
+        app.current_vault = Some(("kv".into(), "https://kv.vault.azure.net/".into()));
+        app.cache_listing(
+            "kv",
+            listing(&[("a", 1), ("b", 1), ("c", 1)]),
+            SystemTime::now(),
+        );
+        let previous = app.index.secrets("kv").unwrap();
+        app.cache_listing(
+            "kv",
+            listing(&[("a", 1), ("c", 2), ("d", 1)]),
+            SystemTime::now(),
+        );
+
+        app.note_changes("kv", &previous);
+        let changes = &app.changes["kv"];
+        assert_eq!(changes.added, ["d"]);
+        assert_eq!(changes.removed, ["b"]);
+        assert_eq!(changes.changed, ["c"]);
+        assert_eq!(
+            latest_toast(&app),
+            Some("'kv' changed since the cached listing: +1 / -1 / ~1 changed (C: review)")
+        );
+        open_changes(&mut app);
+        assert!(matches!(&app.modal, Some(Modal::Changes { lines, .. }) if lines.len() == 5));
+
+        // Watching starts from a clean slate; an unchanged refresh records nothing
+        toggle_watch(&mut app);
+        assert!(app.changes.is_empty());
+        let previous = app.index.secrets("kv").unwrap();
+        app.note_changes("kv", &previous);
+        assert!(app.changes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_offline_fallback() {
+        let (tx, _rx) = crate::events::channel(crate::events::CAPACITY);
+        let mut app = App::new(create_dummy_credential());
+        app.network_failed("Vault discovery failed: timed out".into());
+        assert!(!app.offline); // nothing cached to fall back to
+
+        let meta = SecretMeta {
+            name: "db-password".into(),
+            ..Default::default()
+        };
+        app.cache_listing("kv-a", vec![meta], SystemTime::now());
+        app.network_failed("Vault discovery failed: timed out".into());
+        assert!(app.offline);
+
+        let uri = "https://kv-a.vault.azure.net/".to_string();
+        open_vault(&mut app, "kv-a".into(), uri.clone(), &tx).unwrap();
+        assert_eq!(*app.displayed_secrets, ["db-password"]);
+        assert!(!app.loading);
+        request_secret_value(&mut app, "db-password".into(), ValueAction::Copy, &tx).unwrap();
+        assert!(latest_toast(&app).unwrap().starts_with("Offline"));
+
+        open_vault(&mut app, "kv-b".into(), uri, &tx).unwrap();
+        assert!(app.secrets.is_empty());
+    }
+
+    #[test]
+    fn test_mouse_selection() {
+        let mut app = App::new(create_dummy_credential());
+        app.screen = AppScreen::Secrets;
+        app.displayed_secrets = Arc::new((0..20).map(|i| format!("secret-{}", i)).collect());
+        app.list_rows = Rect::new(1, 5, 40, 10);
+        let click = |column, row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        let now = Instant::now();
+        assert_eq!(handle_mouse(&mut app, click(3, 7), now), None);
+        assert_eq!(app.selected, 2);
+        // A second click on the same row soon after copies it, like Enter
+        let soon = now + Duration::from_millis(200);
+        assert_eq!(
+            handle_mouse(&mut app, click(3, 7), soon),
+            Some(KeyCode::Enter)
+        );
+        // Clicks on the border don't select anything
+        assert_eq!(handle_mouse(&mut app, click(3, 4), soon), None);
+        assert_eq!(app.selected, 2);
+
+        let wheel = MouseEvent {
+            kind: MouseEventKind::ScrollDown,
+            ..click(3, 7)
+        };
+        handle_mouse(&mut app, wheel, soon);
+        assert_eq!(app.selected, 2 + SCROLL_ROWS);
+    }
+
+    #[test]
+    fn test_message_history() {
+        let mut app = App::new(create_dummy_credential());
+        app.screen = AppScreen::Keys;
+        for i in 0..MESSAGE_HISTORY + 5 {
+            app.success(format!("message {}", i));
+        }
+        app.info("Loading keys...".into());
+        assert_eq!(app.history.len(), MESSAGE_HISTORY);
+        assert_eq!(app.history.front().unwrap().1, "message 5");
+        assert_eq!(app.history.back().unwrap().1, "message 204"); // status lines aren't kept
+
+        open_history(&mut app);
+        open_history(&mut app); // opening it again keeps the way back
+        assert_eq!(app.screen, AppScreen::History);
+        assert_eq!(app.history_back, AppScreen::Keys);
+    }
+
+    #[tokio::test]
+    async fn test_error_details_and_retry() {
+        let e = AkvError {
+            causes: vec!["Internal error (500)".into()],
+            ..AkvError::other("request failed")
+        };
+        let failure = Failure::new(
+            "List keys of kv-a".into(),
+            "Failed to list keys: request failed".into(),
+            &e,
+            Some(Retry::ListKeys("kv-a".into())),
+        );
+        assert_eq!(failure.chain, ["request failed", "Internal error (500)"]);
+        assert!(
+            failure
+                .report()
+                .ends_with("Error: request failed\nCaused by: Internal error (500)\n")
+        );
+
+        let (tx, _rx) = crate::events::channel(crate::events::CAPACITY);
+        let mut app = App::new(create_dummy_credential());
+        open_error_details(&mut app);
+        assert!(app.modal.is_none());
+        app.last_failure = Some(failure);
+        open_error_details(&mut app);
+        assert!(matches!(app.modal, Some(Modal::ErrorDetails { .. })));
+        // The vault the keys were listed for isn't open anymore
+        handle_modal_key(&mut app, KeyCode::Char('r'), KeyModifiers::NONE, &tx)
+            .await
+            .unwrap();
+        assert!(app.modal.is_none());
+        assert_eq!(latest_toast(&app), Some("Open 'kv-a' again to retry"));
+    }
+
+    #[tokio::test]
+    async fn test_failure_categories() {
+        let (tx, _rx) = crate::events::channel(crate::events::CAPACITY);
+        let mut app = App::new(create_dummy_credential());
+        let failure = |status: u16| {
+            let e = AkvError::response(status, "request failed");
+            Failure::new(
+                "List keys of kv-a".into(),
+                "Failed to list keys".into(),
+                &e,
+                Some(Retry::ListKeys("kv-a".into())),
+            )
+        };
+
+        // Throttled: retried automatically, backing off further each time
+        let now = Instant::now();
+        failed(&mut app, failure(429), &tx).unwrap();
+        assert!(latest_toast(&app).unwrap().ends_with("retrying in 5s"));
+        assert!(app.retry_due(now).is_none());
+        assert_eq!(
+            app.retry_due(now + Duration::from_secs(6)),
+            Some(Retry::ListKeys("kv-a".into()))
+        );
+        failed(&mut app, failure(429), &tx).unwrap();
+        assert!(latest_toast(&app).unwrap().ends_with("retrying in 10s"));
+
+        // Unreachable: cached data is shown offline
+        app.vault_secret_cache.insert(
+            "kv-a".into(),
+            VaultCacheEntry {
+                secrets: Arc::default(),
+                refreshed_at: SystemTime::now(),
+            },
+        );
+        let refused = AkvError::new(Category::Network, "connection refused");
+        failed(
+            &mut app,
+            Failure::new(
+                "Discover vaults".into(),
+                "Vault discovery failed".into(),
+                &refused,
+                None,
+            ),
+            &tx,
+        )
+        .unwrap();
+        assert!(app.offline);
+
+        // Token refused: sign in again
+        failed(&mut app, failure(401), &tx).unwrap();
+        assert!(matches!(app.modal, Some(Modal::SignIn(_))));
+        assert_eq!(
+            app.last_failure.map(|f| f.error),
+            Some(AkvError::new(Category::Auth, "request failed (401)"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_vault_switch_aborts_listing() {
+        let (tx, mut rx) = crate::events::channel(crate::events::CAPACITY);
+        let mut app = App::new(create_dummy_credential());
+        app.offline = true;
+        app.current_vault = Some(("kv-slow".into(), "https://kv-slow.vault.azure.net/".into()));
+        // A slow listing of the vault being left
+        let tx2 = tx.clone();
+        app.spawn_vault_task("List kv-slow".into(), async move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            let _ = tx2.send(AppEvent::SecretsUpdated("kv-slow".into(), Arc::default()));
+        });
+        assert_eq!(app.tasks.running(), 1);
+
+        let uri = "https://kv-b.vault.azure.net/".to_string();
+        open_vault(&mut app, "kv-b".into(), uri, &tx).unwrap();
+        assert_eq!(app.tasks.running(), 0);
+        drop(tx);
+        // The aborted task dropped its sender without reporting
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[test]
+    fn test_marking_and_action_targets() {
+        let mut app = App::new(create_dummy_credential());
+        app.secrets = Arc::new(vec![
+            "api-key-aws".to_string(),
+            "api-key-google".to_string(),
+            "db-password".to_string(),
+        ]);
+        apply_search(&mut app);
+
+        // Without marks, actions target the selection
+        assert_eq!(app.action_targets(), vec!["api-key-aws".to_string()]);
+
+        // Space toggles the mark on the selection
+        app.toggle_mark_selected();
+        assert!(app.marked.contains("api-key-aws"));
+        app.toggle_mark_selected();
+        assert!(app.marked.is_empty());
+
+        // '*' marks everything matching the filter, and only that
+        app.search_query = "api".to_string();
+        apply_search(&mut app);
+        app.toggle_mark_all_displayed();
+        assert_eq!(
+            app.action_targets(),
+            vec!["api-key-aws".to_string(), "api-key-google".to_string()]
+        );
+
+        // '*' again clears the filtered marks
+        app.toggle_mark_all_displayed();
+        assert!(app.marked.is_empty());
+    }
+
+    #[test]
+    fn test_sort_secrets() {
+        let mut app = App::new(create_dummy_credential());
+        let meta = |name: &str, updated: Option<i64>, expires: Option<i64>| SecretMeta {
+            name: name.to_string(),
+            updated: updated.and_then(|t| time::OffsetDateTime::from_unix_timestamp(t).ok()),
+            expires: expires.and_then(|t| time::OffsetDateTime::from_unix_timestamp(t).ok()),
+            ..Default::default()
+        };
+        app.current_vault = Some(("kv".into(), "https://kv.vault.azure.net/".into()));
+        app.cache_listing(
+            "kv",
+            vec![
+                meta("api-key", Some(300), None),
+                meta("db-password", Some(100), Some(50)),
+                meta("legacy", None, Some(20)),
+            ],
+            SystemTime::now(),
+        );
+        app.secrets = app.vault_secret_cache["kv"].secrets.clone();
+        apply_search(&mut app);
+        // Unfiltered and by name, the list is the cached listing itself
+        assert!(Arc::ptr_eq(&app.displayed_secrets, &app.secrets));
+        app.selected = 1;
+
+        // Newest change first; the selection follows its secret
+        app.cycle_sort(false);
+        assert_eq!(*app.displayed_secrets, ["api-key", "db-password", "legacy"]);
+        assert_eq!(app.selected, 1);
+        app.cycle_sort(true);
+        assert_eq!(*app.displayed_secrets, ["db-password", "api-key", "legacy"]);
+        assert_eq!(app.selected_name().as_deref(), Some("db-password"));
+
+        // Soonest expiry first, secrets that never expire last
+        app.cycle_sort(false);
+        assert_eq!(app.sort, SortKey::Expires);
+        assert_eq!(*app.displayed_secrets, ["legacy", "db-password", "api-key"]);
+        app.cycle_sort(false);
+        app.cycle_sort(true);
+        assert_eq!(*app.displayed_secrets, ["legacy", "db-password", "api-key"]);
+    }
+
+    #[test]
+    fn test_grouped_secrets() {
+        let mut app = App::new(create_dummy_credential());
+        app.screen = AppScreen::Secrets;
+        app.secrets = Arc::new(
+            [
+                "api-key",
+                "pay--db--password",
+                "pay--db--user",
+                "pay--token",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        );
+        apply_search(&mut app);
+        app.selected = 2; // pay--db--user
+        app.toggle_grouped();
+        // The selected secret's groups were opened for it
+        assert_eq!(app.selected_name().as_deref(), Some("pay--db--user"));
+        assert_eq!(app.secret_rows(), 6);
+
+        app.collapse_selected(); // to "pay--db--"
+        app.collapse_selected(); // closes it
+        assert_eq!(app.secret_rows(), 4);
+        assert!(app.toggle_selected_group());
+        app.expand_all(false);
+        assert_eq!(app.secret_rows(), 2);
+        assert_eq!(app.tree[app.selected].key(), "pay--");
+        app.expand_selected();
+        app.expand_selected(); // into the open group
+        assert_eq!(app.tree[app.selected].key(), "pay--db--");
+
+        // Searching opens the groups of the matches
+        app.expand_all(false);
+        app.search_query = "user".into();
+        apply_search(&mut app);
+        let rows: Vec<&str> = app.tree.iter().map(|r| r.key()).collect();
+        assert_eq!(rows, ["pay--", "pay--db--", "pay--db--user"]);
+    }
+
+    #[test]
+    fn test_secret_count() {
+        let mut app = App::new(create_dummy_credential());
+        app.secrets = Arc::new(
+            (0..412)
+                .map(|i| format!("{}-{:03}", if i < 37 { "db" } else { "api" }, i))
+                .collect(),
+        );
+        app.listing_partial = true;
+        apply_search(&mut app);
+        assert_eq!(app.secret_count(), "412+ secrets");
+
+        app.search_query = "db".into();
+        apply_search(&mut app);
+        app.listing_partial = false;
+        assert_eq!(app.secret_count(), "37 / 412 secrets");
+    }
+
+    #[test]
+    fn test_merge_listed() {
+        let mut app = App::new(create_dummy_credential());
+        app.current_vault = Some(("kv-a".into(), "https://kv-a.vault.azure.net/".into()));
+        merge_listed(&mut app, "kv-a", vec!["db-user".into(), "api-key".into()]);
+        app.selected = 1; // db-user
+        merge_listed(&mut app, "kv-a", vec!["cert".into(), "db-user".into()]);
+        merge_listed(&mut app, "kv-b", vec!["other".into()]);
+        assert_eq!(*app.secrets, ["api-key", "cert", "db-user"]);
+        assert_eq!(app.selected_name().as_deref(), Some("db-user"));
+    }
+
+    #[tokio::test]
+    async fn test_background_search() {
+        let (tx, mut rx) = crate::events::channel(crate::events::CAPACITY);
+        let mut app = App::new(create_dummy_credential());
+        app.secrets = Arc::new(
+            (0..BACKGROUND_SEARCH_MIN)
+                .map(|i| format!("secret-{}", i))
+                .collect(),
+        );
+        apply_search(&mut app);
+
+        // Typing only schedules the search of a large vault
+        let now = Instant::now();
+        app.search_query = "secret-1999".into();
+        search_input(&mut app, now);
+        assert_eq!(app.displayed_secrets.len(), BACKGROUND_SEARCH_MIN);
+        assert!(!app.search_due(now));
+        assert!(app.search_due(now + SEARCH_DEBOUNCE));
+
+        start_search(&mut app, &tx);
+        let Some(AppEvent::SearchResults(generation, names)) = rx.recv().await else {
+            panic!("no search results");
+        };
+        // Results of a search overtaken by another one are dropped
+        search_results(&mut app, generation - 1, Vec::new());
+        assert_eq!(app.displayed_secrets.len(), BACKGROUND_SEARCH_MIN);
+        search_results(&mut app, generation, names);
+        assert_eq!(app.displayed_secrets[0], "secret-1999");
+    }
+
+    #[tokio::test]
+    async fn test_value_search() {
+        let (tx, _rx) = crate::events::channel(crate::events::CAPACITY);
+        let mut app = App::new(create_dummy_credential());
+        app.current_vault = Some(("kv-a".into(), "https://kv-a.vault.azure.net/".into()));
+        app.secrets = Arc::new(vec!["api-key".into(), "db-host".into(), "db-url".into()]);
+        apply_search(&mut app);
+
+        // Nothing is fetched without the explicit confirmation
+        open_value_search(&mut app);
+        handle_paste(&mut app, "old-sql.example.com");
+        handle_modal_key(&mut app, KeyCode::Enter, KeyModifiers::NONE, &tx)
+            .await
+            .unwrap();
+        assert!(matches!(
+            &app.modal,
+            Some(Modal::ValueSearch { names, confirm: true, .. }) if names.len() == 3
+        ));
+        handle_modal_key(&mut app, KeyCode::Char('n'), KeyModifiers::NONE, &tx)
+            .await
+            .unwrap();
+        assert!(app.modal.is_none());
+        assert_eq!(app.tasks.list().len(), 0);
+
+        // Matches narrow the list until cleared; results of another vault are ignored
+        show_value_matches(&mut app, "kv-b".into(), vec!["api-key".into()], 0);
+        assert!(app.value_matches.is_none());
+        show_value_matches(&mut app, "kv-a".into(), vec!["db-url".into()], 1);
+        assert_eq!(*app.displayed_secrets, ["db-url"]);
+        assert_eq!(app.secret_count(), "1 / 3 secrets");
+        clear_value_search(&mut app);
+        assert_eq!(app.displayed_secrets.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_requests_go_through_store() {
+        let (tx, mut rx) = crate::events::channel(crate::events::CAPACITY);
+        let mut app = App::new(create_dummy_credential());
+        let uri = "https://kv-a.vault.azure.net/";
+        let store = Arc::new(MemoryStore::with(&[
+            ("api-key", "abc"),
+            ("db-url", "postgres://old-sql.example.com"),
+        ]));
+        app.stores.insert(uri, store.clone());
+        app.current_vault = Some(("kv-a".into(), uri.into()));
+
+        search_values(
+            &mut app,
+            "OLD-SQL",
+            vec!["api-key".into(), "db-url".into()],
+            &tx,
+        )
+        .unwrap();
+        assert!(matches!(
+            rx.recv().await,
+            Some(AppEvent::ValueSearchDone(vault, matches, 0)) if vault == "kv-a" && matches == ["db-url"]
+        ));
+
+        app.modal = Some(Modal::confirm_delete("api-key".into()));
+        handle_modal_key(&mut app, KeyCode::Char('y'), KeyModifiers::NONE, &tx)
+            .await
+            .unwrap();
+        assert!(matches!(
+            rx.recv().await,
+            Some(AppEvent::SubmitSucceeded(_))
+        ));
+        // The vault is listed again after the write
+        assert!(matches!(
+            rx.recv().await,
+            Some(AppEvent::CacheVaultSecrets(..))
+        ));
+        assert!(matches!(
+            rx.recv().await,
+            Some(AppEvent::SecretsUpdated(_, names)) if *names == ["db-url"]
+        ));
+        assert!(store.get("api-key").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_marked_secrets_copied_and_exported() {
+        let (tx, mut rx) = crate::events::channel(crate::events::CAPACITY);
+        let mut app = App::new(create_dummy_credential());
+        let (uri_a, uri_b) = (
+            "https://kv-a.vault.azure.net/",
+            "https://kv-b.vault.azure.net/",
+        );
+        let source = Arc::new(MemoryStore::with(&[
+            ("api-key", "abc"),
+            ("db-url", "postgres://db"),
+            ("smtp-password", "mail"),
+        ]));
+        let dest = Arc::new(MemoryStore::with(&[("api-key", "old")]));
+        app.stores.insert(uri_a, source);
+        app.stores.insert(uri_b, dest.clone());
+        app.state.record_recent("kv-b", uri_b);
+        app.state.record_recent("kv-a", uri_a);
+        app.current_vault = Some(("kv-a".into(), uri_a.into()));
+        app.secrets = Arc::new(vec![
+            "api-key".into(),
+            "db-url".into(),
+            "smtp-password".into(),
+        ]);
+        apply_search(&mut app);
+        app.marked = ["api-key".to_string(), "db-url".to_string()].into();
+
+        // Only the marked secrets are exported
+        open_export(&mut app, Some("kv-a".into()));
+        assert!(matches!(
+            &app.modal,
+            Some(Modal::Export { names: Some(names), .. }) if *names == ["api-key", "db-url"]
+        ));
+
+        // The current vault is no target
+        open_copy_to_vault(&mut app);
+        let Some(Modal::VaultSwitcher { matches, .. }) = &app.modal else {
+            panic!("vault picker not open");
+        };
+        let names: Vec<&str> = matches.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, ["kv-b"]);
+        handle_modal_key(&mut app, KeyCode::Enter, KeyModifiers::NONE, &tx)
+            .await
+            .unwrap();
+        assert!(matches!(
+            rx.recv().await,
+            Some(AppEvent::Finished(msg)) if msg == "Copied 2 secret(s) to kv-b"
+        ));
+        assert_eq!(dest.get("api-key").await.unwrap(), "abc");
+        assert_eq!(dest.get("db-url").await.unwrap(), "postgres://db");
+        assert!(dest.get("smtp-password").await.is_err());
+    }
+
+    #[test]
+    fn test_validate_secret_name() {
+        assert!(validate_secret_name("prod-db-password-01").is_ok());
+        assert!(validate_secret_name(&"a".repeat(127)).is_ok());
+        assert!(validate_secret_name("").is_err());
+        assert!(validate_secret_name(&"a".repeat(128)).is_err());
+        assert!(validate_secret_name("db_password").is_err());
+        assert!(validate_secret_name("db.password").is_err());
+        assert!(validate_secret_name("pässword").is_err());
+    }
+
+    #[test]
+    fn test_paste_into_modal_fields() {
+        let mut app = App::new(create_dummy_credential());
+        app.modal = Some(Modal::add());
+        // Names are single-line: newlines are dropped
+        handle_paste(&mut app, "db-\r\npassword\n");
+        if let Some(Modal::Add { input_mode, .. }) = &mut app.modal {
+            *input_mode = AddInputMode::Value;
+        }
+        handle_paste(&mut app, "-----BEGIN KEY-----\r\nabc\r\n-----END KEY-----");
+        match &app.modal {
+            Some(Modal::Add { name, value, .. }) => {
+                assert_eq!(name.text(), "db-password");
+                assert_eq!(value.text(), "-----BEGIN KEY-----\nabc\n-----END KEY-----");
+            }
+            other => panic!("unexpected modal: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_clipboard_clear_timer() {
+        let mut app = App::new(create_dummy_credential());
+        let now = Instant::now();
+        assert!(!app.clipboard_clear_due(now));
+
+        app.arm_clipboard_clear(now);
+        assert!(!app.clipboard_clear_due(now + Duration::from_secs(29)));
+        assert!(app.clipboard_clear_due(now + DEFAULT_CLIPBOARD_CLEAR));
+
+        // Disabled: copying never schedules a clear
+        app.clipboard_clear_after = None;
+        app.arm_clipboard_clear(now);
+        assert!(!app.clipboard_clear_due(now + Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_clipboard_cleared_only_if_unchanged() {
+        let mut app = App::new(create_dummy_credential());
+        app.clipboard = ClipboardBackend::Memory;
+        app.copy_secret("s3cret".into()).unwrap();
+        app.clear_clipboard();
+        assert_eq!(app.clipboard.get_contents().unwrap(), "");
+        assert_eq!(latest_toast(&app), Some("Clipboard cleared"));
+
+        // Something else was copied before the timeout: left alone, and no toast
+        app.copy_secret("s3cret".into()).unwrap();
+        app.clipboard.set_contents("meeting notes".into()).unwrap();
+        app.info("Copied".into());
+        app.clear_clipboard();
+        assert_eq!(app.clipboard.get_contents().unwrap(), "meeting notes");
+        assert_eq!(latest_toast(&app), Some("Copied"));
+        assert!(app.clipboard_clear_at.is_none());
+    }
+
     #[test]
     fn test_token_should_refresh() {
         let mut app = App::new(create_dummy_credential());
@@ -445,4 +3977,85 @@ mod tests {
         });
         assert!(app.token_should_refresh());
     }
+
+    #[test]
+    fn test_apply_vault_search_fields() {
+        let mut app = App::new(create_dummy_credential());
+        let vault = |name: &str, sub: &str, rg: &str| Vault {
+            name: name.to_string(),
+            uri: format!("https://{}.vault.azure.net/", name),
+            alias: None,
+            subscription: Some(sub.to_string()),
+            resource_group: Some(rg.to_string()),
+            location: None,
+            properties: None,
+        };
+        app.vaults = vec![
+            vault("kv-app-01", "Production", "rg-app-weu"),
+            vault("kv-app-02", "Development", "rg-app-neu"),
+            vault("kv-shared", "Production", "rg-platform"),
+        ];
+        app.favorites = vec!["kv-shared".to_string()];
+
+        // No query: favorites first, then discovery order
+        apply_vault_search(&mut app);
+        let names: Vec<&str> = app
+            .displayed_vaults
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect();
+        assert_eq!(names, ["kv-shared", "kv-app-01", "kv-app-02"]);
+
+        app.vault_search_query = "development".to_string();
+        apply_vault_search(&mut app);
+        assert_eq!(app.displayed_vaults.len(), 1);
+        assert_eq!(app.displayed_vaults[0].name, "kv-app-02");
+
+        app.vault_search_query = "platform".to_string();
+        apply_vault_search(&mut app);
+        assert_eq!(app.displayed_vaults.len(), 1);
+        assert_eq!(app.displayed_vaults[0].name, "kv-shared");
+
+        // Aliases are searchable too
+        app.vaults[0].alias = Some("payments".to_string());
+        app.vault_search_query = "paymnts".to_string();
+        apply_vault_search(&mut app);
+        assert_eq!(app.displayed_vaults[0].name, "kv-app-01");
+
+        // Hidden vaults drop out of the list unless explicitly shown
+        app.vault_search_query.clear();
+        app.state.hidden = vec!["kv-app-02".to_string()];
+        apply_vault_search(&mut app);
+        assert_eq!(app.displayed_vaults.len(), 2);
+        app.show_hidden = true;
+        apply_vault_search(&mut app);
+        assert_eq!(app.displayed_vaults.len(), 3);
+    }
+
+    #[test]
+    fn test_vault_switcher_candidates() {
+        let mut app = App::new(create_dummy_credential());
+        app.state
+            .record_recent("kv-dev", "https://kv-dev.vault.azure.net/");
+        app.state
+            .record_recent("kv-prod", "https://kv-prod.vault.azure.net/");
+
+        // Before discovery, recently opened vaults are offered (most recent first)
+        open_vault_switcher(&mut app);
+        let Some(Modal::VaultSwitcher { matches, .. }) = &app.modal else {
+            panic!("switcher not open");
+        };
+        let names: Vec<&str> = matches.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, ["kv-prod", "kv-dev"]);
+
+        if let Some(Modal::VaultSwitcher { query, .. }) = &mut app.modal {
+            query.push_str("dev");
+        }
+        update_vault_switcher(&mut app);
+        let Some(Modal::VaultSwitcher { matches, .. }) = &app.modal else {
+            panic!("switcher not open");
+        };
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].name, "kv-dev");
+    }
 }
@@ -0,0 +1,132 @@
+use crate::model::SecretMeta;
+
+/// A compliance problem with a secret's attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Issue {
+    NoExpiry,
+    NoContentType,
+    DisabledReferenced, // disabled, but its name matches `audit_referenced`
+}
+
+impl Issue {
+    pub fn label(self) -> &'static str {
+        match self {
+            Issue::NoExpiry => "no expiry",
+            Issue::NoContentType => "no content type",
+            Issue::DisabledReferenced => "disabled but referenced",
+        }
+    }
+}
+
+/// A flagged secret (the audit view `A` and `akv audit`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub vault: String,
+    pub name: String,
+    pub issues: Vec<Issue>,
+}
+
+/// Issues of one secret. `referenced` are name patterns (`*` matches anything,
+/// case-insensitive) of secrets that applications still use.
+pub fn check(meta: &SecretMeta, referenced: &[String]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if meta.expires.is_none() {
+        issues.push(Issue::NoExpiry);
+    }
+    if meta.content_type.as_deref().is_none_or(str::is_empty) {
+        issues.push(Issue::NoContentType);
+    }
+    if !meta.enabled && referenced.iter().any(|p| matches(p, &meta.name)) {
+        issues.push(Issue::DisabledReferenced);
+    }
+    issues
+}
+
+/// Flagged secrets of the given (vault, metadata) pairs, sorted by vault and name.
+pub fn audit<'a>(
+    secrets: impl Iterator<Item = (&'a str, &'a SecretMeta)>,
+    referenced: &[String],
+) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = secrets
+        .filter_map(|(vault, meta)| {
+            let issues = check(meta, referenced);
+            (!issues.is_empty()).then(|| Finding {
+                vault: vault.to_string(),
+                name: meta.name.clone(),
+                issues,
+            })
+        })
+        .collect();
+    findings.sort_by(|a, b| (&a.vault, &a.name).cmp(&(&b.vault, &b.name)));
+    findings
+}
+
+/// Glob-style match where `*` stands for any run of characters.
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let name = name.to_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, rest) = parts.split_first().unwrap();
+    let Some(mut remaining) = name.strip_prefix(first) else {
+        return false;
+    };
+    let Some((last, middle)) = rest.split_last() else {
+        return remaining.is_empty(); // no `*`: exact match
+    };
+    for part in middle {
+        match remaining.find(part) {
+            Some(i) => remaining = &remaining[i + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_checks() {
+        assert!(matches("db-*", "DB-password"));
+        assert!(matches("*-conn-*", "orders-conn-string"));
+        assert!(matches("api-key", "api-key"));
+        assert!(!matches("api-key", "api-key-old"));
+        assert!(!matches("*-prod", "prod-db"));
+
+        let referenced = vec!["db-*".to_string()];
+        let compliant = SecretMeta {
+            name: "api-key".into(),
+            enabled: true,
+            content_type: Some("text/plain".into()),
+            expires: Some(time::OffsetDateTime::now_utc()),
+            ..Default::default()
+        };
+        let disabled = SecretMeta {
+            name: "db-password".into(),
+            enabled: false,
+            ..compliant.clone()
+        };
+        let bare = SecretMeta {
+            name: "legacy".into(),
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(check(&compliant, &referenced).is_empty());
+        assert_eq!(check(&disabled, &referenced), [Issue::DisabledReferenced]);
+        assert_eq!(
+            check(&bare, &referenced),
+            [Issue::NoExpiry, Issue::NoContentType]
+        );
+
+        let findings = audit(
+            [("kv-b", &bare), ("kv-a", &compliant), ("kv-a", &disabled)].into_iter(),
+            &referenced,
+        );
+        let flagged: Vec<(&str, &str)> = findings
+            .iter()
+            .map(|f| (f.vault.as_str(), f.name.as_str()))
+            .collect();
+        assert_eq!(flagged, [("kv-a", "db-password"), ("kv-b", "legacy")]);
+    }
+}
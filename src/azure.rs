@@ -1,35 +1,153 @@
+//! Azure access: sign-in, vault discovery through ARM, and the secrets and keys clients.
+//!
+//! Keys go through [`KeyClient`], a small hand-written client for the keys REST API, rather
+//! than `azure_security_keyvault_keys::KeyClient`. The keys SDK crate isn't a dependency
+//! (it couldn't be fetched in the build environment this was written in), and the secrets
+//! SDK doesn't expose its pipeline. `KeyClient` therefore builds its own pipeline with the
+//! same options and authorization policy, so keys get the retries, throttling handling and
+//! API log the secrets do. It only covers what the keys view and `akv crypto` need (list,
+//! get, rotation policy and the key operations); moving to the SDK client only touches
+//! `KeyClient` and its callers in this module and `crypto.rs`.
+
 use std::convert::TryInto;
 use std::error::Error;
 use std::process::Command;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
 
+use async_trait::async_trait;
 use azure_core::credentials::TokenCredential;
-use azure_identity::DeveloperToolsCredential;
-use azure_security_keyvault_secrets::{ResourceExt, SecretClient};
-use futures::{TryStreamExt, future::join_all};
-use reqwest::Client;
+use azure_core::error::ErrorKind;
+use azure_core::http::policies::auth::BearerTokenAuthorizationPolicy;
+use azure_core::http::{
+    ClientOptions, Context, ExponentialRetryOptions, Method, Pipeline, Request, RetryOptions,
+    Transport, Url,
+};
+use azure_security_keyvault_secrets::{
+    ResourceExt, SecretClient, SecretClientOptions,
+    models::{Secret, SecretProperties, SetSecretParameters},
+};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use futures::{StreamExt, TryStreamExt, future::join_all, stream};
+use reqwest::{Certificate, Client};
 use serde_json::Value;
 use time::OffsetDateTime;
-use tokio::sync::Semaphore;
-use tokio::sync::mpsc::UnboundedSender;
 use tokio::task;
-use tracing::debug;
+use tracing::{debug, warn};
 
-use crate::model::AppEvent;
+use crate::api_log::{self, ApiCallPolicy, TryCountPolicy};
+use crate::audit;
+use crate::cloud::cloud;
+use crate::config::{HttpConfig, TlsConfig};
+use crate::encoding::expand_path;
+use crate::error::AkvError;
+use crate::model::{
+    Access, ActivityEntry, Identity, KeyInfo, SecretMeta, SecretVersion, Tenant, Vault,
+    VaultProperties,
+};
+use crate::ratelimit::{RateLimitPolicy, throttle};
+use crate::rotation::RotationPolicy;
+use crate::store::SecretStore;
 
 const API_VERSION_SUBSCRIPTIONS: &str = "2020-01-01";
 // TODO: Update to 2026-02-01 before Feb 27, 2027 to address RBAC transition.
+const API_VERSION_TENANTS: &str = "2022-12-01";
 const API_VERSION_VAULTS: &str = "2025-05-01";
+const API_VERSION_PERMISSIONS: &str = "2022-04-01";
+const SET_SECRET_ACTION: &str = "Microsoft.KeyVault/vaults/secrets/setSecret/action";
+const ACTIVITY_TIMESPAN: &str = "P30D"; // how far back the activity log looks
+const API_VERSION_KEYS: &str = "7.5";
+
+/// HTTP client and retry budget shared by ARM calls and the Key Vault SDK.
+struct Http {
+    client: Client,
+    retries: u32,
+}
+
+static HTTP: OnceLock<Http> = OnceLock::new();
+
+/// Use `client` (e.g. with extra root certificates and timeouts) and retry failed requests
+/// up to `retries` times. Must be called before the first request.
+pub fn set_http_client(client: Client, retries: u32) {
+    let _ = HTTP.set(Http { client, retries });
+}
+
+fn http() -> &'static Http {
+    HTTP.get_or_init(|| Http {
+        client: Client::new(),
+        retries: 3,
+    })
+}
+
+pub fn http_client() -> Client {
+    http().client.clone()
+}
+
+/// Build the shared HTTP client from the `[http]` and `[tls]` settings.
+pub fn build_http_client(http: &HttpConfig, tls: &TlsConfig) -> Result<Client, String> {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(http.timeout_secs))
+        .connect_timeout(Duration::from_secs(http.connect_timeout_secs));
+    if let Some(path) = &tls.ca_bundle {
+        let path = expand_path(&path.to_string_lossy());
+        let pem = std::fs::read(&path)
+            .map_err(|e| format!("Cannot read CA bundle {}: {}", path.display(), e))?;
+        let certs = Certificate::from_pem_bundle(&pem)
+            .map_err(|e| format!("Invalid CA bundle {}: {}", path.display(), e))?;
+        if certs.is_empty() {
+            return Err(format!("No certificates found in {}", path.display()));
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    if tls.no_proxy {
+        builder = builder.no_proxy();
+    }
+    if tls.accept_invalid_certs {
+        warn!("TLS certificate verification is disabled (tls.accept_invalid_certs)");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Cannot create HTTP client: {}", e))
+}
+
+/// SDK pipeline options using the shared HTTP client and retry budget.
+pub fn client_options() -> ClientOptions {
+    ClientOptions {
+        transport: Some(Transport::new(Arc::new(http_client()))),
+        retry: RetryOptions::exponential(ExponentialRetryOptions {
+            max_retries: http().retries,
+            ..Default::default()
+        }),
+        per_call_policies: vec![Arc::new(ApiCallPolicy)],
+        per_try_policies: vec![Arc::new(RateLimitPolicy), Arc::new(TryCountPolicy)],
+        ..Default::default()
+    }
+}
+
+/// Key Vault client that goes through the shared HTTP client.
+pub fn secret_client(
+    uri: &str,
+    credential: Arc<dyn TokenCredential>,
+) -> azure_core::Result<SecretClient> {
+    let options = SecretClientOptions {
+        client_options: client_options(),
+        ..Default::default()
+    };
+    SecretClient::new(uri, credential, Some(options))
+}
 
 /// Refresh token and return (token_string, fetched_at, ttl).
 /// Uses the SDK get_token and reads expires_on (OffsetDateTime) when available.
 pub async fn refresh_token(
-    credential: Arc<DeveloperToolsCredential>,
-) -> Result<(String, Instant, Duration), Box<dyn Error>> {
+    credential: Arc<dyn TokenCredential>,
+) -> Result<(String, Instant, Duration), AkvError> {
     debug!("Refreshing token via SDK");
     let token_response = credential
-        .get_token(&["https://management.azure.com/.default"], None)
+        .get_token(&[&cloud().management_scope()], None)
         .await?;
     let token_str = token_response.token.secret().to_string();
 
@@ -44,43 +162,212 @@ pub async fn refresh_token(
     Ok((token_str, fetched_at, ttl))
 }
 
+/// Whether `e` means no credential could produce a token (e.g. not logged in to az / azd).
+pub fn is_credential_error(e: &(dyn Error + 'static)) -> bool {
+    e.downcast_ref::<azure_core::Error>()
+        .is_some_and(|e| matches!(e.kind(), ErrorKind::Credential))
+}
+
+/// Whether `e` means Azure couldn't be reached at all (DNS, connection, timeout), as opposed
+/// to an error response.
+pub fn is_network_error(e: &(dyn Error + 'static)) -> bool {
+    let mut source = Some(e);
+    while let Some(e) = source {
+        if let Some(e) = e.downcast_ref::<reqwest::Error>()
+            && (e.is_connect() || e.is_timeout())
+        {
+            return true;
+        }
+        if let Some(e) = e.downcast_ref::<azure_core::Error>()
+            && matches!(e.kind(), ErrorKind::Io)
+        {
+            return true;
+        }
+        if e.is::<std::io::Error>() {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
+
+/// The principal a JWT access token belongs to. Claims are only decoded for display, never
+/// trusted: the token was obtained from our own credential.
+pub fn token_identity(token: &str) -> Option<Identity> {
+    let claims = token_claims(token)?;
+    let principal = [
+        "upn",
+        "preferred_username",
+        "unique_name",
+        "app_displayname",
+        "appid",
+    ]
+    .iter()
+    .find_map(|claim| claims[claim].as_str())?;
+    Some(Identity {
+        principal: principal.to_string(),
+        tenant: claims["tid"].as_str().map(String::from),
+    })
+}
+
+/// Decoded (unverified) payload of a JWT.
+fn token_claims(token: &str) -> Option<Value> {
+    let payload = token.split('.').nth(1)?;
+    serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?).ok()
+}
+
+/// Whether the signed-in principal may change a vault's secrets, checked when the vault is
+/// opened. RBAC vaults are asked for the caller's effective data actions; with access
+/// policies only the principal's own policy is visible, so None (unknown) unless it grants
+/// `set` (a group may grant more).
+pub async fn probe_access(
+    credential: Arc<dyn TokenCredential>,
+    props: &VaultProperties,
+) -> Result<Option<Access>, AkvError> {
+    let Some(id) = &props.resource_id else {
+        return Ok(None);
+    };
+    let (token, _, _) = refresh_token(credential).await?;
+    let base_url = cloud().management.trim_end_matches('/');
+    let client = http_client();
+    if props.rbac {
+        let url = format!(
+            "{}{}/providers/Microsoft.Authorization/permissions?api-version={}",
+            base_url, id, API_VERSION_PERMISSIONS
+        );
+        let permissions = vault_json(arm_get(&client, &url, &token).await?).await?;
+        return Ok(Some(rbac_access(&permissions)));
+    }
+    let Some(oid) = token_claims(&token).and_then(|c| c["oid"].as_str().map(String::from)) else {
+        return Ok(None);
+    };
+    let url = format!("{}{}?api-version={}", base_url, id, API_VERSION_VAULTS);
+    let vault = vault_json(arm_get(&client, &url, &token).await?).await?;
+    Ok(policy_access(&vault["properties"]["accessPolicies"], &oid))
+}
+
+/// Vaults from `az keyvault list` output in the subscriptions `filter` selects. The CLI only
+/// reports the subscription ID (inside the resource ID), so display names in `filter` are
+/// resolved through the subscriptions ARM listed (`matched`, as ID and name).
+fn cli_vaults(data: &Value, filter: &[String], matched: &[(String, String)]) -> Vec<Vault> {
+    let unresolved: Vec<&String> = filter
+        .iter()
+        .filter(|f| {
+            !matched
+                .iter()
+                .any(|(id, name)| f.eq_ignore_ascii_case(id) || f.eq_ignore_ascii_case(name))
+        })
+        .collect();
+    if !unresolved.is_empty() {
+        warn!(
+            "Subscription filter {:?} matched no subscription; the az CLI fallback takes them as IDs",
+            unresolved
+        );
+    }
+    // Unresolved entries may still be IDs of subscriptions only the CLI can see
+    let ids: Vec<String> = matched
+        .iter()
+        .map(|(id, _)| id)
+        .chain(unresolved)
+        .map(|id| format!("/subscriptions/{}/", id.to_ascii_lowercase()))
+        .collect();
+    data.as_array()
+        .into_iter()
+        .flatten()
+        .filter(|item| {
+            let id = item["id"].as_str().unwrap_or_default().to_ascii_lowercase();
+            filter.is_empty() || ids.iter().any(|prefix| id.contains(prefix.as_str()))
+        })
+        .filter_map(parse_vault)
+        .map(|mut vault| {
+            // Named like the vaults ARM lists
+            let name = matched.iter().find(|(id, _)| {
+                vault
+                    .subscription
+                    .as_ref()
+                    .is_some_and(|s| s.eq_ignore_ascii_case(id))
+            });
+            if let Some((_, name)) = name.filter(|(_, name)| !name.is_empty()) {
+                vault.subscription = Some(name.clone());
+            }
+            vault
+        })
+        .collect()
+}
+
+/// Access from a `Microsoft.Authorization/permissions` response: a permission grants an
+/// action when one of its data actions matches and none of its not-data actions do.
+fn rbac_access(permissions: &Value) -> Access {
+    let grants = |action: &str| {
+        permissions["value"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|permission| {
+                let listed = |field: &str| {
+                    permission[field]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .any(|pattern| audit::matches(pattern, action))
+                };
+                listed("dataActions") && !listed("notDataActions")
+            })
+    };
+    if grants(SET_SECRET_ACTION) {
+        Access::ReadWrite
+    } else {
+        Access::ReadOnly
+    }
+}
+
+/// ReadWrite when an access policy of `object_id` grants `set` on secrets, otherwise None.
+fn policy_access(policies: &Value, object_id: &str) -> Option<Access> {
+    policies
+        .as_array()?
+        .iter()
+        .filter(|p| {
+            p["objectId"]
+                .as_str()
+                .is_some_and(|o| o.eq_ignore_ascii_case(object_id))
+        })
+        .flat_map(|p| p["permissions"]["secrets"].as_array().into_iter().flatten())
+        .filter_map(Value::as_str)
+        .any(|perm| perm.eq_ignore_ascii_case("set") || perm.eq_ignore_ascii_case("all"))
+        .then_some(Access::ReadWrite)
+}
+
 /// Get token then discover vaults in ARM (parallel per-subscription).
 /// Returns optional token info (token_str,fetched_at,ttl) and vault list.
+/// A non-empty `subscriptions` limits discovery to those subscription IDs or display names.
 pub async fn get_token_then_discover(
-    credential: Arc<DeveloperToolsCredential>,
-) -> Result<(Option<(String, Instant, Duration)>, Vec<(String, String)>), Box<dyn Error>> {
+    credential: Arc<dyn TokenCredential>,
+    subscriptions: &[String],
+) -> Result<(Option<(String, Instant, Duration)>, Vec<Vault>), AkvError> {
     // Acquire token
     let (token_str, fetched_at, ttl) = refresh_token(credential.clone()).await?;
-    let client = Client::new();
-    // Delegate to internal discovery with real Azure URL
-    let base_url = "https://management.azure.com";
-    let vaults = discover_resources(&client, &token_str, base_url).await?;
+    let client = http_client();
+    // Delegate to internal discovery with the selected cloud's ARM endpoint
+    let base_url = cloud().management.trim_end_matches('/');
+    let (vaults, matched) =
+        discover_resources(&client, &token_str, base_url, subscriptions).await?;
 
     // Fallback to az CLI executed in blocking thread if no vaults found
     if vaults.is_empty() {
         debug!("No vaults from ARM; attempting az CLI fallback");
-        if let Ok(out) = task::spawn_blocking(|| {
+        if let Ok(Ok(out)) = task::spawn_blocking(|| {
             Command::new("az")
                 .args(["keyvault", "list", "-o", "json"])
                 .output()
         })
-        .await?
+        .await
         {
             if out.status.success() {
                 let data: Value = serde_json::from_slice(&out.stdout)?;
-                if let Some(arr) = data.as_array() {
-                    let mut extra_vaults = Vec::new();
-                    for item in arr {
-                        if let (Some(name), Some(uri)) = (
-                            item["name"].as_str(),
-                            item["properties"]["vaultUri"].as_str(),
-                        ) {
-                            extra_vaults.push((name.to_string(), uri.to_string()));
-                        }
-                    }
-                    // Return a combination of found vaults (though likely only one source will yield results)
-                    // The original logic replaced the empty vector, here we can extend or just return if discover_resources failed to find anything.
+                if data.is_array() {
                     // Since vaults is empty here, we can just return the CLI results.
+                    let extra_vaults = cli_vaults(&data, subscriptions, &matched);
                     return Ok((Some((token_str, fetched_at, ttl)), extra_vaults));
                 }
             } else {
@@ -94,27 +381,163 @@ pub async fn get_token_then_discover(
     Ok((Some((token_str, fetched_at, ttl)), vaults))
 }
 
-/// Internal discovery logic that can be pointed to a mock server
+/// Tenants the signed-in account can access, sorted by display name.
+pub async fn list_tenants(credential: Arc<dyn TokenCredential>) -> Result<Vec<Tenant>, AkvError> {
+    let (token_str, _, _) = refresh_token(credential).await?;
+    fetch_tenants(
+        &http_client(),
+        &token_str,
+        cloud().management.trim_end_matches('/'),
+    )
+    .await
+}
+
+async fn fetch_tenants(
+    client: &Client,
+    token_str: &str,
+    base_url: &str,
+) -> Result<Vec<Tenant>, AkvError> {
+    let mut next_link = Some(format!(
+        "{}/tenants?api-version={}",
+        base_url, API_VERSION_TENANTS
+    ));
+    let mut tenants = Vec::new();
+    while let Some(url) = next_link {
+        let resp = arm_get(client, &url, token_str).await?.error_for_status()?;
+        let page: Value = resp.json().await?;
+        for item in page["value"].as_array().into_iter().flatten() {
+            if let Some(id) = item["tenantId"].as_str() {
+                tenants.push(Tenant {
+                    id: id.to_string(),
+                    name: item["displayName"].as_str().unwrap_or(id).to_string(),
+                    domain: item["defaultDomain"].as_str().map(String::from),
+                });
+            }
+        }
+        next_link = page["nextLink"].as_str().map(|s| s.to_string());
+    }
+    tenants.sort_by_key(|t| t.name.to_lowercase());
+    Ok(tenants)
+}
+
+/// A vault from an ARM (or `az keyvault list`) resource. Subscription ID and resource group
+/// are taken from the resource ID: `/subscriptions/{sub}/resourceGroups/{rg}/providers/...`.
+fn parse_vault(item: &Value) -> Option<Vault> {
+    let name = item["name"].as_str()?;
+    let uri = item["properties"]["vaultUri"].as_str()?;
+    let id_segment = |key: &str| {
+        let mut parts = item["id"].as_str()?.split('/');
+        parts.find(|p| p.eq_ignore_ascii_case(key))?;
+        parts.next().map(String::from)
+    };
+    Some(Vault {
+        name: name.to_string(),
+        uri: uri.to_string(),
+        alias: None,
+        subscription: id_segment("subscriptions"),
+        resource_group: id_segment("resourceGroups"),
+        location: item["location"].as_str().map(String::from),
+        properties: Some(parse_vault_properties(item)),
+    })
+}
+
+/// Settings of a `Microsoft.KeyVault/vaults` resource. Soft delete is on unless explicitly
+/// disabled (it can no longer be turned off).
+fn parse_vault_properties(item: &Value) -> VaultProperties {
+    let props = &item["properties"];
+    let acls = &props["networkAcls"];
+    let strings = |v: &Value, field: &str| -> Vec<String> {
+        v.as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|rule| rule[field].as_str())
+            .map(String::from)
+            .collect()
+    };
+    VaultProperties {
+        resource_id: item["id"].as_str().map(String::from),
+        sku: props["sku"]["name"].as_str().map(String::from),
+        soft_delete: props["enableSoftDelete"].as_bool().unwrap_or(true),
+        retention_days: props["softDeleteRetentionInDays"]
+            .as_u64()
+            .map(|d| d as u32),
+        purge_protection: props["enablePurgeProtection"].as_bool().unwrap_or(false),
+        rbac: props["enableRbacAuthorization"].as_bool().unwrap_or(false),
+        public_access: props["publicNetworkAccess"].as_str().map(String::from),
+        default_action: acls["defaultAction"].as_str().map(String::from),
+        bypass: acls["bypass"].as_str().map(String::from),
+        ip_rules: strings(&acls["ipRules"], "value"),
+        subnets: strings(&acls["virtualNetworkRules"], "id")
+            .iter()
+            .map(|id| id.rsplit('/').next().unwrap_or(id).to_string())
+            .collect(),
+    }
+}
+
+/// GET an ARM (or Key Vault REST) URL, retrying throttling (429), server errors and timeouts with backoff.
+/// The last response is returned as-is once the retries are used up.
+async fn arm_get(client: &Client, url: &str, token: &str) -> reqwest::Result<reqwest::Response> {
+    let mut attempt = 0;
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(String::from));
+    let started = Instant::now();
+    loop {
+        if let Some(host) = &host {
+            throttle(host).await;
+        }
+        let result = client.get(url).bearer_auth(token).send().await;
+        let retryable = match &result {
+            Ok(resp) => {
+                resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
+                    || resp.status().is_server_error()
+            }
+            Err(e) => e.is_timeout() || e.is_connect(),
+        };
+        if !retryable || attempt >= http().retries {
+            let status = result.as_ref().ok().map(|r| r.status().as_u16());
+            api_log::record("GET", url, status, started, attempt + 1);
+            return result;
+        }
+        attempt += 1;
+        let delay = Duration::from_millis(500 << (attempt - 1).min(5));
+        debug!("Retrying {} in {:?} (attempt {})", url, delay, attempt);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Internal discovery logic that can be pointed to a mock server. Also returns the
+/// subscriptions (ID, display name) that passed `filter`, to filter the az CLI fallback.
 async fn discover_resources(
     client: &Client,
     token_str: &str,
     base_url: &str,
-) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    filter: &[String],
+) -> Result<(Vec<Vault>, Vec<(String, String)>), AkvError> {
     let mut subs_url = Some(format!(
         "{}/subscriptions?api-version={}",
         base_url, API_VERSION_SUBSCRIPTIONS
     ));
     let mut subscriptions = Vec::new();
-    let mut vaults: Vec<(String, String)> = Vec::new();
+    let mut vaults: Vec<Vault> = Vec::new();
 
     while let Some(url) = subs_url {
-        let resp = client.get(&url).bearer_auth(token_str).send().await?;
+        let resp = arm_get(client, &url, token_str).await?;
         let page: Value = resp.json().await?;
 
         if let Some(arr) = page["value"].as_array() {
             for sub in arr {
                 if let Some(sub_id) = sub["subscriptionId"].as_str() {
-                    subscriptions.push(sub_id.to_string());
+                    let name = sub["displayName"].as_str().unwrap_or_default();
+                    let wanted = filter.is_empty()
+                        || filter.iter().any(|f| {
+                            f.eq_ignore_ascii_case(sub_id) || f.eq_ignore_ascii_case(name)
+                        });
+                    if wanted {
+                        subscriptions.push((sub_id.to_string(), name.to_string()));
+                    } else {
+                        debug!("Skipping subscription {} ({})", sub_id, name);
+                    }
                 }
             }
         }
@@ -123,7 +546,7 @@ async fn discover_resources(
     }
 
     let mut futures = Vec::new();
-    for sub_id in subscriptions {
+    for (sub_id, sub_name) in subscriptions.clone() {
         let client_clone = client.clone();
         let bearer_clone = token_str.to_string();
         // We need to pass the base_url into the future, but we can't easily capture it if it's a reference unless we clone a String
@@ -137,23 +560,16 @@ async fn discover_resources(
             ));
 
             while let Some(url) = next_link {
-                let resp = client_clone
-                    .get(&url)
-                    .bearer_auth(&bearer_clone)
-                    .send()
-                    .await
-                    .ok()?;
+                let resp = arm_get(&client_clone, &url, &bearer_clone).await.ok()?;
                 let page: Value = resp.json().await.ok()?;
 
                 if let Some(v) = page["value"].as_array() {
-                    for item in v {
-                        if let (Some(name), Some(uri)) = (
-                            item["name"].as_str(),
-                            item["properties"]["vaultUri"].as_str(),
-                        ) {
-                            vaults_list.push((name.to_string(), uri.to_string()));
+                    vaults_list.extend(v.iter().filter_map(parse_vault).map(|mut vault| {
+                        if !sub_name.is_empty() {
+                            vault.subscription = Some(sub_name.clone());
                         }
-                    }
+                        vault
+                    }));
                 }
                 next_link = page["nextLink"].as_str().map(|s| s.to_string());
             }
@@ -166,15 +582,718 @@ async fn discover_resources(
         vaults.extend(opt);
     }
 
-    Ok(vaults)
+    Ok((vaults, subscriptions))
+}
+
+/// The listing metadata kept for a secret.
+pub fn secret_meta(name: String, props: &SecretProperties) -> SecretMeta {
+    let attributes = props.attributes.as_ref();
+    let mut tags: Vec<(String, String)> = props
+        .tags
+        .iter()
+        .flatten()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    tags.sort();
+    SecretMeta {
+        name,
+        enabled: attributes.and_then(|a| a.enabled).unwrap_or(true),
+        content_type: props.content_type.clone(),
+        tags,
+        created: attributes.and_then(|a| a.created),
+        updated: attributes.and_then(|a| a.updated),
+        expires: attributes.and_then(|a| a.expires),
+    }
+}
+
+/// List all secrets of a vault with their metadata as (name, properties), sorted by name.
+pub async fn list_secrets_with_properties(
+    client: &SecretClient,
+) -> Result<Vec<(String, SecretProperties)>, AkvError> {
+    let mut pager = client.list_secret_properties(None)?.into_stream();
+    let mut items = Vec::new();
+    while let Some(item) = pager.try_next().await? {
+        if let Ok(rid) = item.resource_id() {
+            items.push((rid.name, item));
+        }
+    }
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(items)
+}
+
+/// Create a secret or add a new version with the given value.
+pub async fn set_secret_value(
+    client: &SecretClient,
+    name: &str,
+    value: String,
+    content_type: Option<String>,
+) -> Result<(), AkvError> {
+    let params = SetSecretParameters {
+        value: Some(value),
+        content_type,
+        ..Default::default()
+    };
+    client.set_secret(name, params.try_into()?, None).await?;
+    Ok(())
+}
+
+/// Delete a secret (soft-delete when the vault has it enabled).
+pub async fn delete_secret(client: &SecretClient, name: &str) -> Result<(), AkvError> {
+    client.delete_secret(name, None).await?;
+    Ok(())
+}
+
+/// Vault URI for a name, or the argument itself when it is already a URI.
+pub fn vault_uri(vault: &str) -> String {
+    if vault.starts_with("https://") {
+        vault.to_string()
+    } else {
+        format!("https://{}.{}/", vault, cloud().vault_suffix)
+    }
+}
+
+/// Vault name for a name or URI (`https://my-kv.vault.azure.net/` -> `my-kv`).
+pub fn vault_name(vault: &str) -> String {
+    match vault.strip_prefix("https://") {
+        Some(host) => host.split(['.', '/']).next().unwrap_or(host).to_string(),
+        None => vault.to_string(),
+    }
+}
+
+/// Fetch the current version of a single secret, including its attributes.
+pub async fn get_secret(client: &SecretClient, name: &str) -> Result<Secret, AkvError> {
+    let resp = client.get_secret(name, None).await?;
+    let body = resp.into_body();
+    Ok(serde_json::from_slice::<Secret>(&body)?)
+}
+
+/// Fetch the current value of a single secret.
+pub async fn get_secret_value(client: Arc<SecretClient>, name: &str) -> Result<String, AkvError> {
+    Ok(get_secret(&client, name).await?.value.unwrap_or_default())
+}
+
+/// The `SecretStore` of a Key Vault, through the SDK client.
+pub struct AzureStore {
+    client: SecretClient,
+}
+
+impl AzureStore {
+    pub fn new(uri: &str, credential: Arc<dyn TokenCredential>) -> azure_core::Result<Self> {
+        Ok(Self {
+            client: secret_client(uri, credential)?,
+        })
+    }
+
+    #[cfg(test)]
+    pub fn from_client(client: SecretClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl SecretStore for AzureStore {
+    async fn list(&self, found: &mut (dyn FnMut(SecretMeta) + Send)) -> Result<(), AkvError> {
+        let mut pager = self.client.list_secret_properties(None)?.into_stream();
+        while let Some(item) = pager.try_next().await? {
+            if let Ok(rid) = item.resource_id() {
+                found(secret_meta(rid.name, &item));
+            }
+        }
+        Ok(())
+    }
+
+    async fn get(&self, name: &str) -> Result<String, AkvError> {
+        Ok(get_secret(&self.client, name)
+            .await?
+            .value
+            .unwrap_or_default())
+    }
+
+    async fn set(
+        &self,
+        name: &str,
+        value: String,
+        content_type: Option<String>,
+    ) -> Result<(), AkvError> {
+        set_secret_value(&self.client, name, value, content_type).await
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), AkvError> {
+        delete_secret(&self.client, name).await
+    }
+
+    async fn versions(&self, name: &str) -> Result<Vec<SecretVersion>, AkvError> {
+        let mut pager = self
+            .client
+            .list_secret_properties_versions(name, None)?
+            .into_stream();
+        let mut versions = Vec::new();
+        while let Some(item) = pager.try_next().await? {
+            let Some(version) = item.resource_id().ok().and_then(|rid| rid.version) else {
+                continue;
+            };
+            let meta = secret_meta(name.to_string(), &item);
+            versions.push(SecretVersion {
+                version,
+                enabled: meta.enabled,
+                created: meta.created,
+                updated: meta.updated,
+                expires: meta.expires,
+            });
+        }
+        versions.sort_by_key(|v| std::cmp::Reverse(v.created));
+        Ok(versions)
+    }
+}
+
+/// Client for the Key Vault keys REST API (see the module doc for why it isn't the SDK's):
+/// bearer authentication for the vault scope, retries, throttling and the API log.
+pub struct KeyClient {
+    endpoint: Url,
+    pipeline: Pipeline,
+}
+
+impl KeyClient {
+    pub fn new(vault_uri: &str, credential: Arc<dyn TokenCredential>) -> azure_core::Result<Self> {
+        let auth = BearerTokenAuthorizationPolicy::new(credential, [cloud().vault_scope()]);
+        let pipeline = Pipeline::new(
+            option_env!("CARGO_PKG_NAME"),
+            option_env!("CARGO_PKG_VERSION"),
+            client_options(),
+            Vec::new(),
+            vec![Arc::new(auth)],
+            None,
+        );
+        Ok(Self {
+            endpoint: Url::parse(vault_uri)?,
+            pipeline,
+        })
+    }
+
+    /// Names of the vault's keys, across all pages of the listing.
+    pub async fn list_key_names(&self) -> azure_core::Result<Vec<String>> {
+        let mut next_link = Some(self.url(&["keys"]));
+        let mut names = Vec::new();
+        while let Some(url) = next_link {
+            let page = self.send(Method::Get, url, None).await?;
+            for item in page["value"].as_array().into_iter().flatten() {
+                if let Some(name) = item["kid"].as_str().and_then(|kid| kid.rsplit('/').next()) {
+                    names.push(name.to_string());
+                }
+            }
+            next_link = page["nextLink"].as_str().map(Url::parse).transpose()?;
+        }
+        Ok(names)
+    }
+
+    /// The key bundle of the current version of `name`.
+    pub async fn get_key(&self, name: &str) -> azure_core::Result<Value> {
+        self.send(Method::Get, self.url(&["keys", name]), None)
+            .await
+    }
+
+    pub async fn get_rotation_policy(&self, name: &str) -> azure_core::Result<Value> {
+        self.send(
+            Method::Get,
+            self.url(&["keys", name, "rotationpolicy"]),
+            None,
+        )
+        .await
+    }
+
+    pub async fn set_rotation_policy(
+        &self,
+        name: &str,
+        policy: &Value,
+    ) -> azure_core::Result<Value> {
+        let url = self.url(&["keys", name, "rotationpolicy"]);
+        self.send(Method::Put, url, Some(policy)).await
+    }
+
+    /// Run a key operation (`encrypt`, `sign`, ...) with a version of `name`.
+    pub async fn key_operation(
+        &self,
+        name: &str,
+        version: &str,
+        op: &str,
+        parameters: &Value,
+    ) -> azure_core::Result<Value> {
+        let url = self.url(&["keys", name, version, op]);
+        self.send(Method::Post, url, Some(parameters)).await
+    }
+
+    fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.endpoint.clone();
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.pop_if_empty().extend(segments);
+        }
+        url.query_pairs_mut()
+            .append_pair("api-version", API_VERSION_KEYS);
+        url
+    }
+
+    /// Send a request; error responses come back as `azure_core` errors with the status and
+    /// the message Key Vault sent, like those of the secrets client.
+    async fn send(
+        &self,
+        method: Method,
+        url: Url,
+        body: Option<&Value>,
+    ) -> azure_core::Result<Value> {
+        let mut request = Request::new(url, method);
+        request.insert_header("accept", "application/json");
+        if let Some(body) = body {
+            request.insert_header("content-type", "application/json");
+            request.set_json(body)?;
+        }
+        let response = self
+            .pipeline
+            .send(&Context::new(), &mut request, None)
+            .await?;
+        response.into_body().json()
+    }
+}
+
+/// Keys of a vault with their type, size or curve and rotation policy, sorted by name.
+pub async fn list_keys(
+    credential: Arc<dyn TokenCredential>,
+    vault_uri: &str,
+) -> Result<Vec<KeyInfo>, AkvError> {
+    let client = KeyClient::new(vault_uri, credential)?;
+    Ok(fetch_keys(&client).await?)
+}
+
+async fn fetch_keys(client: &KeyClient) -> azure_core::Result<Vec<KeyInfo>> {
+    let names = client.list_key_names().await?;
+    // The listing has no key material, so each key's current version is fetched
+    let mut keys: Vec<KeyInfo> = stream::iter(names)
+        .map(|name| fetch_key(client, name))
+        .buffer_unordered(8)
+        .try_collect()
+        .await?;
+    keys.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(keys)
+}
+
+async fn fetch_key(client: &KeyClient, name: String) -> azure_core::Result<KeyInfo> {
+    let item = client.get_key(&name).await?;
+    // Reading the policy needs an extra permission; treat failures as "no policy"
+    let rotation = client
+        .get_rotation_policy(&name)
+        .await
+        .ok()
+        .and_then(|policy| RotationPolicy::from_json(&policy).summary());
+    Ok(parse_key(name, &item, rotation))
+}
+
+/// A key bundle (`GET /keys/{name}`) as `KeyInfo`.
+fn parse_key(name: String, item: &Value, rotation: Option<String>) -> KeyInfo {
+    let key = &item["key"];
+    // RSA size from the modulus, ignoring a leading zero byte
+    let size = key["n"]
+        .as_str()
+        .and_then(|n| URL_SAFE_NO_PAD.decode(n.trim_end_matches('=')).ok())
+        .map(|n| (n.iter().skip_while(|b| **b == 0).count() * 8) as u32);
+    KeyInfo {
+        name,
+        version: key["kid"]
+            .as_str()
+            .and_then(|kid| kid.rsplit('/').next())
+            .unwrap_or_default()
+            .to_string(),
+        kty: key["kty"].as_str().unwrap_or("?").to_string(),
+        size,
+        curve: key["crv"].as_str().map(String::from),
+        enabled: item["attributes"]["enabled"].as_bool().unwrap_or(true),
+        ops: key["key_ops"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|op| op.as_str().map(String::from))
+            .collect(),
+        rotation,
+    }
+}
+
+/// Rotation policy of a key.
+pub async fn get_rotation_policy(
+    credential: Arc<dyn TokenCredential>,
+    vault_uri: &str,
+    key: &str,
+) -> Result<RotationPolicy, AkvError> {
+    let client = KeyClient::new(vault_uri, credential)?;
+    Ok(RotationPolicy::from_json(
+        &client.get_rotation_policy(key).await?,
+    ))
+}
+
+/// Replace the rotation policy of a key.
+pub async fn set_rotation_policy(
+    credential: Arc<dyn TokenCredential>,
+    vault_uri: &str,
+    key: &str,
+    policy: &RotationPolicy,
+) -> Result<(), AkvError> {
+    let client = KeyClient::new(vault_uri, credential)?;
+    client.set_rotation_policy(key, &policy.to_json()).await?;
+    Ok(())
+}
+
+/// Recent get/set/delete operations on a secret from the vault's audit logs (`AuditEvent`
+/// diagnostics sent to Log Analytics), newest first. Queried through the vault's resource
+/// ID, so every workspace the logs go to (and the caller can read) is searched.
+pub async fn secret_activity(
+    credential: Arc<dyn TokenCredential>,
+    resource_id: &str,
+    secret: &str,
+) -> Result<Vec<ActivityEntry>, AkvError> {
+    let token = credential
+        .get_token(&[&cloud().log_analytics_scope()], None)
+        .await?;
+    query_activity(
+        &http_client(),
+        token.token.secret(),
+        cloud().log_analytics.trim_end_matches('/'),
+        resource_id,
+        secret,
+    )
+    .await
+}
+
+async fn query_activity(
+    client: &Client,
+    token: &str,
+    base_url: &str,
+    resource_id: &str,
+    secret: &str,
+) -> Result<Vec<ActivityEntry>, AkvError> {
+    // Identity columns only exist once such a caller showed up, hence column_ifexists
+    let query = format!(
+        r#"AzureDiagnostics
+| where Category == "AuditEvent" and id_s matches regex @"/secrets/{}(/|$)"
+| project TimeGenerated, OperationName,
+    Caller = coalesce(column_ifexists("identity_claim_upn_s", ""), column_ifexists("identity_claim_appid_g", ""), column_ifexists("identity_claim_oid_g", "")),
+    CallerIPAddress, ResultSignature
+| order by TimeGenerated desc
+| take 100"#,
+        secret
+    );
+    let url = format!("{}/v1{}/query", base_url, resource_id);
+    let resp = api_log::send(
+        client
+            .post(&url)
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "query": query, "timespan": ACTIVITY_TIMESPAN })),
+    )
+    .await?;
+    let result = vault_json(resp).await?;
+    let cell = |row: &Value, i: usize| row[i].as_str().unwrap_or_default().to_string();
+    Ok(result["tables"][0]["rows"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|row| ActivityEntry {
+            time: cell(row, 0),
+            operation: cell(row, 1),
+            caller: cell(row, 2),
+            ip: cell(row, 3),
+            result: cell(row, 4),
+        })
+        .collect())
+}
+
+/// JSON body of a Key Vault REST (or ARM) response, or the error message it sent.
+async fn vault_json(resp: reqwest::Response) -> Result<Value, AkvError> {
+    let status = resp.status();
+    let body: Value = resp.json().await.unwrap_or_default();
+    if status.is_success() {
+        return Ok(body);
+    }
+    Err(AkvError::response(
+        status.as_u16(),
+        body["error"]["message"]
+            .as_str()
+            .unwrap_or("request failed"),
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{method, path};
+    use crate::error::Category;
+    use crate::mock_vault::MockCredential;
+    use wiremock::matchers::{body_partial_json, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
+    #[test]
+    fn test_vault_name_and_uri() {
+        assert_eq!(vault_uri("my-kv"), "https://my-kv.vault.azure.net/");
+        assert_eq!(vault_name("my-kv"), "my-kv");
+        assert_eq!(vault_name("https://my-kv.vault.azure.net/"), "my-kv");
+        assert_eq!(
+            vault_uri("https://my-kv.vault.azure.cn/"),
+            "https://my-kv.vault.azure.cn/"
+        );
+    }
+
+    #[test]
+    fn test_build_http_client_ca_bundle() {
+        let http = HttpConfig::default();
+        assert!(build_http_client(&http, &TlsConfig::default()).is_ok());
+        let missing = TlsConfig {
+            ca_bundle: Some("/nonexistent/ca.pem".into()),
+            ..Default::default()
+        };
+        assert!(build_http_client(&http, &missing).is_err());
+
+        let path = std::env::temp_dir().join(format!("akv-ca-{}.pem", std::process::id()));
+        std::fs::write(&path, "not a certificate").unwrap();
+        let empty = TlsConfig {
+            ca_bundle: Some(path.clone()),
+            ..Default::default()
+        };
+        assert!(build_http_client(&http, &empty).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_arm_get_retries_server_errors() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let url = format!("{}/flaky", mock_server.uri());
+        let resp = arm_get(&Client::new(), &url, "fake_token").await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::OK);
+    }
+
+    #[test]
+    fn test_token_identity() {
+        let encode = |claims: Value| URL_SAFE_NO_PAD.encode(claims.to_string());
+        let user = format!(
+            "e30.{}.sig",
+            encode(serde_json::json!({"upn": "jane@contoso.com", "tid": "t1", "appid": "x"}))
+        );
+        assert_eq!(
+            token_identity(&user),
+            Some(Identity {
+                principal: "jane@contoso.com".into(),
+                tenant: Some("t1".into())
+            })
+        );
+        let sp = format!("e30.{}.", encode(serde_json::json!({"appid": "0000-1111"})));
+        assert_eq!(token_identity(&sp).unwrap().principal, "0000-1111");
+        assert_eq!(token_identity("not-a-jwt"), None);
+    }
+
+    #[test]
+    fn test_access_preflight() {
+        // Key Vault Secrets User: read only
+        let reader = serde_json::json!({"value": [
+            {"actions": [], "dataActions": ["Microsoft.KeyVault/vaults/secrets/getSecret/action",
+                "Microsoft.KeyVault/vaults/secrets/readMetadata/action"], "notDataActions": []}
+        ]});
+        assert_eq!(rbac_access(&reader), Access::ReadOnly);
+        // Key Vault Secrets Officer (wildcard), once with the write action excluded
+        let officer = serde_json::json!({"value": [
+            {"dataActions": ["Microsoft.KeyVault/vaults/secrets/*"], "notDataActions": []}
+        ]});
+        assert_eq!(rbac_access(&officer), Access::ReadWrite);
+        let restricted = serde_json::json!({"value": [
+            {"dataActions": ["Microsoft.KeyVault/vaults/*"],
+             "notDataActions": ["Microsoft.KeyVault/vaults/secrets/setSecret/action"]}
+        ]});
+        assert_eq!(rbac_access(&restricted), Access::ReadOnly);
+
+        let policies = serde_json::json!([
+            {"objectId": "me", "permissions": {"secrets": ["Get", "List"]}},
+            {"objectId": "ME", "permissions": {"secrets": ["Set"]}},
+            {"objectId": "someone-else", "permissions": {"secrets": ["all"]}}
+        ]);
+        assert_eq!(policy_access(&policies, "me"), Some(Access::ReadWrite));
+        assert_eq!(policy_access(&policies, "nobody"), None);
+    }
+
+    #[test]
+    fn test_cli_vaults_name_filter() {
+        let vault = |sub: &str, name: &str| {
+            serde_json::json!({
+                "id": format!("/subscriptions/{}/resourceGroups/rg/providers/Microsoft.KeyVault/vaults/{}", sub, name),
+                "name": name,
+                "properties": {"vaultUri": format!("https://{}.vault.azure.net/", name)}
+            })
+        };
+        let data = serde_json::json!([vault("sub1", "dev-kv"), vault("SUB2", "prod-kv")]);
+        let matched = [("sub2".to_string(), "Production".to_string())];
+
+        // A display name selects the vaults of the subscription it resolved to
+        let vaults = cli_vaults(&data, &["production".to_string()], &matched);
+        assert_eq!(vaults.len(), 1);
+        assert_eq!(vaults[0].name, "prod-kv");
+        assert_eq!(vaults[0].subscription.as_deref(), Some("Production"));
+
+        // Unresolved entries are taken as IDs; no filter keeps everything
+        let vaults = cli_vaults(&data, &["SUB1".to_string()], &[]);
+        assert_eq!(vaults.len(), 1);
+        assert_eq!(vaults[0].subscription.as_deref(), Some("sub1"));
+        assert_eq!(cli_vaults(&data, &[], &matched).len(), 2);
+        assert!(cli_vaults(&data, &["Staging".to_string()], &[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_is_network_error() {
+        // Nothing listens on port 1
+        let refused = reqwest::Client::new()
+            .get("http://127.0.0.1:1/")
+            .send()
+            .await
+            .unwrap_err();
+        assert!(is_network_error(&refused));
+        assert_eq!(AkvError::from(refused).category, Category::Network);
+        let io = azure_core::Error::new(ErrorKind::Io, std::io::Error::other("reset"));
+        assert!(is_network_error(&io));
+        let denied = azure_core::Error::with_message(ErrorKind::Other, "403 Forbidden");
+        assert!(!is_network_error(&denied));
+    }
+
+    #[tokio::test]
+    async fn test_query_activity() {
+        let mock_server = MockServer::start().await;
+        let vault = "/subscriptions/s/resourceGroups/rg/providers/Microsoft.KeyVault/vaults/kv";
+        Mock::given(method("POST"))
+            .and(path(format!("/v1{}/query", vault)))
+            .and(body_partial_json(serde_json::json!({ "timespan": "P30D" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tables": [{
+                    "name": "PrimaryResult",
+                    "columns": [],
+                    "rows": [
+                        ["2026-10-01T12:00:00.123Z", "SecretSet", "jane@contoso.com", "203.0.113.7", "OK"],
+                        ["2026-09-30T08:15:00Z", "SecretGet", "0000-1111", "10.0.0.4", "Forbidden"]
+                    ]
+                }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new();
+        let entries = query_activity(&client, "token", &mock_server.uri(), vault, "db-password")
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0],
+            ActivityEntry {
+                time: "2026-10-01T12:00:00.123Z".into(),
+                operation: "SecretSet".into(),
+                caller: "jane@contoso.com".into(),
+                ip: "203.0.113.7".into(),
+                result: "OK".into(),
+            }
+        );
+        assert_eq!(entries[1].result, "Forbidden");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_keys() {
+        let mock_server = MockServer::start().await;
+        let base = mock_server.uri();
+        let listing = serde_json::json!({
+            "value": [
+                {"kid": format!("{}/keys/signing", base), "attributes": {"enabled": true}},
+                {"kid": format!("{}/keys/backup", base), "attributes": {"enabled": false}}
+            ]
+        });
+        Mock::given(method("GET"))
+            .and(path("/keys"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(listing))
+            .mount(&mock_server)
+            .await;
+        // 2048-bit modulus with the usual leading zero byte
+        let modulus = URL_SAFE_NO_PAD.encode([&[0u8][..], &[0xc5; 256]].concat());
+        Mock::given(method("GET"))
+            .and(path("/keys/backup"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "key": {"kty": "RSA", "n": modulus, "e": "AQAB", "key_ops": ["wrapKey", "unwrapKey"]},
+                "attributes": {"enabled": false}
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/keys/signing"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "key": {
+                    "kid": format!("{}/keys/signing/0123abcd", base),
+                    "kty": "EC-HSM",
+                    "crv": "P-256",
+                    "key_ops": ["sign", "verify"]
+                },
+                "attributes": {"enabled": true}
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/keys/signing/rotationpolicy"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "lifetimeActions": [
+                    {"trigger": {"timeAfterCreate": "P90D"}, "action": {"type": "Rotate"}},
+                    {"trigger": {"timeBeforeExpiry": "P30D"}, "action": {"type": "Notify"}}
+                ],
+                "attributes": {"expiryTime": "P1Y"}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = KeyClient::new(&base, Arc::new(MockCredential)).unwrap();
+        let keys = fetch_keys(&client).await.unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].name, "backup");
+        assert_eq!(keys[0].size, Some(2048));
+        assert!(!keys[0].enabled);
+        assert_eq!(keys[0].rotation, None); // policy request failed (404)
+        assert_eq!(keys[1].version, "0123abcd");
+        assert_eq!(keys[1].curve.as_deref(), Some("P-256"));
+        assert_eq!(keys[1].ops, ["sign", "verify"]);
+        assert_eq!(
+            keys[1].rotation.as_deref(),
+            Some("rotate 90d after creation, notify 30d before expiry, versions expire after 1y")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_tenants() {
+        let mock_server = MockServer::start().await;
+        let tenants = serde_json::json!({
+            "value": [
+                {"tenantId": "t2", "displayName": "Fabrikam", "defaultDomain": "fabrikam.com"},
+                {"tenantId": "t1", "displayName": "contoso"},
+                {"tenantId": "t3"}
+            ]
+        });
+        Mock::given(method("GET"))
+            .and(path("/tenants"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(tenants))
+            .mount(&mock_server)
+            .await;
+
+        let tenants = fetch_tenants(&Client::new(), "fake_token", &mock_server.uri())
+            .await
+            .unwrap();
+        let names: Vec<&str> = tenants.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["contoso", "Fabrikam", "t3"]);
+        assert_eq!(tenants[1].domain.as_deref(), Some("fabrikam.com"));
+    }
+
     #[tokio::test]
     async fn test_pagination_logic() {
         let mock_server = MockServer::start().await;
@@ -193,7 +1312,7 @@ mod tests {
 
         // 2. Mock Subscriptions (Page 2) -> Returns sub2, no nextLink
         let sub_page2 = serde_json::json!({
-            "value": [{"subscriptionId": "sub2"}]
+            "value": [{"subscriptionId": "sub2", "displayName": "Production"}]
         });
         Mock::given(method("GET"))
             .and(path("/subscriptions_page2"))
@@ -228,7 +1347,24 @@ mod tests {
 
         // 5. Mock Vaults for sub2 -> Returns vault3, no pagination
         let v_sub2 = serde_json::json!({
-            "value": [{"name": "vault3", "properties": {"vaultUri": "https://vault3.vault.azure.net/"}}]
+            "value": [{
+                "id": "/subscriptions/sub2/resourceGroups/rg-prod/providers/Microsoft.KeyVault/vaults/vault3",
+                "name": "vault3",
+                "location": "westeurope",
+                "properties": {
+                    "vaultUri": "https://vault3.vault.azure.net/",
+                    "sku": {"family": "A", "name": "premium"},
+                    "softDeleteRetentionInDays": 90,
+                    "enablePurgeProtection": true,
+                    "enableRbacAuthorization": true,
+                    "networkAcls": {
+                        "defaultAction": "Deny",
+                        "bypass": "AzureServices",
+                        "ipRules": [{"value": "203.0.113.0/24"}],
+                        "virtualNetworkRules": [{"id": "/subscriptions/sub2/resourceGroups/rg-net/providers/Microsoft.Network/virtualNetworks/vnet/subnets/apps"}]
+                    }
+                }
+            }]
         });
         Mock::given(method("GET"))
             .and(path(
@@ -239,9 +1375,14 @@ mod tests {
             .await;
 
         // Run discovery
-        let res = discover_resources(&client, "fake_token", &mock_server.uri()).await;
+        let res = discover_resources(&client, "fake_token", &mock_server.uri(), &[]).await;
         assert!(res.is_ok());
-        let mut vaults = res.unwrap();
+        let mut vaults: Vec<(String, String)> = res
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|v| (v.name, v.uri))
+            .collect();
         // Sort for deterministic comparison
         vaults.sort();
 
@@ -267,104 +1408,37 @@ mod tests {
         for e in expected {
             assert!(vaults.contains(&e), "Missing {:?}", e);
         }
-    }
-}
-
-/// Incrementally list secrets and send updates for the given vault back to UI.
-/// Also sends CacheVaultSecrets for silent caching.
-pub async fn list_secrets_incremental(
-    client: Arc<SecretClient>,
-    tx: UnboundedSender<AppEvent>,
-    vault_name: String,
-) -> Result<(), Box<dyn Error>> {
-    debug!("Starting incremental list for vault '{}'", vault_name);
-    let mut pager = client.list_secret_properties(None)?.into_stream();
-    let mut names = Vec::new();
-    const BATCH: usize = 20;
-    while let Some(item) = pager.try_next().await? {
-        if let Ok(rid) = item.resource_id() {
-            names.push(rid.name);
-        }
-        if names.len() % BATCH == 0 {
-            let mut sorted = names.clone();
-            sorted.sort();
-            let _ = tx.send(AppEvent::SecretsUpdated(vault_name.clone(), sorted.clone()));
-            let _ = tx.send(AppEvent::CacheVaultSecrets(
-                vault_name.clone(),
-                names.clone(),
-            ));
-        }
-    }
-    names.sort();
-    let _ = tx.send(AppEvent::SecretsUpdated(vault_name.clone(), names.clone()));
-    let _ = tx.send(AppEvent::CacheVaultSecrets(vault_name.clone(), names));
-    debug!("Completed incremental list for vault '{}'", vault_name);
-    Ok(())
-}
-
-/// List secrets fully and update cache (used after write/delete to ensure cache is fresh).
-pub async fn list_secrets_and_cache(
-    client: Arc<SecretClient>,
-    tx: UnboundedSender<AppEvent>,
-    vault_name: String,
-) -> Result<(), Box<dyn Error>> {
-    debug!("Starting full list+cache for vault '{}'", vault_name);
-    let mut pager = client.list_secret_properties(None)?.into_stream();
-    let mut names = Vec::new();
-    while let Some(item) = pager.try_next().await? {
-        if let Ok(rid) = item.resource_id() {
-            names.push(rid.name);
-        }
-    }
-    names.sort();
-    let _ = tx.send(AppEvent::CacheVaultSecrets(
-        vault_name.clone(),
-        names.clone(),
-    ));
-    let _ = tx.send(AppEvent::SecretsUpdated(vault_name.clone(), names));
-    debug!("Completed full list+cache for vault '{}'", vault_name);
-    Ok(())
-}
 
-/// Preload secrets for all vaults using bounded concurrency and populate cache silently.
-pub async fn preload_all_vaults(
-    credential: Arc<DeveloperToolsCredential>,
-    tx: UnboundedSender<AppEvent>,
-    vaults: Vec<(String, String)>,
-    sem: Arc<Semaphore>,
-) {
-    debug!("preload_all_vaults: starting, {} vaults", vaults.len());
-    let client_cred = credential;
-    let mut handles = Vec::new();
-    for (name, uri) in vaults.into_iter() {
-        let tx2 = tx.clone();
-        let permit = sem.clone();
-        let name_clone = name.clone();
-        let uri_clone = uri.clone();
-        let cred = client_cred.clone();
-        let handle = tokio::spawn(async move {
-            let _p = permit.acquire_owned().await.expect("semaphore");
-            debug!("Preloading vault '{}' (uri={})", name_clone, uri_clone);
-            match SecretClient::new(&uri_clone, cred.clone(), None) {
-                Ok(client) => {
-                    let client_arc = Arc::new(client);
-                    if let Err(e) =
-                        list_secrets_and_cache(client_arc, tx2.clone(), name_clone.clone()).await
-                    {
-                        debug!("Preload failed for {}: {}", name_clone, e);
-                    } else {
-                        debug!("Preload succeeded for {}", name_clone);
-                    }
-                }
-                Err(e) => {
-                    debug!("Failed to create client for {}: {}", name_clone, e);
-                }
-            }
-        });
-        handles.push(handle);
-    }
-    for h in handles {
-        let _ = h.await;
+        // Subscription filter matches display names case-insensitively
+        let filter = vec!["production".to_string()];
+        let (vaults, matched) =
+            discover_resources(&client, "fake_token", &mock_server.uri(), &filter)
+                .await
+                .unwrap();
+        assert_eq!(matched, [("sub2".to_string(), "Production".to_string())]);
+        assert_eq!(
+            vaults,
+            vec![Vault {
+                name: "vault3".to_string(),
+                uri: "https://vault3.vault.azure.net/".to_string(),
+                alias: None,
+                subscription: Some("Production".to_string()),
+                resource_group: Some("rg-prod".to_string()),
+                location: Some("westeurope".to_string()),
+                properties: Some(VaultProperties {
+                    resource_id: Some("/subscriptions/sub2/resourceGroups/rg-prod/providers/Microsoft.KeyVault/vaults/vault3".to_string()),
+                    sku: Some("premium".to_string()),
+                    soft_delete: true,
+                    retention_days: Some(90),
+                    purge_protection: true,
+                    rbac: true,
+                    public_access: None,
+                    default_action: Some("Deny".to_string()),
+                    bypass: Some("AzureServices".to_string()),
+                    ip_rules: vec!["203.0.113.0/24".to_string()],
+                    subnets: vec!["apps".to_string()],
+                }),
+            }]
+        );
     }
-    debug!("preload_all_vaults: done");
 }
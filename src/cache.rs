@@ -0,0 +1,332 @@
+use std::io::{ErrorKind, Write};
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::model::{SecretMeta, Vault};
+
+/// File format marker, bumped whenever `Snapshot` changes incompatibly.
+const MAGIC: &[u8] = b"AKVC1";
+
+/// Vault and secret listings (names and metadata, never values) saved on exit and shown on
+/// the next start while discovery runs.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub vaults: Vec<Vault>,
+    pub listings: Vec<CachedListing>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct CachedListing {
+    pub vault: String,
+    pub refreshed_at: u64, // unix seconds
+    pub secrets: Vec<SecretMeta>,
+}
+
+/// Where the cache key is kept (`cache_key` in the config).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStore {
+    Auto,       // OS keyring, falling back to a passphrase
+    Keyring,    // macOS Keychain, Windows Credential Manager or the Secret Service
+    Passphrase, // derived from a passphrase prompted at startup (or AKV_TUI_CACHE_PASSPHRASE)
+    File,       // `cache.key` next to the cache, readable by the owner only
+}
+
+impl FromStr for KeyStore {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "keyring" | "keychain" => Ok(Self::Keyring),
+            "passphrase" => Ok(Self::Passphrase),
+            "file" => Ok(Self::File),
+            other => Err(format!(
+                "Unknown cache_key '{}' (expected auto, keyring, passphrase or file)",
+                other
+            )),
+        }
+    }
+}
+
+/// Keyring service name; the account is `cache-key` (`cache-key-<profile>` per profile).
+const KEYRING_SERVICE: &str = "akv-tui";
+const PBKDF2_ITERATIONS: u32 = 600_000;
+const SALT_LEN: usize = 16;
+
+/// `secrets.cache` in the cache directory, encrypted with AES-256-GCM under a key from the
+/// configured `KeyStore`.
+pub struct DiskCache {
+    path: PathBuf,
+    key: Vec<u8>,
+}
+
+impl DiskCache {
+    /// Open the cache in `dir`, fetching (or creating) its key. Blocks on the keyring and may
+    /// prompt for a passphrase, so call it before the TUI starts.
+    pub fn open(dir: &Path, store: KeyStore, profile: Option<&str>) -> Result<Self, String> {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        let key = match store {
+            KeyStore::Keyring => keyring_key(dir, profile)?,
+            KeyStore::Passphrase => passphrase_key(dir)?,
+            KeyStore::File => file_key(&dir.join("cache.key"))?,
+            KeyStore::Auto => keyring_key(dir, profile).or_else(|e| {
+                warn!("OS keyring unavailable, using a passphrase: {}", e);
+                passphrase_key(dir)
+            })?,
+        };
+        Ok(Self::with_key(dir, key))
+    }
+
+    fn with_key(dir: &Path, key: Vec<u8>) -> Self {
+        Self {
+            path: dir.join("secrets.cache"),
+            key,
+        }
+    }
+
+    /// Read the cache; `Ok(None)` when there is none yet.
+    pub fn load(&self) -> Result<Option<Snapshot>, String> {
+        let Ok(data) = std::fs::read(&self.path) else {
+            return Ok(None);
+        };
+        let plain = decrypt(&self.key, &data)?;
+        serde_json::from_slice(&plain)
+            .map(Some)
+            .map_err(|e| format!("Invalid cache {}: {}", self.path.display(), e))
+    }
+
+    pub fn save(&self, snapshot: &Snapshot) -> Result<(), String> {
+        let plain = serde_json::to_vec(snapshot).map_err(|e| e.to_string())?;
+        let data = encrypt(&self.key, plain)?;
+        std::fs::write(&self.path, data)
+            .map_err(|e| format!("Cannot save {}: {}", self.path.display(), e))
+    }
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![0u8; len];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Cannot generate random bytes")?;
+    Ok(bytes)
+}
+
+/// Write a file only the owner can read.
+fn write_private(path: &Path, data: &[u8]) -> Result<(), String> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(path)
+        .and_then(|mut f| f.write_all(data))
+        .map_err(|e| format!("Cannot save {}: {}", path.display(), e))
+}
+
+/// Read a key (or salt) file of `len` bytes, `Ok(None)` when there is none yet. A file that
+/// can't be read or has the wrong size is an error rather than replaced: a new key would make
+/// the existing cache undecryptable for good.
+fn read_key_file(path: &Path, len: usize) -> Result<Option<Vec<u8>>, String> {
+    match std::fs::read(path) {
+        Ok(key) if key.len() == len => Ok(Some(key)),
+        Ok(key) => Err(format!(
+            "Invalid {}: {} bytes instead of {}",
+            path.display(),
+            key.len(),
+            len
+        )),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Cannot read {}: {}", path.display(), e)),
+    }
+}
+
+fn file_key(path: &Path) -> Result<Vec<u8>, String> {
+    if let Some(key) = read_key_file(path, AES_256_GCM.key_len())? {
+        return Ok(key);
+    }
+    let key = random_bytes(AES_256_GCM.key_len())?;
+    write_private(path, &key)?;
+    Ok(key)
+}
+
+/// The key stored in the OS keyring, created on first use. A `cache.key` file left by the
+/// `file` store is moved into the keyring so the existing cache stays readable.
+fn keyring_key(dir: &Path, profile: Option<&str>) -> Result<Vec<u8>, String> {
+    let account = match profile {
+        Some(profile) => format!("cache-key-{}", profile),
+        None => "cache-key".to_string(),
+    };
+    let entry = keyring::Entry::new(KEYRING_SERVICE, &account).map_err(|e| e.to_string())?;
+    match entry.get_password() {
+        Ok(stored) => STANDARD
+            .decode(stored)
+            .ok()
+            .filter(|key| key.len() == AES_256_GCM.key_len())
+            .ok_or_else(|| format!("Invalid cache key in the keyring ({})", account)),
+        Err(keyring::Error::NoEntry) => {
+            let legacy = dir.join("cache.key");
+            let key = match read_key_file(&legacy, AES_256_GCM.key_len())? {
+                Some(key) => key,
+                None => random_bytes(AES_256_GCM.key_len())?,
+            };
+            entry
+                .set_password(&STANDARD.encode(&key))
+                .map_err(|e| e.to_string())?;
+            let _ = std::fs::remove_file(legacy);
+            Ok(key)
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Derive the key from a passphrase and the random salt in `cache.salt`.
+fn passphrase_key(dir: &Path) -> Result<Vec<u8>, String> {
+    let passphrase = match std::env::var("AKV_TUI_CACHE_PASSPHRASE") {
+        Ok(passphrase) => passphrase,
+        Err(_) => rpassword::prompt_password("akv: passphrase for the disk cache: ")
+            .map_err(|e| format!("Cannot read the cache passphrase: {}", e))?,
+    };
+    if passphrase.is_empty() {
+        return Err("No cache passphrase given".into());
+    }
+    let salt_path = dir.join("cache.salt");
+    let salt = match read_key_file(&salt_path, SALT_LEN)? {
+        Some(salt) => salt,
+        None => {
+            let salt = random_bytes(SALT_LEN)?;
+            write_private(&salt_path, &salt)?;
+            salt
+        }
+    };
+    Ok(derive_key(&passphrase, &salt))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Vec<u8> {
+    let mut key = vec![0u8; AES_256_GCM.key_len()];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    key
+}
+
+fn cipher(key: &[u8]) -> Result<LessSafeKey, String> {
+    UnboundKey::new(&AES_256_GCM, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| "Invalid cache key".to_string())
+}
+
+/// `MAGIC || nonce || ciphertext+tag`
+fn encrypt(key: &[u8], mut plain: Vec<u8>) -> Result<Vec<u8>, String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| "Cannot generate a nonce")?;
+    cipher(key)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(MAGIC),
+            &mut plain,
+        )
+        .map_err(|_| "Cannot encrypt the cache")?;
+    Ok([MAGIC, &nonce, &plain].concat())
+}
+
+fn decrypt(key: &[u8], data: &[u8]) -> Result<Vec<u8>, String> {
+    let rest = data
+        .strip_prefix(MAGIC)
+        .filter(|rest| rest.len() >= NONCE_LEN)
+        .ok_or("Unsupported cache format")?;
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| "Invalid cache nonce")?;
+    let mut sealed = sealed.to_vec();
+    let plain = cipher(key)?
+        .open_in_place(nonce, Aad::from(MAGIC), &mut sealed)
+        .map_err(|_| "Cache cannot be decrypted (wrong key or corrupted)")?;
+    Ok(plain.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_round_trip_and_tampering() {
+        let dir = std::env::temp_dir().join(format!("akv-cache-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let key = derive_key("correct horse", b"salt");
+        let cache = DiskCache::with_key(&dir, key);
+        assert_eq!(cache.load(), Ok(None));
+
+        let snapshot = Snapshot {
+            vaults: vec![Vault {
+                name: "kv-prod".into(),
+                uri: "https://kv-prod.vault.azure.net/".into(),
+                alias: None,
+                subscription: Some("Production".into()),
+                resource_group: Some("rg-prod".into()),
+                location: Some("westeurope".into()),
+                properties: None,
+            }],
+            listings: vec![CachedListing {
+                vault: "kv-prod".into(),
+                refreshed_at: 1_700_000_000,
+                secrets: vec![SecretMeta {
+                    name: "db-password".into(),
+                    enabled: true,
+                    tags: vec![("env".into(), "prod".into())],
+                    updated: Some(
+                        time::OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap(),
+                    ),
+                    ..Default::default()
+                }],
+            }],
+        };
+        cache.save(&snapshot).unwrap();
+        assert_eq!(cache.load(), Ok(Some(snapshot)));
+        let wrong = DiskCache::with_key(&dir, derive_key("wrong", b"salt"));
+        assert!(wrong.load().is_err());
+
+        // Nothing readable on disk, and any modification is rejected
+        let mut data = std::fs::read(dir.join("secrets.cache")).unwrap();
+        assert!(!String::from_utf8_lossy(&data).contains("db-password"));
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        std::fs::write(dir.join("secrets.cache"), data).unwrap();
+        assert!(cache.load().is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_key_never_replaced() {
+        let dir = std::env::temp_dir().join(format!("akv-cache-key-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cache.key");
+        let key = file_key(&path).unwrap();
+        assert_eq!(file_key(&path), Ok(key));
+
+        // A damaged key file is reported, and left for the user to restore
+        std::fs::write(&path, b"truncated").unwrap();
+        assert!(
+            file_key(&path)
+                .unwrap_err()
+                .contains("9 bytes instead of 32")
+        );
+        assert_eq!(std::fs::read(&path).unwrap(), b"truncated");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
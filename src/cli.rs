@@ -0,0 +1,582 @@
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::sync::Arc;
+
+use azure_core::credentials::TokenCredential;
+use azure_core::time::to_rfc3339;
+use azure_security_keyvault_secrets::SecretClient;
+use azure_security_keyvault_secrets::models::SecretAttributes;
+use futures::{StreamExt, stream};
+use serde_json::{Map, Value, json};
+
+use crate::audit;
+use crate::azure::{
+    AzureStore, delete_secret, get_secret, get_secret_value, list_secrets_with_properties,
+    secret_client, secret_meta, set_secret_value, vault_uri,
+};
+use crate::config::Config;
+use crate::encoding::{encode_base64, env_var_name, read_stdin_value};
+use crate::error::AkvError;
+use crate::model::SecretMeta;
+use crate::store::SecretStore;
+
+pub const USAGE: &str = "\
+Usage:
+  akv [--vault <vault>]            Start the interactive TUI (optionally inside a vault)
+  akv list <vault> [--values]      List secret names (optionally with values)
+  akv get <vault> <name>           Print a secret value
+  akv set <vault> <name> [value]   Create or update a secret; without a value it is
+                                   read from stdin (binary input, or --base64, is
+                                   stored base64-encoded)
+  akv delete <vault> <name>        Delete a secret
+  akv versions <vault> <name>      List the versions of a secret, newest first
+  akv audit <vault>                Flag secrets without an expiry or content type, and
+                                   disabled ones matching audit_referenced; exits
+                                   with status 1 when anything is flagged
+  akv pick [--vault <vault>] [--name-only]
+                                   Choose a secret in the TUI and print its value
+                                   (or name) to stdout, e.g. TOKEN=$(akv pick)
+  akv run --vault <vault> [--prefix FOO_] -- <command> [args...]
+                                   Run a command with the vault's secrets as
+                                   environment variables (db-password -> DB_PASSWORD);
+                                   --prefix only injects variables starting with FOO_
+
+Options:
+  -o, --output <text|json>         Output format (json includes attributes)
+  --cloud <public|usgov|china>     Azure cloud to use (default public)
+  --config <path>                  Config file (default ~/.config/akv-tui/config.toml)
+  --credential <kind>              auto (default), cli, client-secret, certificate,
+                                   managed-identity or workload-identity
+  --profile <name>                 Use [profiles.<name>] from the config file
+  --subscription <id|name>         Only discover vaults in this subscription (repeatable)
+  --offline                        Browse cached vaults and secret names read-only,
+                                   without network calls
+  --demo                           Try the TUI on generated vaults and secrets, without
+                                   signing in or any Azure calls
+  --stdin                          Read a piped value for the next Add dialog of
+                                   the TUI, e.g. cat cert.pem | akv --stdin
+  --script <file>                  Run the TUI on keys from a file (key, type, wait,
+                                   expect) without a terminal; exits with status 1
+                                   when an expect fails
+  --no-color                       Draw without colors, only bold, dim and reverse
+                                   video (also when NO_COLOR is set)
+
+<vault> is a vault name or its full https:// URI.";
+
+/// Flags that take a value; their values must not be mistaken for positional arguments.
+const VALUE_FLAGS: &[&str] = &[
+    "--clipboard",
+    "--clipboard-clear",
+    "--cloud",
+    "--config",
+    "--credential",
+    "--output",
+    "-o",
+    "--vault",
+    "--prefix",
+    "--script",
+    "--profile",
+    "--subscription",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// A parsed subcommand together with its output options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    pub command: Command,
+    pub output: OutputFormat,
+}
+
+/// A non-interactive subcommand.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    List {
+        vault: String,
+        with_values: bool,
+    },
+    Get {
+        vault: String,
+        name: String,
+    },
+    Set {
+        vault: String,
+        name: String,
+        value: Option<String>, // None = read from stdin
+        base64: bool,
+    },
+    Delete {
+        vault: String,
+        name: String,
+    },
+    Versions {
+        vault: String,
+        name: String,
+    },
+    Run {
+        vault: String,
+        prefix: Option<String>,
+        command: Vec<String>,
+    },
+    Pick {
+        vault: Option<String>,
+        name_only: bool,
+    },
+    Audit {
+        vault: String,
+    },
+}
+
+/// Arguments before a `--` separator (what follows belongs to `run`'s child process).
+fn own_args(args: &[String]) -> &[String] {
+    &args[..args.iter().position(|a| a == "--").unwrap_or(args.len())]
+}
+
+/// Positional arguments with flags (and their values) removed.
+fn positionals(args: &[String]) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut iter = own_args(args).iter();
+    while let Some(arg) = iter.next() {
+        if VALUE_FLAGS.contains(&arg.as_str()) {
+            iter.next();
+        } else if !arg.starts_with("--") {
+            out.push(arg.as_str());
+        }
+    }
+    out
+}
+
+/// Value of a flag such as `--output json`.
+fn flag_value<'a>(args: &'a [String], names: &[&str]) -> Option<&'a str> {
+    let args = own_args(args);
+    args.iter()
+        .position(|a| names.contains(&a.as_str()))
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+}
+
+/// Every value of a repeatable flag such as `--subscription a --subscription b`.
+pub fn flag_values<'a>(args: &'a [String], name: &str) -> Vec<&'a str> {
+    own_args(args)
+        .windows(2)
+        .filter(|w| w[0] == name)
+        .map(|w| w[1].as_str())
+        .collect()
+}
+
+/// Parse `args` (without the program name). Returns Ok(None) when no subcommand is given
+/// and the TUI should start.
+pub fn parse(args: &[String]) -> Result<Option<Invocation>, String> {
+    let output = match flag_value(args, &["--output", "-o"]) {
+        None | Some("text") => OutputFormat::Text,
+        Some("json") => OutputFormat::Json,
+        Some(other) => {
+            return Err(format!(
+                "Invalid output format '{}' (expected text or json)",
+                other
+            ));
+        }
+    };
+    let pos = positionals(args);
+    let Some((&sub, rest)) = pos.split_first() else {
+        return Ok(None);
+    };
+    let arg = |i: usize, what: &str| {
+        rest.get(i)
+            .map(|s| s.to_string())
+            .ok_or_else(|| format!("{}: missing <{}>\n\n{}", sub, what, USAGE))
+    };
+    let cmd = match sub {
+        "list" => Command::List {
+            vault: arg(0, "vault")?,
+            with_values: own_args(args).iter().any(|a| a == "--values"),
+        },
+        "get" => Command::Get {
+            vault: arg(0, "vault")?,
+            name: arg(1, "name")?,
+        },
+        "set" => Command::Set {
+            vault: arg(0, "vault")?,
+            name: arg(1, "name")?,
+            value: rest.get(2).filter(|v| **v != "-").map(|v| v.to_string()),
+            base64: own_args(args).iter().any(|a| a == "--base64"),
+        },
+        "delete" => Command::Delete {
+            vault: arg(0, "vault")?,
+            name: arg(1, "name")?,
+        },
+        "versions" => Command::Versions {
+            vault: arg(0, "vault")?,
+            name: arg(1, "name")?,
+        },
+        "audit" => Command::Audit {
+            vault: arg(0, "vault")?,
+        },
+        "pick" => Command::Pick {
+            vault: flag_value(args, &["--vault"])
+                .or_else(|| rest.first().copied())
+                .map(String::from),
+            name_only: own_args(args).iter().any(|a| a == "--name-only"),
+        },
+        "run" => {
+            let command: Vec<String> = args
+                .iter()
+                .skip_while(|a| *a != "--")
+                .skip(1)
+                .cloned()
+                .collect();
+            if command.is_empty() {
+                return Err(format!("run: missing -- <command>\n\n{}", USAGE));
+            }
+            Command::Run {
+                vault: flag_value(args, &["--vault"])
+                    .map(String::from)
+                    .or_else(|| rest.first().map(|s| s.to_string()))
+                    .ok_or_else(|| format!("run: missing --vault <vault>\n\n{}", USAGE))?,
+                prefix: flag_value(args, &["--prefix"]).map(String::from),
+                command,
+            }
+        }
+        other => return Err(format!("Unknown command '{}'\n\n{}", other, USAGE)),
+    };
+    Ok(Some(Invocation {
+        command: cmd,
+        output,
+    }))
+}
+
+/// Execute a subcommand, printing results to stdout.
+pub async fn run(
+    invocation: Invocation,
+    credential: Arc<dyn TokenCredential>,
+    config: &Config,
+) -> Result<(), AkvError> {
+    let json = invocation.output == OutputFormat::Json;
+    let client = |vault: &str| secret_client(&vault_uri(vault), credential.clone());
+    match invocation.command {
+        Command::List { vault, with_values } => {
+            let client = Arc::new(client(&vault)?);
+            let items = list_secrets_with_properties(&client).await?;
+            let mut out = Vec::new();
+            for (name, props) in items {
+                let value = if with_values {
+                    Some(get_secret_value(client.clone(), &name).await?)
+                } else {
+                    None
+                };
+                if json {
+                    let mut obj = secret_json(
+                        &name,
+                        props.attributes.as_ref(),
+                        props.content_type.as_deref(),
+                        props.tags.as_ref(),
+                    );
+                    if let Some(value) = value {
+                        obj.insert("value".into(), Value::String(value));
+                    }
+                    out.push(Value::Object(obj));
+                } else if let Some(value) = value {
+                    println!("{}\t{}", name, value);
+                } else {
+                    println!("{}", name);
+                }
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&out)?);
+            }
+        }
+        Command::Get { vault, name } => {
+            let secret = get_secret(&client(&vault)?, &name).await?;
+            let value = secret.value.clone().unwrap_or_default();
+            if json {
+                let mut obj = secret_json(
+                    &name,
+                    secret.attributes.as_ref(),
+                    secret.content_type.as_deref(),
+                    secret.tags.as_ref(),
+                );
+                obj.insert("value".into(), Value::String(value));
+                println!("{}", serde_json::to_string_pretty(&obj)?);
+            } else {
+                println!("{}", value);
+            }
+        }
+        Command::Set {
+            vault,
+            name,
+            value,
+            base64,
+        } => {
+            let (value, content_type) = match value {
+                Some(value) if base64 => (encode_base64(&value), None),
+                Some(value) => (value, None),
+                None if std::io::stdin().is_terminal() => {
+                    return Err(format!(
+                        "set: missing <value> (pass it as an argument or pipe it via stdin)\n\n{}",
+                        USAGE
+                    )
+                    .into());
+                }
+                None => {
+                    let piped = read_stdin_value(base64).map_err(|e| format!("set: {}", e))?;
+                    (piped.value, Some(piped.content_type))
+                }
+            };
+            set_secret_value(&client(&vault)?, &name, value, content_type).await?;
+            report(
+                json,
+                &name,
+                "set",
+                format!("Secret '{}' created/updated", name),
+            )?;
+        }
+        Command::Delete { vault, name } => {
+            delete_secret(&client(&vault)?, &name).await?;
+            report(json, &name, "deleted", format!("Secret '{}' deleted", name))?;
+        }
+        Command::Versions { vault, name } => {
+            let store = AzureStore::new(&vault_uri(&vault), credential.clone())?;
+            let versions = store.versions(&name).await?;
+            let time = |t: Option<&time::OffsetDateTime>| t.map(to_rfc3339);
+            if json {
+                let out: Vec<Value> = versions
+                    .iter()
+                    .map(|v| {
+                        json!({
+                            "version": v.version,
+                            "enabled": v.enabled,
+                            "created": time(v.created.as_ref()),
+                            "updated": time(v.updated.as_ref()),
+                            "expires": time(v.expires.as_ref()),
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&out)?);
+            } else {
+                for v in &versions {
+                    println!(
+                        "{}\t{}\t{}",
+                        v.version,
+                        time(v.created.as_ref()).unwrap_or_else(|| "-".into()),
+                        if v.enabled { "enabled" } else { "disabled" }
+                    );
+                }
+            }
+        }
+        Command::Run {
+            vault,
+            prefix,
+            command,
+        } => {
+            let client = Arc::new(client(&vault)?);
+            let env = secret_env(client, prefix.as_deref()).await?;
+            let status = std::process::Command::new(&command[0])
+                .args(&command[1..])
+                .envs(env)
+                .status()
+                .map_err(|e| format!("Failed to run '{}': {}", command[0], e))?;
+            std::process::exit(status.code().unwrap_or(1));
+        }
+        Command::Audit { vault } => {
+            let secrets: Vec<SecretMeta> = list_secrets_with_properties(&client(&vault)?)
+                .await?
+                .into_iter()
+                .map(|(name, props)| secret_meta(name, &props))
+                .collect();
+            let findings = audit::audit(
+                secrets.iter().map(|s| (vault.as_str(), s)),
+                &config.audit_referenced,
+            );
+            if json {
+                let out: Vec<Value> = findings
+                    .iter()
+                    .map(|f| {
+                        let issues: Vec<&str> = f.issues.iter().map(|i| i.label()).collect();
+                        json!({ "name": f.name, "issues": issues })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&out)?);
+            } else {
+                for f in &findings {
+                    let issues: Vec<&str> = f.issues.iter().map(|i| i.label()).collect();
+                    println!("{}\t{}", f.name, issues.join(", "));
+                }
+                eprintln!("{} of {} secret(s) flagged", findings.len(), secrets.len());
+            }
+            if !findings.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Command::Pick { .. } => return Err("pick is interactive and runs in the TUI".into()),
+    }
+    Ok(())
+}
+
+/// Fetch the enabled secrets of a vault as (ENV_NAME, value), keeping only names that
+/// start with `prefix` when given.
+async fn secret_env(
+    client: Arc<SecretClient>,
+    prefix: Option<&str>,
+) -> Result<Vec<(String, String)>, AkvError> {
+    let names: Vec<(String, String)> = list_secrets_with_properties(&client)
+        .await?
+        .into_iter()
+        .filter(|(_, props)| props.attributes.as_ref().and_then(|a| a.enabled) != Some(false))
+        .map(|(name, _)| (env_var_name(&name), name))
+        .filter(|(env, _)| prefix.is_none_or(|p| env.starts_with(p)))
+        .collect();
+
+    // Fetch values with bounded concurrency to stay clear of throttling
+    let values: Vec<Result<(String, String), AkvError>> = stream::iter(names)
+        .map(|(env, name)| {
+            let client = client.clone();
+            async move { Ok((env, get_secret_value(client, &name).await?)) }
+        })
+        .buffered(8)
+        .collect()
+        .await;
+    let mut env: Vec<(String, String)> = values.into_iter().collect::<Result<_, _>>()?;
+
+    // Different secret names can map to the same variable (a-b / a_b); keep the first
+    env.sort_by(|a, b| a.0.cmp(&b.0));
+    env.dedup_by(|b, a| {
+        let dup = a.0 == b.0;
+        if dup {
+            eprintln!("Warning: several secrets map to ${}; using the first", a.0);
+        }
+        dup
+    });
+    Ok(env)
+}
+
+/// Result line for write commands: JSON on stdout, or a human message on stderr.
+fn report(json: bool, name: &str, result: &str, message: String) -> Result<(), AkvError> {
+    if json {
+        println!("{}", json!({ "name": name, "result": result }));
+    } else {
+        eprintln!("{}", message);
+    }
+    Ok(())
+}
+
+/// Machine-readable view of a secret's metadata (timestamps as RFC 3339).
+fn secret_json(
+    name: &str,
+    attributes: Option<&SecretAttributes>,
+    content_type: Option<&str>,
+    tags: Option<&HashMap<String, String>>,
+) -> Map<String, Value> {
+    let time = |t: Option<&time::OffsetDateTime>| t.map(|t| Value::String(to_rfc3339(t)));
+    let mut obj = Map::new();
+    obj.insert("name".into(), json!(name));
+    obj.insert("contentType".into(), json!(content_type));
+    if let Some(a) = attributes {
+        obj.insert("enabled".into(), json!(a.enabled));
+        obj.insert("created".into(), json!(time(a.created.as_ref())));
+        obj.insert("updated".into(), json!(time(a.updated.as_ref())));
+        obj.insert("expires".into(), json!(time(a.expires.as_ref())));
+        obj.insert("notBefore".into(), json!(time(a.not_before.as_ref())));
+    }
+    obj.insert("tags".into(), json!(tags.cloned().unwrap_or_default()));
+    obj
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_subcommands() {
+        assert_eq!(parse(&args("")), Ok(None));
+        assert_eq!(parse(&args("--debug --clipboard osc52")), Ok(None));
+        assert_eq!(
+            parse(&args("get --debug my-vault db-pass")),
+            Ok(Some(Invocation {
+                command: Command::Get {
+                    vault: "my-vault".into(),
+                    name: "db-pass".into()
+                },
+                output: OutputFormat::Text,
+            }))
+        );
+        assert_eq!(
+            parse(&args("list --clipboard-clear 5 my-vault -o json --values")),
+            Ok(Some(Invocation {
+                command: Command::List {
+                    vault: "my-vault".into(),
+                    with_values: true,
+                },
+                output: OutputFormat::Json,
+            }))
+        );
+        assert_eq!(
+            parse(&args("set my-vault name - --base64")).map(|i| i.map(|i| i.command)),
+            Ok(Some(Command::Set {
+                vault: "my-vault".into(),
+                name: "name".into(),
+                value: None,
+                base64: true,
+            }))
+        );
+        assert_eq!(
+            parse(&args("audit my-vault")).map(|i| i.map(|i| i.command)),
+            Ok(Some(Command::Audit {
+                vault: "my-vault".into()
+            }))
+        );
+        assert_eq!(
+            parse(&args("versions my-vault db-pass")).map(|i| i.map(|i| i.command)),
+            Ok(Some(Command::Versions {
+                vault: "my-vault".into(),
+                name: "db-pass".into()
+            }))
+        );
+        assert!(parse(&args("versions my-vault")).is_err());
+        assert!(parse(&args("set my-vault")).is_err());
+        assert!(parse(&args("frobnicate")).is_err());
+        assert!(parse(&args("list v --output yaml")).is_err());
+
+        // Everything after `--` belongs to the child, including flags
+        assert_eq!(
+            parse(&args("run --vault v --prefix APP_ -- env -o json")),
+            Ok(Some(Invocation {
+                command: Command::Run {
+                    vault: "v".into(),
+                    prefix: Some("APP_".into()),
+                    command: args("env -o json"),
+                },
+                output: OutputFormat::Text,
+            }))
+        );
+        assert!(parse(&args("run --vault v")).is_err());
+        assert_eq!(
+            parse(&args("pick --name-only")).map(|i| i.map(|i| i.command)),
+            Ok(Some(Command::Pick {
+                vault: None,
+                name_only: true
+            }))
+        );
+
+        // Global flags don't turn into subcommands
+        let tui = args("--subscription sub-a --subscription Production --vault kv");
+        assert_eq!(parse(&tui), Ok(None));
+        assert_eq!(flag_values(&tui, "--subscription"), ["sub-a", "Production"]);
+    }
+
+    #[test]
+    fn test_secret_json() {
+        let obj = secret_json("db", None, Some("text/plain"), None);
+        assert_eq!(
+            Value::Object(obj),
+            json!({ "name": "db", "contentType": "text/plain", "tags": {} })
+        );
+    }
+}
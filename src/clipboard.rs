@@ -0,0 +1,179 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use clipboard::{ClipboardContext, ClipboardProvider};
+
+/// Where copied values go. `Native` uses the `clipboard` crate (X11/macOS/Windows), which
+/// doesn't work on Wayland or inside WSL; the other backends shell out or use the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardBackend {
+    Native,
+    Command, // wl-copy/wl-paste on Wayland, xclip elsewhere
+    Wsl,     // clip.exe / powershell.exe Get-Clipboard
+    Osc52,   // terminal escape sequence (works over SSH, write-only)
+    #[cfg(test)]
+    Memory, // a buffer per thread, so tests never touch the real clipboard
+}
+
+#[cfg(test)]
+thread_local! {
+    static MEMORY: std::cell::RefCell<String> = const { std::cell::RefCell::new(String::new()) };
+}
+
+impl FromStr for ClipboardBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::detect()),
+            "native" => Ok(Self::Native),
+            "command" | "wl-copy" | "xclip" => Ok(Self::Command),
+            "wsl" | "clip.exe" => Ok(Self::Wsl),
+            "osc52" => Ok(Self::Osc52),
+            other => Err(format!(
+                "Unknown clipboard backend '{}' (expected auto, native, command, wsl or osc52)",
+                other
+            )),
+        }
+    }
+}
+
+impl ClipboardBackend {
+    /// Pick a backend for the current environment: WSL, then Wayland, then native.
+    pub fn detect() -> Self {
+        let wsl = std::env::var_os("WSL_DISTRO_NAME").is_some()
+            || std::fs::read_to_string("/proc/sys/kernel/osrelease")
+                .is_ok_and(|r| r.to_ascii_lowercase().contains("microsoft"));
+        if wsl {
+            Self::Wsl
+        } else if wayland() {
+            Self::Command
+        } else {
+            Self::Native
+        }
+    }
+
+    pub fn set_contents(&self, text: String) -> Result<(), String> {
+        match self {
+            Self::Native => {
+                let mut ctx: ClipboardContext =
+                    ClipboardProvider::new().map_err(|e| format!("Clipboard init error: {}", e))?;
+                ctx.set_contents(text)
+                    .map_err(|e| format!("Clipboard error: {}", e))
+            }
+            Self::Command if wayland() => pipe_to("wl-copy", &[], &text),
+            Self::Command => pipe_to("xclip", &["-selection", "clipboard"], &text),
+            Self::Wsl => pipe_to("clip.exe", &[], &text),
+            Self::Osc52 => {
+                let mut out = std::io::stdout();
+                out.write_all(osc52_sequence(&text, std::env::var_os("TMUX").is_some()).as_bytes())
+                    .and_then(|_| out.flush())
+                    .map_err(|e| format!("Clipboard error: {}", e))
+            }
+            #[cfg(test)]
+            Self::Memory => {
+                MEMORY.with(|m| *m.borrow_mut() = text);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn get_contents(&self) -> Result<String, String> {
+        match self {
+            Self::Native => {
+                let mut ctx: ClipboardContext =
+                    ClipboardProvider::new().map_err(|e| format!("Clipboard init error: {}", e))?;
+                ctx.get_contents()
+                    .map_err(|e| format!("Clipboard error: {}", e))
+            }
+            Self::Command if wayland() => read_from("wl-paste", &["--no-newline"]),
+            Self::Command => read_from("xclip", &["-selection", "clipboard", "-o"]),
+            Self::Wsl => read_from(
+                "powershell.exe",
+                &["-NoProfile", "-Command", "Get-Clipboard"],
+            )
+            .map(|s| s.trim_end_matches(['\r', '\n']).to_string()),
+            Self::Osc52 => Err("Reading the clipboard is not supported with OSC 52".into()),
+            #[cfg(test)]
+            Self::Memory => Ok(MEMORY.with(|m| m.borrow().clone())),
+        }
+    }
+}
+
+fn wayland() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+fn pipe_to(program: &str, args: &[&str], text: &str) -> Result<(), String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Clipboard error: cannot run {}: {}", program, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("Clipboard error: {}", e))?;
+    }
+    let status = child
+        .wait()
+        .map_err(|e| format!("Clipboard error: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Clipboard error: {} exited with {}",
+            program, status
+        ))
+    }
+}
+
+fn read_from(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| format!("Clipboard error: cannot run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Clipboard error: {} exited with {}",
+            program, output.status
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// OSC 52 "set clipboard" sequence; inside tmux it has to be wrapped in a DCS passthrough.
+fn osc52_sequence(text: &str, tmux: bool) -> String {
+    let seq = format!("\x1b]52;c;{}\x07", STANDARD.encode(text.as_bytes()));
+    if tmux {
+        format!("\x1bPtmux;{}\x1b\\", seq.replace('\x1b', "\x1b\x1b"))
+    } else {
+        seq
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_backend_and_osc52() {
+        assert_eq!("native".parse(), Ok(ClipboardBackend::Native));
+        assert_eq!("wl-copy".parse(), Ok(ClipboardBackend::Command));
+        assert_eq!("WSL".parse(), Ok(ClipboardBackend::Wsl));
+        assert_eq!("osc52".parse(), Ok(ClipboardBackend::Osc52));
+        assert!("pbcopy".parse::<ClipboardBackend>().is_err());
+
+        assert_eq!(osc52_sequence("hi", false), "\x1b]52;c;aGk=\x07");
+        assert_eq!(
+            osc52_sequence("hi", true),
+            "\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// Endpoints of an Azure cloud: where ARM lives, which DNS suffix vaults use and where
+/// service principals sign in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cloud {
+    pub management: String,
+    pub vault_suffix: String,
+    pub authority_host: String,
+    pub log_analytics: String, // Azure Monitor Logs query API
+}
+
+impl Cloud {
+    pub fn public() -> Self {
+        Self::new(
+            "https://management.azure.com",
+            "vault.azure.net",
+            "https://login.microsoftonline.com",
+            "https://api.loganalytics.io",
+        )
+    }
+
+    fn new(
+        management: &str,
+        vault_suffix: &str,
+        authority_host: &str,
+        log_analytics: &str,
+    ) -> Self {
+        Self {
+            management: management.to_string(),
+            vault_suffix: vault_suffix.to_string(),
+            authority_host: authority_host.to_string(),
+            log_analytics: log_analytics.to_string(),
+        }
+    }
+
+    /// OAuth scope for ARM calls in this cloud.
+    pub fn management_scope(&self) -> String {
+        format!("{}/.default", self.management.trim_end_matches('/'))
+    }
+
+    /// OAuth scope for Key Vault REST calls (keys; secrets go through the SDK).
+    pub fn vault_scope(&self) -> String {
+        format!("https://{}/.default", self.vault_suffix)
+    }
+
+    /// OAuth scope for Log Analytics queries (the activity log).
+    pub fn log_analytics_scope(&self) -> String {
+        format!("{}/.default", self.log_analytics.trim_end_matches('/'))
+    }
+}
+
+impl FromStr for Cloud {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "public" | "azurecloud" => Ok(Self::public()),
+            "usgov" | "azureusgovernment" => Ok(Self::new(
+                "https://management.usgovcloudapi.net",
+                "vault.usgovcloudapi.net",
+                "https://login.microsoftonline.us",
+                "https://api.loganalytics.us",
+            )),
+            "china" | "azurechinacloud" => Ok(Self::new(
+                "https://management.chinacloudapi.cn",
+                "vault.azure.cn",
+                "https://login.chinacloudapi.cn",
+                "https://api.loganalytics.azure.cn",
+            )),
+            other => Err(format!(
+                "Unknown cloud '{}' (expected public, usgov or china)",
+                other
+            )),
+        }
+    }
+}
+
+static CLOUD: OnceLock<Cloud> = OnceLock::new();
+
+/// Select the cloud for this run; must be called before the first Azure request.
+pub fn set_cloud(cloud: Cloud) {
+    let _ = CLOUD.set(cloud);
+}
+
+/// The selected cloud (public Azure unless `set_cloud` said otherwise).
+pub fn cloud() -> &'static Cloud {
+    CLOUD.get_or_init(Cloud::public)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cloud_presets() {
+        assert_eq!(
+            "public".parse::<Cloud>().unwrap().management_scope(),
+            "https://management.azure.com/.default"
+        );
+        let gov: Cloud = "AzureUSGovernment".parse().unwrap();
+        assert_eq!(gov.vault_suffix, "vault.usgovcloudapi.net");
+        let china: Cloud = "china".parse().unwrap();
+        assert_eq!(china.management, "https://management.chinacloudapi.cn");
+        assert_eq!(
+            china.log_analytics_scope(),
+            "https://api.loganalytics.azure.cn/.default"
+        );
+        assert!("mars".parse::<Cloud>().is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use std::str::FromStr;
+
+use ratatui::layout::Constraint;
+
+use crate::model::SecretMeta;
+
+/// A column of the secrets table, chosen and ordered with `columns` in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Name,
+    Updated,
+    Expires,
+    Enabled,
+    ContentType,
+    Tags,
+}
+
+/// Columns shown when the config doesn't choose any.
+pub const DEFAULT_COLUMNS: [Column; 3] = [Column::Name, Column::Updated, Column::Expires];
+
+impl Column {
+    /// Name in the config file.
+    pub fn key(self) -> &'static str {
+        match self {
+            Column::Name => "name",
+            Column::Updated => "updated",
+            Column::Expires => "expires",
+            Column::Enabled => "enabled",
+            Column::ContentType => "content-type",
+            Column::Tags => "tags",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Column::Name => "Name",
+            Column::Updated => "Updated",
+            Column::Expires => "Expires",
+            Column::Enabled => "Enabled",
+            Column::ContentType => "Content type",
+            Column::Tags => "Tags",
+        }
+    }
+
+    /// Dates and flags get what they need; names, content types and tags share the rest.
+    pub fn width(self) -> Constraint {
+        match self {
+            Column::Name => Constraint::Fill(3),
+            Column::Updated | Column::Expires => Constraint::Length(10),
+            Column::Enabled => Constraint::Length(7),
+            Column::ContentType => Constraint::Fill(1),
+            Column::Tags => Constraint::Fill(2),
+        }
+    }
+
+    /// The cell of a secret. Everything but the name comes from the listing metadata, so it
+    /// stays empty until the vault's listing is indexed.
+    pub fn text(self, name: &str, meta: Option<&SecretMeta>) -> String {
+        let date = |d: Option<time::OffsetDateTime>| d.map(|d| d.date().to_string());
+        match (self, meta) {
+            (Column::Name, _) => name.to_string(),
+            (_, None) => String::new(),
+            (Column::Updated, Some(m)) => date(m.updated).unwrap_or_default(),
+            (Column::Expires, Some(m)) => date(m.expires).unwrap_or_else(|| "never".into()),
+            (Column::Enabled, Some(m)) => if m.enabled { "yes" } else { "no" }.to_string(),
+            (Column::ContentType, Some(m)) => m.content_type.clone().unwrap_or_default(),
+            (Column::Tags, Some(m)) => m
+                .tags
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "name" => Ok(Column::Name),
+            "updated" => Ok(Column::Updated),
+            "expires" => Ok(Column::Expires),
+            "enabled" => Ok(Column::Enabled),
+            "content-type" => Ok(Column::ContentType),
+            "tags" => Ok(Column::Tags),
+            other => Err(format!(
+                "Unknown column '{}' (expected name, updated, expires, enabled, content-type or tags)",
+                other
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_cells() {
+        assert_eq!("Content_Type".parse(), Ok(Column::ContentType));
+        assert!("size".parse::<Column>().is_err());
+
+        let meta = SecretMeta {
+            name: "db-password".into(),
+            enabled: false,
+            tags: vec![("env".into(), "prod".into()), ("team".into(), "pay".into())],
+            updated: time::OffsetDateTime::from_unix_timestamp(1_772_359_200).ok(), // 2026-03-01
+            ..Default::default()
+        };
+        let cells: Vec<String> = [
+            Column::Name,
+            Column::Updated,
+            Column::Expires,
+            Column::Enabled,
+            Column::Tags,
+        ]
+        .iter()
+        .map(|c| c.text("db-password", Some(&meta)))
+        .collect();
+        assert_eq!(
+            cells,
+            [
+                "db-password",
+                "2026-03-01",
+                "never",
+                "no",
+                "env=prod, team=pay"
+            ]
+        );
+        // Not indexed yet
+        assert_eq!(Column::Expires.text("db-password", None), "");
+    }
+}
@@ -0,0 +1,917 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+use tracing_subscriber::EnvFilter;
+
+use crate::cache::KeyStore;
+use crate::clipboard::ClipboardBackend;
+use crate::cloud::Cloud;
+use crate::columns::{Column, DEFAULT_COLUMNS};
+use crate::credential::CredentialKind;
+use crate::encoding::expand_path;
+use crate::hooks::{HookEvent, Hooks};
+use crate::logging::LogFormat;
+use crate::theme::Theme;
+
+/// Secrets-screen actions that can be rebound, with their default keys.
+pub const ACTIONS: &[(&str, char)] = &[
+    ("quit", 'q'),
+    ("help", '?'),
+    ("vaults", 'v'),
+    ("tenant", 't'),
+    ("expiring", 'E'),
+    ("audit", 'A'),
+    ("report", 'R'),
+    ("keys", 'K'),
+    ("activity", 'L'),
+    ("tasks", 'T'),
+    ("error_details", '!'),
+    ("messages", 'm'),
+    ("offline", 'o'),
+    ("favorite", 'f'),
+    ("hide", 'h'),
+    ("show_hidden", 'H'),
+    ("search", '/'),
+    ("value_search", 'V'),
+    ("add", 'a'),
+    ("edit", 'e'),
+    ("delete", 'd'),
+    ("refresh", 'r'),
+    ("watch", 'w'),
+    ("copy_name", 'y'),
+    ("copy_export", 'x'),
+    ("preview", 'p'),
+    ("base64_copy", 'b'),
+    ("base64_preview", 'B'),
+    ("conn_string", 'c'),
+    ("json_view", 'J'),
+    ("mark", ' '),
+    ("mark_all", '*'),
+    ("sort", 'o'),
+    ("sort_reverse", 'O'),
+    ("group", 'z'),
+    ("expand", 'l'),
+    ("collapse", 'h'),
+    ("expand_all", '+'),
+    ("collapse_all", '-'),
+    ("down", 'j'),
+    ("up", 'k'),
+];
+
+/// User configuration loaded from `~/.config/akv-tui/config.toml`. Every field is optional;
+/// missing ones fall back to the built-in defaults.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Age after which a cached vault listing is refreshed in the background.
+    pub cache_ttl_secs: u64,
+    /// Secrets expiring within this many days are listed on the expiry dashboard (`E`).
+    pub expiry_window_days: u64,
+    /// Name patterns (`*` wildcards) of secrets applications still use; the audit (`A`,
+    /// `akv audit`) flags them when disabled.
+    pub audit_referenced: Vec<String>,
+    /// How often watch mode (`w`) re-lists the current vault.
+    pub watch_interval_secs: u64,
+    /// Load every vault's secret names in the background after discovery.
+    pub preload: bool,
+    /// Vaults listed concurrently while preloading after discovery.
+    pub preload_concurrency: usize,
+    /// Spinner interval, and how soon a change is drawn; an idle screen isn't redrawn.
+    pub tick_ms: u64,
+    /// Longest wait for terminal input between checks for background events; lower is
+    /// snappier, higher uses less CPU.
+    pub poll_ms: u64,
+    /// auto, native, command, wsl or osc52
+    pub clipboard: String,
+    /// Seconds before a copied secret is wiped from the clipboard (0 = never).
+    pub clipboard_clear_secs: u64,
+    /// Vault opened on startup instead of the vault list.
+    pub default_vault: Option<String>,
+    /// Only discover vaults in these subscriptions (IDs or display names).
+    pub subscriptions: Vec<String>,
+    /// public, usgov or china
+    pub cloud: String,
+    /// ARM endpoint overriding the cloud preset (Azure Stack Hub, private clouds).
+    pub management_endpoint: Option<String>,
+    /// Vault DNS suffix overriding the cloud preset, e.g. `vault.local.azurestack.external`.
+    pub vault_suffix: Option<String>,
+    /// auto, cli, client-secret, certificate, managed-identity or workload-identity
+    pub credential: String,
+    /// Azure AD tenant to sign in to (default: the Azure CLI's current tenant).
+    pub tenant: Option<String>,
+    /// Vaults listed first (and starred) on the vault selection screen.
+    pub favorites: Vec<String>,
+    /// Vault name -> friendly display name, e.g. `kvpmtprd001eastus2 = "prod-payments"`.
+    pub aliases: HashMap<String, String>,
+    /// Where on-disk caches live (default: `~/.cache/akv-tui[/<profile>]`).
+    pub cache_dir: Option<PathBuf>,
+    /// Desktop notification when preload or a bulk operation finishes while the terminal
+    /// isn't focused.
+    pub notifications: bool,
+    /// Mouse support (wheel scrolling, click to select, double click to open or copy). Off
+    /// leaves the mouse to the terminal, e.g. for selecting text.
+    pub mouse: bool,
+    /// Columns of the secrets table, in order: name, updated, expires, enabled, content-type
+    /// and tags.
+    pub columns: Vec<String>,
+    /// Splits secret names into the groups of the grouped list (`z`), e.g. "--" or "-".
+    pub group_delimiter: String,
+    /// Keep an encrypted copy of vault and secret listings in the cache dir between runs.
+    pub disk_cache: bool,
+    /// Where the disk cache key is kept: auto, keyring, passphrase or file.
+    pub cache_key: String,
+    /// Profile used when `--profile` isn't given.
+    pub profile: Option<String>,
+    pub profiles: HashMap<String, Profile>,
+    pub theme: ThemeConfig,
+    pub http: HttpConfig,
+    pub rate_limit: RateLimitConfig,
+    pub tls: TlsConfig,
+    pub hooks: HooksConfig,
+    pub log: LogConfig,
+    /// Action name -> key, e.g. `add = "n"`.
+    pub keys: HashMap<String, String>,
+}
+
+/// Named overrides for one tenant/context, e.g. `[profiles.work]`. Unset fields keep the
+/// top-level values.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub cloud: Option<String>,
+    pub credential: Option<String>,
+    pub tenant: Option<String>,
+    pub subscriptions: Option<Vec<String>>,
+    pub favorites: Option<Vec<String>>,
+    pub default_vault: Option<String>,
+    pub cache_dir: Option<PathBuf>,
+}
+
+/// Timeouts and retries for ARM and Key Vault requests.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    /// Whole-request timeout, so a vault in an unreachable region can't stall preload.
+    pub timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+    /// Retries for throttled (429), failed (5xx) and timed-out requests.
+    pub retries: u32,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 30,
+            connect_timeout_secs: 10,
+            retries: 3,
+        }
+    }
+}
+
+/// Client-side request rates, to stay under Key Vault's throttling limits (4000 requests
+/// per 10 seconds per vault for secrets) when preloading or exporting many vaults.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Requests per second to each vault (0 = unlimited).
+    pub per_vault: u32,
+    /// Requests per second across all vaults, e.g. for a subscription-wide limit (0 = unlimited).
+    pub global: u32,
+    /// Vault name -> requests per second, overriding `per_vault`.
+    pub vaults: HashMap<String, u32>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            per_vault: 200,
+            global: 0,
+            vaults: HashMap::new(),
+        }
+    }
+}
+
+/// HTTP/TLS settings for TLS-inspecting corporate proxies.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM file with extra root certificates (e.g. the proxy's CA).
+    pub ca_bundle: Option<PathBuf>,
+    /// Ignore HTTPS_PROXY and the system proxy settings.
+    pub no_proxy: bool,
+    /// Skip certificate verification entirely. Last resort; never use on untrusted networks.
+    pub accept_invalid_certs: bool,
+}
+
+/// The `--debug` log.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// Log file (default: `$XDG_STATE_HOME/akv-tui/akv.log`).
+    pub path: Option<PathBuf>,
+    /// text or json (one object per line)
+    pub format: String,
+    /// Size in MB after which the log is rotated (0 = never).
+    pub max_size_mb: u64,
+    /// Rotated logs kept next to it, `akv.log.1` being the newest.
+    pub max_files: usize,
+    /// Level of everything not in `modules`: error, warn, info, debug or trace.
+    pub level: String,
+    /// Module path -> level, e.g. `azure_core = "info"` or `"akv::azure" = "trace"`.
+    pub modules: HashMap<String, String>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            format: "text".into(),
+            max_size_mb: 10,
+            max_files: 3,
+            level: "debug".into(),
+            modules: HashMap::new(),
+        }
+    }
+}
+
+/// Commands run after secrets are copied or changed in the TUI, e.g.
+/// `on_copy = "logger -t akv 'copied {vault}/{name}'"`. `{vault}` and `{name}` are filled in,
+/// and `{value}` only where it's written out.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    pub on_copy: Option<String>,
+    pub on_secret_created: Option<String>,
+    pub on_secret_updated: Option<String>,
+    pub on_secret_deleted: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// UI color preset: dark, light or high-contrast.
+    pub preset: String,
+    /// syntect theme used by the value preview; the preset picks one when unset.
+    pub syntax: Option<String>,
+    /// Draw with plain ASCII only, for consoles and fonts without emoji or box drawing.
+    pub ascii: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            cache_ttl_secs: 30 * 60,
+            expiry_window_days: 30,
+            audit_referenced: Vec::new(),
+            watch_interval_secs: 30,
+            preload: true,
+            preload_concurrency: 4,
+            tick_ms: 50,
+            poll_ms: 20,
+            clipboard: "auto".into(),
+            clipboard_clear_secs: 30,
+            default_vault: None,
+            subscriptions: Vec::new(),
+            cloud: "public".into(),
+            management_endpoint: None,
+            vault_suffix: None,
+            credential: "auto".into(),
+            tenant: None,
+            favorites: Vec::new(),
+            aliases: HashMap::new(),
+            cache_dir: None,
+            notifications: true,
+            mouse: true,
+            columns: DEFAULT_COLUMNS
+                .iter()
+                .map(|c| c.key().to_string())
+                .collect(),
+            group_delimiter: "--".into(),
+            disk_cache: true,
+            cache_key: "auto".into(),
+            profile: None,
+            profiles: HashMap::new(),
+            theme: ThemeConfig::default(),
+            http: HttpConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            tls: TlsConfig::default(),
+            hooks: HooksConfig::default(),
+            log: LogConfig::default(),
+            keys: HashMap::new(),
+        }
+    }
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            preset: "dark".into(),
+            syntax: None,
+            ascii: false,
+        }
+    }
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/akv-tui/config.toml`, falling back to `~/.config` (or `%APPDATA%`).
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+        Some(base.join("akv-tui").join("config.toml"))
+    }
+
+    /// Load the config file (`--config`, else `AKV_TUI_CONFIG`, else the default location),
+    /// then apply the selected profile (`--profile`, else `AKV_TUI_PROFILE`, else `profile`)
+    /// and `AKV_TUI_*` overrides on top.
+    pub fn load(path: Option<&Path>, profile: Option<&str>) -> Result<Self, String> {
+        let env_path = std::env::var_os("AKV_TUI_CONFIG").map(PathBuf::from);
+        let mut config = Self::load_file(path.or(env_path.as_deref()))?;
+        let profile = profile
+            .map(String::from)
+            .or_else(|| std::env::var("AKV_TUI_PROFILE").ok())
+            .or_else(|| config.profile.clone());
+        if let Some(profile) = profile {
+            config.use_profile(&profile)?;
+        }
+        config.apply_env(|key| std::env::var(key).ok())?;
+        Ok(config)
+    }
+
+    /// The vault name behind an alias (names without an alias are returned unchanged).
+    pub fn resolve_vault(&self, vault: &str) -> String {
+        self.aliases
+            .iter()
+            .find(|(_, alias)| alias.eq_ignore_ascii_case(vault))
+            .map_or_else(|| vault.to_string(), |(name, _)| name.clone())
+    }
+
+    /// Apply `[profiles.<name>]` over the top-level settings.
+    pub fn use_profile(&mut self, name: &str) -> Result<(), String> {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            let mut known: Vec<_> = self.profiles.keys().cloned().collect();
+            known.sort();
+            return Err(format!(
+                "Unknown profile '{}' (configured: {})",
+                name,
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            ));
+        };
+        self.profile = Some(name.to_string());
+        if let Some(cloud) = profile.cloud {
+            self.cloud = cloud;
+        }
+        if let Some(credential) = profile.credential {
+            self.credential = credential;
+        }
+        if profile.tenant.is_some() {
+            self.tenant = profile.tenant;
+        }
+        if let Some(subscriptions) = profile.subscriptions {
+            self.subscriptions = subscriptions;
+        }
+        if let Some(favorites) = profile.favorites {
+            self.favorites = favorites;
+        }
+        if profile.default_vault.is_some() {
+            self.default_vault = profile.default_vault;
+        }
+        if profile.cache_dir.is_some() {
+            self.cache_dir = profile.cache_dir;
+        }
+        Ok(())
+    }
+
+    /// Cache directory for the active profile, so tenants never share cached data.
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        if let Some(dir) = &self.cache_dir {
+            return Some(expand_path(&dir.to_string_lossy()));
+        }
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))
+            .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+        let dir = base.join("akv-tui");
+        Some(match &self.profile {
+            Some(profile) => dir.join(profile),
+            None => dir,
+        })
+    }
+
+    /// Load from an explicit path (which must exist) or the default location (optional).
+    fn load_file(path: Option<&Path>) -> Result<Self, String> {
+        let (path, required) = match path {
+            Some(p) => (p.to_path_buf(), true),
+            None => match Self::default_path() {
+                Some(p) => (p, false),
+                None => return Ok(Self::default()),
+            },
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if !required && e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Cannot read config {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(text).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Override settings from `AKV_TUI_*` variables (looked up through `var`).
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        fn number<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid {} value '{}'", key, value))
+        }
+        fn flag(key: &str, value: &str) -> Result<bool, String> {
+            match value.trim().to_ascii_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => Ok(true),
+                "" | "0" | "false" | "no" | "off" => Ok(false),
+                _ => Err(format!("Invalid {} value '{}'", key, value)),
+            }
+        }
+
+        if let Some(v) = var("AKV_TUI_CACHE_TTL") {
+            self.cache_ttl_secs = number("AKV_TUI_CACHE_TTL", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_EXPIRY_WINDOW") {
+            self.expiry_window_days = number("AKV_TUI_EXPIRY_WINDOW", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_WATCH_INTERVAL") {
+            self.watch_interval_secs = number("AKV_TUI_WATCH_INTERVAL", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_NO_PRELOAD") {
+            self.preload = !flag("AKV_TUI_NO_PRELOAD", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_NO_NOTIFICATIONS") {
+            self.notifications = !flag("AKV_TUI_NO_NOTIFICATIONS", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_NO_MOUSE") {
+            self.mouse = !flag("AKV_TUI_NO_MOUSE", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_NO_DISK_CACHE") {
+            self.disk_cache = !flag("AKV_TUI_NO_DISK_CACHE", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_CACHE_KEY") {
+            self.cache_key = v;
+        }
+        if let Some(v) = var("AKV_TUI_PRELOAD_CONCURRENCY") {
+            self.preload_concurrency = number("AKV_TUI_PRELOAD_CONCURRENCY", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_TICK_MS") {
+            self.tick_ms = number("AKV_TUI_TICK_MS", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_POLL_MS") {
+            self.poll_ms = number("AKV_TUI_POLL_MS", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_CLIPBOARD") {
+            self.clipboard = v;
+        }
+        if let Some(v) = var("AKV_TUI_CLIPBOARD_CLEAR") {
+            self.clipboard_clear_secs = number("AKV_TUI_CLIPBOARD_CLEAR", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_DEFAULT_VAULT") {
+            self.default_vault = (!v.trim().is_empty()).then(|| v.trim().to_string());
+        }
+        if let Some(v) = var("AKV_TUI_SUBSCRIPTIONS") {
+            self.subscriptions = v
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        if let Some(v) = var("AKV_TUI_COLUMNS") {
+            self.columns = v
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        if let Some(v) = var("AKV_TUI_GROUP_DELIMITER") {
+            self.group_delimiter = v;
+        }
+        if let Some(v) = var("AKV_TUI_CLOUD") {
+            self.cloud = v;
+        }
+        if let Some(v) = var("AKV_TUI_MANAGEMENT_ENDPOINT") {
+            self.management_endpoint = Some(v);
+        }
+        if let Some(v) = var("AKV_TUI_VAULT_SUFFIX") {
+            self.vault_suffix = Some(v);
+        }
+        if let Some(v) = var("AKV_TUI_CREDENTIAL") {
+            self.credential = v;
+        }
+        if let Some(v) = var("AKV_TUI_TENANT") {
+            self.tenant = (!v.trim().is_empty()).then(|| v.trim().to_string());
+        }
+        if let Some(v) = var("AKV_TUI_TIMEOUT") {
+            self.http.timeout_secs = number("AKV_TUI_TIMEOUT", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_RETRIES") {
+            self.http.retries = number("AKV_TUI_RETRIES", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_CA_BUNDLE") {
+            self.tls.ca_bundle = (!v.trim().is_empty()).then(|| PathBuf::from(v.trim()));
+        }
+        if let Some(v) = var("AKV_TUI_NO_PROXY") {
+            self.tls.no_proxy = flag("AKV_TUI_NO_PROXY", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_THEME") {
+            self.theme.syntax = Some(v);
+        }
+        if let Some(v) = var("AKV_TUI_COLORS") {
+            self.theme.preset = v;
+        }
+        if let Some(v) = var("AKV_TUI_ASCII") {
+            self.theme.ascii = flag("AKV_TUI_ASCII", &v)?;
+        }
+        if let Some(v) = var("AKV_TUI_LOG_FILE") {
+            self.log.path = (!v.trim().is_empty()).then(|| PathBuf::from(v.trim()));
+        }
+        if let Some(v) = var("AKV_TUI_LOG_FORMAT") {
+            self.log.format = v;
+        }
+        if let Some(v) = var("AKV_TUI_LOG_LEVEL") {
+            self.log.level = v;
+        }
+        self.validate()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.preload_concurrency == 0 {
+            return Err("preload_concurrency must be at least 1".into());
+        }
+        if self.http.timeout_secs == 0 || self.http.connect_timeout_secs == 0 {
+            return Err("http timeouts must be at least 1 second".into());
+        }
+        if self.watch_interval_secs == 0 {
+            return Err("watch_interval_secs must be at least 1".into());
+        }
+        if !(10..=1000).contains(&self.tick_ms) {
+            return Err("tick_ms must be between 10 and 1000".into());
+        }
+        // A longer wait would hold back the spinner
+        if self.poll_ms == 0 || self.poll_ms > self.tick_ms {
+            return Err(format!(
+                "poll_ms must be between 1 and tick_ms ({})",
+                self.tick_ms
+            ));
+        }
+        if self.group_delimiter.is_empty() {
+            return Err("group_delimiter must not be empty".into());
+        }
+        self.clipboard_backend()?;
+        self.cloud()?;
+        self.credential_kind()?;
+        self.cache_key_store()?;
+        self.keymap()?;
+        self.ui_theme()?;
+        self.columns()?;
+        self.hooks()?;
+        self.log_format()?;
+        self.log_filter()?;
+        Ok(())
+    }
+
+    pub fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.cache_ttl_secs)
+    }
+
+    pub fn expiry_window(&self) -> Duration {
+        Duration::from_secs(self.expiry_window_days * 24 * 60 * 60)
+    }
+
+    pub fn watch_interval(&self) -> Duration {
+        Duration::from_secs(self.watch_interval_secs)
+    }
+
+    pub fn tick_rate(&self) -> Duration {
+        Duration::from_millis(self.tick_ms)
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_ms)
+    }
+
+    pub fn clipboard_backend(&self) -> Result<ClipboardBackend, String> {
+        self.clipboard.parse()
+    }
+
+    pub fn credential_kind(&self) -> Result<CredentialKind, String> {
+        self.credential.parse()
+    }
+
+    pub fn cache_key_store(&self) -> Result<KeyStore, String> {
+        self.cache_key.parse()
+    }
+
+    /// The secrets table columns; the name can't be left out, as it's what the rows are.
+    pub fn columns(&self) -> Result<Vec<Column>, String> {
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| c.parse())
+            .collect::<Result<Vec<Column>, String>>()?;
+        if !columns.contains(&Column::Name) {
+            return Err("columns must include name".into());
+        }
+        Ok(columns)
+    }
+
+    /// Where `--debug` logs: configured, or the state dir next to `state.toml`.
+    pub fn log_path(&self) -> Option<PathBuf> {
+        if let Some(path) = &self.log.path {
+            return Some(expand_path(&path.to_string_lossy()));
+        }
+        let base = std::env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local").join("state"))
+            })
+            .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+        Some(base.join("akv-tui").join("akv.log"))
+    }
+
+    pub fn log_format(&self) -> Result<LogFormat, String> {
+        self.log.format.parse()
+    }
+
+    /// The log filter: `level`, and the `modules` overriding it.
+    pub fn log_filter(&self) -> Result<EnvFilter, String> {
+        fn level(value: &str) -> Result<&str, String> {
+            match value.trim().to_ascii_lowercase().as_str() {
+                "off" | "error" | "warn" | "info" | "debug" | "trace" => Ok(value.trim()),
+                _ => Err(format!(
+                    "Invalid log level '{}' (expected error, warn, info, debug, trace or off)",
+                    value
+                )),
+            }
+        }
+        let mut directives = vec![level(&self.log.level)?.to_string()];
+        let mut modules: Vec<_> = self.log.modules.iter().collect();
+        modules.sort();
+        for (module, value) in modules {
+            let valid = !module.is_empty()
+                && module.split("::").all(|part| {
+                    !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_')
+                });
+            if !valid {
+                return Err(format!("Invalid module '{}' in [log.modules]", module));
+            }
+            directives.push(format!("{}={}", module, level(value)?));
+        }
+        EnvFilter::try_new(directives.join(",")).map_err(|e| e.to_string())
+    }
+
+    pub fn hooks(&self) -> Result<Hooks, String> {
+        let hooks = &self.hooks;
+        Hooks::new(
+            [
+                (HookEvent::Copy, &hooks.on_copy),
+                (HookEvent::SecretCreated, &hooks.on_secret_created),
+                (HookEvent::SecretUpdated, &hooks.on_secret_updated),
+                (HookEvent::SecretDeleted, &hooks.on_secret_deleted),
+            ]
+            .into_iter()
+            .filter_map(|(event, command)| Some((event, command.as_deref()?))),
+        )
+        .map_err(|e| format!("[hooks] {}", e))
+    }
+
+    pub fn ui_theme(&self) -> Result<Theme, String> {
+        let mut theme: Theme = self.theme.preset.parse()?;
+        theme.ascii = self.theme.ascii;
+        Ok(theme)
+    }
+
+    /// The syntect theme of the value preview: configured, or the preset's.
+    pub fn syntax_theme(&self) -> Result<String, String> {
+        match &self.theme.syntax {
+            Some(name) => Ok(name.clone()),
+            None => Ok(self.ui_theme()?.syntax.to_string()),
+        }
+    }
+
+    /// The cloud preset with any custom endpoints applied.
+    pub fn cloud(&self) -> Result<Cloud, String> {
+        let mut cloud: Cloud = self.cloud.parse()?;
+        if let Some(endpoint) = &self.management_endpoint {
+            if !endpoint.starts_with("https://") {
+                return Err(format!(
+                    "management_endpoint must be an https:// URL, got '{}'",
+                    endpoint
+                ));
+            }
+            cloud.management = endpoint.trim_end_matches('/').to_string();
+        }
+        if let Some(suffix) = &self.vault_suffix {
+            let suffix = suffix.trim().trim_matches('.');
+            if suffix.is_empty() || suffix.contains('/') {
+                return Err(format!("Invalid vault_suffix '{}'", suffix));
+            }
+            cloud.vault_suffix = suffix.to_string();
+        }
+        Ok(cloud)
+    }
+
+    pub fn clipboard_clear_after(&self) -> Option<Duration> {
+        (self.clipboard_clear_secs > 0).then(|| Duration::from_secs(self.clipboard_clear_secs))
+    }
+
+    /// Pressed key -> default key for rebound actions (`space` names the space bar).
+    pub fn keymap(&self) -> Result<HashMap<char, char>, String> {
+        let mut map = HashMap::new();
+        for (action, key) in &self.keys {
+            let Some(&(_, default)) = ACTIONS.iter().find(|(a, _)| a == action) else {
+                return Err(format!("Unknown action '{}' in [keys]", action));
+            };
+            let pressed = match key.as_str() {
+                "space" => ' ',
+                k if k.chars().count() == 1 => k.chars().next().unwrap_or(default),
+                _ => {
+                    return Err(format!(
+                        "Key for '{}' must be a single character, got '{}'",
+                        action, key
+                    ));
+                }
+            };
+            map.insert(pressed, default);
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+
+        let config = Config::parse(
+            r#"
+            cache_ttl_secs = 600
+            preload_concurrency = 8
+            clipboard = "osc52"
+            default_vault = "team-kv"
+            subscriptions = ["Production"]
+            mouse = false
+            columns = ["name", "tags", "expires"]
+
+            [theme]
+            preset = "light"
+            syntax = "InspiredGitHub"
+            ascii = true
+
+            [aliases]
+            kvpmtprd001eastus2 = "prod-payments"
+
+            [rate_limit]
+            global = 500
+            vaults = { kvpmtprd001eastus2 = 50 }
+
+            [keys]
+            add = "n"
+            mark = "space"
+
+            [log]
+            format = "json"
+            modules = { azure_core = "info", "akv::azure" = "trace" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.cache_ttl(), Duration::from_secs(600));
+        assert_eq!(config.tick_rate(), Duration::from_millis(50));
+        assert_eq!(config.clipboard_backend(), Ok(ClipboardBackend::Osc52));
+        assert_eq!(config.default_vault.as_deref(), Some("team-kv"));
+        assert!(!config.mouse);
+        assert_eq!(
+            config.columns(),
+            Ok(vec![Column::Name, Column::Tags, Column::Expires])
+        );
+        assert!(Config::parse("columns = [\"updated\"]").is_err());
+        let theme = config.ui_theme().unwrap();
+        assert_eq!(theme.background, Theme::light().background);
+        assert!(theme.ascii);
+        assert_eq!(
+            Config::default().syntax_theme().unwrap(),
+            "base16-ocean.dark"
+        );
+        assert!(Config::parse("[theme]\npreset = \"neon\"").is_err());
+        assert_eq!(config.resolve_vault("Prod-Payments"), "kvpmtprd001eastus2");
+        assert_eq!(config.resolve_vault("team-kv"), "team-kv");
+        assert_eq!(config.rate_limit.per_vault, 200);
+        assert_eq!(config.rate_limit.vaults["kvpmtprd001eastus2"], 50);
+        let stack = Config::parse(
+            "management_endpoint = \"https://management.local.azurestack.external/\"\nvault_suffix = \".vault.local.azurestack.external\"",
+        )
+        .unwrap()
+        .cloud()
+        .unwrap();
+        assert_eq!(
+            stack.management_scope(),
+            "https://management.local.azurestack.external/.default"
+        );
+        assert_eq!(stack.vault_suffix, "vault.local.azurestack.external");
+        assert_eq!(config.log_format(), Ok(LogFormat::Json));
+        assert_eq!(
+            config.log_filter().unwrap().to_string(),
+            "azure_core=info,akv::azure=trace,debug"
+        );
+        let keymap = config.keymap().unwrap();
+        assert_eq!(keymap.get(&'n'), Some(&'a'));
+        assert_eq!(keymap.get(&' '), Some(&' '));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(Config::parse("cache_ttl = 5").is_err()); // unknown field
+        assert!(Config::parse("preload_concurrency = 0").is_err());
+        assert!(Config::parse("clipboard = \"pbcopy\"").is_err());
+        assert!(Config::parse("management_endpoint = \"http://arm.local\"").is_err());
+        assert!(Config::parse("[keys]\nfly = \"f\"").is_err());
+        assert!(Config::parse("[keys]\nadd = \"ctrl+n\"").is_err());
+        assert!(Config::parse("[hooks]\non_paste = \"echo\"").is_err());
+        assert!(Config::parse("[log]\nformat = \"xml\"").is_err());
+        assert!(Config::parse("[log]\nlevel = \"verbose\"").is_err());
+        assert!(Config::parse("[log.modules]\n\"azure core\" = \"info\"").is_err());
+        assert!(Config::parse("[hooks]\non_secret_deleted = \"echo {value}\"").is_err());
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut config = Config::parse(
+            r#"
+            subscriptions = ["Shared"]
+            favorites = ["team-kv"]
+
+            [profiles.customer]
+            tenant = "contoso.onmicrosoft.com"
+            subscriptions = ["Contoso Prod"]
+            cache_dir = "/tmp/akv-customer"
+            "#,
+        )
+        .unwrap();
+        config.use_profile("customer").unwrap();
+        assert_eq!(config.profile.as_deref(), Some("customer"));
+        assert_eq!(config.tenant.as_deref(), Some("contoso.onmicrosoft.com"));
+        assert_eq!(config.subscriptions, vec!["Contoso Prod"]);
+        assert_eq!(config.favorites, vec!["team-kv"]); // not overridden
+        assert_eq!(config.cache_dir(), Some(PathBuf::from("/tmp/akv-customer")));
+        assert!(config.use_profile("work").is_err());
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let env: HashMap<&str, &str> = [
+            ("AKV_TUI_DEFAULT_VAULT", "ci-kv"),
+            ("AKV_TUI_CACHE_TTL", "60"),
+            ("AKV_TUI_EXPIRY_WINDOW", "14"),
+            ("AKV_TUI_NO_PRELOAD", "1"),
+            ("AKV_TUI_NO_DISK_CACHE", "yes"),
+            ("AKV_TUI_CACHE_KEY", "Passphrase"),
+            ("AKV_TUI_SUBSCRIPTIONS", "sub-a, Production"),
+            ("AKV_TUI_LOG_FORMAT", "json"),
+            ("AKV_TUI_POLL_MS", "5"),
+        ]
+        .into();
+        let mut config = Config::parse("default_vault = \"file-kv\"\ncache_ttl_secs = 5").unwrap();
+        config
+            .apply_env(|k| env.get(k).map(|v| v.to_string()))
+            .unwrap();
+        assert_eq!(config.default_vault.as_deref(), Some("ci-kv"));
+        assert_eq!(config.cache_ttl_secs, 60);
+        assert_eq!(config.expiry_window(), Duration::from_secs(14 * 86400));
+        assert!(!config.preload);
+        assert!(!config.disk_cache);
+        assert_eq!(config.cache_key_store(), Ok(KeyStore::Passphrase));
+        assert_eq!(config.subscriptions, vec!["sub-a", "Production"]);
+        assert_eq!(config.log_format(), Ok(LogFormat::Json));
+        assert_eq!(config.poll_interval(), Duration::from_millis(5));
+
+        let bad = |k: &str| (k == "AKV_TUI_CACHE_TTL").then(|| "soon".to_string());
+        assert!(Config::default().apply_env(bad).is_err());
+        let bad = |k: &str| (k == "AKV_TUI_CACHE_KEY").then(|| "plaintext".to_string());
+        assert!(Config::default().apply_env(bad).is_err());
+        // Polling longer than a tick would stall the spinner
+        let bad = |k: &str| (k == "AKV_TUI_POLL_MS").then(|| "60".to_string());
+        assert!(Config::default().apply_env(bad).is_err());
+        assert!(Config::parse("tick_ms = 5000").is_err());
+    }
+}
@@ -0,0 +1,176 @@
+/// Well-known `key=value;` connection-string flavours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnStringKind {
+    Sql,
+    Storage,
+    ServiceBus,
+}
+
+impl ConnStringKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConnStringKind::Sql => "SQL",
+            ConnStringKind::Storage => "Storage",
+            ConnStringKind::ServiceBus => "Service Bus",
+        }
+    }
+}
+
+/// One copyable component. Derived components (e.g. the bare host of `Server=tcp:host,1433`)
+/// don't appear verbatim in the string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnPart {
+    pub key: String,
+    pub value: String,
+    pub derived: bool,
+}
+
+impl ConnPart {
+    /// Keys, passwords and SAS tokens are masked in the picker.
+    pub fn is_sensitive(&self) -> bool {
+        let key = self.key.to_ascii_lowercase();
+        ["key", "password", "pwd", "secret", "signature"]
+            .iter()
+            .any(|s| key.contains(s))
+    }
+}
+
+/// Picker state for copying single components out of a connection string.
+#[derive(Debug, Clone)]
+pub struct ConnStringPicker {
+    pub secret: String,
+    pub kind: ConnStringKind,
+    pub parts: Vec<ConnPart>,
+    pub selected: usize,
+}
+
+impl ConnStringPicker {
+    /// Returns None unless the value looks like a SQL, Storage or Service Bus connection string.
+    pub fn parse(secret: &str, value: &str) -> Option<Self> {
+        let pairs = split_pairs(value)?;
+        let has = |k: &str| pairs.iter().any(|(key, _)| key.eq_ignore_ascii_case(k));
+        let get = |k: &str| {
+            pairs
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(k))
+                .map(|(_, v)| v.clone())
+        };
+
+        let kind = if has("AccountName") && (has("AccountKey") || has("SharedAccessSignature")) {
+            ConnStringKind::Storage
+        } else if get("Endpoint").is_some_and(|e| e.starts_with("sb://")) {
+            ConnStringKind::ServiceBus
+        } else if (has("Server") || has("Data Source"))
+            && (has("Database") || has("Initial Catalog"))
+        {
+            ConnStringKind::Sql
+        } else {
+            return None;
+        };
+
+        let host = match kind {
+            ConnStringKind::Sql => get("Server").or_else(|| get("Data Source")).map(|s| {
+                let s = s.strip_prefix("tcp:").unwrap_or(&s);
+                s.split(',').next().unwrap_or(s).to_string()
+            }),
+            ConnStringKind::ServiceBus => get("Endpoint").map(|e| {
+                e.trim_start_matches("sb://")
+                    .trim_end_matches('/')
+                    .to_string()
+            }),
+            ConnStringKind::Storage => None,
+        };
+        let mut parts: Vec<ConnPart> = pairs
+            .into_iter()
+            .map(|(key, value)| ConnPart {
+                key,
+                value,
+                derived: false,
+            })
+            .collect();
+
+        if let Some(host) = host {
+            parts.insert(
+                0,
+                ConnPart {
+                    key: "Host".into(),
+                    value: host,
+                    derived: true,
+                },
+            );
+        }
+
+        Some(Self {
+            secret: secret.to_string(),
+            kind,
+            parts,
+            selected: 0,
+        })
+    }
+
+    pub fn move_down(&mut self) {
+        if !self.parts.is_empty() {
+            self.selected = (self.selected + 1).min(self.parts.len() - 1);
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn selected_part(&self) -> Option<&ConnPart> {
+        self.parts.get(self.selected)
+    }
+}
+
+/// Split `k1=v1;k2=v2` into trimmed pairs. Values may contain '=' (base64 keys);
+/// returns None unless there are at least two pairs.
+fn split_pairs(value: &str) -> Option<Vec<(String, String)>> {
+    let pairs: Vec<(String, String)> = value
+        .trim()
+        .split(';')
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| {
+            s.split_once('=')
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        })
+        .collect::<Option<_>>()?;
+    (pairs.len() >= 2).then_some(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_kinds_and_derived_host() {
+        let sql = "Server=tcp:mydb.database.windows.net,1433;Initial Catalog=orders;User ID=app;Password=p@ss=1;";
+        let p = ConnStringPicker::parse("db", sql).unwrap();
+        assert_eq!(p.kind, ConnStringKind::Sql);
+        assert_eq!(p.parts[0].key, "Host");
+        assert_eq!(p.parts[0].value, "mydb.database.windows.net");
+        let pwd = p.parts.iter().find(|c| c.key == "Password").unwrap();
+        assert_eq!(pwd.value, "p@ss=1");
+        assert!(pwd.is_sensitive());
+
+        let storage = "DefaultEndpointsProtocol=https;AccountName=acct;AccountKey=abc==;EndpointSuffix=core.windows.net";
+        let p = ConnStringPicker::parse("st", storage).unwrap();
+        assert_eq!(p.kind, ConnStringKind::Storage);
+        assert_eq!(p.parts.len(), 4);
+        assert_eq!(p.parts[2].value, "abc==");
+
+        let sb =
+            "Endpoint=sb://ns.servicebus.windows.net/;SharedAccessKeyName=Root;SharedAccessKey=k=";
+        let p = ConnStringPicker::parse("sb", sb).unwrap();
+        assert_eq!(p.kind, ConnStringKind::ServiceBus);
+        assert_eq!(p.parts[0].value, "ns.servicebus.windows.net");
+    }
+
+    #[test]
+    fn test_rejects_other_values() {
+        assert!(ConnStringPicker::parse("s", "hunter2").is_none());
+        assert!(ConnStringPicker::parse("s", "a=b").is_none());
+        assert!(ConnStringPicker::parse("s", "a=b;c=d").is_none());
+        assert!(ConnStringPicker::parse("s", "a=b;nope").is_none());
+    }
+}
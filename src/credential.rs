@@ -0,0 +1,170 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use azure_core::cloud::{CloudConfiguration, CustomConfiguration};
+use azure_core::credentials::{Secret, TokenCredential};
+use azure_core::http::ClientOptions;
+use azure_identity::{
+    AzureCliCredential, AzureCliCredentialOptions, ClientCertificateCredential,
+    ClientCertificateCredentialOptions, ClientSecretCredential, ClientSecretCredentialOptions,
+    DeveloperToolsCredential, ManagedIdentityCredential, ManagedIdentityCredentialOptions,
+    UserAssignedId, WorkloadIdentityCredential, WorkloadIdentityCredentialOptions,
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+use crate::azure::client_options;
+use crate::cloud::{Cloud, cloud};
+use crate::error::AkvError;
+
+/// How to sign in. `Auto` picks a service principal or workload identity when the usual
+/// `AZURE_*` variables are set and falls back to the developer tools (az / azd) login.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialKind {
+    Auto,
+    DeveloperTools,
+    ClientSecret,      // AZURE_TENANT_ID, AZURE_CLIENT_ID, AZURE_CLIENT_SECRET
+    ClientCertificate, // ... AZURE_CLIENT_CERTIFICATE_PATH (PKCS#12) [+ _PASSWORD]
+    ManagedIdentity,   // optional AZURE_CLIENT_ID for a user-assigned identity
+    WorkloadIdentity,  // AZURE_TENANT_ID, AZURE_CLIENT_ID, AZURE_FEDERATED_TOKEN_FILE
+}
+
+impl FromStr for CredentialKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "cli" | "developer" | "developer-tools" => Ok(Self::DeveloperTools),
+            "secret" | "client-secret" | "service-principal" | "env" => Ok(Self::ClientSecret),
+            "certificate" | "client-certificate" => Ok(Self::ClientCertificate),
+            "managed-identity" | "msi" => Ok(Self::ManagedIdentity),
+            "workload-identity" => Ok(Self::WorkloadIdentity),
+            other => Err(format!(
+                "Unknown credential '{}' (expected auto, cli, client-secret, certificate, managed-identity or workload-identity)",
+                other
+            )),
+        }
+    }
+}
+
+impl CredentialKind {
+    /// Resolve `Auto` from the environment (looked up through `var`).
+    pub fn resolve(self, var: impl Fn(&str) -> Option<String>) -> Self {
+        if self != Self::Auto {
+            return self;
+        }
+        if var("AZURE_CLIENT_SECRET").is_some() {
+            Self::ClientSecret
+        } else if var("AZURE_CLIENT_CERTIFICATE_PATH").is_some() {
+            Self::ClientCertificate
+        } else if var("AZURE_FEDERATED_TOKEN_FILE").is_some() {
+            Self::WorkloadIdentity
+        } else {
+            Self::DeveloperTools
+        }
+    }
+}
+
+/// Create the credential used for ARM and Key Vault. `tenant` pins the Azure CLI to a tenant
+/// and overrides `AZURE_TENANT_ID` for service principals.
+pub fn create(
+    kind: CredentialKind,
+    tenant: Option<&str>,
+) -> Result<Arc<dyn TokenCredential>, AkvError> {
+    let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+    let require = |key: &str| env(key).ok_or_else(|| format!("{} is not set", key));
+    let tenant_id = || tenant.map_or_else(|| require("AZURE_TENANT_ID"), |t| Ok(t.to_string()));
+
+    let credential: Arc<dyn TokenCredential> = match kind.resolve(env) {
+        CredentialKind::Auto | CredentialKind::DeveloperTools => match tenant {
+            Some(tenant) => AzureCliCredential::new(Some(AzureCliCredentialOptions {
+                tenant_id: Some(tenant.to_string()),
+                ..Default::default()
+            }))?,
+            None => DeveloperToolsCredential::new(None)?,
+        },
+        CredentialKind::ClientSecret => ClientSecretCredential::new(
+            &tenant_id()?,
+            require("AZURE_CLIENT_ID")?,
+            Secret::new(require("AZURE_CLIENT_SECRET")?),
+            Some(ClientSecretCredentialOptions {
+                client_options: identity_options(),
+            }),
+        )?,
+        CredentialKind::ClientCertificate => {
+            let path = require("AZURE_CLIENT_CERTIFICATE_PATH")?;
+            let pkcs12 = std::fs::read(&path)
+                .map_err(|e| format!("Cannot read certificate {}: {}", path, e))?;
+            ClientCertificateCredential::new(
+                tenant_id()?,
+                require("AZURE_CLIENT_ID")?,
+                Secret::new(STANDARD.encode(pkcs12)),
+                Some(ClientCertificateCredentialOptions {
+                    client_options: identity_options(),
+                    password: env("AZURE_CLIENT_CERTIFICATE_PASSWORD").map(Secret::new),
+                }),
+            )?
+        }
+        CredentialKind::ManagedIdentity => {
+            ManagedIdentityCredential::new(Some(ManagedIdentityCredentialOptions {
+                user_assigned_id: env("AZURE_CLIENT_ID").map(UserAssignedId::ClientId),
+                client_options: identity_options(),
+            }))?
+        }
+        CredentialKind::WorkloadIdentity => {
+            let mut options = WorkloadIdentityCredentialOptions {
+                tenant_id: tenant.map(String::from),
+                ..Default::default()
+            };
+            options.credential_options.client_options = identity_options();
+            WorkloadIdentityCredential::new(Some(options))?
+        }
+    };
+    Ok(credential)
+}
+
+/// Shared HTTP settings plus the sign-in authority of the selected cloud.
+fn identity_options() -> ClientOptions {
+    let mut options = client_options();
+    let selected = cloud();
+    if selected.authority_host != Cloud::public().authority_host {
+        let mut custom = CustomConfiguration::default();
+        custom.authority_host = selected.authority_host.clone();
+        options.cloud = Some(Arc::new(CloudConfiguration::from(custom)));
+    }
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credential_kind() {
+        assert_eq!("cli".parse(), Ok(CredentialKind::DeveloperTools));
+        assert_eq!("MSI".parse(), Ok(CredentialKind::ManagedIdentity));
+        assert!("kerberos".parse::<CredentialKind>().is_err());
+
+        let none = |_: &str| None;
+        assert_eq!(
+            CredentialKind::Auto.resolve(none),
+            CredentialKind::DeveloperTools
+        );
+        let sp = |k: &str| (k == "AZURE_CLIENT_SECRET").then(|| "s".to_string());
+        assert_eq!(
+            CredentialKind::Auto.resolve(sp),
+            CredentialKind::ClientSecret
+        );
+        let wi = |k: &str| (k == "AZURE_FEDERATED_TOKEN_FILE").then(|| "/t".to_string());
+        assert_eq!(
+            CredentialKind::Auto.resolve(wi),
+            CredentialKind::WorkloadIdentity
+        );
+        // An explicit choice is never overridden by the environment
+        assert_eq!(
+            CredentialKind::ManagedIdentity.resolve(sp),
+            CredentialKind::ManagedIdentity
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use azure_core::credentials::TokenCredential;
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use ring::digest;
+use serde_json::json;
+
+use crate::azure::KeyClient;
+use crate::encoding::decoded_text;
+use crate::error::AkvError;
+use crate::model::KeyInfo;
+use crate::textarea::TextArea;
+
+/// A Key Vault key operation. The name is both the REST action and the `key_ops` entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CryptoOp {
+    Encrypt,
+    Decrypt,
+    Sign,
+    Verify,
+    WrapKey,
+    UnwrapKey,
+}
+
+impl CryptoOp {
+    const ALL: [CryptoOp; 6] = [
+        CryptoOp::Encrypt,
+        CryptoOp::Decrypt,
+        CryptoOp::Sign,
+        CryptoOp::Verify,
+        CryptoOp::WrapKey,
+        CryptoOp::UnwrapKey,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CryptoOp::Encrypt => "encrypt",
+            CryptoOp::Decrypt => "decrypt",
+            CryptoOp::Sign => "sign",
+            CryptoOp::Verify => "verify",
+            CryptoOp::WrapKey => "wrapKey",
+            CryptoOp::UnwrapKey => "unwrapKey",
+        }
+    }
+
+    /// Decrypt and unwrap take the base64 output of encrypt/wrap rather than raw data.
+    pub fn takes_base64(self) -> bool {
+        matches!(self, CryptoOp::Decrypt | CryptoOp::UnwrapKey)
+    }
+}
+
+/// Algorithm used for `op` with `key`; None when the key type doesn't support it.
+pub fn algorithm(key: &KeyInfo, op: CryptoOp) -> Option<&'static str> {
+    match (key.kty.trim_end_matches("-HSM"), op) {
+        ("RSA", CryptoOp::Sign | CryptoOp::Verify) => Some("RS256"),
+        ("RSA", _) => Some("RSA-OAEP-256"),
+        ("EC", CryptoOp::Sign | CryptoOp::Verify) => match key.curve.as_deref()? {
+            "P-256" => Some("ES256"),
+            "P-256K" => Some("ES256K"),
+            "P-384" => Some("ES384"),
+            "P-521" => Some("ES512"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Digest that sign/verify send for `alg` (Key Vault signs digests, not data).
+fn digest_for(alg: &str, data: &[u8]) -> Vec<u8> {
+    let algorithm = match alg {
+        "ES384" => &digest::SHA384,
+        "ES512" => &digest::SHA512,
+        _ => &digest::SHA256,
+    };
+    digest::digest(algorithm, data).as_ref().to_vec()
+}
+
+/// Which field of the crypto dialog has focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CryptoField {
+    Operation,
+    Input,
+    Signature, // verify only
+}
+
+/// The crypto dialog opened with Enter on the keys screen.
+#[derive(Debug, Clone)]
+pub struct CryptoForm {
+    pub key: KeyInfo,
+    pub ops: Vec<CryptoOp>, // permitted by the key and supported by its type
+    pub selected: usize,
+    pub focus: CryptoField,
+    pub input: TextArea,     // file to read the input from; empty = clipboard
+    pub signature: TextArea, // base64 signature to verify
+    pub pending: bool,
+    pub result: Option<Result<String, String>>,
+}
+
+impl CryptoForm {
+    /// None when the key permits no operation this dialog supports.
+    pub fn new(key: KeyInfo) -> Option<Self> {
+        let ops: Vec<CryptoOp> = CryptoOp::ALL
+            .into_iter()
+            .filter(|op| key.ops.iter().any(|o| o == op.name()) && algorithm(&key, *op).is_some())
+            .collect();
+        if ops.is_empty() {
+            return None;
+        }
+        Some(Self {
+            key,
+            ops,
+            selected: 0,
+            focus: CryptoField::Operation,
+            input: TextArea::default(),
+            signature: TextArea::default(),
+            pending: false,
+            result: None,
+        })
+    }
+
+    pub fn op(&self) -> CryptoOp {
+        self.ops[self.selected]
+    }
+
+    pub fn next_focus(&mut self) {
+        self.focus = match self.focus {
+            CryptoField::Operation => CryptoField::Input,
+            CryptoField::Input if self.op() == CryptoOp::Verify => CryptoField::Signature,
+            _ => CryptoField::Operation,
+        };
+    }
+
+    /// Select the next (`delta` 1) or previous (-1) operation.
+    pub fn cycle_op(&mut self, delta: isize) {
+        let len = self.ops.len() as isize;
+        self.selected = (self.selected as isize + delta).rem_euclid(len) as usize;
+        self.result = None;
+    }
+}
+
+/// Run `op` with the current version of `key`. `input` is the data (the decoded ciphertext
+/// for decrypt/unwrap) and `signature` the one to verify. Returns what to show: base64url
+/// for encrypt/sign/wrap, the plaintext for decrypt/unwrap and a verdict for verify.
+pub async fn run(
+    credential: Arc<dyn TokenCredential>,
+    vault_uri: &str,
+    key: &KeyInfo,
+    op: CryptoOp,
+    input: Vec<u8>,
+    signature: Option<Vec<u8>>,
+) -> Result<String, AkvError> {
+    let client = KeyClient::new(vault_uri, credential)?;
+    call(&client, key, op, &input, signature.as_deref()).await
+}
+
+async fn call(
+    client: &KeyClient,
+    key: &KeyInfo,
+    op: CryptoOp,
+    input: &[u8],
+    signature: Option<&[u8]>,
+) -> Result<String, AkvError> {
+    let alg = algorithm(key, op).ok_or("Operation not supported by this key")?;
+    let b64 = |bytes: &[u8]| URL_SAFE_NO_PAD.encode(bytes);
+    let body = match (op, signature) {
+        (CryptoOp::Sign, _) => json!({ "alg": alg, "value": b64(&digest_for(alg, input)) }),
+        (CryptoOp::Verify, Some(signature)) => json!({
+            "alg": alg,
+            "digest": b64(&digest_for(alg, input)),
+            "value": b64(signature),
+        }),
+        (CryptoOp::Verify, None) => return Err("No signature to verify".into()),
+        _ => json!({ "alg": alg, "value": b64(input) }),
+    };
+    let reply = client
+        .key_operation(&key.name, &key.version, op.name(), &body)
+        .await?;
+
+    match op {
+        CryptoOp::Verify => Ok(match reply["value"].as_bool() {
+            Some(true) => "Signature is valid".into(),
+            _ => "Signature is NOT valid".into(),
+        }),
+        CryptoOp::Decrypt | CryptoOp::UnwrapKey => {
+            let value = reply["value"].as_str().unwrap_or_default();
+            let bytes = URL_SAFE_NO_PAD
+                .decode(value.trim_end_matches('='))
+                .map_err(|e| format!("Invalid response: {}", e))?;
+            // Binary plaintext (e.g. an unwrapped key) is shown base64-encoded
+            Ok(decoded_text(&bytes).unwrap_or_else(|| STANDARD.encode(bytes)))
+        }
+        _ => Ok(reply["value"].as_str().unwrap_or_default().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_vault::MockCredential;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn key(kty: &str, curve: Option<&str>, ops: &[&str]) -> KeyInfo {
+        KeyInfo {
+            name: "signing".into(),
+            version: "v1".into(),
+            kty: kty.into(),
+            size: None,
+            curve: curve.map(String::from),
+            enabled: true,
+            ops: ops.iter().map(|s| s.to_string()).collect(),
+            rotation: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_verify_send_digests() {
+        let ec = key("EC-HSM", Some("P-384"), &["sign", "verify", "encrypt"]);
+        let form = CryptoForm::new(ec.clone()).unwrap();
+        assert_eq!(form.ops, [CryptoOp::Sign, CryptoOp::Verify]); // EC can't encrypt
+        assert!(CryptoForm::new(key("RSA", None, &[])).is_none());
+        assert_eq!(
+            algorithm(&key("RSA", None, &[]), CryptoOp::WrapKey),
+            Some("RSA-OAEP-256")
+        );
+
+        let mock_server = MockServer::start().await;
+        let digest = URL_SAFE_NO_PAD.encode(digest_for("ES384", b"hello"));
+        assert_eq!(digest.len(), 64); // SHA-384
+        Mock::given(method("POST"))
+            .and(path("/keys/signing/v1/sign"))
+            .and(body_partial_json(
+                json!({ "alg": "ES384", "value": digest }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "value": "c2ln" })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/keys/signing/v1/verify"))
+            .and(body_partial_json(
+                json!({ "digest": digest, "value": "c2ln" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "value": true })))
+            .mount(&mock_server)
+            .await;
+
+        let client = KeyClient::new(&mock_server.uri(), Arc::new(MockCredential)).unwrap();
+        let call = |op, signature| call(&client, &ec, op, b"hello", signature);
+        assert_eq!(call(CryptoOp::Sign, None).await.unwrap(), "c2ln");
+        assert_eq!(
+            call(CryptoOp::Verify, Some(&b"sig"[..])).await.unwrap(),
+            "Signature is valid"
+        );
+        assert!(call(CryptoOp::Verify, None).await.is_err());
+    }
+}
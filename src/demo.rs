@@ -0,0 +1,239 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+use time::OffsetDateTime;
+
+use crate::azure::vault_uri;
+use crate::generator::{self, GeneratorOptions};
+use crate::model::{SecretMeta, Vault, VaultProperties};
+use crate::store::{MemoryStore, SecretStore, StorePool};
+
+/// Round trip of a demo request, so spinners, incremental listings and pending writes show.
+pub const LATENCY: Duration = Duration::from_millis(250);
+
+/// The generated vaults: subscription, resource group, location, name and secret count.
+/// `kv-tenants-prod` is large enough for background search and incremental listing.
+const VAULTS: &[(&str, &str, &str, &str, usize)] = &[
+    (
+        "Contoso Production",
+        "rg-payments-prod",
+        "westeurope",
+        "kv-payments-prod",
+        120,
+    ),
+    (
+        "Contoso Production",
+        "rg-platform-prod",
+        "westeurope",
+        "kv-platform-prod",
+        48,
+    ),
+    (
+        "Contoso Production",
+        "rg-tenants-prod",
+        "westeurope",
+        "kv-tenants-prod",
+        2400,
+    ),
+    (
+        "Contoso Development",
+        "rg-payments-dev",
+        "northeurope",
+        "kv-payments-dev",
+        80,
+    ),
+    (
+        "Contoso Development",
+        "rg-data-dev",
+        "eastus",
+        "kv-data-dev",
+        30,
+    ),
+    (
+        "Contoso Sandbox",
+        "rg-sandbox",
+        "eastus2",
+        "kv-sandbox-jdoe",
+        6,
+    ),
+];
+
+const SERVICES: &[&str] = &[
+    "orders",
+    "billing",
+    "catalog",
+    "identity",
+    "search",
+    "notifications",
+    "reports",
+    "gateway",
+    "inventory",
+    "shipping",
+    "analytics",
+    "checkout",
+];
+
+const ITEMS: &[&str] = &[
+    "db-password",
+    "db-connection-string",
+    "api-key",
+    "client-id",
+    "client-secret",
+    "storage-key",
+    "jwt-signing-key",
+    "redis-password",
+    "smtp-password",
+    "settings",
+];
+
+const TEAMS: &[&str] = &["platform", "payments", "data", "web"];
+
+/// The demo vaults, as discovery would list them.
+pub fn vaults() -> Vec<Vault> {
+    VAULTS
+        .iter()
+        .map(|(subscription, group, location, name, _)| Vault {
+            name: name.to_string(),
+            uri: vault_uri(name),
+            alias: None,
+            subscription: Some(subscription.to_string()),
+            resource_group: Some(group.to_string()),
+            location: Some(location.to_string()),
+            properties: Some(VaultProperties {
+                resource_id: Some(format!(
+                    "/subscriptions/00000000-0000-0000-0000-000000000000/resourceGroups/{}/providers/Microsoft.KeyVault/vaults/{}",
+                    group, name
+                )),
+                sku: Some("standard".into()),
+                soft_delete: true,
+                retention_days: Some(90),
+                purge_protection: name.ends_with("-prod"),
+                rbac: true,
+                public_access: Some("Enabled".into()),
+                default_action: Some("Allow".into()),
+                ..Default::default()
+            }),
+        })
+        .collect()
+}
+
+/// A store per demo vault with generated secrets, answering after `latency`. Names and
+/// the ages of their attributes are the same on every run; the values are random.
+pub fn stores(latency: Duration) -> StorePool {
+    StorePool::fixed(VAULTS.iter().enumerate().map(|(i, vault)| {
+        let (_, _, _, name, count) = *vault;
+        let mut rng = StdRng::seed_from_u64(i as u64);
+        let store: Arc<dyn SecretStore> =
+            Arc::new(MemoryStore::new(secrets(&mut rng, count), latency));
+        (vault_uri(name), store)
+    }))
+}
+
+/// `count` secrets: one per service and item, then numbered customer API keys.
+fn secrets(rng: &mut StdRng, count: usize) -> Vec<(SecretMeta, String)> {
+    let now = OffsetDateTime::now_utc();
+    let days = |n: i64| time::Duration::days(n);
+    let names = SERVICES
+        .iter()
+        .flat_map(|service| ITEMS.iter().map(move |item| (*service, *item)))
+        .map(|(service, item)| (format!("{}--{}", service, item), service, item))
+        .chain((1..).map(|n| (format!("customer-{:04}-api-key", n), "customer", "api-key")));
+    names
+        .take(count)
+        .map(|(name, service, item)| {
+            let created = now - days(rng.random_range(30..900));
+            let updated = created + days(rng.random_range(0..30));
+            // Some are overdue or due soon, for the expiry dashboard and the audit
+            let expires = rng
+                .random_bool(0.6)
+                .then(|| now + days(rng.random_range(-20..400)));
+            let (value, content_type) = value(service, item);
+            let meta = SecretMeta {
+                name,
+                enabled: !rng.random_bool(0.05),
+                content_type,
+                tags: vec![
+                    ("env".into(), "demo".into()),
+                    ("owner".into(), TEAMS.choose(rng).unwrap().to_string()),
+                ],
+                created: Some(created),
+                updated: Some(updated.min(now)),
+                expires,
+            };
+            (meta, value)
+        })
+        .collect()
+}
+
+/// A plausible value of `item` for `service`, with its content type.
+fn value(service: &str, item: &str) -> (String, Option<String>) {
+    let random = |length| {
+        generator::generate(&GeneratorOptions {
+            length,
+            ..Default::default()
+        })
+    };
+    match item {
+        "db-connection-string" => (
+            format!(
+                "Server=tcp:{0}-sql.database.windows.net,1433;Database={0};User ID={0}-app;Password={1};Encrypt=true",
+                service,
+                random(24)
+            ),
+            Some("text/plain".into()),
+        ),
+        "client-id" => (
+            format!(
+                "{}-{}-{}-{}-{}",
+                random(8),
+                random(4),
+                random(4),
+                random(4),
+                random(12)
+            )
+            .to_lowercase(),
+            None,
+        ),
+        "settings" => (
+            format!(
+                "{{\"endpoint\": \"https://{}.contoso.example\", \"timeoutSeconds\": 30, \"retries\": 3}}",
+                service
+            ),
+            Some("application/json".into()),
+        ),
+        _ => (random(32), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_demo_data() {
+        let credential: Arc<dyn azure_core::credentials::TokenCredential> =
+            azure_identity::DeveloperToolsCredential::new(None).unwrap();
+        let mut pool = stores(Duration::ZERO);
+        let vaults = vaults();
+        assert_eq!(vaults.len(), VAULTS.len());
+
+        let tenants = pool.get(&vaults[2].uri, &credential).unwrap();
+        let mut names = Vec::new();
+        tenants.list(&mut |s| names.push(s.name)).await.unwrap();
+        assert_eq!(names.len(), 2400);
+        assert!(names.contains(&"orders--db-password".to_string()));
+        assert!(names.contains(&"customer-2280-api-key".to_string()));
+
+        let settings = tenants.get("orders--settings").await.unwrap();
+        assert!(settings.contains("https://orders.contoso.example"));
+
+        // Demo mode never reaches out to Key Vault
+        assert!(
+            pool.get("https://kv-real.vault.azure.net/", &credential)
+                .is_err()
+        );
+    }
+}
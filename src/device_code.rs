@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use azure_core::credentials::{AccessToken, TokenCredential, TokenRequestOptions};
+use azure_core::error::{Error as AzureError, ErrorKind};
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::azure::http_client;
+use crate::cloud::cloud;
+
+/// Public client ID of the Azure CLI, which is pre-authorized for ARM and Key Vault.
+const CLIENT_ID: &str = "04b07795-8ddb-461a-bbee-02f9e1bf7b46";
+
+/// A pending device-code sign-in: the user opens `verification_uri` and enters `user_code`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceCode {
+    pub user_code: String,
+    pub verification_uri: String,
+    pub expires_in: u64,
+    #[serde(default = "default_interval")]
+    interval: u64,
+    device_code: String,
+    #[serde(skip)]
+    tenant: String,
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: i64,
+    refresh_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OAuthError {
+    error: String,
+    error_description: Option<String>,
+}
+
+fn endpoint(tenant: &str, path: &str) -> String {
+    format!(
+        "{}/{}/oauth2/v2.0/{}",
+        cloud().authority_host.trim_end_matches('/'),
+        tenant,
+        path
+    )
+}
+
+/// Ask Entra ID for a device code. Without a tenant any work or school account can sign in.
+pub async fn start(tenant: Option<&str>) -> Result<DeviceCode, String> {
+    let tenant = tenant.unwrap_or("organizations").to_string();
+    let scope = format!("{} offline_access", cloud().management_scope());
+    let resp = http_client()
+        .post(endpoint(&tenant, "devicecode"))
+        .form(&[("client_id", CLIENT_ID), ("scope", scope.as_str())])
+        .send()
+        .await
+        .map_err(|e| format!("Device code request failed: {}", e))?;
+    let body = resp.text().await.map_err(|e| e.to_string())?;
+    let mut code: DeviceCode = serde_json::from_str(&body).map_err(|_| oauth_error(&body))?;
+    code.tenant = tenant;
+    Ok(code)
+}
+
+/// Poll until the user has signed in (or the code expires) and return a credential that
+/// exchanges the resulting refresh token for ARM and Key Vault tokens.
+pub async fn wait(code: DeviceCode) -> Result<DeviceCodeCredential, String> {
+    let client = http_client();
+    let deadline = std::time::Instant::now() + Duration::from_secs(code.expires_in);
+    let mut interval = code.interval.max(1);
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        if std::time::Instant::now() >= deadline {
+            return Err("The device code expired before sign-in completed".into());
+        }
+        let resp = client
+            .post(endpoint(&code.tenant, "token"))
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("client_id", CLIENT_ID),
+                ("device_code", code.device_code.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("Sign-in failed: {}", e))?;
+        let body = resp.text().await.map_err(|e| e.to_string())?;
+        if let Ok(token) = serde_json::from_str::<TokenResponse>(&body) {
+            let refresh_token = token
+                .refresh_token
+                .ok_or("Sign-in succeeded but no refresh token was issued")?;
+            return Ok(DeviceCodeCredential {
+                tenant: code.tenant,
+                refresh_token: Mutex::new(refresh_token),
+                tokens: Mutex::new(HashMap::new()),
+            });
+        }
+        match serde_json::from_str::<OAuthError>(&body) {
+            Ok(e) if e.error == "authorization_pending" => {}
+            Ok(e) if e.error == "slow_down" => interval += 5,
+            _ => return Err(oauth_error(&body)),
+        }
+    }
+}
+
+fn oauth_error(body: &str) -> String {
+    match serde_json::from_str::<OAuthError>(body) {
+        Ok(e) => format!(
+            "Sign-in failed: {}",
+            e.error_description
+                .as_deref()
+                .and_then(|d| d.lines().next())
+                .unwrap_or(&e.error)
+        ),
+        Err(_) => "Sign-in failed: unexpected response from the identity provider".into(),
+    }
+}
+
+/// Credential backed by the refresh token from a device-code sign-in. Access tokens are
+/// cached per scope and renewed shortly before they expire.
+#[derive(Debug)]
+pub struct DeviceCodeCredential {
+    tenant: String,
+    refresh_token: Mutex<String>,
+    tokens: Mutex<HashMap<String, AccessToken>>,
+}
+
+impl DeviceCodeCredential {
+    /// The same sign-in, redeemed against another tenant (e.g. one the account is a guest in).
+    pub fn for_tenant(&self, tenant: &str) -> Self {
+        Self {
+            tenant: tenant.to_string(),
+            refresh_token: Mutex::new(self.refresh_token.lock().unwrap().clone()),
+            tokens: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenCredential for DeviceCodeCredential {
+    async fn get_token(
+        &self,
+        scopes: &[&str],
+        _options: Option<TokenRequestOptions<'_>>,
+    ) -> azure_core::Result<AccessToken> {
+        let key = scopes.join(" ");
+        let renew_after = OffsetDateTime::now_utc() + time::Duration::minutes(2);
+        if let Some(token) = self.tokens.lock().unwrap().get(&key)
+            && token.expires_on > renew_after
+        {
+            return Ok(token.clone());
+        }
+
+        let refresh_token = self.refresh_token.lock().unwrap().clone();
+        let scope = format!("{} offline_access", key);
+        let credential_error = |msg: String| AzureError::with_message(ErrorKind::Credential, msg);
+        let resp = http_client()
+            .post(endpoint(&self.tenant, "token"))
+            .form(&[
+                ("grant_type", "refresh_token"),
+                ("client_id", CLIENT_ID),
+                ("refresh_token", refresh_token.as_str()),
+                ("scope", scope.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| credential_error(format!("Token refresh failed: {}", e)))?;
+        let body = resp
+            .text()
+            .await
+            .map_err(|e| credential_error(e.to_string()))?;
+        let token: TokenResponse =
+            serde_json::from_str(&body).map_err(|_| credential_error(oauth_error(&body)))?;
+
+        if let Some(rotated) = token.refresh_token {
+            *self.refresh_token.lock().unwrap() = rotated;
+        }
+        let access = AccessToken::new(
+            token.access_token,
+            OffsetDateTime::now_utc() + time::Duration::seconds(token.expires_in),
+        );
+        self.tokens.lock().unwrap().insert(key, access.clone());
+        Ok(access)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_device_code_and_errors() {
+        let code: DeviceCode = serde_json::from_str(
+            r#"{"device_code":"DC","user_code":"ABCD-EFGH","verification_uri":"https://microsoft.com/devicelogin","expires_in":900,"message":"To sign in..."}"#,
+        )
+        .unwrap();
+        assert_eq!(code.user_code, "ABCD-EFGH");
+        assert_eq!(code.interval, 5);
+
+        assert_eq!(
+            oauth_error(
+                r#"{"error":"expired_token","error_description":"AADSTS70043: The code expired.\r\nTrace ID: 1"}"#
+            ),
+            "Sign-in failed: AADSTS70043: The code expired."
+        );
+    }
+}
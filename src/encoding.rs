@@ -0,0 +1,267 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE, URL_SAFE_NO_PAD};
+
+/// Decode a base64 secret value. Whitespace (line-wrapped PEM bodies, trailing newlines)
+/// is ignored, and both the standard and URL-safe alphabets are accepted, padded or not.
+pub fn decode_base64(value: &str) -> Result<Vec<u8>, String> {
+    let compact: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.is_empty() {
+        return Err("Value is empty".into());
+    }
+    STANDARD
+        .decode(&compact)
+        .or_else(|_| URL_SAFE.decode(&compact))
+        .or_else(|_| URL_SAFE_NO_PAD.decode(compact.trim_end_matches('=')))
+        .map_err(|e| format!("Value is not valid base64: {}", e))
+}
+
+pub fn encode_base64(value: &str) -> String {
+    STANDARD.encode(value.as_bytes())
+}
+
+/// Decoded bytes as text, or None when they aren't UTF-8 (binary keys, DER certificates).
+pub fn decoded_text(bytes: &[u8]) -> Option<String> {
+    std::str::from_utf8(bytes).ok().map(|s| s.to_string())
+}
+
+/// Classic `xxd`-style dump used to preview binary payloads.
+pub fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!("{:08x}  {:<47}  {}", i * 16, hex.join(" "), ascii)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Turn a secret name into a valid shell variable name: `db-password` -> `DB_PASSWORD`.
+pub fn env_var_name(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// `export NAME='value'` with the value single-quoted so nothing in it is expanded.
+pub fn export_statement(name: &str, value: &str) -> String {
+    format!(
+        "export {}='{}'",
+        env_var_name(name),
+        value.replace('\'', "'\\''")
+    )
+}
+
+/// A file read for upload as a secret value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileValue {
+    pub value: String,
+    pub content_type: String,
+    pub base64: bool, // content was base64-encoded (binary file or forced)
+}
+
+/// Expand a leading `~/` to the home directory.
+pub fn expand_path(path: &str) -> PathBuf {
+    let path = path.trim();
+    if let Some(rest) = path.strip_prefix("~/")
+        && let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))
+    {
+        return PathBuf::from(home).join(rest);
+    }
+    PathBuf::from(path)
+}
+
+/// Read a file as a secret value. Text files are stored as-is unless `force_base64` is set;
+/// binary files (anything that isn't UTF-8) are always base64-encoded.
+pub fn read_file_value(path: &str, force_base64: bool) -> Result<FileValue, String> {
+    if path.trim().is_empty() {
+        return Err("File path is empty".into());
+    }
+    let path = expand_path(path);
+    let bytes =
+        std::fs::read(&path).map_err(|e| format!("Cannot read '{}': {}", path.display(), e))?;
+    let content_type = content_type_for(&path, &bytes);
+    Ok(bytes_value(&bytes, content_type, force_base64))
+}
+
+/// Read a piped value from stdin. A single trailing newline (as added by `echo`) is dropped
+/// from text input; binary input is base64-encoded like uploaded files.
+pub fn read_stdin_value(force_base64: bool) -> Result<FileValue, String> {
+    read_value(std::io::stdin(), force_base64)
+}
+
+/// `read_stdin_value` from any reader. Nothing (or just a newline) is an error: it is what a
+/// failed command earlier in the pipe leaves, not a value to store.
+fn read_value(mut reader: impl Read, force_base64: bool) -> Result<FileValue, String> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Cannot read stdin: {}", e))?;
+    if std::str::from_utf8(&bytes).is_ok() {
+        if bytes.ends_with(b"\r\n") {
+            bytes.truncate(bytes.len() - 2);
+        } else if bytes.ends_with(b"\n") {
+            bytes.pop();
+        }
+    }
+    if bytes.is_empty() {
+        return Err("stdin was empty".into());
+    }
+    let content_type = content_type_for(Path::new(""), &bytes);
+    Ok(bytes_value(&bytes, content_type, force_base64))
+}
+
+fn bytes_value(bytes: &[u8], content_type: String, force_base64: bool) -> FileValue {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !force_base64 => FileValue {
+            value: text.to_string(),
+            content_type,
+            base64: false,
+        },
+        _ => FileValue {
+            value: STANDARD.encode(bytes),
+            content_type,
+            base64: true,
+        },
+    }
+}
+
+/// Content type recorded on uploaded secrets, guessed from the extension and content.
+fn content_type_for(path: &Path, bytes: &[u8]) -> String {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let ct = match ext.as_str() {
+        "pfx" | "p12" => "application/x-pkcs12",
+        "pem" | "crt" | "cer" | "key" => "application/x-pem-file",
+        "json" => "application/json",
+        "yaml" | "yml" => "application/x-yaml",
+        "xml" | "lic" if bytes.starts_with(b"<") => "application/xml",
+        _ if bytes.starts_with(b"-----BEGIN ") => "application/x-pem-file",
+        _ if file_name == "kubeconfig"
+            || (file_name == "config" && bytes.starts_with(b"apiVersion")) =>
+        {
+            "application/x-yaml"
+        }
+        _ if std::str::from_utf8(bytes).is_ok() => "text/plain",
+        _ => "application/octet-stream",
+    };
+    ct.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_round_trip_and_variants() {
+        let encoded = encode_base64("hello world");
+        assert_eq!(encoded, "aGVsbG8gd29ybGQ=");
+        assert_eq!(decode_base64(&encoded).unwrap(), b"hello world");
+
+        // Line-wrapped and unpadded URL-safe input
+        assert_eq!(
+            decode_base64("aGVsbG8g\nd29ybGQ=\n").unwrap(),
+            b"hello world"
+        );
+        assert_eq!(decode_base64("-_8").unwrap(), vec![0xfb, 0xff]);
+
+        assert!(decode_base64("not base64!").is_err());
+        assert!(decode_base64("  ").is_err());
+    }
+
+    #[test]
+    fn test_piped_value() {
+        let text = read_value(&b"s3cret\r\n"[..], false).unwrap();
+        assert_eq!(text.value, "s3cret");
+        assert!(!text.base64);
+        let binary = read_value(&[0xff, 0x00][..], false).unwrap();
+        assert!(binary.base64);
+        // An empty pipe (e.g. `cat missing.pem | akv set ...`) must not become a value
+        assert_eq!(read_value(&b""[..], false).unwrap_err(), "stdin was empty");
+        assert!(read_value(&b"\n"[..], true).is_err());
+    }
+
+    #[test]
+    fn test_binary_payloads() {
+        assert_eq!(decoded_text(&[0xff, 0x00]), None);
+        assert_eq!(decoded_text(b"abc").as_deref(), Some("abc"));
+        assert_eq!(
+            hex_dump(b"AB\x00"),
+            format!("00000000  {:<47}  AB.", "41 42 00")
+        );
+    }
+
+    #[test]
+    fn test_read_file_value() {
+        let dir = std::env::temp_dir().join(format!("akv-upload-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pem = dir.join("tls.crt");
+        std::fs::write(
+            &pem,
+            "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n",
+        )
+        .unwrap();
+        let bin = dir.join("cert.pfx");
+        std::fs::write(&bin, [0x30, 0x82, 0xff, 0x00]).unwrap();
+
+        let v = read_file_value(pem.to_str().unwrap(), false).unwrap();
+        assert!(!v.base64);
+        assert!(v.value.starts_with("-----BEGIN"));
+        assert_eq!(v.content_type, "application/x-pem-file");
+
+        let v = read_file_value(pem.to_str().unwrap(), true).unwrap();
+        assert!(v.base64);
+        assert_eq!(
+            decode_base64(&v.value).unwrap(),
+            std::fs::read(&pem).unwrap()
+        );
+
+        // Binary content is always base64-encoded
+        let v = read_file_value(bin.to_str().unwrap(), false).unwrap();
+        assert!(v.base64);
+        assert_eq!(v.value, "MIL/AA==");
+        assert_eq!(v.content_type, "application/x-pkcs12");
+
+        assert!(read_file_value(dir.join("missing").to_str().unwrap(), false).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export_statement() {
+        assert_eq!(env_var_name("db-password"), "DB_PASSWORD");
+        assert_eq!(env_var_name("3rd-party.key"), "_3RD_PARTY_KEY");
+        assert_eq!(
+            export_statement("api-key", "it's $HOME"),
+            "export API_KEY='it'\\''s $HOME'"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::error::Error;
+
+use thiserror::Error;
+
+use crate::azure::{is_credential_error, is_network_error};
+
+/// What went wrong in a background operation, by what the UI can do about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// No credential could sign in, or the token was rejected (401): offer to sign in.
+    Auth,
+    /// Still throttled (429) after the retries of the HTTP client: try again later.
+    Throttled,
+    /// The secret, key or vault is gone (404).
+    NotFound,
+    /// Azure couldn't be reached (DNS, connection, timeout): fall back to cached data.
+    Network,
+    /// Refused (403): the remedy names the role or access policy to ask for.
+    Permission,
+    Other,
+}
+
+/// The error of every request to Azure and of the secret stores. SDK and reqwest errors are
+/// classified once, when converted, so callers only look at `category`.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{message}")]
+pub struct AkvError {
+    pub category: Category,
+    pub message: String,
+    pub causes: Vec<String>, // messages of the errors it wraps, outermost first
+}
+
+impl AkvError {
+    pub fn new(category: Category, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            message: message.into(),
+            causes: Vec::new(),
+        }
+    }
+
+    pub fn other(message: impl Into<String>) -> Self {
+        Self::new(Category::Other, message)
+    }
+
+    /// An error response (Key Vault REST or ARM) with `status` and the message it carried.
+    pub fn response(status: u16, message: &str) -> Self {
+        Self::new(
+            category_of(Some(status)),
+            format!("{} ({})", message, status),
+        )
+    }
+
+    /// The error and its causes, outermost first.
+    pub fn chain(&self) -> Vec<String> {
+        let mut chain = vec![self.message.clone()];
+        chain.extend(self.causes.iter().cloned());
+        chain
+    }
+
+    /// The category of `e` from its kind or the status of the response it (or an error it
+    /// wraps) carries, keeping the messages of its sources.
+    fn classify(e: &(dyn Error + 'static)) -> Self {
+        let category = if is_credential_error(e) {
+            Category::Auth
+        } else if is_network_error(e) {
+            Category::Network
+        } else {
+            category_of(status(e))
+        };
+        let mut causes: Vec<String> = Vec::new();
+        let message = e.to_string();
+        let mut source = e.source();
+        while let Some(e) = source {
+            // Wrappers often repeat their source's message
+            let text = e.to_string();
+            if causes.last().unwrap_or(&message) != &text {
+                causes.push(text);
+            }
+            source = e.source();
+        }
+        Self {
+            category,
+            message,
+            causes,
+        }
+    }
+}
+
+impl From<azure_core::Error> for AkvError {
+    fn from(e: azure_core::Error) -> Self {
+        Self::classify(&e)
+    }
+}
+
+impl From<reqwest::Error> for AkvError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::classify(&e)
+    }
+}
+
+impl From<serde_json::Error> for AkvError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::other(e.to_string())
+    }
+}
+
+impl From<String> for AkvError {
+    fn from(message: String) -> Self {
+        Self::other(message)
+    }
+}
+
+impl From<&str> for AkvError {
+    fn from(message: &str) -> Self {
+        Self::other(message)
+    }
+}
+
+fn category_of(status: Option<u16>) -> Category {
+    match status {
+        Some(401) => Category::Auth,
+        Some(403) => Category::Permission,
+        Some(404) => Category::NotFound,
+        Some(429) => Category::Throttled,
+        _ => Category::Other,
+    }
+}
+
+/// The HTTP status of the error response `e` (or an error it wraps) carries, if any.
+fn status(e: &(dyn Error + 'static)) -> Option<u16> {
+    let mut source = Some(e);
+    while let Some(e) = source {
+        let status = if let Some(e) = e.downcast_ref::<azure_core::Error>() {
+            e.http_status().map(u16::from)
+        } else {
+            e.downcast_ref::<reqwest::Error>()
+                .and_then(|e| e.status())
+                .map(|s| s.as_u16())
+        };
+        if status.is_some() {
+            return status;
+        }
+        source = e.source();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let response = |status: u16| AkvError::response(status, "request failed");
+        assert_eq!(
+            response(429),
+            AkvError::new(Category::Throttled, "request failed (429)")
+        );
+        assert_eq!(response(401).category, Category::Auth);
+        assert_eq!(response(403).category, Category::Permission);
+        assert_eq!(response(500).category, Category::Other);
+
+        // The status of a wrapped error counts, and its message is kept as a cause
+        let inner = azure_core::Error::with_message(
+            azure_core::error::ErrorKind::HttpResponse {
+                status: azure_core::http::StatusCode::NotFound,
+                error_code: None,
+                raw_response: None,
+            },
+            "Secret not found",
+        );
+        let wrapped = azure_core::Error::new(azure_core::error::ErrorKind::Other, inner);
+        let e = AkvError::from(wrapped);
+        assert_eq!(e.category, Category::NotFound);
+        assert_eq!(
+            e.chain().last().map(String::as_str),
+            Some("Secret not found")
+        );
+
+        let io = azure_core::Error::new(
+            azure_core::error::ErrorKind::Io,
+            std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "refused"),
+        );
+        assert_eq!(AkvError::from(io).category, Category::Network);
+        assert_eq!(AkvError::from("no signature").category, Category::Other);
+    }
+}
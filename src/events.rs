@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::mpsc::{self, error::SendError, error::TrySendError};
+
+use crate::model::AppEvent;
+
+/// Events the channel holds before senders fall back to the overflow queue.
+pub const CAPACITY: usize = 1024;
+
+/// Events that didn't fit in the channel, delivered after it in order. Progress is coalesced
+/// here, so a runaway preload keeps at most one listing per vault; nothing else is dropped.
+type Overflow = Arc<Mutex<VecDeque<AppEvent>>>;
+
+/// The sending half of the channel from background tasks to the UI. Sending never blocks.
+#[derive(Clone)]
+pub struct EventSender {
+    tx: mpsc::Sender<AppEvent>,
+    overflow: Overflow,
+}
+
+pub struct EventReceiver {
+    rx: mpsc::Receiver<AppEvent>,
+    overflow: Overflow,
+}
+
+/// A bounded channel of `capacity` events for background tasks -> UI.
+pub fn channel(capacity: usize) -> (EventSender, EventReceiver) {
+    let (tx, rx) = mpsc::channel(capacity);
+    let overflow = Overflow::default();
+    (
+        EventSender {
+            tx,
+            overflow: overflow.clone(),
+        },
+        EventReceiver { rx, overflow },
+    )
+}
+
+impl EventSender {
+    /// Queue `event`; fails only once the UI is gone. While the channel is full, events go to the
+    /// overflow queue (progress coalesced with its latest state), and so do all events after them
+    /// until the UI caught up, to keep their order.
+    pub fn send(&self, event: AppEvent) -> Result<(), SendError<()>> {
+        let mut overflow = self.overflow.lock().unwrap_or_else(|e| e.into_inner());
+        let event = if overflow.is_empty() {
+            match self.tx.try_send(event) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Closed(_)) => return Err(SendError(())),
+                Err(TrySendError::Full(event)) => event,
+            }
+        } else if self.tx.is_closed() {
+            return Err(SendError(()));
+        } else {
+            event
+        };
+        coalesce(&mut overflow, event);
+        Ok(())
+    }
+}
+
+impl EventReceiver {
+    /// The next event if one is waiting.
+    pub fn try_recv(&mut self) -> Option<AppEvent> {
+        self.rx.try_recv().ok().or_else(|| {
+            let mut overflow = self.overflow.lock().unwrap_or_else(|e| e.into_inner());
+            overflow.pop_front()
+        })
+    }
+
+    /// Wait for the next event; `None` once every sender is gone and everything was received.
+    #[cfg(test)]
+    pub async fn recv(&mut self) -> Option<AppEvent> {
+        match self.try_recv() {
+            Some(event) => Some(event),
+            None => self.rx.recv().await,
+        }
+    }
+}
+
+/// Queue `event`, merging it into a queued event of the same vault (or search) that it makes
+/// stale: listing batches are appended to each other, full listings and search results replace
+/// the older ones. Errors and everything else are always queued.
+fn coalesce(queue: &mut VecDeque<AppEvent>, event: AppEvent) {
+    use AppEvent::*;
+    if let SecretsListed(vault, names) = &event
+        && let Some(SecretsListed(_, queued)) = queue
+            .iter_mut()
+            .rev()
+            .find(|e| matches!(e, SecretsListed(v, _) if v == vault))
+    {
+        queued.extend(names.iter().cloned());
+        return;
+    }
+    let stale = |queued: &AppEvent| match (queued, &event) {
+        (SecretsUpdated(a, _), SecretsUpdated(b, _))
+        | (CacheVaultSecrets(a, _), CacheVaultSecrets(b, _)) => a == b,
+        (SearchResults(..), SearchResults(..)) => true,
+        _ => false,
+    };
+    queue.retain(|queued| !stale(queued));
+    queue.push_back(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AkvError;
+    use crate::model::Failure;
+
+    #[tokio::test]
+    async fn test_overflow_coalesces_progress() {
+        let (tx, mut rx) = channel(2);
+        let listing =
+            |n: usize| AppEvent::SecretsUpdated("kv-a".into(), Arc::new(vec![n.to_string(); n]));
+        tx.send(AppEvent::Finished("first".into())).unwrap();
+        tx.send(listing(1)).unwrap();
+        // Full: a runaway preload keeps only its latest listing, errors all stay
+        for n in 2..1000 {
+            tx.send(listing(n)).unwrap();
+            tx.send(AppEvent::SecretsListed("kv-b".into(), vec![n.to_string()]))
+                .unwrap();
+        }
+        let refused = AkvError::other("refused");
+        let failure = Failure::new("List kv-b".into(), "failed".into(), &refused, None);
+        tx.send(AppEvent::Failed(failure)).unwrap();
+        assert_eq!(rx.overflow.lock().unwrap().len(), 3);
+
+        let mut received = Vec::new();
+        while let Some(event) = rx.try_recv() {
+            received.push(match event {
+                AppEvent::Finished(m) => m,
+                AppEvent::Failed(f) => f.message,
+                AppEvent::SecretsUpdated(_, names) => format!("listing {}", names.len()),
+                AppEvent::SecretsListed(_, names) => format!("listed {}", names.len()),
+                _ => unreachable!(),
+            });
+        }
+        assert_eq!(
+            received,
+            ["first", "listing 1", "listed 998", "listing 999", "failed"]
+        );
+
+        // Caught up: the channel is used again, and closes with the UI
+        tx.send(listing(3)).unwrap();
+        assert!(rx.overflow.lock().unwrap().is_empty());
+        drop(rx);
+        assert!(tx.send(AppEvent::Finished("late".into())).is_err());
+    }
+}
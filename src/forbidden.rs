@@ -0,0 +1,137 @@
+use crate::error::{AkvError, Category};
+use crate::model::VaultProperties;
+
+/// What was being done when Key Vault refused, to name the role (or access-policy
+/// permissions) that allows it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Need {
+    ReadSecrets,
+    WriteSecrets,
+    UseKeys,    // list keys, crypto operations
+    ManageKeys, // rotation policies
+}
+
+impl Need {
+    /// Built-in role for vaults using Azure RBAC.
+    fn role(self) -> &'static str {
+        match self {
+            Need::ReadSecrets => "Key Vault Secrets User",
+            Need::WriteSecrets => "Key Vault Secrets Officer",
+            Need::UseKeys => "Key Vault Crypto User",
+            Need::ManageKeys => "Key Vault Crypto Officer",
+        }
+    }
+
+    /// `az keyvault set-policy` arguments for vaults using access policies.
+    fn policy(self) -> &'static str {
+        match self {
+            Need::ReadSecrets => "--secret-permissions get list",
+            Need::WriteSecrets => "--secret-permissions get list set delete",
+            Need::UseKeys => {
+                "--key-permissions get list encrypt decrypt sign verify wrapKey unwrapKey"
+            }
+            Need::ManageKeys => "--key-permissions get list getrotationpolicy setrotationpolicy",
+        }
+    }
+}
+
+/// Whether `e` is a 403 response.
+pub fn is_forbidden(e: &AkvError) -> bool {
+    e.category == Category::Permission
+}
+
+/// What to do about a 403 from `vault`: the role to ask for, or the access-policy permissions,
+/// with the az command that grants it. `assignee` is the signed-in principal when known;
+/// vaults without ARM properties are assumed to use RBAC, the default for new vaults.
+pub fn remedy(
+    vault: &str,
+    props: Option<&VaultProperties>,
+    assignee: Option<&str>,
+    need: Need,
+) -> String {
+    let assignee = assignee.unwrap_or("<your-upn>");
+    if props.is_some_and(|p| !p.rbac) {
+        let principal = if assignee.contains('@') {
+            "--upn"
+        } else {
+            "--spn"
+        };
+        return format!(
+            "Your access policy on '{}' doesn't allow this; ask the vault owner or run: az keyvault set-policy --name {} {} {} {}",
+            vault,
+            vault,
+            principal,
+            assignee,
+            need.policy()
+        );
+    }
+    let scope = props
+        .and_then(|p| p.resource_id.clone())
+        .unwrap_or_else(|| format!("$(az keyvault show --name {} --query id -o tsv)", vault));
+    format!(
+        "You need the '{}' role on '{}'; ask the vault owner or run: az role assignment create --role \"{}\" --assignee {} --scope {}",
+        need.role(),
+        vault,
+        need.role(),
+        assignee,
+        scope
+    )
+}
+
+/// "`what`: `e`", or the remedy when the request was refused.
+pub fn describe(what: &str, e: &AkvError, remedy: &str) -> String {
+    if is_forbidden(e) {
+        format!("{}: access denied. {}", what, remedy)
+    } else {
+        format!("{}: {}", what, e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forbidden_remedy() {
+        let denied = AkvError::response(403, "Caller is not authorized");
+        let missing = AkvError::response(404, "Key not found");
+        assert!(is_forbidden(&denied));
+        assert!(!is_forbidden(&missing));
+        assert_eq!(
+            describe("Failed to list keys", &missing, "unused"),
+            "Failed to list keys: Key not found (404)"
+        );
+
+        let rbac = VaultProperties {
+            resource_id: Some(
+                "/subscriptions/s/resourceGroups/rg/providers/Microsoft.KeyVault/vaults/kv".into(),
+            ),
+            rbac: true,
+            ..Default::default()
+        };
+        let message = describe(
+            "Failed to get secret",
+            &denied,
+            &remedy(
+                "kv",
+                Some(&rbac),
+                Some("jane@contoso.com"),
+                Need::ReadSecrets,
+            ),
+        );
+        assert_eq!(
+            message,
+            "Failed to get secret: access denied. You need the 'Key Vault Secrets User' role on 'kv'; ask the vault owner or run: az role assignment create --role \"Key Vault Secrets User\" --assignee jane@contoso.com --scope /subscriptions/s/resourceGroups/rg/providers/Microsoft.KeyVault/vaults/kv"
+        );
+
+        let policies = VaultProperties::default();
+        assert!(
+            remedy("kv", Some(&policies), Some("0000-1111"), Need::WriteSecrets).ends_with(
+                "az keyvault set-policy --name kv --spn 0000-1111 --secret-permissions get list set delete"
+            )
+        );
+        assert!(remedy("kv", None, None, Need::ManageKeys).contains(
+            "--assignee <your-upn> --scope $(az keyvault show --name kv --query id -o tsv)"
+        ));
+    }
+}
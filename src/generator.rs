@@ -0,0 +1,135 @@
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!@#$%^&*()-_=+[]{}:,.?";
+
+pub const MIN_LENGTH: usize = 8;
+pub const MAX_LENGTH: usize = 256;
+
+/// Options for the random secret generator popup in the Add modal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorOptions {
+    pub length: usize,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+    pub focus: usize, // 0 = length, 1..=4 = character classes
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        Self {
+            length: 32,
+            lowercase: true,
+            uppercase: true,
+            digits: true,
+            symbols: false,
+            focus: 0,
+        }
+    }
+}
+
+impl GeneratorOptions {
+    pub const ROWS: usize = 5;
+
+    fn classes(&self) -> Vec<&'static str> {
+        [
+            (self.lowercase, LOWERCASE),
+            (self.uppercase, UPPERCASE),
+            (self.digits, DIGITS),
+            (self.symbols, SYMBOLS),
+        ]
+        .into_iter()
+        .filter_map(|(on, set)| on.then_some(set))
+        .collect()
+    }
+
+    pub fn adjust_length(&mut self, delta: isize) {
+        let len = self.length as isize + delta;
+        self.length = (len.max(MIN_LENGTH as isize) as usize).min(MAX_LENGTH);
+    }
+
+    /// Toggle the focused character class; refuses to disable the last enabled one.
+    pub fn toggle_focused(&mut self) {
+        let enabled = self.classes().len();
+        let flag = match self.focus {
+            1 => &mut self.lowercase,
+            2 => &mut self.uppercase,
+            3 => &mut self.digits,
+            4 => &mut self.symbols,
+            _ => return,
+        };
+        if !*flag || enabled > 1 {
+            *flag = !*flag;
+        }
+    }
+}
+
+/// Generate a random value from the OS-seeded CSPRNG, guaranteeing at least one
+/// character from every enabled class.
+pub fn generate(opts: &GeneratorOptions) -> String {
+    let classes = opts.classes();
+    if classes.is_empty() {
+        return String::new();
+    }
+    let mut rng = rand::rng();
+    let pool: Vec<char> = classes.iter().flat_map(|c| c.chars()).collect();
+    let mut out: Vec<char> = classes
+        .iter()
+        .map(|c| {
+            let chars: Vec<char> = c.chars().collect();
+            chars[rng.random_range(0..chars.len())]
+        })
+        .collect();
+    while out.len() < opts.length {
+        out.push(pool[rng.random_range(0..pool.len())]);
+    }
+    out.shuffle(&mut rng);
+    out.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_respects_length_and_classes() {
+        let opts = GeneratorOptions {
+            length: 40,
+            lowercase: false,
+            uppercase: true,
+            digits: true,
+            symbols: false,
+            focus: 0,
+        };
+        let value = generate(&opts);
+        assert_eq!(value.chars().count(), 40);
+        assert!(
+            value
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        );
+        assert!(value.chars().any(|c| c.is_ascii_uppercase()));
+        assert!(value.chars().any(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_options_bounds() {
+        let mut opts = GeneratorOptions::default();
+        opts.adjust_length(-1000);
+        assert_eq!(opts.length, MIN_LENGTH);
+        opts.adjust_length(1000);
+        assert_eq!(opts.length, MAX_LENGTH);
+
+        // The last enabled class cannot be switched off
+        opts.uppercase = false;
+        opts.digits = false;
+        opts.focus = 1;
+        opts.toggle_focused();
+        assert!(opts.lowercase);
+    }
+}
@@ -0,0 +1,191 @@
+use std::sync::OnceLock;
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+/// Detected shape of a secret value, used to pick a highlighter in the preview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueFormat {
+    Json,
+    Pem,
+    Yaml,
+    Plain,
+}
+
+impl ValueFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ValueFormat::Json => "JSON",
+            ValueFormat::Pem => "PEM",
+            ValueFormat::Yaml => "YAML",
+            ValueFormat::Plain => "text",
+        }
+    }
+}
+
+/// Guess the value format. YAML is only claimed for multi-line `key: value` documents so
+/// that ordinary single-line passwords stay plain text.
+pub fn detect_format(value: &str) -> ValueFormat {
+    let trimmed = value.trim();
+    if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(trimmed).is_ok()
+    {
+        return ValueFormat::Json;
+    }
+    if trimmed.starts_with("-----BEGIN ") && trimmed.contains("-----END ") {
+        return ValueFormat::Pem;
+    }
+    let lines: Vec<&str> = trimmed
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .collect();
+    if lines.len() > 1
+        && lines.iter().all(|l| {
+            let l = l.trim_start();
+            l.starts_with("- ")
+                || l == "---"
+                || l.split_once(':').is_some_and(|(k, _)| !k.contains(' '))
+        })
+    {
+        return ValueFormat::Yaml;
+    }
+    ValueFormat::Plain
+}
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Choose the syntect theme for previews; must be called before the first `highlight`.
+pub fn set_syntax_theme(name: &str) -> Result<(), String> {
+    let mut themes = ThemeSet::load_defaults();
+    let Some(theme) = themes.themes.remove(name) else {
+        let mut known: Vec<_> = themes.themes.keys().cloned().collect();
+        known.sort();
+        return Err(format!(
+            "Unknown syntax theme '{}' (available: {})",
+            name,
+            known.join(", ")
+        ));
+    };
+    let _ = THEME.set(theme);
+    Ok(())
+}
+
+fn theme() -> &'static Theme {
+    THEME.get_or_init(|| {
+        let mut themes = ThemeSet::load_defaults();
+        themes
+            .themes
+            .remove("base16-ocean.dark")
+            .unwrap_or_default()
+    })
+}
+
+/// Render a value into styled lines for the preview modal.
+pub fn highlight(value: &str, format: ValueFormat) -> Vec<Line<'static>> {
+    match format {
+        ValueFormat::Json => {
+            // Pretty-print so minified documents become readable
+            let pretty = serde_json::from_str::<serde_json::Value>(value.trim())
+                .ok()
+                .and_then(|v| serde_json::to_string_pretty(&v).ok())
+                .unwrap_or_else(|| value.to_string());
+            highlight_with_syntax(&pretty, "json")
+        }
+        ValueFormat::Yaml => highlight_with_syntax(value, "yaml"),
+        ValueFormat::Pem => highlight_pem(value),
+        ValueFormat::Plain => value.lines().map(|l| Line::from(l.to_string())).collect(),
+    }
+}
+
+fn highlight_with_syntax(text: &str, extension: &str) -> Vec<Line<'static>> {
+    let ss = syntax_set();
+    let Some(syntax) = ss.find_syntax_by_extension(extension) else {
+        return text.lines().map(|l| Line::from(l.to_string())).collect();
+    };
+    let mut h = HighlightLines::new(syntax, theme());
+    LinesWithEndings::from(text)
+        .map(|line| match h.highlight_line(line, ss) {
+            Ok(ranges) => Line::from(
+                ranges
+                    .into_iter()
+                    .map(|(style, s)| {
+                        let mut st = Style::default().fg(Color::Rgb(
+                            style.foreground.r,
+                            style.foreground.g,
+                            style.foreground.b,
+                        ));
+                        if style.font_style.contains(FontStyle::BOLD) {
+                            st = st.add_modifier(Modifier::BOLD);
+                        }
+                        Span::styled(s.trim_end_matches(['\n', '\r']).to_string(), st)
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            Err(_) => Line::from(line.trim_end().to_string()),
+        })
+        .collect()
+}
+
+fn highlight_pem(text: &str) -> Vec<Line<'static>> {
+    text.lines()
+        .map(|l| {
+            if l.starts_with("-----") {
+                Line::from(Span::styled(
+                    l.to_string(),
+                    Style::default()
+                        .fg(Color::Magenta)
+                        .add_modifier(Modifier::BOLD),
+                ))
+            } else if l.contains(':') {
+                // Encapsulated headers, e.g. "Proc-Type: 4,ENCRYPTED"
+                Line::from(Span::styled(
+                    l.to_string(),
+                    Style::default().fg(Color::Cyan),
+                ))
+            } else {
+                Line::from(Span::styled(
+                    l.to_string(),
+                    Style::default().fg(Color::DarkGray),
+                ))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(detect_format(r#"{"a": 1}"#), ValueFormat::Json);
+        assert_eq!(
+            detect_format("-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n"),
+            ValueFormat::Pem
+        );
+        assert_eq!(
+            detect_format("apiVersion: v1\nkind: Config\nusers:\n- name: x"),
+            ValueFormat::Yaml
+        );
+        assert_eq!(detect_format("hunter2"), ValueFormat::Plain);
+        assert_eq!(detect_format("user:pass"), ValueFormat::Plain);
+        assert_eq!(detect_format("{not json"), ValueFormat::Plain);
+    }
+
+    #[test]
+    fn test_highlight_json_pretty_prints() {
+        let lines = highlight(r#"{"a":1,"b":"x"}"#, ValueFormat::Json);
+        assert_eq!(lines.len(), 4);
+        let text: String = lines[1].spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, r#"  "a": 1,"#);
+    }
+}
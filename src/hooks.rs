@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use tracing::{debug, warn};
+
+/// What happened to a secret in the TUI, each with its `[hooks]` key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HookEvent {
+    Copy,
+    SecretCreated,
+    SecretUpdated,
+    SecretDeleted,
+}
+
+impl HookEvent {
+    pub fn key(self) -> &'static str {
+        match self {
+            HookEvent::Copy => "on_copy",
+            HookEvent::SecretCreated => "on_secret_created",
+            HookEvent::SecretUpdated => "on_secret_updated",
+            HookEvent::SecretDeleted => "on_secret_deleted",
+        }
+    }
+}
+
+/// A hook's command line, split into words; `{vault}`, `{name}` and `{value}` in a word are
+/// replaced when it runs.
+#[derive(Debug, Clone, PartialEq)]
+struct HookCommand {
+    words: Vec<String>,
+}
+
+/// User commands run when secrets are copied, created, updated or deleted, e.g. to notify a
+/// channel or append to an audit log. They get the vault and secret name; the value only
+/// where the command asks for `{value}`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Hooks {
+    commands: Arc<HashMap<HookEvent, HookCommand>>,
+}
+
+impl Hooks {
+    /// Hooks from `(event, command line)` pairs, checking each command.
+    pub fn new<'a>(
+        commands: impl IntoIterator<Item = (HookEvent, &'a str)>,
+    ) -> Result<Self, String> {
+        let mut parsed = HashMap::new();
+        for (event, line) in commands {
+            let command =
+                HookCommand::parse(line).map_err(|e| format!("{}: {}", event.key(), e))?;
+            if event == HookEvent::SecretDeleted && command.uses_value() {
+                return Err(format!(
+                    "{}: a deleted secret has no {{value}}",
+                    event.key()
+                ));
+            }
+            parsed.insert(event, command);
+        }
+        Ok(Self {
+            commands: Arc::new(parsed),
+        })
+    }
+
+    /// Whether a hook for `event` wants the secret value, so callers know to pass it.
+    pub fn wants_value(&self, event: HookEvent) -> bool {
+        self.commands.get(&event).is_some_and(|c| c.uses_value())
+    }
+
+    /// Run the hook for `event`, if any, without waiting for it. The command doesn't go
+    /// through a shell, so names can't inject anything; its output is discarded and a failure
+    /// only ends up in the log.
+    pub fn run(&self, event: HookEvent, vault: &str, name: &str, value: Option<&str>) {
+        let Some(command) = self.commands.get(&event) else {
+            return;
+        };
+        let words = command.expand(vault, name, value);
+        let (vault, name) = (vault.to_string(), name.to_string());
+        std::thread::spawn(move || {
+            let Some((program, args)) = words.split_first() else {
+                return;
+            };
+            debug!("Running {} hook: {}", event.key(), program);
+            let status = Command::new(program)
+                .args(args)
+                .env("AKV_HOOK", event.key())
+                .env("AKV_VAULT", &vault)
+                .env("AKV_SECRET_NAME", &name)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+            match status {
+                Ok(status) if status.success() => {}
+                Ok(status) => warn!("{} hook for '{}' exited with {}", event.key(), name, status),
+                Err(e) => warn!("Cannot run {} hook {}: {}", event.key(), program, e),
+            }
+        });
+    }
+}
+
+impl HookCommand {
+    /// Split on whitespace, with '...' and "..." keeping a word together.
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut words = Vec::new();
+        let mut word: Option<String> = None;
+        let mut quote: Option<char> = None;
+        for c in line.chars() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), c) => word.get_or_insert_default().push(c),
+                (None, '\'' | '"') => {
+                    quote = Some(c);
+                    word.get_or_insert_default();
+                }
+                (None, c) if c.is_whitespace() => words.extend(word.take()),
+                (None, c) => word.get_or_insert_default().push(c),
+            }
+        }
+        if quote.is_some() {
+            return Err(format!("unterminated quote in '{}'", line));
+        }
+        words.extend(word);
+        if words.is_empty() {
+            return Err("empty command".into());
+        }
+        // `{word}` is a placeholder; other braces (e.g. JSON) are kept as they are
+        for word in &words {
+            for (start, _) in word.match_indices('{') {
+                let Some(len) = word[start + 1..].find('}') else {
+                    break;
+                };
+                let key = &word[start + 1..start + 1 + len];
+                let is_placeholder =
+                    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if is_placeholder && !matches!(key, "vault" | "name" | "value") {
+                    return Err(format!(
+                        "unknown placeholder {{{}}} (expected {{vault}}, {{name}} or {{value}})",
+                        key
+                    ));
+                }
+            }
+        }
+        Ok(Self { words })
+    }
+
+    fn uses_value(&self) -> bool {
+        self.words.iter().any(|w| w.contains("{value}"))
+    }
+
+    fn expand(&self, vault: &str, name: &str, value: Option<&str>) -> Vec<String> {
+        self.words
+            .iter()
+            .map(|w| {
+                w.replace("{vault}", vault)
+                    .replace("{name}", name)
+                    .replace("{value}", value.unwrap_or_default())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hooks() {
+        let command =
+            HookCommand::parse(r#"notify-send "Copied {name}" --app-name='akv {vault}'"#).unwrap();
+        assert_eq!(
+            command.words,
+            ["notify-send", "Copied {name}", "--app-name=akv {vault}"]
+        );
+        assert!(!command.uses_value());
+        assert_eq!(
+            command.expand("kv-a", "db-password", Some("s3cret")),
+            ["notify-send", "Copied db-password", "--app-name=akv kv-a"]
+        );
+        assert_eq!(HookCommand::parse("a '' b").unwrap().words, ["a", "", "b"]);
+        assert!(HookCommand::parse("  ").is_err());
+        assert!(HookCommand::parse("echo 'open").is_err());
+        assert!(HookCommand::parse("echo {secret}").is_err());
+        // JSON braces aren't placeholders
+        let post = HookCommand::parse(r#"curl -d '{"secret":"{name}"}' https://audit"#).unwrap();
+        assert_eq!(
+            post.expand("kv-a", "api-key", None)[2],
+            r#"{"secret":"api-key"}"#
+        );
+
+        let hooks = Hooks::new([
+            (HookEvent::Copy, "audit copy {name}"),
+            (
+                HookEvent::SecretCreated,
+                "backup-secret {vault}/{name} {value}",
+            ),
+        ])
+        .unwrap();
+        assert!(!hooks.wants_value(HookEvent::Copy));
+        assert!(hooks.wants_value(HookEvent::SecretCreated));
+        assert!(!hooks.wants_value(HookEvent::SecretDeleted));
+        assert!(Hooks::new([(HookEvent::SecretDeleted, "echo {value}")]).is_err());
+    }
+}
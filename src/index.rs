@@ -0,0 +1,201 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+
+use crate::model::SecretMeta;
+
+/// Searchable metadata of every listed vault, fed by `CacheVaultSecrets` (preload, opening a
+/// vault, refreshes). Names are lowercased once and tags are kept in an inverted index, so a
+/// keystroke only runs the fuzzy matcher on entries that can still match.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    vaults: HashMap<String, Arc<VaultIndex>>, // shared with background searches
+}
+
+/// The index of one vault; never changed once built, so searches can run on a snapshot.
+#[derive(Debug, Default)]
+pub struct VaultIndex {
+    entries: Vec<IndexEntry>,          // sorted by name
+    tags: HashMap<String, Vec<usize>>, // "key" and "key=value" (lowercase) -> entry positions
+}
+
+#[derive(Debug)]
+struct IndexEntry {
+    meta: SecretMeta,
+    key: Vec<char>, // lowercased name, for the subsequence prefilter
+}
+
+/// A search query: free text matched fuzzily against names plus `tag:key` / `tag:key=value`
+/// terms that must all match.
+#[derive(Debug, Default, PartialEq)]
+pub struct Query {
+    pub text: String,
+    pub tags: Vec<String>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Self {
+        let mut query = Self::default();
+        let mut words = Vec::new();
+        for word in input.split_whitespace() {
+            match word.strip_prefix("tag:") {
+                Some(tag) if !tag.is_empty() => query.tags.push(tag.to_lowercase()),
+                _ => words.push(word),
+            }
+        }
+        query.text = words.join(" ");
+        query
+    }
+}
+
+impl SearchIndex {
+    /// Replace the indexed secrets of a vault.
+    pub fn update_vault(&mut self, vault: &str, mut secrets: Vec<SecretMeta>) {
+        secrets.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut index = VaultIndex::default();
+        for (pos, meta) in secrets.into_iter().enumerate() {
+            for (k, v) in &meta.tags {
+                let k = k.to_lowercase();
+                index
+                    .tags
+                    .entry(format!("{}={}", k, v.to_lowercase()))
+                    .or_default()
+                    .push(pos);
+                index.tags.entry(k).or_default().push(pos);
+            }
+            let key = meta.name.to_lowercase().chars().collect();
+            index.entries.push(IndexEntry { meta, key });
+        }
+        self.vaults.insert(vault.to_string(), Arc::new(index));
+    }
+
+    /// The index of a vault, cheap to hand to a background search. `None` when the vault
+    /// hasn't been indexed yet.
+    pub fn vault(&self, vault: &str) -> Option<Arc<VaultIndex>> {
+        self.vaults.get(vault).cloned()
+    }
+
+    /// Indexed metadata of a vault, sorted by name.
+    pub fn secrets(&self, vault: &str) -> Option<Vec<SecretMeta>> {
+        let index = self.vaults.get(vault)?;
+        Some(index.entries.iter().map(|e| e.meta.clone()).collect())
+    }
+
+    /// Indexed metadata of one secret.
+    pub fn meta(&self, vault: &str, name: &str) -> Option<&SecretMeta> {
+        let entries = &self.vaults.get(vault)?.entries;
+        let pos = entries
+            .binary_search_by(|e| e.meta.name.as_str().cmp(name))
+            .ok()?;
+        Some(&entries[pos].meta)
+    }
+
+    pub fn remove_vault(&mut self, vault: &str) {
+        self.vaults.remove(vault);
+    }
+
+    /// Every indexed secret with its vault.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SecretMeta)> {
+        self.vaults
+            .iter()
+            .flat_map(|(vault, index)| index.entries.iter().map(move |e| (vault.as_str(), &e.meta)))
+    }
+
+    /// Forget everything, e.g. after switching tenants.
+    pub fn clear(&mut self) {
+        self.vaults.clear();
+    }
+}
+
+impl VaultIndex {
+    /// Names matching `query`, best match first (alphabetical without free text).
+    pub fn search(&self, query: &Query) -> Vec<String> {
+        let mut candidates: Vec<usize> = (0..self.entries.len()).collect();
+        for tag in &query.tags {
+            let postings = self.tags.get(tag).map(Vec::as_slice).unwrap_or_default();
+            candidates.retain(|pos| postings.binary_search(pos).is_ok());
+        }
+        if query.text.is_empty() {
+            return candidates
+                .into_iter()
+                .map(|pos| self.entries[pos].meta.name.clone())
+                .collect();
+        }
+
+        let needle: Vec<char> = query
+            .text
+            .to_lowercase()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let matcher = SkimMatcherV2::default();
+        let mut results: Vec<(i64, &str)> = candidates
+            .into_iter()
+            .map(|pos| &self.entries[pos])
+            .filter(|e| is_subsequence(&needle, &e.key))
+            .filter_map(|e| {
+                matcher
+                    .fuzzy_match(&e.meta.name, &query.text)
+                    .map(|score| (score, e.meta.name.as_str()))
+            })
+            .collect();
+        results.sort_by_key(|r| Reverse(r.0));
+        results.into_iter().map(|(_, n)| n.to_string()).collect()
+    }
+}
+
+/// Cheap check that every needle char appears in order; the fuzzy matcher can't match otherwise.
+fn is_subsequence(needle: &[char], haystack: &[char]) -> bool {
+    let mut rest = haystack.iter();
+    needle.iter().all(|c| rest.any(|h| h == c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(name: &str, tags: &[(&str, &str)]) -> SecretMeta {
+        SecretMeta {
+            name: name.to_string(),
+            enabled: true,
+            tags: tags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_search_names_and_tags() {
+        let mut index = SearchIndex::default();
+        index.update_vault(
+            "kv",
+            vec![
+                meta("db-password", &[("env", "prod"), ("owner", "data")]),
+                meta("api-key", &[("env", "dev")]),
+                meta("db-user", &[("env", "dev")]),
+            ],
+        );
+
+        let search =
+            |index: &SearchIndex, q: &str| index.vault("kv").unwrap().search(&Query::parse(q));
+        let mut hits = search(&index, "db");
+        hits.sort();
+        assert_eq!(hits, ["db-password", "db-user"]);
+        assert_eq!(search(&index, "tag:env=dev"), ["api-key", "db-user"]);
+        assert_eq!(search(&index, "tag:ENV=Dev db"), ["db-user"]);
+        assert_eq!(search(&index, "tag:owner"), ["db-password"]);
+        assert!(search(&index, "tag:missing").is_empty());
+        assert!(index.vault("other-vault").is_none());
+        assert_eq!(index.meta("kv", "db-user").unwrap().tags[0].1, "dev");
+        assert!(index.meta("kv", "db").is_none());
+
+        // Re-indexing a vault replaces its entries
+        index.update_vault("kv", vec![meta("api-key", &[])]);
+        assert!(search(&index, "tag:env").is_empty());
+    }
+}
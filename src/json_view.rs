@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+/// Drill-down tree state for a secret whose value parses as JSON.
+/// Nodes are addressed by JSON pointer (RFC 6901), the root being "".
+#[derive(Debug, Clone)]
+pub struct JsonViewer {
+    pub secret: String,
+    pub root: Value,
+    pub expanded: HashSet<String>,
+    pub selected: usize,
+}
+
+/// One visible line of the flattened tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonRow {
+    pub pointer: String,
+    pub depth: usize,
+    pub key: String,
+    pub summary: String,
+    pub is_container: bool,
+    pub expanded: bool,
+}
+
+impl JsonViewer {
+    /// Returns None when the value isn't a JSON object or array (scalars have nothing to drill into).
+    pub fn parse(secret: &str, value: &str) -> Option<Self> {
+        let root: Value = serde_json::from_str(value.trim()).ok()?;
+        if !(root.is_object() || root.is_array()) {
+            return None;
+        }
+        Some(Self {
+            secret: secret.to_string(),
+            root,
+            expanded: HashSet::new(),
+            selected: 0,
+        })
+    }
+
+    /// Flatten the expanded part of the tree into display rows (top-level children first).
+    pub fn rows(&self) -> Vec<JsonRow> {
+        let mut out = Vec::new();
+        self.push_children(&self.root, "", 0, &mut out);
+        out
+    }
+
+    fn push_children(&self, value: &Value, pointer: &str, depth: usize, out: &mut Vec<JsonRow>) {
+        let children: Vec<(String, &Value)> = match value {
+            Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+            Value::Array(arr) => arr
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (i.to_string(), v))
+                .collect(),
+            _ => return,
+        };
+        for (key, child) in children {
+            let child_pointer = format!("{}/{}", pointer, escape_pointer(&key));
+            let is_container = child.is_object() || child.is_array();
+            let expanded = is_container && self.expanded.contains(&child_pointer);
+            out.push(JsonRow {
+                pointer: child_pointer.clone(),
+                depth,
+                key,
+                summary: summarize(child),
+                is_container,
+                expanded,
+            });
+            if expanded {
+                self.push_children(child, &child_pointer, depth + 1, out);
+            }
+        }
+    }
+
+    pub fn selected_row(&self) -> Option<JsonRow> {
+        self.rows().into_iter().nth(self.selected)
+    }
+
+    pub fn move_down(&mut self) {
+        let len = self.rows().len();
+        if len > 0 {
+            self.selected = (self.selected + 1).min(len - 1);
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Expand the selected container.
+    pub fn expand(&mut self) {
+        if let Some(row) = self.selected_row()
+            && row.is_container
+        {
+            self.expanded.insert(row.pointer);
+        }
+    }
+
+    /// Collapse the selected container, or jump to the parent when on a leaf/collapsed node.
+    pub fn collapse(&mut self) {
+        let Some(row) = self.selected_row() else {
+            return;
+        };
+        if row.expanded {
+            self.expanded.remove(&row.pointer);
+            return;
+        }
+        if let Some((parent, _)) = row.pointer.rsplit_once('/')
+            && !parent.is_empty()
+        {
+            self.expanded.remove(parent);
+            if let Some(idx) = self.rows().iter().position(|r| r.pointer == parent) {
+                self.selected = idx;
+            }
+        }
+    }
+
+    /// Text to copy for the selected node: strings unquoted, everything else as JSON.
+    pub fn selected_copy_text(&self) -> Option<String> {
+        let row = self.selected_row()?;
+        let value = self.root.pointer(&row.pointer)?;
+        Some(match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+    }
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn summarize(value: &Value) -> String {
+    match value {
+        Value::Object(map) => format!("{{…}} ({} keys)", map.len()),
+        Value::Array(arr) => format!("[…] ({} items)", arr.len()),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SA: &str = r#"{"type":"service_account","project_id":"demo","creds":{"client_email":"a@b.c","keys/ids":[1,2]}}"#;
+
+    #[test]
+    fn test_parse_rejects_scalars_and_invalid() {
+        assert!(JsonViewer::parse("s", "not json").is_none());
+        assert!(JsonViewer::parse("s", "\"just a string\"").is_none());
+        assert!(JsonViewer::parse("s", SA).is_some());
+    }
+
+    #[test]
+    fn test_drill_down_and_copy_leaf() {
+        let mut v = JsonViewer::parse("sa", SA).unwrap();
+        let keys: Vec<String> = v.rows().into_iter().map(|r| r.key).collect();
+        assert_eq!(keys, vec!["creds", "project_id", "type"]);
+
+        // Expand "creds" and select its email
+        v.expand();
+        assert_eq!(v.rows().len(), 5);
+        v.move_down();
+        assert_eq!(v.selected_row().unwrap().pointer, "/creds/client_email");
+        assert_eq!(v.selected_copy_text().unwrap(), "a@b.c");
+
+        // Keys containing '/' are pointer-escaped
+        v.move_down();
+        assert_eq!(v.selected_row().unwrap().pointer, "/creds/keys~1ids");
+        assert_eq!(v.selected_copy_text().unwrap(), "[1,2]");
+
+        // Collapse from a child jumps to and folds the parent
+        v.collapse();
+        assert_eq!(v.selected, 0);
+        assert_eq!(v.rows().len(), 3);
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+
+use crate::model::AppScreen;
+
+/// Where the bindings of a help section apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Everywhere,
+    Screen(AppScreen),
+    Dialog, // handled before rebinding, so always the default keys
+}
+
+/// A group of key bindings in the help overlay (`?`): keys as typed (alternatives separated
+/// by " / ") and what they do.
+pub struct Section {
+    pub title: &'static str,
+    pub scope: Scope,
+    pub bindings: &'static [(&'static str, &'static str)],
+}
+
+/// Every key binding of the app, the single source of the help overlay. A test checks it
+/// against the keys the screens handle in main.rs, so a new binding can't be left out.
+pub const SECTIONS: &[Section] = &[
+    Section {
+        title: "Everywhere",
+        scope: Scope::Everywhere,
+        bindings: &[
+            ("?", "This help"),
+            (
+                "F12",
+                "Recent Azure calls with their time, status and retries",
+            ),
+            ("q / Ctrl+C", "Quit"),
+        ],
+    },
+    Section {
+        title: "Vault list",
+        scope: Scope::Screen(AppScreen::VaultSelection),
+        bindings: &[
+            ("j / ↓", "Move down"),
+            ("k / ↑", "Move up"),
+            ("Ctrl+D / Ctrl+U", "Half a page down / up"),
+            ("PageDown / PageUp", "A page down / up"),
+            ("gg / G", "Jump to the top / bottom"),
+            ("Enter / double click", "Open the selected vault"),
+            ("/", "Filter by name, subscription or resource group"),
+            ("Esc", "Clear the filter"),
+            ("v", "Discover the vaults again"),
+            ("Ctrl+R", "Drop the cached secrets of all vaults"),
+            ("i", "Vault settings (SKU, soft delete, network rules)"),
+            ("f", "Star or unstar as a favorite"),
+            ("h", "Hide or unhide the vault"),
+            ("H", "Show hidden vaults"),
+            ("o", "Go offline or back online"),
+            ("t", "Switch tenant"),
+            ("E", "Secrets expiring soon in all cached vaults"),
+            ("A", "Audit all cached vaults"),
+            ("R", "Export a metadata report of all cached vaults"),
+            ("T", "Background tasks"),
+            ("!", "Details of the last failed operation"),
+            ("m", "Message history"),
+        ],
+    },
+    Section {
+        title: "Secrets",
+        scope: Scope::Screen(AppScreen::Secrets),
+        bindings: &[
+            ("j / ↓", "Move down"),
+            ("k / ↑", "Move up"),
+            ("Ctrl+D / Ctrl+U", "Half a page down / up"),
+            ("PageDown / PageUp", "A page down / up"),
+            ("gg / G", "Jump to the top / bottom"),
+            ("Enter / double click", "Copy the value"),
+            ("y", "Copy the name (or the marked names)"),
+            ("x", "Copy as an export statement"),
+            ("p", "Preview the value"),
+            ("b", "Base64-decode and copy the value"),
+            ("B", "Base64-decode and preview the value"),
+            ("c", "Copy a component of a connection string"),
+            ("J", "Browse a JSON value"),
+            ("/", "Search"),
+            ("V", "Search inside the values (fetches them), Esc clears"),
+            ("o", "Sort by name, last update or expiry"),
+            ("O", "Reverse the sort order"),
+            ("z", "Group by name prefix, or list flat again"),
+            ("Enter", "Open or close the selected group"),
+            ("l / →", "Open the group, or go into it"),
+            ("h / ←", "Close the group, or go to the enclosing one"),
+            ("+ / -", "Open / close all groups"),
+            ("Space", "Mark or unmark"),
+            ("*", "Mark or unmark all filtered secrets"),
+            ("Esc", "Clear the marks"),
+            ("a", "Add a secret"),
+            ("e", "Edit the value"),
+            ("d", "Delete (or delete the marked secrets)"),
+            (
+                "Y",
+                "Copy the secret (or the marked secrets) to another vault",
+            ),
+            ("r", "Refresh"),
+            ("Ctrl+R", "Drop the cached listing and list again"),
+            ("w", "Watch for changes"),
+            ("C", "Changes found since the cached listings"),
+            ("L", "Recent operations on the secret (audit logs)"),
+            ("K", "Keys of the vault"),
+            ("A", "Audit the vault"),
+            (
+                "R",
+                "Export a metadata report (of the marked secrets, if any)",
+            ),
+            ("Ctrl+P", "Quick-switch to another vault"),
+            ("T", "Background tasks"),
+            ("!", "Details of the last failed operation"),
+            ("m", "Message history"),
+            ("v", "Back to the vault list"),
+        ],
+    },
+    Section {
+        title: "Keys",
+        scope: Scope::Screen(AppScreen::Keys),
+        bindings: &[
+            ("j / ↓", "Move down"),
+            ("k / ↑", "Move up"),
+            ("Enter", "Crypto operations with the key"),
+            ("P", "Rotation policy"),
+            ("r", "Refresh"),
+            ("!", "Details of the last failed operation"),
+            ("m", "Message history"),
+            ("Esc / s", "Back to the secrets"),
+            ("v", "Back to the vault list"),
+        ],
+    },
+    Section {
+        title: "Expiring secrets",
+        scope: Scope::Screen(AppScreen::Expiring),
+        bindings: &[
+            ("j / ↓", "Move down"),
+            ("k / ↑", "Move up"),
+            ("Enter", "Open the secret in its vault"),
+            ("E", "Check the cached vaults again"),
+            ("Esc / v", "Back to the vault list"),
+        ],
+    },
+    Section {
+        title: "Audit",
+        scope: Scope::Screen(AppScreen::Audit),
+        bindings: &[
+            ("j / ↓", "Move down"),
+            ("k / ↑", "Move up"),
+            ("Enter", "Open the secret in its vault"),
+            ("Esc", "Back"),
+            ("v", "Back to the vault list"),
+        ],
+    },
+    Section {
+        title: "Message history",
+        scope: Scope::Screen(AppScreen::History),
+        bindings: &[
+            ("j / ↓", "Scroll down"),
+            ("k / ↑", "Scroll up"),
+            ("g / G", "Jump to the newest / oldest message"),
+            ("Esc / m", "Back"),
+        ],
+    },
+    Section {
+        title: "Add and Edit dialogs",
+        scope: Scope::Dialog,
+        bindings: &[
+            ("Tab", "Next field (Add)"),
+            ("Ctrl+S", "Save"),
+            ("Ctrl+G", "Generate a random value (Add)"),
+            ("Ctrl+O", "Read the value from a file (Add)"),
+            ("Ctrl+V", "Paste the clipboard"),
+            ("Ctrl+R", "Hide or reveal the value"),
+            ("Ctrl+B", "Base64-encode the value on save"),
+            ("Esc", "Cancel"),
+        ],
+    },
+    Section {
+        title: "Text fields",
+        scope: Scope::Dialog,
+        bindings: &[
+            ("Home / Ctrl+A", "Start of the line"),
+            ("End / Ctrl+E", "End of the line"),
+            ("Ctrl+← / Alt+B", "Previous word"),
+            ("Ctrl+→ / Alt+F", "Next word"),
+            ("Ctrl+W", "Delete the previous word"),
+        ],
+    },
+    Section {
+        title: "Previews, changes, activity and this help",
+        scope: Scope::Dialog,
+        bindings: &[
+            ("j / k", "Scroll"),
+            ("PageDown / PageUp", "Scroll by a page"),
+            ("g / G", "Jump to the top / bottom"),
+            ("Esc / q", "Close"),
+        ],
+    },
+    Section {
+        title: "JSON viewer and connection strings",
+        scope: Scope::Dialog,
+        bindings: &[
+            ("j / k", "Move"),
+            ("l / h", "Expand / collapse (JSON)"),
+            ("Enter / y", "Copy the field"),
+            ("Esc / q", "Close"),
+        ],
+    },
+    Section {
+        title: "Other dialogs",
+        scope: Scope::Dialog,
+        bindings: &[
+            ("y / n", "Confirm or cancel a delete"),
+            ("c / Delete", "Cancel the selected background task"),
+            ("c", "Copy the error details, or the sign-in code"),
+            ("r", "Retry the failed operation"),
+            ("Ctrl+Y", "Copy the crypto result"),
+            ("Ctrl+S", "Save the rotation policy"),
+        ],
+    },
+];
+
+/// The sections shown on `screen`: its own first, then the global and dialog ones.
+pub fn help_sections(screen: AppScreen) -> Vec<&'static Section> {
+    let own = SECTIONS.iter().filter(|s| s.scope == Scope::Screen(screen));
+    let rest = SECTIONS
+        .iter()
+        .filter(|s| matches!(s.scope, Scope::Everywhere | Scope::Dialog));
+    own.chain(rest).collect()
+}
+
+/// `keys` as currently bound: keys of actions rebound in `[keys]` (pressed -> default) are
+/// replaced by the configured ones.
+pub fn display_keys(keys: &str, keymap: &HashMap<char, char>) -> String {
+    keys.split(" / ")
+        .map(|key| {
+            let default = match key {
+                "Space" => ' ',
+                _ if key.chars().count() == 1 => key.chars().next().unwrap_or_default(),
+                _ => return key.to_string(),
+            };
+            let mut rebound: Vec<char> = keymap
+                .iter()
+                .filter(|&(pressed, d)| *d == default && *pressed != default)
+                .map(|(pressed, _)| *pressed)
+                .collect();
+            if rebound.is_empty() {
+                return key.to_string();
+            }
+            rebound.sort();
+            rebound
+                .iter()
+                .map(|c| match c {
+                    ' ' => "Space".to_string(),
+                    c => c.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" / ")
+        })
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ACTIONS;
+
+    /// Keys the screen arms of the event loop in main.rs match on, as the help writes them.
+    fn dispatched_keys() -> Vec<(String, Vec<String>)> {
+        let main = include_str!("main.rs");
+        let start = main
+            .find("match app.screen {")
+            .expect("no screen dispatch in main.rs");
+        // Split at `AppScreen::<name> =>`; other mentions of a screen are part of an arm's code
+        let mut arms: Vec<(String, String)> = Vec::new();
+        for part in main[start..].split("AppScreen::").skip(1) {
+            let name: String = part.chars().take_while(|c| c.is_alphanumeric()).collect();
+            match arms.last_mut() {
+                Some((_, code)) if !part[name.len()..].trim_start().starts_with("=>") => {
+                    code.push_str(part)
+                }
+                _ => arms.push((name, part.to_string())),
+            }
+        }
+        let mut screens: Vec<(String, Vec<String>)> = Vec::new();
+        for (name, arm) in arms {
+            let mut keys = Vec::new();
+            for pattern in arm.split("KeyCode::").skip(1) {
+                // Up to the arm's `=>`, for a Ctrl guard
+                let head = &pattern[..pattern.find("=>").unwrap_or(pattern.len())];
+                let ctrl = head.contains("CONTROL");
+                let key: String = pattern
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric())
+                    .collect();
+                let named = match key.as_str() {
+                    "Char" => {
+                        let chars = &pattern[..pattern.find(')').unwrap_or(0)];
+                        for c in chars.split('\'').skip(1).step_by(2) {
+                            keys.push(match (ctrl, c) {
+                                (true, c) => format!("Ctrl+{}", c.to_uppercase()),
+                                (false, " ") => "Space".to_string(),
+                                (false, c) => c.to_string(),
+                            });
+                        }
+                        continue;
+                    }
+                    "Down" => "↓",
+                    "Up" => "↑",
+                    "Left" => "←",
+                    "Right" => "→",
+                    other => other,
+                };
+                keys.push(named.to_string());
+            }
+            screens.push((name, keys));
+        }
+        screens
+    }
+
+    #[test]
+    fn test_help_covers_dispatch() {
+        let screens = dispatched_keys();
+        assert!(screens.len() >= 6, "screen arms not found: {:?}", screens);
+        let mut missing = Vec::new();
+        for (screen, keys) in screens {
+            let listed: Vec<&str> = SECTIONS
+                .iter()
+                .filter(|s| match s.scope {
+                    Scope::Screen(on) => format!("{:?}", on) == screen,
+                    Scope::Everywhere => true,
+                    Scope::Dialog => false,
+                })
+                .flat_map(|s| s.bindings.iter())
+                .flat_map(|(keys, _)| keys.split(" / "))
+                .collect();
+            // Backspace only edits a filter
+            for key in keys.iter().filter(|k| *k != "Backspace") {
+                if !listed.contains(&key.as_str()) {
+                    missing.push(format!("{} on {}", key, screen));
+                }
+            }
+        }
+        assert!(missing.is_empty(), "Not in the help: {:?}", missing);
+    }
+
+    #[test]
+    fn test_help_covers_actions() {
+        // Every rebindable action is listed, so the help shows its configured key
+        let listed: Vec<&str> = SECTIONS
+            .iter()
+            .filter(|s| s.scope != Scope::Dialog)
+            .flat_map(|s| s.bindings.iter())
+            .flat_map(|(keys, _)| keys.split(" / "))
+            .collect();
+        for (action, key) in ACTIONS {
+            let key = if *key == ' ' {
+                "Space".to_string()
+            } else {
+                key.to_string()
+            };
+            assert!(
+                listed.contains(&key.as_str()),
+                "'{}' isn't in the help",
+                action
+            );
+        }
+
+        let sections = help_sections(AppScreen::Keys);
+        assert_eq!(sections[0].title, "Keys");
+        assert!(
+            sections
+                .iter()
+                .all(|s| s.scope != Scope::Screen(AppScreen::Secrets))
+        );
+
+        let keymap = HashMap::from([('n', 'a'), ('J', 'j')]);
+        assert_eq!(display_keys("a", &keymap), "n");
+        assert_eq!(display_keys("j / ↓", &keymap), "J / ↓");
+        assert_eq!(display_keys("Ctrl+A", &keymap), "Ctrl+A");
+    }
+}
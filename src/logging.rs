@@ -0,0 +1,248 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use azure_core::time::to_rfc3339;
+use serde_json::{Map, Value};
+use time::OffsetDateTime;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{self, FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+/// How `--debug` writes its log lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json, // one object per line, for log shippers and `jq`
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "Unknown log format '{}' (expected text or json)",
+                other
+            )),
+        }
+    }
+}
+
+/// The debug log layer: `format` lines filtered by `filter`, appended to `path` and rotated
+/// after `max_size` bytes (0 = never), keeping `max_files` older logs.
+pub fn layer(
+    path: &Path,
+    format: LogFormat,
+    filter: EnvFilter,
+    max_size: u64,
+    max_files: usize,
+) -> Result<Box<dyn Layer<Registry> + Send + Sync>, String> {
+    let file = RotatingFile::open(path, max_size, max_files)
+        .map_err(|e| format!("Cannot open log {}: {}", path.display(), e))?;
+    let writer = LogWriter(Arc::new(Mutex::new(file)));
+    let layer = fmt::layer()
+        .with_writer(move || writer.clone())
+        .with_ansi(false)
+        .with_target(false);
+    Ok(match format {
+        LogFormat::Text => layer.with_filter(filter).boxed(),
+        LogFormat::Json => layer.event_format(JsonFormat).with_filter(filter).boxed(),
+    })
+}
+
+/// The log file, renamed to `<name>.1` (older ones moving up to `<name>.<max_files>`) before
+/// a line would take it past `max_size`.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    fn open(path: &Path, max_size: u64, max_files: usize) -> io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = Self::append(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            size: file.metadata()?.len(),
+            file,
+            max_size,
+            max_files,
+        })
+    }
+
+    /// Secret names end up in the log, so only the owner may read it.
+    fn append(path: &Path) -> io::Result<File> {
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(path)
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let _ = std::fs::remove_file(self.rotated(self.max_files.max(1)));
+        for n in (1..self.max_files).rev() {
+            let _ = std::fs::rename(self.rotated(n), self.rotated(n + 1));
+        }
+        if self.max_files > 0 {
+            std::fs::rename(&self.path, self.rotated(1))?;
+        } else {
+            std::fs::remove_file(&self.path)?;
+        }
+        self.file = Self::append(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        let full = self.size + line.len() as u64 > self.max_size;
+        if self.max_size > 0 && self.size > 0 && full {
+            self.rotate()?;
+        }
+        self.file.write_all(line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+/// Shared by the layer's writers; every log line arrives in one `write`.
+#[derive(Clone)]
+struct LogWriter(Arc<Mutex<RotatingFile>>);
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut file = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        file.write_line(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .file
+            .flush()
+    }
+}
+
+/// `{"timestamp", "level", "target", "spans", "fields": {"message", ...}}` per event.
+struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+        let spans: Vec<Value> = ctx
+            .event_scope()
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+            .map(|span| Value::String(span.name().to_string()))
+            .collect();
+        let mut line = Map::new();
+        line.insert(
+            "timestamp".into(),
+            to_rfc3339(&OffsetDateTime::now_utc()).into(),
+        );
+        line.insert("level".into(), event.metadata().level().as_str().into());
+        line.insert("target".into(), event.metadata().target().into());
+        if !spans.is_empty() {
+            line.insert("spans".into(), Value::Array(spans));
+        }
+        line.insert("fields".into(), Value::Object(fields.0));
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+#[derive(Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().into(), format!("{:?}", value).into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn test_json_log_rotation() {
+        let dir = std::env::temp_dir().join(format!("akv-log-test-{}", std::process::id()));
+        let path = dir.join("akv.log");
+        let layer = layer(&path, LogFormat::Json, EnvFilter::new("debug"), 300, 2).unwrap();
+        let subscriber = Registry::default().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            for n in 0..10 {
+                tracing::info!(vault = "kv-a", n, "Listed {} secrets", n * 10);
+            }
+            tracing::trace!("filtered out");
+        });
+
+        let current = std::fs::read_to_string(&path).unwrap();
+        let last: Value = serde_json::from_str(current.lines().last().unwrap()).unwrap();
+        assert_eq!(last["level"], "INFO");
+        assert_eq!(last["fields"]["message"], "Listed 90 secrets");
+        assert_eq!(last["fields"]["vault"], "kv-a");
+        assert_eq!(last["fields"]["n"], 9);
+        assert!(current.len() <= 300);
+        // Rotated twice or more, the oldest lines dropped
+        assert!(dir.join("akv.log.1").exists() && dir.join("akv.log.2").exists());
+        assert!(!dir.join("akv.log.3").exists());
+        assert!(!current.contains("filtered out"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!("JSON".parse(), Ok(LogFormat::Json));
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+}
@@ -1,157 +1,564 @@
 // src/main.rs
 use std::env;
 use std::error::Error;
-use std::fs::OpenOptions;
+use std::io::{BufWriter, IsTerminal, Write};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-
-use azure_identity::DeveloperToolsCredential;
-use azure_security_keyvault_secrets::{SecretClient, models::Secret};
-use clipboard::{ClipboardContext, ClipboardProvider};
-use crossterm::event::{self, Event as CEvent, KeyCode, KeyEvent};
-use crossterm::{
-    execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+use azure_core::credentials::TokenCredential;
+use crossterm::event::{
+    self, EnableBracketedPaste, EnableFocusChange, EnableMouseCapture, Event as CEvent, KeyCode,
+    KeyEvent,
 };
-use ratatui::{Terminal, backend::CrosstermBackend};
+use crossterm::{execute, terminal::EnterAlternateScreen};
+use ratatui::layout::Rect;
+use ratatui::{Terminal, TerminalOptions, Viewport, backend::CrosstermBackend};
 use tokio::sync::Semaphore;
-use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
-use tracing_subscriber::{EnvFilter, Registry, fmt, prelude::*};
+use tracing_subscriber::{Registry, prelude::*};
 
+mod api_log;
 mod app;
+mod audit;
 mod azure;
+mod cache;
+mod cli;
+mod clipboard;
+mod cloud;
+mod columns;
+mod config;
+mod connstr;
+mod credential;
+mod crypto;
+mod demo;
+mod device_code;
+mod encoding;
+mod error;
+mod events;
+mod forbidden;
+mod generator;
+mod highlight;
+mod hooks;
+mod index;
+mod json_view;
+mod keymap;
+mod logging;
+#[cfg(test)]
+mod mock_vault;
 mod model;
+mod nav;
+mod notify;
+mod ratelimit;
+mod report;
+mod rotation;
+mod script;
+mod state;
+mod store;
+mod tasks;
+mod term;
+mod textarea;
+mod theme;
+mod toast;
+mod tree;
 mod ui;
 
-use app::{App, apply_search, apply_vault_search, handle_modal_key};
-use azure::{
-    get_token_then_discover, list_secrets_and_cache, list_secrets_incremental, preload_all_vaults,
-    refresh_token,
+use app::{
+    App, DEMO_HINT, OFFLINE_HINT, apply_search, apply_value_action, apply_vault_search,
+    clear_value_search, failed, handle_modal_key, handle_mouse, handle_paste, merge_listed,
+    open_activity, open_audit, open_audit_selected, open_changes, open_copy_to_vault, open_crypto,
+    open_error_details, open_expiring, open_expiring_selected, open_export, open_help,
+    open_history, open_keys, open_rotation, open_value_search, open_vault, open_vault_info,
+    open_vault_switcher, refresh_vault, request_secret_value, retry, search_input, search_results,
+    show_activity, show_value_matches, start_discovery, start_search, toggle_favorite_selected,
+    toggle_hidden_selected, toggle_watch, watch_refresh,
+};
+use azure::{list_tenants, refresh_token, token_identity, vault_name, vault_uri};
+use cache::DiskCache;
+use config::Config;
+use encoding::read_stdin_value;
+use forbidden::Need;
+use model::{
+    Access, AddInputMode, AppEvent, AppScreen, BulkItemStatus, Failure, Modal, SignInState,
+    TokenCache, ValueAction, VaultCacheEntry,
 };
-use model::{AddInputMode, AppEvent, AppScreen, Modal, TokenCache, VaultCacheEntry};
+use nav::Motion;
+use rotation::RotationForm;
+use script::Script;
+use state::State;
+use store::preload_vault;
+use textarea::TextArea;
 use ui::draw_ui;
 
+/// Longest time an idle screen goes without a redraw, so ages and running times stay current.
+const IDLE_REDRAW: Duration = Duration::from_secs(1);
+
+/// How long quitting waits for writes in flight (e.g. a `set_secret`) before abandoning them.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
 #[tokio::main]
 #[allow(clippy::collapsible_if)]
 async fn main() -> Result<(), Box<dyn Error>> {
     // parse flags
-    let args: Vec<String> = env::args().collect();
+    let all_args: Vec<String> = env::args().collect();
+    // Everything after `--` belongs to the child process of `akv run`
+    let args = &all_args[..all_args
+        .iter()
+        .position(|a| a == "--")
+        .unwrap_or(all_args.len())];
     let debug_mode = args.iter().any(|s| s == "--debug");
 
-    // initialize tracing to file only when --debug is passed
-    if debug_mode {
-        // open log file in append mode
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("azure_tui.log")?;
-        // default filter: debug (for verbose investigation)
-        let filter = EnvFilter::new("debug");
-        // build two layers if desired; here we only write to file
-        let fmt_layer = fmt::layer()
-            .with_writer(move || file.try_clone().expect("log file clone"))
-            .with_target(false);
-        Registry::default().with(filter).with(fmt_layer).init();
-        info!("Tracing initialized to azure_tui.log (debug)");
+    if args.iter().any(|s| s == "--help" || s == "-h") {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    let command = match cli::parse(&all_args[1..]) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    // Config file, profile and AKV_TUI_* variables first; command-line flags override them
+    let config_path = args
+        .iter()
+        .position(|s| s == "--config")
+        .and_then(|i| args.get(i + 1))
+        .map(std::path::PathBuf::from);
+    let profile = args
+        .iter()
+        .position(|s| s == "--profile")
+        .and_then(|i| args.get(i + 1));
+    let mut config = match Config::load(config_path.as_deref(), profile.map(|p| p.as_str())) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Invalid config: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    // Log to a file only when --debug is passed, as set up under [log]. API calls are always
+    // collected, for the overlay (F12)
+    let log_path = debug_mode.then(|| config.log_path()).map(|path| {
+        path.unwrap_or_else(|| {
+            eprintln!("No log file location; set path under [log] or AKV_TUI_LOG_FILE");
+            std::process::exit(2);
+        })
+    });
+    let log_layer = match &log_path {
+        Some(path) => Some(
+            logging::layer(
+                path,
+                config.log_format()?,
+                config.log_filter()?,
+                config.log.max_size_mb * 1024 * 1024,
+                config.log.max_files,
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }),
+        ),
+        None => None,
+    };
+    Registry::default()
+        .with(log_layer)
+        .with(api_log::layer())
+        .init();
+    if let Some(path) = &log_path {
+        info!("Logging to {}", path.display());
     }
 
     info!("Starting Azure Key Vault TUI");
+    if let Some(profile) = &config.profile {
+        info!(
+            "Using profile '{}' (cache dir {:?})",
+            profile,
+            config.cache_dir()
+        );
+    }
+
+    // --cloud <public|usgov|china> selects ARM and vault endpoints for every request
+    // (custom endpoints from the config still apply on top)
+    if let Some(name) = args
+        .iter()
+        .position(|s| s == "--cloud")
+        .and_then(|i| args.get(i + 1))
+    {
+        config.cloud = name.clone();
+    }
+    cloud::set_cloud(config.cloud()?);
+    azure::set_http_client(
+        azure::build_http_client(&config.http, &config.tls)?,
+        config.http.retries,
+    );
+    ratelimit::set_rate_limits(&config.rate_limit);
+
+    // Create credential & app. --credential <kind> overrides the configured sign-in method
+    if let Some(kind) = args
+        .iter()
+        .position(|s| s == "--credential")
+        .and_then(|i| args.get(i + 1))
+    {
+        config.credential = kind.clone();
+    }
+    // --demo: generated vaults and secrets in memory, no sign-in and no Azure calls
+    let demo = args.iter().any(|s| s == "--demo");
+    // --script <file>: keys from a file instead of the terminal, for smoke tests in CI
+    let mut script = match args
+        .iter()
+        .position(|s| s == "--script")
+        .and_then(|i| args.get(i + 1))
+    {
+        Some(path) => match Script::load(std::path::Path::new(path)) {
+            Ok(script) => Some(script),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+    let credential: Arc<dyn TokenCredential> = if demo {
+        azure_identity::DeveloperToolsCredential::new(None)?
+    } else {
+        credential::create(config.credential_kind()?, config.tenant.as_deref())?
+    };
+
+    // Headless subcommands print to stdout and exit without starting the TUI
+    let mut pick = None;
+    match command {
+        Some(cli::Invocation {
+            command: cli::Command::Pick { vault, name_only },
+            ..
+        }) => pick = Some((vault, name_only)),
+        Some(_) if demo => {
+            eprintln!("--demo only applies to the interactive TUI");
+            std::process::exit(2);
+        }
+        Some(invocation) => {
+            if let Err(e) = cli::run(invocation, credential, &config).await {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        None => {}
+    }
+    let flag_vault = args
+        .iter()
+        .position(|s| s == "--vault")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+    highlight::set_syntax_theme(&config.syntax_theme()?)?;
+    let mut ui_theme = config.ui_theme()?;
+    // https://no-color.org: any non-empty NO_COLOR disables colors
+    ui_theme.monochrome =
+        args.iter().any(|s| s == "--no-color") || env::var("NO_COLOR").is_ok_and(|v| !v.is_empty());
+    theme::set_theme(ui_theme);
 
-    // Create credential & app
-    let credential = DeveloperToolsCredential::new(None)?;
     let mut app = App::new(credential.clone());
+    if demo {
+        app.demo = true;
+        app.stores = demo::stores(demo::LATENCY);
+    }
+    // --offline: browse the disk cache without any network calls
+    app.offline = args.iter().any(|s| s == "--offline");
+    app.clipboard = config.clipboard_backend()?;
+    app.clipboard_clear_after = config.clipboard_clear_after();
+    app.cache_ttl = config.cache_ttl();
+    app.watch_interval = config.watch_interval();
+    app.expiry_window = config.expiry_window();
+    app.audit_referenced = config.audit_referenced.clone();
+    app.notifications = config.notifications;
+    // Hooks would report made-up secrets to real notifiers and audit logs
+    if !demo {
+        app.hooks = config.hooks()?;
+    }
+    app.keymap = config.keymap()?;
+    app.columns = config.columns()?;
+    app.group_delimiter = config.group_delimiter.clone();
+    app.subscriptions = config.subscriptions.clone();
+    app.profile = config.profile.clone();
+    app.aliases = config.aliases.clone();
+    // Favorites starred in the TUI (state file) win over the configured list. Demo mode
+    // neither reads nor records state, so demo vaults don't show up among the recent ones, and
+    // scripts start from the same screens on every run
+    app.state_path =
+        State::default_path(app.profile.as_deref()).filter(|_| !demo && script.is_none());
+    let state = app
+        .state_path
+        .as_deref()
+        .map(State::load)
+        .unwrap_or_default();
+    app.favorites = state.favorites.unwrap_or_else(|| config.favorites.clone());
+    app.tenant = config.tenant.clone();
+    app.credential_kind = config.credential_kind()?;
+    // --subscription <id|name> (repeatable) replaces the configured filter
+    let flag_subscriptions = cli::flag_values(args, "--subscription");
+    if !flag_subscriptions.is_empty() {
+        app.subscriptions = flag_subscriptions.into_iter().map(String::from).collect();
+    }
+    app.pick_name_only = pick.as_ref().map(|(_, name_only)| *name_only);
+    // `cat cert.pem | akv --stdin`: keep the piped value for the Add dialog (keys come from
+    // the tty). Only on request: an inherited pipe that stays open would block the start.
+    if args.iter().any(|s| s == "--stdin") {
+        if pick.is_some() || script.is_some() || std::io::stdin().is_terminal() {
+            eprintln!("--stdin needs a value piped into the interactive TUI");
+            std::process::exit(2);
+        }
+        match read_stdin_value(false) {
+            Ok(value) => app.stdin_value = Some(value),
+            Err(e) => {
+                eprintln!("--stdin: {}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+    // --clipboard <backend>: auto (default), native, command, wsl or osc52
+    if let Some(backend) = args
+        .iter()
+        .position(|s| s == "--clipboard")
+        .and_then(|i| args.get(i + 1))
+    {
+        app.clipboard = backend.parse()?;
+    }
+    // --clipboard-clear <secs>: wipe copied secrets after this long (0 = never)
+    if let Some(secs) = args
+        .iter()
+        .position(|s| s == "--clipboard-clear")
+        .and_then(|i| args.get(i + 1))
+    {
+        let secs: u64 = secs
+            .parse()
+            .map_err(|_| format!("Invalid --clipboard-clear value '{}'", secs))?;
+        app.clipboard_clear_after = (secs > 0).then(|| Duration::from_secs(secs));
+    }
 
-    // Terminal setup
-    let mut stdout = std::io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    crossterm::terminal::enable_raw_mode()?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    // Unlock the disk cache before the TUI starts: the keyring may block and the passphrase
+    // fallback prompts on the terminal (so scripts, which have none, go without)
+    let use_disk_cache = config.disk_cache && !demo && script.is_none();
+    let disk_cache = match config.cache_dir().filter(|_| use_disk_cache) {
+        Some(dir) => {
+            let store = config.cache_key_store()?;
+            let profile = config.profile.clone();
+            let opened = tokio::task::spawn_blocking(move || {
+                DiskCache::open(&dir, store, profile.as_deref())
+            })
+            .await?;
+            opened
+                .inspect_err(|e| {
+                    warn!("Disk cache disabled: {}", e);
+                    app.warn(format!("Disk cache disabled: {}", e));
+                })
+                .ok()
+        }
+        None => None,
+    };
 
-    // Channel for background tasks -> UI
-    let (tx, mut rx) = mpsc::unbounded_channel::<AppEvent>();
+    // Terminal setup. A script draws on a screen of its own, checked by its `expect` lines
+    let (mut terminal, restore) = if script.is_some() {
+        let out: Box<dyn Write> = Box::new(std::io::sink());
+        let area = Rect::new(0, 0, script::SCREEN_WIDTH, script::SCREEN_HEIGHT);
+        let terminal = Terminal::with_options(
+            CrosstermBackend::new(out),
+            TerminalOptions {
+                viewport: Viewport::Fixed(area),
+            },
+        )?;
+        (terminal, None)
+    } else {
+        // In pick mode stdout carries the result, so draw on stderr (still the terminal)
+        let mut out: Box<dyn Write> = if pick.is_some() {
+            Box::new(BufWriter::new(std::io::stderr()))
+        } else {
+            Box::new(std::io::stdout())
+        };
+        // Neither a panic, a signal nor an error returned from here on may leave the shell in
+        // raw mode on the alternate screen
+        let restore = term::RestoreOnDrop::new(pick.is_some());
+        term::install_panic_hook(pick.is_some());
+        // Focus reports tell whether finished background work needs a desktop notification
+        execute!(
+            out,
+            EnterAlternateScreen,
+            EnableBracketedPaste,
+            EnableFocusChange
+        )?;
+        if config.mouse {
+            execute!(out, EnableMouseCapture)?;
+        }
+        crossterm::terminal::enable_raw_mode()?;
+        (Terminal::new(CrosstermBackend::new(out))?, Some(restore))
+    };
+
+    // Channel for background tasks -> UI, bounded: progress coalesces while the UI falls behind
+    let (tx, mut rx) = events::channel(events::CAPACITY);
+    term::handle_signals(tx.clone(), pick.is_some());
+
+    // `--vault <vault>` (or `pick --vault`, or default_vault in the config) opens that vault
+    // directly; discovery is then skipped until the vault list is requested with 'v'
+    let start_vault = match &pick {
+        Some((vault, _)) => vault.clone(),
+        None => flag_vault.or(config.default_vault.clone()),
+    };
+
+    // Listings saved by the previous run show up right away; discovery replaces them
+    if let Some(cache) = &disk_cache {
+        match cache.load() {
+            Ok(Some(snapshot)) => app.restore_cache(snapshot),
+            Ok(None) => {}
+            Err(e) => warn!("Ignoring disk cache: {}", e),
+        }
+    }
 
     // Semaphore to bound concurrent preload tasks (avoid throttling)
-    let preload_concurrency = Arc::new(Semaphore::new(4)); // tune as needed
+    let preload_concurrency = Arc::new(Semaphore::new(config.preload_concurrency));
 
     // Kick off initial discovery (background). The welcome screen will show while this runs.
-    {
-        let tx2 = tx.clone();
-        let cred = credential.clone();
-        app.loading = true;
-        app.message = Some("Discovering vaults...".into());
-        tokio::spawn(async move {
-            debug!("Initial discover task started");
-            match get_token_then_discover(cred.clone()).await {
-                Ok((token_opt, vaults)) => {
-                    if let Some((token, fetched_at, ttl)) = token_opt {
-                        let _ = tx2.send(AppEvent::TokenCached(token, fetched_at, ttl));
-                    }
-                    let _ = tx2.send(AppEvent::VaultsLoaded(vaults));
-                }
-                Err(e) => {
-                    let _ = tx2.send(AppEvent::Message(format!("Vault discovery failed: {}", e)));
-                }
-            }
-        });
+    if start_vault.is_none() {
+        let message = if demo {
+            "Demo mode: discovering generated vaults..."
+        } else {
+            "Discovering vaults..."
+        };
+        start_discovery(&mut app, message, &tx);
+    }
+
+    // Aliases from the config work wherever a vault name does
+    if let Some(vault) = start_vault.map(|v| config.resolve_vault(&v)) {
+        open_vault(&mut app, vault_name(&vault), vault_uri(&vault), &tx)?;
     }
 
-    let tick_rate = Duration::from_millis(50);
+    let tick_rate = config.tick_rate();
+    let poll_interval = config.poll_interval();
     let mut last_tick = Instant::now();
+    // Redraw only after something changed (input, background events, expired toasts) or
+    // while the spinner turns; clocks such as the cache age are refreshed every IDLE_REDRAW.
+    let mut dirty = true;
+    let mut last_draw = Instant::now();
+    let mut script_failure: Option<String> = None;
 
     loop {
-        // Advance spinner + redraw periodically
+        // Advance spinner + redraw what changed
         if last_tick.elapsed() >= tick_rate {
+            let now = Instant::now();
             if app.loading {
                 app.throbber_state.calc_next();
+                dirty = true;
+            }
+            app.tasks.reap(now);
+            dirty |= app.toasts.expire(now);
+            if dirty || last_draw.elapsed() >= IDLE_REDRAW {
+                if let Ok(frame) = terminal.draw(|f| draw_ui(f, &mut app))
+                    && let Some(script) = &mut script
+                {
+                    script.show(frame.buffer);
+                }
+                dirty = false;
+                last_draw = now;
             }
-            terminal.draw(|f| draw_ui(f, &mut app)).ok();
-            last_tick = Instant::now();
+            last_tick = now;
         }
 
-        // Auto-dismiss welcome screen after 1.5s
+        if app.quit {
+            break;
+        }
+
+        // A throttled operation backed off long enough
+        if let Some(retry_op) = app.retry_due(Instant::now()) {
+            retry(&mut app, retry_op, &tx)?;
+            dirty = true;
+        }
+
+        // Watch mode: re-list the current vault every `watch_interval`
+        if app.watch_due(Instant::now()) {
+            watch_refresh(&mut app, &tx)?;
+            dirty = true;
+        }
+
+        // Search a large vault once typing pauses
+        if app.search_due(Instant::now()) {
+            start_search(&mut app, &tx);
+        }
+
+        // Wipe a copied secret from the clipboard once its timeout has passed
+        if app.clipboard_clear_due(Instant::now()) {
+            app.clear_clipboard();
+            dirty = true;
+        }
+
+        // Auto-dismiss welcome screen after 1.5s (unless it offers to resume the last vault)
         if app.screen == AppScreen::Welcome
+            && app.state.recent.is_empty()
             && app.welcome_shown_at.elapsed() >= Duration::from_millis(1500)
         {
             app.screen = AppScreen::VaultSelection;
+            dirty = true;
         }
 
         // Drain background events
-        while let Ok(ev) = rx.try_recv() {
+        while let Some(ev) = rx.try_recv() {
+            dirty = true;
             match ev {
                 AppEvent::VaultsLoaded(v) => {
                     debug!("VaultsLoaded: {} vaults", v.len());
-                    app.vaults = v;
-                    apply_vault_search(&mut app); // Update displayed_vaults
+                    app.offline = false;
+                    app.set_vaults(v); // Updates displayed_vaults
                     app.loading = false;
                     if app.displayed_vaults.is_empty() {
                         // If empty, message depends on if it's because of search or no vaults at all.
                         // But here we just loaded fresh, so search query should be empty effectively (or applied).
                         // If search query was active during load (unlikely logic path but possible), we respect it.
                         if app.vaults.is_empty() {
-                            app.message = Some("No vaults found (press 'v' to retry)".into());
+                            app.warn("No vaults found (press 'v' to retry)".into());
                         } else {
-                            app.message = Some("No vaults match search".into());
+                            app.info("No vaults match search".into());
                         }
                     } else {
-                        app.message = Some(format!(
+                        app.success(format!(
                             "Discovered {} vault(s). Use ↑/↓ and Enter to select.",
                             app.displayed_vaults.len()
                         ));
                         // Start silent preload in background (on ALL vaults, not just displayed)
-                        let vaults_to_preload = app.vaults.clone();
-                        let cred = app.credential.clone();
-                        let tx2 = tx.clone();
-                        let sem = preload_concurrency.clone();
-                        tokio::spawn(async move {
-                            info!(
-                                "Starting background preload for {} vaults",
-                                vaults_to_preload.len()
-                            );
-                            preload_all_vaults(cred, tx2, vaults_to_preload, sem).await;
-                            info!("Background preload finished");
-                        });
+                        if config.preload {
+                            let vaults_to_preload: Vec<_> = app
+                                .vaults
+                                .iter()
+                                .filter(|v| !app.is_hidden(&v.name))
+                                .map(|v| (v.name.clone(), v.uri.clone()))
+                                .collect();
+                            let count = vaults_to_preload.len();
+                            info!("Starting background preload for {} vaults", count);
+                            // One task per vault, so each can be followed and cancelled in the
+                            // task panel; the last one to finish reports the whole preload
+                            let remaining = Arc::new(AtomicUsize::new(count));
+                            for (name, uri) in vaults_to_preload {
+                                let store = app.stores.get(&uri, &app.credential);
+                                let tx2 = tx.clone();
+                                let sem = preload_concurrency.clone();
+                                let remaining = remaining.clone();
+                                let label = format!("Preload {}", name);
+                                app.tasks.spawn(label, None, async move {
+                                    match store {
+                                        Ok(store) => {
+                                            preload_vault(store, tx2.clone(), name, sem).await
+                                        }
+                                        Err(e) => {
+                                            debug!("Failed to create client for {}: {}", name, e)
+                                        }
+                                    }
+                                    if remaining.fetch_sub(1, Ordering::SeqCst) == 1 {
+                                        info!("Background preload finished");
+                                        let _ = tx2.send(AppEvent::Finished(format!(
+                                            "Preloaded the secrets of {} vault(s)",
+                                            count
+                                        )));
+                                    }
+                                });
+                            }
+                        }
                     }
                 }
                 AppEvent::SecretsUpdated(vault_name, secrets) => {
@@ -160,21 +567,20 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         vault_name,
                         secrets.len()
                     );
-                    let mut sorted = secrets.clone();
-                    sorted.sort();
                     app.vault_secret_cache.insert(
                         vault_name.clone(),
                         VaultCacheEntry {
-                            secrets: sorted.clone(),
-                            refreshed_at: Instant::now(),
+                            secrets: secrets.clone(),
+                            refreshed_at: SystemTime::now(),
                         },
                     );
                     if let Some((current_name, _)) = &app.current_vault {
                         if *current_name == vault_name {
-                            app.secrets = sorted.clone();
+                            app.secrets = secrets;
+                            app.marked.retain(|m| app.secrets.binary_search(m).is_ok());
                             apply_search(&mut app);
                             app.loading = false;
-                            app.message = Some(format!(
+                            app.info(format!(
                                 "Loaded {} secrets (from {})",
                                 app.secrets.len(),
                                 vault_name
@@ -182,33 +588,61 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         }
                     }
                 }
+                AppEvent::SecretsListed(vault_name, names) => {
+                    merge_listed(&mut app, &vault_name, names);
+                }
+                AppEvent::ListingComplete(vault_name) => {
+                    if app
+                        .current_vault
+                        .as_ref()
+                        .is_some_and(|(name, _)| *name == vault_name)
+                    {
+                        app.listing_partial = false;
+                    }
+                }
                 AppEvent::CacheVaultSecrets(vault_name, secrets) => {
                     debug!(
                         "CacheVaultSecrets (silent) for {} ({} items)",
                         vault_name,
                         secrets.len()
                     );
-                    let mut sorted = secrets.clone();
-                    sorted.sort();
-                    app.vault_secret_cache.insert(
-                        vault_name,
-                        VaultCacheEntry {
-                            secrets: sorted,
-                            refreshed_at: Instant::now(),
-                        },
+                    let previous = app.index.secrets(&vault_name);
+                    app.cache_listing(
+                        &vault_name,
+                        Arc::unwrap_or_clone(secrets),
+                        SystemTime::now(),
                     );
+                    if let Some(previous) = previous {
+                        app.note_changes(&vault_name, &previous);
+                    }
                 }
-                AppEvent::OpenEdit(name, value) => {
-                    app.modal = Some(Modal::Edit { name, value });
-                    app.loading = false;
+                AppEvent::SearchResults(generation, names) => {
+                    search_results(&mut app, generation, names);
+                }
+                AppEvent::Quit => {
+                    info!("Quitting on a signal");
+                    app.quit = true;
                 }
-                AppEvent::Message(msg) => {
-                    warn!("Background message: {}", msg);
+                AppEvent::ValueSearchDone(vault_name, matches, unreadable) => {
+                    show_value_matches(&mut app, vault_name, matches, unreadable);
+                }
+                AppEvent::Finished(msg) => {
+                    info!("Background operation finished: {}", msg);
+                    if app.notifications && !app.focused {
+                        notify::desktop(&msg);
+                    }
+                    app.success(msg);
+                }
+                AppEvent::OpenEdit(name, value) => {
+                    app.modal = Some(Modal::edit(name, &value));
                     app.loading = false;
-                    app.message = Some(msg);
                 }
-                AppEvent::TokenCached(_token, fetched_at, ttl) => {
+                AppEvent::Failed(failure) => failed(&mut app, failure, &tx)?,
+                AppEvent::TokenCached(token, fetched_at, ttl) => {
                     debug!("TokenCached (ttl={:?})", ttl);
+                    if let Some(identity) = token_identity(&token) {
+                        app.identity = Some(identity);
+                    }
                     // we store token string in cache with underscore-prefixed field
                     app.token_cache = Some(TokenCache {
                         _token: String::new(),
@@ -216,42 +650,181 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         ttl,
                     });
                 }
-                AppEvent::SecretValueLoaded(vault, name, value) => {
-                    app.secret_value_cache
-                        .insert((vault.clone(), name.clone()), value.clone());
+                AppEvent::DeviceCodeIssued(code) => {
+                    if let Some(Modal::SignIn(state)) = &mut app.modal {
+                        *state = SignInState::Waiting(code.clone());
+                        let tx2 = tx.clone();
+                        tokio::spawn(async move {
+                            let _ = tx2.send(match device_code::wait(code).await {
+                                Ok(cred) => AppEvent::SignedIn(Arc::new(cred)),
+                                Err(e) => AppEvent::SignInFailed(e),
+                            });
+                        });
+                    }
+                }
+                AppEvent::SignedIn(cred) => {
+                    info!("Signed in with device code");
+                    app.credential = cred.clone();
+                    app.stores.clear();
+                    app.device_login = Some(cred);
+                    app.token_cache = None;
+                    if matches!(app.modal, Some(Modal::SignIn(_))) {
+                        app.modal = None;
+                    }
+                    start_discovery(&mut app, "Signed in. Discovering vaults...", &tx);
+                }
+                AppEvent::SignInFailed(e) => match &mut app.modal {
+                    Some(Modal::SignIn(state)) => *state = SignInState::Failed(e),
+                    _ => app.error(e),
+                },
+                AppEvent::KeysLoaded(vault, keys) => {
+                    // Ignore a listing that arrives after switching vaults
+                    if app.current_vault.as_ref().map(|(name, _)| name) == Some(&vault) {
+                        app.loading = false;
+                        app.info(format!("{} key(s) in '{}'", keys.len(), vault));
+                        app.keys_state.select((!keys.is_empty()).then_some(0));
+                        app.keys = keys;
+                    }
+                }
+                AppEvent::ActivityLoaded(name, entries) => show_activity(&mut app, name, entries),
+                AppEvent::AccessProbed(vault_name, access) => {
+                    if app
+                        .current_vault
+                        .as_ref()
+                        .is_some_and(|(n, _)| *n == vault_name)
+                    {
+                        app.access = Some(access);
+                    }
+                }
+                AppEvent::RotationPolicyLoaded(key, policy) => {
                     app.loading = false;
-                    let ctx: Result<ClipboardContext, _> = ClipboardProvider::new();
-                    match ctx {
-                        Ok(mut ctx) => {
-                            if ctx.set_contents(value).is_ok() {
-                                app.message =
-                                    Some(format!("Secret '{}' copied to clipboard", name));
-                            } else {
-                                app.message = Some("Clipboard error".into());
-                            }
-                        }
-                        Err(e) => {
-                            app.message = Some(format!("Clipboard init error: {}", e));
+                    app.toasts.dismiss_info();
+                    app.modal = Some(Modal::Rotation(RotationForm::new(key, &policy)));
+                }
+                AppEvent::CryptoDone(result) => {
+                    if let Some(Modal::Crypto(form)) = &mut app.modal {
+                        form.pending = false;
+                        form.result = Some(result);
+                    }
+                }
+                AppEvent::TenantsLoaded(tenants) => {
+                    app.loading = false;
+                    if tenants.is_empty() {
+                        app.warn("No tenants found for this account".into());
+                    } else {
+                        let selected = tenants
+                            .iter()
+                            .position(|t| Some(&t.id) == app.tenant.as_ref())
+                            .unwrap_or(0);
+                        app.info(format!("{} tenant(s) available", tenants.len()));
+                        app.modal = Some(Modal::TenantPicker { tenants, selected });
+                    }
+                }
+                AppEvent::BulkDeleteItem(name, outcome) => {
+                    if let Some(Modal::BulkDelete {
+                        names, statuses, ..
+                    }) = &mut app.modal
+                    {
+                        if let Some(idx) = names.iter().position(|n| *n == name) {
+                            statuses[idx] = match outcome {
+                                Ok(()) => BulkItemStatus::Done,
+                                Err(e) => BulkItemStatus::Failed(e),
+                            };
                         }
                     }
                 }
+                AppEvent::SubmitSucceeded(msg) => {
+                    app.loading = false;
+                    app.success(msg);
+                    if app
+                        .modal
+                        .as_mut()
+                        .and_then(|m| m.submit_state_mut())
+                        .is_some_and(|st| st.pending)
+                    {
+                        app.modal = None;
+                    }
+                }
+                AppEvent::SubmitFailed(err) => {
+                    warn!("Submit failed: {}", err);
+                    app.loading = false;
+                    // Also shown inline unless the dialog was dismissed while the request was in flight
+                    if let Some(st) = app.modal.as_mut().and_then(|m| m.submit_state_mut())
+                        && st.pending
+                    {
+                        st.pending = false;
+                        st.error = Some(err.clone());
+                    }
+                    app.error(err);
+                }
+                AppEvent::SecretValueLoaded(vault, name, value, action) => {
+                    app.secret_value_cache
+                        .insert((vault.clone(), name.clone()), value.clone());
+                    app.loading = false;
+                    apply_value_action(&mut app, &name, value, action, false);
+                }
             }
         }
 
-        // Input handling
-        if event::poll(Duration::from_millis(20))? {
-            if let CEvent::Key(KeyEvent {
-                code, modifiers, ..
-            }) = event::read()?
-            {
-                // if user presses any key during welcome, skip it
+        // Input handling: from the terminal, or the next step of the script
+        let ev = match &mut script {
+            Some(script) if script.done() => break,
+            Some(script) => match script.next(Instant::now()) {
+                Ok(ev) => {
+                    if ev.is_none() {
+                        tokio::time::sleep(poll_interval).await;
+                    }
+                    ev
+                }
+                Err(e) => {
+                    script_failure = Some(e);
+                    break;
+                }
+            },
+            None if event::poll(poll_interval)? => Some(event::read()?),
+            None => None,
+        };
+        if let Some(ev) = ev {
+            dirty = true;
+            match &ev {
+                CEvent::Paste(text) => handle_paste(&mut app, text),
+                CEvent::FocusGained => app.focused = true,
+                CEvent::FocusLost => app.focused = false,
+                // Lay out for the new size right away instead of on the next tick
+                CEvent::Resize(..) => {
+                    terminal.draw(|f| draw_ui(f, &mut app)).ok();
+                }
+                _ => {}
+            }
+            // A double click acts like Enter on the selected row
+            let key = match ev {
+                CEvent::Key(KeyEvent {
+                    code, modifiers, ..
+                }) => Some((code, modifiers)),
+                CEvent::Mouse(mouse) => handle_mouse(&mut app, mouse, Instant::now())
+                    .map(|code| (code, event::KeyModifiers::NONE)),
+                _ => None,
+            };
+            if let Some((code, modifiers)) = key {
+                // if user presses any key during welcome, skip it (Enter resumes the last vault)
                 if app.screen == AppScreen::Welcome {
                     app.screen = AppScreen::VaultSelection;
+                    if code == KeyCode::Enter
+                        && let Some(last) = app.state.recent.first().cloned()
+                    {
+                        open_vault(&mut app, last.name, last.uri, &tx)?;
+                    }
+                    continue;
+                }
+
+                // The API overlay stays on over every screen and dialog until toggled off
+                if code == KeyCode::F(12) {
+                    app.api_overlay = !app.api_overlay;
                     continue;
                 }
 
                 // Modal handling prioritized
-                if handle_modal_key(&mut app, code, &tx).await? {
+                if handle_modal_key(&mut app, code, modifiers, &tx).await? {
                     continue;
                 }
 
@@ -265,20 +838,31 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         }
                         KeyCode::Enter => {
                             app.search_mode = false;
+                            if app.search_at.is_some() {
+                                start_search(&mut app, &tx);
+                            }
                         }
                         KeyCode::Backspace => {
                             app.search_query.pop();
-                            apply_search(&mut app);
+                            search_input(&mut app, Instant::now());
                         }
                         KeyCode::Char(c) => {
                             app.search_query.push(c);
-                            apply_search(&mut app);
+                            search_input(&mut app, Instant::now());
                         }
                         _ => {}
                     }
                     continue;
                 }
 
+                // Rebound keys from the config act like the default key they replace
+                let code = match code {
+                    KeyCode::Char(c) if !app.vault_search_mode => {
+                        KeyCode::Char(app.keymap.get(&c).copied().unwrap_or(c))
+                    }
+                    other => other,
+                };
+
                 // Global quit
                 if (modifiers == event::KeyModifiers::CONTROL && code == KeyCode::Char('c'))
                     || code == KeyCode::Char('q')
@@ -286,8 +870,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     break;
                 }
 
+                if code == KeyCode::Char('?') && !app.vault_search_mode {
+                    open_help(&mut app);
+                    continue;
+                }
+
                 // Token near-expiry refresh check
-                if app.token_should_refresh() {
+                if !app.offline && !app.demo && app.token_should_refresh() {
                     debug!("Token near expiry or missing -> refreshing in background");
                     let tx2 = tx.clone();
                     let cred = app.credential.clone();
@@ -297,15 +886,26 @@ async fn main() -> Result<(), Box<dyn Error>> {
                                 let _ = tx2.send(AppEvent::TokenCached(token, fetched_at, ttl));
                             }
                             Err(e) => {
-                                let _ = tx2.send(AppEvent::Message(format!(
-                                    "Failed to refresh token: {}",
-                                    e
+                                let _ = tx2.send(AppEvent::Failed(Failure::new(
+                                    "Refresh the token".into(),
+                                    format!("Failed to refresh token: {}", e),
+                                    &e,
+                                    None,
                                 )));
                             }
                         }
                     });
                 }
 
+                // j/k, Ctrl+D/U, PageUp/PageDown and gg/G on the vault and secrets lists
+                if matches!(app.screen, AppScreen::VaultSelection | AppScreen::Secrets)
+                    && !app.vault_search_mode
+                    && let Some(motion) = Motion::from_key(code, modifiers, &mut app.pending_g)
+                {
+                    app.move_selection(motion);
+                    continue;
+                }
+
                 match app.screen {
                     AppScreen::VaultSelection => {
                         if app.vault_search_mode {
@@ -335,122 +935,83 @@ async fn main() -> Result<(), Box<dyn Error>> {
                                     app.vault_search_query.clear();
                                     apply_vault_search(&mut app);
                                 }
-                                KeyCode::Down | KeyCode::Char('j') => {
-                                    if !app.displayed_vaults.is_empty() {
-                                        let current = app.vault_list_state.selected().unwrap_or(0);
-                                        let next =
-                                            (current + 1).min(app.displayed_vaults.len() - 1);
-                                        app.vault_list_state.select(Some(next));
-                                    }
-                                }
-                                KeyCode::Up | KeyCode::Char('k') => {
-                                    if !app.displayed_vaults.is_empty() {
-                                        let current = app.vault_list_state.selected().unwrap_or(0);
-                                        if current > 0 {
-                                            app.vault_list_state.select(Some(current - 1));
-                                        }
-                                    }
-                                }
                                 KeyCode::Enter => {
-                                    if let Some(selected_idx) = app.vault_list_state.selected() {
-                                        if let Some((name, uri)) =
-                                            app.displayed_vaults.get(selected_idx).cloned()
-                                        {
-                                            app.current_vault = Some((name.clone(), uri.clone()));
-                                            // check cache existence without holding borrow across mutable calls
-                                            let cache_has_entry =
-                                                app.vault_secret_cache.contains_key(&name);
-                                            if cache_has_entry {
-                                                if let Some(entry) =
-                                                    app.vault_secret_cache.get(&name)
-                                                {
-                                                    let cached_secrets = entry.secrets.clone();
-                                                    let refreshed_at = entry.refreshed_at;
-                                                    // use cached secrets
-                                                    app.secrets = cached_secrets;
-                                                    apply_search(&mut app);
-                                                    app.screen = AppScreen::Secrets;
-                                                    app.loading = false;
-                                                    app.message = Some(format!(
-                                                        "Using cached secrets for '{}'",
-                                                        name
-                                                    ));
-                                                    // refresh silently if older than 30 minutes
-                                                    let age =
-                                                        Instant::now().duration_since(refreshed_at);
-                                                    if age > Duration::from_secs(60 * 30) {
-                                                        let tx2 = tx.clone();
-                                                        let client = SecretClient::new(
-                                                            &uri,
-                                                            app.credential.clone(),
-                                                            None,
-                                                        )?;
-                                                        let client_arc = Arc::new(client);
-                                                        let name_clone = name.clone();
-                                                        tokio::spawn(async move {
-                                                            let _ = list_secrets_and_cache(
-                                                                client_arc,
-                                                                tx2.clone(),
-                                                                name_clone,
-                                                            )
-                                                            .await;
-                                                        });
-                                                    }
-                                                }
-                                            } else {
-                                                // No cache -> incremental load
-                                                app.screen = AppScreen::Secrets;
-                                                app.loading = true;
-                                                app.message = Some("Loading secrets...".into());
-                                                let tx2 = tx.clone();
-                                                let client = SecretClient::new(
-                                                    &uri,
-                                                    app.credential.clone(),
-                                                    None,
-                                                )?;
-                                                let client_arc = Arc::new(client);
-                                                let name_clone = name.clone();
-                                                tokio::spawn(async move {
-                                                    if let Err(e) = list_secrets_incremental(
-                                                        client_arc,
-                                                        tx2.clone(),
-                                                        name_clone.clone(),
-                                                    )
-                                                    .await
-                                                    {
-                                                        let _ =
-                                                            tx2.send(AppEvent::Message(format!(
-                                                                "Failed to list secrets: {}",
-                                                                e
-                                                            )));
-                                                    }
-                                                });
-                                            }
-                                        }
+                                    if let Some(vault) = app
+                                        .vault_list_state
+                                        .selected()
+                                        .and_then(|i| app.displayed_vaults.get(i).cloned())
+                                    {
+                                        open_vault(&mut app, vault.name, vault.uri, &tx)?;
                                     }
                                 }
                                 KeyCode::Char('v') => {
+                                    start_discovery(&mut app, "Refreshing vaults...", &tx);
+                                }
+                                KeyCode::Char('r')
+                                    if modifiers.contains(event::KeyModifiers::CONTROL) =>
+                                {
+                                    app.invalidate_cache(None);
+                                    app.success("Cleared cached secrets of all vaults".into());
+                                }
+                                KeyCode::Char('i') => open_vault_info(&mut app),
+                                KeyCode::Char('T') => {
+                                    app.modal = Some(Modal::Tasks { selected: 0 })
+                                }
+                                KeyCode::Char('!') => open_error_details(&mut app),
+                                KeyCode::Char('m') => open_history(&mut app),
+                                KeyCode::Char('f') => toggle_favorite_selected(&mut app),
+                                KeyCode::Char('h') => toggle_hidden_selected(&mut app),
+                                KeyCode::Char('H') => {
+                                    app.show_hidden = !app.show_hidden;
+                                    apply_vault_search(&mut app);
+                                    app.info(if app.show_hidden {
+                                        format!(
+                                            "Showing {} hidden vault(s)",
+                                            app.state.hidden.len()
+                                        )
+                                    } else {
+                                        "Hidden vaults are no longer shown".to_string()
+                                    });
+                                }
+                                KeyCode::Char('o') if app.offline => {
+                                    app.offline = false;
+                                    start_discovery(&mut app, "Going online...", &tx);
+                                }
+                                KeyCode::Char('o') => {
+                                    app.offline = true;
+                                    apply_vault_search(&mut app);
+                                    app.warn(format!(
+                                        "Offline: browsing cached data ({})",
+                                        OFFLINE_HINT
+                                    ));
+                                }
+                                KeyCode::Char('E') => open_expiring(&mut app),
+                                KeyCode::Char('A') => open_audit(&mut app, None),
+                                KeyCode::Char('R') => open_export(&mut app, None),
+                                KeyCode::Char('t') if app.offline => {
+                                    app.warn(format!(
+                                        "Offline: cannot list tenants ({})",
+                                        OFFLINE_HINT
+                                    ));
+                                }
+                                KeyCode::Char('t') if app.demo => {
+                                    app.warn(format!("Tenants are {}", DEMO_HINT));
+                                }
+                                KeyCode::Char('t') => {
                                     app.loading = true;
-                                    app.message = Some("Refreshing vaults...".into());
+                                    app.info("Loading tenants...".into());
                                     let tx2 = tx.clone();
                                     let cred = app.credential.clone();
                                     tokio::spawn(async move {
-                                        match get_token_then_discover(cred.clone()).await {
-                                            Ok((token_opt, vaults)) => {
-                                                if let Some((token, fetched_at, ttl)) = token_opt {
-                                                    let _ = tx2.send(AppEvent::TokenCached(
-                                                        token, fetched_at, ttl,
-                                                    ));
-                                                }
-                                                let _ = tx2.send(AppEvent::VaultsLoaded(vaults));
-                                            }
-                                            Err(e) => {
-                                                let _ = tx2.send(AppEvent::Message(format!(
-                                                    "Vault discovery failed: {}",
-                                                    e
-                                                )));
-                                            }
-                                        }
+                                        let _ = tx2.send(match list_tenants(cred).await {
+                                            Ok(tenants) => AppEvent::TenantsLoaded(tenants),
+                                            Err(e) => AppEvent::Failed(Failure::new(
+                                                "List tenants".into(),
+                                                format!("Failed to list tenants: {}", e),
+                                                &e,
+                                                None,
+                                            )),
+                                        });
                                     });
                                 }
                                 _ => {}
@@ -459,82 +1020,95 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     }
 
                     AppScreen::Secrets => match code {
-                        KeyCode::Char('j') | KeyCode::Down => {
-                            if !app.displayed_secrets.is_empty() {
-                                app.selected =
-                                    (app.selected + 1).min(app.displayed_secrets.len() - 1);
-                                app.list_state.select(Some(app.selected));
-                            }
+                        KeyCode::Char('o') | KeyCode::Char('O') => {
+                            app.cycle_sort(code == KeyCode::Char('O'));
                         }
-                        KeyCode::Char('k') | KeyCode::Up => {
-                            if !app.displayed_secrets.is_empty() {
-                                if app.selected > 0 {
-                                    app.selected -= 1;
-                                }
-                                app.list_state.select(Some(app.selected));
-                            }
+                        KeyCode::Char('z') => app.toggle_grouped(),
+                        KeyCode::Char('l') | KeyCode::Right if app.grouped => app.expand_selected(),
+                        KeyCode::Char('h') | KeyCode::Left if app.grouped => {
+                            app.collapse_selected()
+                        }
+                        KeyCode::Char('+') if app.grouped => app.expand_all(true),
+                        KeyCode::Char('-') if app.grouped => app.expand_all(false),
+                        KeyCode::Char(' ') => {
+                            app.toggle_mark_selected();
+                        }
+                        KeyCode::Char('*') => {
+                            app.toggle_mark_all_displayed();
+                            app.info(format!("{} secret(s) marked", app.marked.len()));
+                        }
+                        KeyCode::Esc if !app.marked.is_empty() => {
+                            app.marked.clear();
+                            app.info("Marks cleared".into());
                         }
+                        KeyCode::Esc if app.value_matches.is_some() => clear_value_search(&mut app),
+                        KeyCode::Char('V') => open_value_search(&mut app),
                         KeyCode::Char('v') => {
                             app.screen = AppScreen::VaultSelection;
-                            app.loading = true;
-                            app.message = Some("Refreshing vaults...".into());
-                            let tx2 = tx.clone();
-                            let cred = app.credential.clone();
-                            tokio::spawn(async move {
-                                match get_token_then_discover(cred.clone()).await {
-                                    Ok((token_opt, vaults)) => {
-                                        if let Some((token, fetched_at, ttl)) = token_opt {
-                                            let _ = tx2.send(AppEvent::TokenCached(
-                                                token, fetched_at, ttl,
-                                            ));
-                                        }
-                                        let _ = tx2.send(AppEvent::VaultsLoaded(vaults));
-                                    }
-                                    Err(e) => {
-                                        let _ = tx2.send(AppEvent::Message(format!(
-                                            "Vault discovery failed: {}",
-                                            e
-                                        )));
-                                    }
-                                }
-                            });
+                            start_discovery(&mut app, "Refreshing vaults...", &tx);
                         }
-                        KeyCode::Char('r') => {
-                            if app.current_vault.is_none() {
-                                app.message = Some("No vault selected".into());
-                            } else if let Some((name, uri)) = &app.current_vault {
-                                app.loading = true;
-                                app.message = Some("Refreshing secrets...".into());
-                                let tx2 = tx.clone();
-                                let client = SecretClient::new(uri, app.credential.clone(), None)?;
-                                let client_arc = Arc::new(client);
-                                let name_clone = name.clone();
-                                tokio::spawn(async move {
-                                    if let Err(e) = list_secrets_incremental(
-                                        client_arc,
-                                        tx2.clone(),
-                                        name_clone.clone(),
-                                    )
-                                    .await
-                                    {
-                                        let _ = tx2.send(AppEvent::Message(format!(
-                                            "Refresh error: {}",
-                                            e
-                                        )));
-                                    }
-                                });
+                        KeyCode::Char('r')
+                            if modifiers.contains(event::KeyModifiers::CONTROL) && !app.offline =>
+                        {
+                            // Drop this vault's cached listing and values, then list it again
+                            if let Some((name, _)) = app.current_vault.clone() {
+                                app.invalidate_cache(Some(&name));
                             }
+                            refresh_vault(&mut app, &tx)?;
+                        }
+                        KeyCode::Char('r') => refresh_vault(&mut app, &tx)?,
+                        KeyCode::Char('w') => toggle_watch(&mut app),
+                        KeyCode::Char('C') => open_changes(&mut app),
+                        KeyCode::Char('A') => {
+                            let vault = app.current_vault.as_ref().map(|(name, _)| name.clone());
+                            open_audit(&mut app, vault);
+                        }
+                        KeyCode::Char('R') => {
+                            let vault = app.current_vault.as_ref().map(|(name, _)| name.clone());
+                            open_export(&mut app, vault);
+                        }
+                        KeyCode::Char('K') => open_keys(&mut app, &tx),
+                        KeyCode::Char('L') => open_activity(&mut app, &tx),
+                        KeyCode::Char('T') => app.modal = Some(Modal::Tasks { selected: 0 }),
+                        KeyCode::Char('!') => open_error_details(&mut app),
+                        KeyCode::Char('m') => open_history(&mut app),
+                        KeyCode::Char('Y') => open_copy_to_vault(&mut app),
+                        KeyCode::Char('a' | 'e' | 'd') if app.offline => {
+                            app.warn(format!("Offline: read-only ({})", OFFLINE_HINT));
+                        }
+                        KeyCode::Char('a' | 'e' | 'd') if app.access == Some(Access::ReadOnly) => {
+                            app.warn(
+                                "Read-only: you have no permission to change secrets in this vault"
+                                    .into(),
+                            );
                         }
                         KeyCode::Char('a') => {
-                            app.modal = Some(Modal::Add {
-                                name: String::new(),
-                                value: String::new(),
-                                input_mode: AddInputMode::Name,
-                            });
+                            let mut modal = Modal::add();
+                            // A value piped into `akv` prefills the first Add, hidden
+                            if let (
+                                Some(piped),
+                                Modal::Add {
+                                    value, input_mode, ..
+                                },
+                            ) = (app.stdin_value.take(), &mut modal)
+                            {
+                                *value = TextArea::from_text(&piped.value);
+                                value.set_masked(true);
+                                *input_mode = AddInputMode::Name;
+                                app.info(format!(
+                                    "Value prefilled from stdin ({} bytes{})",
+                                    piped.value.len(),
+                                    if piped.base64 { ", base64-encoded" } else { "" }
+                                ));
+                            }
+                            app.modal = Some(modal);
                         }
                         KeyCode::Char('d') => {
-                            if let Some(name) = app.selected_name() {
-                                app.modal = Some(Modal::ConfirmDelete { name });
+                            let mut targets = app.action_targets();
+                            if targets.len() > 1 {
+                                app.modal = Some(Modal::bulk_delete(targets));
+                            } else if let Some(name) = targets.pop() {
+                                app.modal = Some(Modal::confirm_delete(name));
                             }
                         }
                         KeyCode::Char('/') => {
@@ -543,121 +1117,199 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         }
                         KeyCode::Char('e') => {
                             if let Some(name) = app.selected_name() {
-                                if let Some((_, uri)) = &app.current_vault {
+                                if let Some((_, uri)) = app.current_vault.clone() {
                                     app.loading = true;
-                                    app.message = Some("Fetching secret for edit...".into());
+                                    app.info("Fetching secret for edit...".into());
+                                    let remedy = app.remedy(Need::ReadSecrets);
                                     let name_clone = name.clone();
-                                    let client =
-                                        SecretClient::new(uri, app.credential.clone(), None)?;
-                                    let client_arc = Arc::new(client);
+                                    let store = app.stores.get(&uri, &app.credential)?;
                                     let tx2 = tx.clone();
                                     tokio::spawn(async move {
-                                        match client_arc.get_secret(&name_clone, None).await {
-                                            Ok(resp) => {
-                                                let body = resp.into_body();
-                                                match serde_json::from_slice::<Secret>(&body) {
-                                                    Ok(secret) => {
-                                                        let val = secret.value.unwrap_or_default();
-                                                        let _ = tx2.send(AppEvent::OpenEdit(
-                                                            name_clone, val,
-                                                        ));
-                                                    }
-                                                    Err(e) => {
-                                                        let _ =
-                                                            tx2.send(AppEvent::Message(format!(
-                                                                "Failed to parse secret JSON: {}",
-                                                                e
-                                                            )));
-                                                    }
-                                                }
+                                        match store.get(&name_clone).await {
+                                            Ok(val) => {
+                                                let _ =
+                                                    tx2.send(AppEvent::OpenEdit(name_clone, val));
                                             }
                                             Err(e) => {
-                                                let _ = tx2.send(AppEvent::Message(format!(
-                                                    "Failed to get secret for edit: {}",
-                                                    e
+                                                let _ = tx2.send(AppEvent::Failed(Failure::new(
+                                                    format!("Get '{}'", name_clone),
+                                                    forbidden::describe(
+                                                        "Failed to get secret for edit",
+                                                        &e,
+                                                        &remedy,
+                                                    ),
+                                                    &e,
+                                                    None,
                                                 )));
                                             }
                                         }
                                     });
                                 } else {
-                                    app.message = Some("No vault selected".into());
+                                    app.warn("No vault selected".into());
                                 }
                             }
                         }
                         KeyCode::Enter => {
-                            if let Some(name) = app.selected_name() {
-                                if let Some((vault_name, vault_uri)) = &app.current_vault {
-                                    // Check cache first
-                                    if let Some(cached_val) = app
-                                        .secret_value_cache
-                                        .get(&(vault_name.clone(), name.clone()))
-                                    {
-                                        let ctx: Result<ClipboardContext, _> =
-                                            ClipboardProvider::new();
-                                        match ctx {
-                                            Ok(mut ctx) => {
-                                                if ctx.set_contents(cached_val.clone()).is_ok() {
-                                                    app.message = Some(format!(
-                                                        "Secret '{}' copied to clipboard (cached)",
-                                                        name
-                                                    ));
-                                                } else {
-                                                    app.message = Some("Clipboard error".into());
-                                                }
-                                            }
-                                            Err(e) => {
-                                                app.message =
-                                                    Some(format!("Clipboard init error: {}", e));
-                                            }
-                                        }
-                                    } else {
-                                        // Not in cache, fetch it
-                                        app.loading = true;
-                                        app.message = Some("Fetching secret value...".into());
-                                        let name_clone = name.clone();
-                                        let vault_name_clone = vault_name.clone();
-                                        let client = SecretClient::new(
-                                            vault_uri,
-                                            app.credential.clone(),
-                                            None,
-                                        )?;
-                                        let client_arc = Arc::new(client);
-                                        let tx2 = tx.clone();
-                                        tokio::spawn(async move {
-                                            match client_arc.get_secret(&name_clone, None).await {
-                                                Ok(resp) => {
-                                                    let body = resp.into_body();
-                                                    match serde_json::from_slice::<Secret>(&body) {
-                                                        Ok(secret) => {
-                                                            let value =
-                                                                secret.value.unwrap_or_default();
-                                                            let _ = tx2.send(
-                                                                AppEvent::SecretValueLoaded(
-                                                                    vault_name_clone,
-                                                                    name_clone,
-                                                                    value,
-                                                                ),
-                                                            );
-                                                        }
-                                                        Err(e) => {
-                                                            let _ = tx2.send(AppEvent::Message(format!("Failed to parse secret JSON: {}", e)));
-                                                        }
-                                                    }
-                                                }
-                                                Err(e) => {
-                                                    let _ = tx2.send(AppEvent::Message(format!(
-                                                        "Failed to get secret: {}",
-                                                        e
-                                                    )));
-                                                }
-                                            }
-                                        });
+                            // On a group, Enter opens or closes it
+                            if !app.toggle_selected_group()
+                                && let Some(name) = app.selected_name()
+                            {
+                                match app.pick_name_only {
+                                    Some(true) => {
+                                        app.pick_result = Some(name);
+                                        app.quit = true;
                                     }
-                                } else {
-                                    app.message = Some("No vault selected".into());
+                                    Some(false) => request_secret_value(
+                                        &mut app,
+                                        name,
+                                        ValueAction::Pick,
+                                        &tx,
+                                    )?,
+                                    None => request_secret_value(
+                                        &mut app,
+                                        name,
+                                        ValueAction::Copy,
+                                        &tx,
+                                    )?,
                                 }
                             }
                         }
+                        KeyCode::Char('p') if modifiers.contains(event::KeyModifiers::CONTROL) => {
+                            open_vault_switcher(&mut app);
+                        }
+                        KeyCode::Char('p') => {
+                            if let Some(name) = app.selected_name() {
+                                request_secret_value(&mut app, name, ValueAction::Preview, &tx)?;
+                            }
+                        }
+                        KeyCode::Char('b') => {
+                            if let Some(name) = app.selected_name() {
+                                request_secret_value(
+                                    &mut app,
+                                    name,
+                                    ValueAction::CopyDecoded,
+                                    &tx,
+                                )?;
+                            }
+                        }
+                        KeyCode::Char('B') => {
+                            if let Some(name) = app.selected_name() {
+                                request_secret_value(
+                                    &mut app,
+                                    name,
+                                    ValueAction::PreviewDecoded,
+                                    &tx,
+                                )?;
+                            }
+                        }
+                        KeyCode::Char('c') => {
+                            if let Some(name) = app.selected_name() {
+                                request_secret_value(&mut app, name, ValueAction::ConnString, &tx)?;
+                            }
+                        }
+                        KeyCode::Char('J') => {
+                            if let Some(name) = app.selected_name() {
+                                request_secret_value(&mut app, name, ValueAction::ViewJson, &tx)?;
+                            }
+                        }
+                        KeyCode::Char('x') => {
+                            if let Some(name) = app.selected_name() {
+                                request_secret_value(&mut app, name, ValueAction::CopyExport, &tx)?;
+                            }
+                        }
+                        KeyCode::Char('y') => {
+                            // Names only (one per line when marked) — no API call needed
+                            let targets = app.action_targets();
+                            if !targets.is_empty() {
+                                let copied = app.copy_plain(targets.join("\n"));
+                                app.report(copied.map(|()| match targets.as_slice() {
+                                    [name] => format!("Secret name '{}' copied to clipboard", name),
+                                    _ => format!(
+                                        "{} secret names copied to clipboard",
+                                        targets.len()
+                                    ),
+                                }));
+                            }
+                        }
+                        _ => {}
+                    },
+                    AppScreen::Expiring => match code {
+                        KeyCode::Char('j') | KeyCode::Down if !app.expiring.is_empty() => {
+                            let current = app.expiring_state.selected().unwrap_or(0);
+                            let next = (current + 1).min(app.expiring.len() - 1);
+                            app.expiring_state.select(Some(next));
+                        }
+                        KeyCode::Char('k') | KeyCode::Up => {
+                            let current = app.expiring_state.selected().unwrap_or(0);
+                            app.expiring_state.select(Some(current.saturating_sub(1)));
+                        }
+                        KeyCode::Enter => open_expiring_selected(&mut app, &tx)?,
+                        KeyCode::Esc | KeyCode::Char('v') => {
+                            app.screen = AppScreen::VaultSelection;
+                        }
+                        KeyCode::Char('E') => open_expiring(&mut app),
+                        _ => {}
+                    },
+                    AppScreen::History => {
+                        let max = app.history.len().saturating_sub(1) as u16;
+                        match code {
+                            KeyCode::Char('j') | KeyCode::Down => {
+                                app.history_scroll = (app.history_scroll + 1).min(max)
+                            }
+                            KeyCode::Char('k') | KeyCode::Up => {
+                                app.history_scroll = app.history_scroll.saturating_sub(1)
+                            }
+                            KeyCode::Char('g') => app.history_scroll = 0,
+                            KeyCode::Char('G') => app.history_scroll = max,
+                            KeyCode::Esc | KeyCode::Char('m') | KeyCode::Char('q') => {
+                                app.screen = app.history_back
+                            }
+                            _ => {}
+                        }
+                    }
+                    AppScreen::Keys => match code {
+                        KeyCode::Char('j') | KeyCode::Down if !app.keys.is_empty() => {
+                            let current = app.keys_state.selected().unwrap_or(0);
+                            let next = (current + 1).min(app.keys.len() - 1);
+                            app.keys_state.select(Some(next));
+                        }
+                        KeyCode::Char('k') | KeyCode::Up => {
+                            let current = app.keys_state.selected().unwrap_or(0);
+                            app.keys_state.select(Some(current.saturating_sub(1)));
+                        }
+                        KeyCode::Enter => open_crypto(&mut app),
+                        KeyCode::Char('P') => open_rotation(&mut app, &tx),
+                        KeyCode::Char('r') => open_keys(&mut app, &tx),
+                        KeyCode::Char('!') => open_error_details(&mut app),
+                        KeyCode::Char('m') => open_history(&mut app),
+                        KeyCode::Esc | KeyCode::Char('s') => {
+                            app.screen = AppScreen::Secrets;
+                            app.loading = false;
+                        }
+                        KeyCode::Char('v') => {
+                            app.screen = AppScreen::VaultSelection;
+                            start_discovery(&mut app, "Refreshing vaults...", &tx);
+                        }
+                        _ => {}
+                    },
+                    AppScreen::Audit => match code {
+                        KeyCode::Char('j') | KeyCode::Down if !app.audit.is_empty() => {
+                            let current = app.audit_state.selected().unwrap_or(0);
+                            let next = (current + 1).min(app.audit.len() - 1);
+                            app.audit_state.select(Some(next));
+                        }
+                        KeyCode::Char('k') | KeyCode::Up => {
+                            let current = app.audit_state.selected().unwrap_or(0);
+                            app.audit_state.select(Some(current.saturating_sub(1)));
+                        }
+                        KeyCode::Enter => open_audit_selected(&mut app, &tx)?,
+                        // Back to where the audit was started from
+                        KeyCode::Esc if app.audit_vault.is_some() => {
+                            app.screen = AppScreen::Secrets;
+                        }
+                        KeyCode::Esc | KeyCode::Char('v') => {
+                            app.screen = AppScreen::VaultSelection;
+                        }
                         _ => {}
                     },
                     AppScreen::Welcome => {}
@@ -666,10 +1318,45 @@ async fn main() -> Result<(), Box<dyn Error>> {
         }
     }
 
+    // Reads are aborted; writes in flight get a moment to finish rather than being cut off
+    let writing = app.tasks.writing();
+    if writing > 0 {
+        app.info(format!("Waiting for {} write(s) to finish...", writing));
+        terminal.draw(|f| draw_ui(f, &mut app)).ok();
+    }
+    let unfinished = app.tasks.shutdown(SHUTDOWN_TIMEOUT).await;
+
+    // Don't leave a copied secret behind when quitting before the timeout
+    if app.clipboard_clear_at.is_some() {
+        app.clear_clipboard();
+    }
+
     // Cleanup
-    crossterm::terminal::disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+    drop(restore);
+    for label in &unfinished {
+        warn!("Quit before this write finished: {}", label);
+        eprintln!(
+            "Quit before '{}' finished; check the vault for its outcome",
+            label
+        );
+    }
     info!("Exiting Azure Key Vault TUI");
+
+    if let Some(cache) = &disk_cache
+        && let Err(e) = cache.save(&app.cache_snapshot())
+    {
+        warn!("Cannot save disk cache: {}", e);
+    }
+
+    if let Some(e) = script_failure {
+        eprintln!("Script failed: {}", e);
+        std::process::exit(1);
+    }
+    if pick.is_some() {
+        match app.pick_result {
+            Some(result) => println!("{}", result),
+            None => std::process::exit(1), // cancelled, like fzf
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,347 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use azure_core::credentials::{AccessToken, TokenCredential, TokenRequestOptions};
+use azure_security_keyvault_secrets::{SecretClient, SecretClientOptions};
+use serde_json::{Value, json};
+use time::OffsetDateTime;
+use wiremock::matchers::any;
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use crate::azure::{AzureStore, client_options};
+
+/// Secrets per page of a listing, small so the tests page through them.
+const PAGE_SIZE: usize = 2;
+
+const TOKEN: &str = "mock-token";
+
+/// A Key Vault emulated over HTTP for the SDK client: challenge authentication, paged listings
+/// and the get/set/delete/versions endpoints, with the error bodies the real service sends.
+pub struct MockVault {
+    server: MockServer,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    secrets: BTreeMap<String, Vec<Version>>, // oldest version first
+    faults: VecDeque<u16>,                   // statuses of the next authorized requests
+    clock: i64,                              // unix time of the next write
+}
+
+struct Version {
+    id: String,
+    value: String,
+    content_type: Option<String>,
+    created: i64,
+}
+
+impl MockVault {
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+        let state = Arc::new(Mutex::new(State {
+            clock: 1_700_000_000,
+            ..Default::default()
+        }));
+        Mock::given(any())
+            .respond_with(Responder {
+                base: server.uri(),
+                state: state.clone(),
+            })
+            .mount(&server)
+            .await;
+        Self { server, state }
+    }
+
+    /// The vault URI, as discovery would report it.
+    pub fn uri(&self) -> String {
+        format!("{}/", self.server.uri())
+    }
+
+    /// An SDK client for the vault. The challenge names Key Vault's resource, which a local
+    /// server can't match, so that check is off.
+    pub fn client(&self) -> SecretClient {
+        let options = SecretClientOptions {
+            client_options: client_options(),
+            verify_challenge_resource: Some(false),
+            ..Default::default()
+        };
+        SecretClient::new(&self.uri(), Arc::new(MockCredential), Some(options)).unwrap()
+    }
+
+    pub fn store(&self) -> AzureStore {
+        AzureStore::from_client(self.client())
+    }
+
+    /// Add a version of a secret directly, as if set by someone else.
+    pub fn insert(&self, name: &str, value: &str) {
+        self.state.lock().unwrap().set(name, value.into(), None);
+    }
+
+    /// Answer the next authorized request with `status` instead.
+    pub fn fail_next(&self, status: u16) {
+        self.state.lock().unwrap().faults.push_back(status);
+    }
+}
+
+impl State {
+    fn set(&mut self, name: &str, value: String, content_type: Option<String>) -> &Version {
+        self.clock += 60;
+        let versions = self.secrets.entry(name.to_string()).or_default();
+        versions.push(Version {
+            id: format!("{:032x}", self.clock),
+            value,
+            content_type,
+            created: self.clock,
+        });
+        versions.last().unwrap()
+    }
+}
+
+#[derive(Debug)]
+pub struct MockCredential;
+
+#[async_trait]
+impl TokenCredential for MockCredential {
+    async fn get_token(
+        &self,
+        _scopes: &[&str],
+        _options: Option<TokenRequestOptions<'_>>,
+    ) -> azure_core::Result<AccessToken> {
+        let expires_on = OffsetDateTime::now_utc() + time::Duration::hours(1);
+        Ok(AccessToken::new(TOKEN, expires_on))
+    }
+}
+
+struct Responder {
+    base: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl Respond for Responder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let authorized = request
+            .headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            == Some(&format!("Bearer {}", TOKEN));
+        if !authorized {
+            return ResponseTemplate::new(401).insert_header(
+                "WWW-Authenticate",
+                r#"Bearer authorization="https://login.microsoftonline.com/tenant", resource="https://vault.azure.net""#,
+            );
+        }
+        let mut state = self.state.lock().unwrap();
+        if let Some(status) = state.faults.pop_front() {
+            return error(status, "Injected", "Injected failure");
+        }
+        let path: Vec<&str> = request
+            .url
+            .path()
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect();
+        let bundle = |name: &str, v: &Version| {
+            json!({
+                "id": format!("{}/secrets/{}/{}", self.base, name, v.id),
+                "value": v.value,
+                "contentType": v.content_type,
+                "attributes": { "enabled": true, "created": v.created, "updated": v.created },
+                "tags": {}
+            })
+        };
+        let properties = |mut bundle: Value| {
+            bundle.as_object_mut().unwrap().remove("value");
+            bundle
+        };
+        match (request.method.as_str(), path.as_slice()) {
+            ("GET", ["secrets"]) => {
+                let skip: usize = request
+                    .url
+                    .query_pairs()
+                    .find(|(k, _)| k == "$skiptoken")
+                    .and_then(|(_, v)| v.parse().ok())
+                    .unwrap_or(0);
+                let items: Vec<Value> = state
+                    .secrets
+                    .iter()
+                    .skip(skip)
+                    .take(PAGE_SIZE)
+                    .map(|(name, versions)| {
+                        // Listings name the secret without a version
+                        let mut item = properties(bundle(name, versions.last().unwrap()));
+                        item["id"] = json!(format!("{}/secrets/{}", self.base, name));
+                        item
+                    })
+                    .collect();
+                let next = skip + PAGE_SIZE;
+                let next_link = (next < state.secrets.len()).then(|| {
+                    format!(
+                        "{}/secrets?api-version=2025-07-01&$skiptoken={}",
+                        self.base, next
+                    )
+                });
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "value": items, "nextLink": next_link }))
+            }
+            ("PUT", ["secrets", name]) => {
+                let body: Value = match request.body_json() {
+                    Ok(body) => body,
+                    Err(_) => return error(400, "BadParameter", "The request body is invalid"),
+                };
+                let Some(value) = body["value"].as_str() else {
+                    return error(400, "BadParameter", "A value is required");
+                };
+                let content_type = body["contentType"].as_str().map(String::from);
+                let version = state.set(name, value.into(), content_type);
+                ResponseTemplate::new(200).set_body_json(bundle(name, version))
+            }
+            ("DELETE", ["secrets", name]) => match state.secrets.remove(*name) {
+                Some(versions) => {
+                    let mut deleted = properties(bundle(name, versions.last().unwrap()));
+                    deleted["recoveryId"] = json!(format!("{}/deletedsecrets/{}", self.base, name));
+                    ResponseTemplate::new(200).set_body_json(deleted)
+                }
+                None => not_found(name),
+            },
+            ("GET", ["secrets", name, "versions"]) => match state.secrets.get(*name) {
+                Some(versions) => {
+                    let items: Vec<Value> = versions
+                        .iter()
+                        .map(|v| properties(bundle(name, v)))
+                        .collect();
+                    ResponseTemplate::new(200).set_body_json(json!({ "value": items }))
+                }
+                None => not_found(name),
+            },
+            ("GET", ["secrets", name, version @ ..]) => {
+                let found = state.secrets.get(*name).and_then(|versions| match version {
+                    [] => versions.last(),
+                    [id] => versions.iter().find(|v| v.id == *id),
+                    _ => None,
+                });
+                match found {
+                    Some(v) => ResponseTemplate::new(200).set_body_json(bundle(name, v)),
+                    None => not_found(name),
+                }
+            }
+            _ => error(400, "BadParameter", "Unsupported request"),
+        }
+    }
+}
+
+fn not_found(name: &str) -> ResponseTemplate {
+    error(
+        404,
+        "SecretNotFound",
+        &format!(
+            "A secret with (name/id) {} was not found in this key vault.",
+            name
+        ),
+    )
+}
+
+fn error(status: u16, code: &str, message: &str) -> ResponseTemplate {
+    ResponseTemplate::new(status)
+        .set_body_json(json!({ "error": { "code": code, "message": message } }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure::{get_secret, list_secrets_with_properties};
+    use crate::error::Category;
+    use crate::forbidden;
+    use crate::model::AppEvent;
+    use crate::store::{SecretStore, list_secrets_incremental};
+
+    #[tokio::test]
+    async fn test_store_against_mock_vault() {
+        let vault = MockVault::start().await;
+        for name in [
+            "api-key",
+            "db-host",
+            "db-password",
+            "redis-key",
+            "smtp-password",
+        ] {
+            vault.insert(name, &format!("{}-value", name));
+        }
+        let store = vault.store();
+
+        // Three pages
+        let mut names = Vec::new();
+        store.list(&mut |s| names.push(s.name)).await.unwrap();
+        assert_eq!(
+            names,
+            [
+                "api-key",
+                "db-host",
+                "db-password",
+                "redis-key",
+                "smtp-password"
+            ]
+        );
+
+        // A new version, with the body restored after the authentication challenge
+        store
+            .set("db-password", "s3cret".into(), Some("text/plain".into()))
+            .await
+            .unwrap();
+        assert_eq!(store.get("db-password").await.unwrap(), "s3cret");
+        let versions = store.versions("db-password").await.unwrap();
+        assert_eq!(versions.len(), 2);
+        assert!(versions[0].created > versions[1].created);
+        let secret = get_secret(&vault.client(), "db-password").await.unwrap();
+        assert_eq!(secret.content_type.as_deref(), Some("text/plain"));
+
+        // Errors keep the status for their category
+        store.delete("api-key").await.unwrap();
+        let gone = store.get("api-key").await.unwrap_err();
+        assert_eq!(gone.category, Category::NotFound);
+        assert!(store.delete("api-key").await.is_err());
+        vault.fail_next(403);
+        let denied = store.set("db-host", "x".into(), None).await.unwrap_err();
+        assert!(forbidden::is_forbidden(&denied));
+        assert_eq!(store.get("db-host").await.unwrap(), "db-host-value");
+
+        let listed = list_secrets_with_properties(&vault.client()).await.unwrap();
+        assert_eq!(listed.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_listing_events_against_mock_vault() {
+        let vault = MockVault::start().await;
+        for n in 0..5 {
+            vault.insert(&format!("secret-{}", n), "value");
+        }
+        let (tx, mut rx) = crate::events::channel(crate::events::CAPACITY);
+        list_secrets_incremental(Arc::new(vault.store()), tx, "kv-mock".into())
+            .await
+            .unwrap();
+
+        let mut listed = Vec::new();
+        let mut events = Vec::new();
+        while let Some(event) = rx.try_recv() {
+            match event {
+                AppEvent::SecretsListed(_, names) => listed.extend(names),
+                AppEvent::SecretsUpdated(vault, names) => {
+                    events.push(format!("updated {} {}", vault, names.len()))
+                }
+                AppEvent::CacheVaultSecrets(_, secrets) => {
+                    assert!(secrets.iter().all(|s| s.enabled && s.created.is_some()));
+                    events.push(format!("cached {}", secrets.len()));
+                }
+                AppEvent::ListingComplete(vault) => events.push(format!("complete {}", vault)),
+                _ => unreachable!(),
+            }
+        }
+        // The first name goes out right away, the rest in the next batch or the full listing
+        assert_eq!(listed.first().map(String::as_str), Some("secret-0"));
+        assert_eq!(
+            events,
+            ["updated kv-mock 5", "cached 5", "complete kv-mock"]
+        );
+    }
+}
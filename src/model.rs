@@ -1,26 +1,395 @@
+use std::sync::Arc;
 use std::time::Duration;
-use std::time::Instant;
+use std::time::{Instant, SystemTime};
+
+use ratatui::text::Line;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::connstr::ConnStringPicker;
+use crate::crypto::CryptoForm;
+use crate::device_code::{DeviceCode, DeviceCodeCredential};
+use crate::error::AkvError;
+use crate::generator::GeneratorOptions;
+use crate::highlight::ValueFormat;
+use crate::json_view::JsonViewer;
+use crate::rotation::{RotationForm, RotationPolicy};
+use crate::textarea::TextArea;
 
 #[derive(Debug, Clone)]
 pub enum Modal {
     Add {
-        name: String,
-        value: String,
+        name: TextArea,
+        value: TextArea,
         input_mode: AddInputMode,
+        generator: Option<GeneratorOptions>, // open random-value popup
+        file_path: Option<TextArea>,         // Some = value is read from this file on submit
+        encode_base64: bool,                 // base64-encode the value on save
+        status: SubmitState,
     },
     Edit {
         name: String,
-        value: String,
+        value: TextArea,
+        encode_base64: bool,
+        status: SubmitState,
     },
     ConfirmDelete {
         name: String,
+        status: SubmitState,
+    },
+    JsonViewer(JsonViewer),
+    ConnString(ConnStringPicker),
+    Preview {
+        name: String,
+        format: ValueFormat,
+        lines: Vec<Line<'static>>, // pre-highlighted once when opened
+        scroll: u16,
+    },
+    BulkDelete {
+        names: Vec<String>,
+        statuses: Vec<BulkItemStatus>,
+        confirm_input: String,
+        started: bool,
+        selected: Option<usize>, // None = follow the secret being deleted
+    },
+    SignIn(SignInState),
+    Changes {
+        lines: Vec<Line<'static>>, // built once when opened
+        scroll: u16,
+    },
+    TenantPicker {
+        tenants: Vec<Tenant>,
+        selected: usize,
+    },
+    VaultSwitcher {
+        query: String,
+        matches: Vec<Vault>, // fuzzy-ranked candidates for `query`
+        selected: usize,
+        copy: Option<Vec<String>>, // secrets to copy into the picked vault instead of opening it
+    },
+    Export {
+        vault: Option<String>,      // None = every cached vault
+        names: Option<Vec<String>>, // only these secrets of `vault` (the marked ones)
+        path: TextArea,
+    },
+    ValueSearch {
+        needle: TextArea,
+        names: Vec<String>, // secrets whose values would be fetched (the listed ones)
+        confirm: bool,      // asking before any value is fetched
+    },
+    Crypto(CryptoForm),
+    Rotation(RotationForm),
+    VaultInfo(Vault),
+    Activity {
+        name: String,
+        lines: Vec<Line<'static>>, // built once when opened
+        scroll: u16,
+    },
+    Tasks {
+        selected: usize, // row of the task panel (`TaskRegistry::list` order)
+    },
+    ErrorDetails {
+        failure: Failure,
+        scroll: u16,
     },
+    Help {
+        lines: Vec<Line<'static>>, // built once when opened
+        scroll: u16,
+    },
+}
+
+impl Modal {
+    pub fn add() -> Self {
+        Modal::Add {
+            name: TextArea::default(),
+            value: TextArea::default(),
+            input_mode: AddInputMode::Name,
+            generator: None,
+            file_path: None,
+            encode_base64: false,
+            status: SubmitState::default(),
+        }
+    }
+
+    pub fn edit(name: String, value: &str) -> Self {
+        Modal::Edit {
+            name,
+            value: TextArea::from_text(value),
+            encode_base64: false,
+            status: SubmitState::default(),
+        }
+    }
+
+    pub fn confirm_delete(name: String) -> Self {
+        Modal::ConfirmDelete {
+            name,
+            status: SubmitState::default(),
+        }
+    }
+
+    /// Submit state of modals that keep user input around until the request succeeds.
+    pub fn submit_state_mut(&mut self) -> Option<&mut SubmitState> {
+        match self {
+            Modal::Add { status, .. }
+            | Modal::Edit { status, .. }
+            | Modal::ConfirmDelete { status, .. } => Some(status),
+            Modal::Rotation(form) => Some(&mut form.status),
+            Modal::JsonViewer(_)
+            | Modal::ConnString(_)
+            | Modal::Preview { .. }
+            | Modal::BulkDelete { .. }
+            | Modal::SignIn(_)
+            | Modal::Changes { .. }
+            | Modal::TenantPicker { .. }
+            | Modal::VaultSwitcher { .. }
+            | Modal::Export { .. }
+            | Modal::ValueSearch { .. }
+            | Modal::Crypto(_)
+            | Modal::VaultInfo(_)
+            | Modal::Activity { .. }
+            | Modal::Tasks { .. }
+            | Modal::ErrorDetails { .. }
+            | Modal::Help { .. } => None,
+        }
+    }
+
+    /// Bulk delete confirmation for the given secrets, awaiting a typed "yes".
+    pub fn bulk_delete(names: Vec<String>) -> Self {
+        let statuses = vec![BulkItemStatus::Pending; names.len()];
+        Modal::BulkDelete {
+            names,
+            statuses,
+            confirm_input: String::new(),
+            started: false,
+            selected: None,
+        }
+    }
+}
+
+/// In-flight/failed state of a modal submit. The modal stays open (with the user's input)
+/// until the background request succeeds; failures are shown inline.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubmitState {
+    pub pending: bool,
+    pub error: Option<String>,
+}
+
+/// A failed background operation, kept for the error details popup (`!`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    pub operation: String,  // e.g. "List secrets of kv-prod"
+    pub message: String,    // toast summary, with the remedy for a 403
+    pub chain: Vec<String>, // the error and its sources, outermost first
+    pub error: AkvError,    // what the UI does about it: sign in, back off, go offline, ...
+    pub retry: Option<Retry>,
+}
+
+impl Failure {
+    pub fn new(operation: String, message: String, e: &AkvError, retry: Option<Retry>) -> Self {
+        Self {
+            operation,
+            message,
+            chain: e.chain(),
+            error: e.clone(),
+            retry,
+        }
+    }
+
+    /// Plain-text report for the clipboard (bug reports, support tickets).
+    pub fn report(&self) -> String {
+        let mut out = format!("Operation: {}\n{}\n", self.operation, self.message);
+        for (i, e) in self.chain.iter().enumerate() {
+            let prefix = if i == 0 { "Error" } else { "Caused by" };
+            out.push_str(&format!("{}: {}\n", prefix, e));
+        }
+        out
+    }
+}
+
+/// Background operations the error details popup can run again.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Retry {
+    Discover,
+    ListSecrets(String),                   // vault
+    GetValue(String, String, ValueAction), // vault, secret, what to do with the value
+    ListKeys(String),                      // vault
+}
+
+/// Device-code sign-in, offered when no developer tool credential is available.
+#[derive(Debug, Clone)]
+pub enum SignInState {
+    Offer { reason: String },
+    Requesting,
+    Waiting(DeviceCode), // user enters the code in a browser; polling in the background
+    Failed(String),
+}
+
+/// A discovered Key Vault.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Vault {
+    pub name: String,
+    pub uri: String,
+    pub alias: Option<String>, // friendly name from `[aliases]` in the config
+    pub subscription: Option<String>, // display name (ID when discovered through the az CLI)
+    pub resource_group: Option<String>,
+    pub location: Option<String>,
+    #[serde(default)]
+    pub properties: Option<VaultProperties>, // None when not discovered through ARM
+}
+
+/// Settings of a vault from its ARM resource (the `i` popup on the vault list).
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct VaultProperties {
+    pub resource_id: Option<String>,
+    pub sku: Option<String>, // standard or premium
+    pub soft_delete: bool,
+    pub retention_days: Option<u32>, // soft-delete retention
+    pub purge_protection: bool,
+    pub rbac: bool,                     // Azure RBAC instead of access policies
+    pub public_access: Option<String>,  // publicNetworkAccess: Enabled or Disabled
+    pub default_action: Option<String>, // network ACLs: Allow or Deny
+    pub bypass: Option<String>,         // e.g. AzureServices
+    pub ip_rules: Vec<String>,
+    pub subnets: Vec<String>, // virtual network rules, as subnet names
+}
+
+/// A version of a secret, from its version listing (never its value).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecretVersion {
+    pub version: String,
+    pub enabled: bool,
+    pub created: Option<OffsetDateTime>,
+    pub updated: Option<OffsetDateTime>,
+    pub expires: Option<OffsetDateTime>,
+}
+
+/// Listing metadata of a secret (never its value).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SecretMeta {
+    pub name: String,
+    pub enabled: bool,
+    pub content_type: Option<String>,
+    pub tags: Vec<(String, String)>, // sorted by key
+    #[serde(default, with = "time::serde::timestamp::option")]
+    pub created: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::timestamp::option")]
+    pub updated: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::timestamp::option")]
+    pub expires: Option<OffsetDateTime>,
+}
+
+/// Whether the signed-in principal may change the current vault's secrets (the RO/RW badge).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    ReadOnly,
+    ReadWrite,
+}
+
+/// An operation on a secret from the vault's audit logs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityEntry {
+    pub time: String,      // ISO 8601, UTC
+    pub operation: String, // e.g. SecretGet, SecretSet, SecretDelete
+    pub caller: String,    // UPN, app ID or object ID
+    pub ip: String,
+    pub result: String, // HTTP status, e.g. OK or Forbidden
+}
+
+/// A key in a vault (current version).
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyInfo {
+    pub name: String,
+    pub version: String,       // current version, used for crypto operations
+    pub kty: String,           // RSA, RSA-HSM, EC, EC-HSM or oct-HSM
+    pub size: Option<u32>,     // RSA modulus bits
+    pub curve: Option<String>, // EC curve, e.g. P-256
+    pub enabled: bool,
+    pub ops: Vec<String>,         // permitted operations, e.g. sign, verify
+    pub rotation: Option<String>, // rotation policy summary, None = no policy
+}
+
+/// A directory the signed-in account can access (from ARM `/tenants`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tenant {
+    pub id: String,
+    pub name: String,           // display name, falls back to the ID
+    pub domain: Option<String>, // default domain, e.g. contoso.onmicrosoft.com
+}
+
+/// Who the current token was issued to, read from its (unverified) claims.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    pub principal: String, // UPN for users, app name (or ID) for service principals
+    pub tenant: Option<String>, // tenant ID
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkItemStatus {
+    Pending,
+    Done,
+    Failed(String),
+}
+
+impl BulkItemStatus {
+    /// The secret being deleted next (the last one once all are through).
+    pub fn progress(statuses: &[Self]) -> usize {
+        statuses
+            .iter()
+            .position(|st| *st == Self::Pending)
+            .unwrap_or(statuses.len().saturating_sub(1))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum AddInputMode {
     Name,
     Value,
+    File,
+}
+
+/// What to do with a secret value once it has been fetched (or found in the cache).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueAction {
+    Copy,
+    ViewJson,
+    Preview,
+    CopyDecoded,    // base64-decode, then copy
+    PreviewDecoded, // base64-decode, then preview (hex dump for binary)
+    ConnString,     // pick a component of a connection string
+    CopyExport,     // copy as `export NAME='value'`
+    Pick,           // `akv pick`: print the value to stdout and exit
+}
+
+/// What the secrets list is ordered by (`o` cycles through them, `O` reverses the order).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    #[default]
+    Name, // alphabetical, or by relevance while searching
+    Updated,
+    Expires,
+}
+
+impl SortKey {
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Name => SortKey::Updated,
+            SortKey::Updated => SortKey::Expires,
+            SortKey::Expires => SortKey::Name,
+        }
+    }
+
+    /// The direction the key starts in: newest changes and soonest expiries first.
+    pub fn descending_first(self) -> bool {
+        self == SortKey::Updated
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Updated => "updated",
+            SortKey::Expires => "expiry",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -28,23 +397,77 @@ pub enum AppScreen {
     Welcome,
     VaultSelection,
     Secrets,
+    Expiring, // secrets expiring soon across all cached vaults
+    Audit,    // compliance findings, for one vault or all cached ones
+    Keys,     // keys of the current vault
+    History,  // past toasts, newest first
+}
+
+/// A row of the expiring-secrets dashboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpiringSecret {
+    pub vault: String,
+    pub name: String,
+    pub expires: OffsetDateTime,
 }
 
 #[derive(Debug)]
 pub enum AppEvent {
-    VaultsLoaded(Vec<(String, String)>),
-    SecretsUpdated(String, Vec<String>),    // vault_name, secrets
-    CacheVaultSecrets(String, Vec<String>), // vault_name -> cached secrets (silent)
+    VaultsLoaded(Vec<Vault>),
+    SecretsUpdated(String, Arc<Vec<String>>), // vault_name, sorted names (shared with the cache)
+    SecretsListed(String, Vec<String>),       // vault_name, names listed since the previous batch
+    ListingComplete(String), // vault_name: the incremental listing sent its last batch
+    CacheVaultSecrets(String, Arc<Vec<SecretMeta>>), // vault_name -> cached secrets (silent)
     OpenEdit(String, String),
-    Message(String),
     TokenCached(String, Instant, Duration), // token, fetched_at, ttl
-    SecretValueLoaded(String, String, String), // vault_name, secret_name, value
+    SecretValueLoaded(String, String, String, ValueAction), // vault_name, secret_name, value, action
+    BulkDeleteItem(String, Result<(), String>),             // secret_name, outcome
+    SubmitSucceeded(String), // close the submitting modal, show message
+    SubmitFailed(String),    // keep the modal open, show error inline
+    DeviceCodeIssued(DeviceCode),
+    SignedIn(Arc<DeviceCodeCredential>),
+    SignInFailed(String),
+    TenantsLoaded(Vec<Tenant>),                   // open the tenant picker
+    KeysLoaded(String, Vec<KeyInfo>),             // vault_name, keys
+    CryptoDone(Result<String, String>),           // crypto dialog result
+    RotationPolicyLoaded(String, RotationPolicy), // key_name, policy -> open the editor
+    AccessProbed(String, Access),                 // vault_name, permission preflight result
+    ActivityLoaded(String, Vec<ActivityEntry>),   // secret_name, audit log entries (newest first)
+    Failed(Failure), // handled by its category: sign in, back off, go offline; details with `!`
+    Finished(String), // long background operation done -> toast, or desktop notification
+    ValueSearchDone(String, Vec<String>, usize), // vault_name, secrets whose value matched, unreadable
+    SearchResults(u64, Vec<String>),             // search generation, ranked secret names
+    Quit,                                        // SIGINT / SIGTERM: quit as if `q` was pressed
+}
+
+/// What a refresh found compared to the listing it replaced (names sorted).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListingChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>, // `updated` timestamp differs
+}
+
+impl ListingChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// e.g. "+2 / -1 / ~3 changed"
+    pub fn summary(&self) -> String {
+        format!(
+            "+{} / -{} / ~{} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct VaultCacheEntry {
-    pub secrets: Vec<String>,
-    pub refreshed_at: Instant,
+    pub secrets: Arc<Vec<String>>, // sorted; shared with `App::secrets` while the vault is open
+    pub refreshed_at: SystemTime,  // wall clock, so entries restored from disk keep their age
 }
 
 #[derive(Debug, Clone)]
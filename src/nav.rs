@@ -0,0 +1,128 @@
+use std::ops::Range;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+/// Vim-style movement in a list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    Down,         // j / ↓
+    Up,           // k / ↑
+    HalfPageDown, // Ctrl+D
+    HalfPageUp,   // Ctrl+U
+    PageDown,     // PageDown
+    PageUp,       // PageUp
+    Top,          // gg / Home
+    Bottom,       // G / End
+}
+
+impl Motion {
+    /// The motion of a key, if any. `g` only jumps to the top when pressed twice in a row
+    /// (`gg`); `pending_g` remembers the first one.
+    pub fn from_key(code: KeyCode, modifiers: KeyModifiers, pending_g: &mut bool) -> Option<Self> {
+        let gg = std::mem::take(pending_g);
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        Some(match code {
+            KeyCode::Char('j') | KeyCode::Down if !ctrl => Motion::Down,
+            KeyCode::Char('k') | KeyCode::Up if !ctrl => Motion::Up,
+            KeyCode::Char('d') if ctrl => Motion::HalfPageDown,
+            KeyCode::Char('u') if ctrl => Motion::HalfPageUp,
+            KeyCode::PageDown => Motion::PageDown,
+            KeyCode::PageUp => Motion::PageUp,
+            KeyCode::Char('g') if !ctrl && gg => Motion::Top,
+            KeyCode::Char('g') if !ctrl => {
+                *pending_g = true;
+                return None;
+            }
+            KeyCode::Char('G') | KeyCode::End => Motion::Bottom,
+            KeyCode::Home => Motion::Top,
+            _ => return None,
+        })
+    }
+
+    /// The row to select in a list of `len` rows, `page` of which are visible at once.
+    pub fn apply(self, selected: usize, len: usize, page: usize) -> usize {
+        let last = len.saturating_sub(1);
+        let page = page.max(1);
+        let half = (page / 2).max(1);
+        let target = match self {
+            Motion::Down => selected + 1,
+            Motion::Up => selected.saturating_sub(1),
+            Motion::HalfPageDown => selected + half,
+            Motion::HalfPageUp => selected.saturating_sub(half),
+            Motion::PageDown => selected + page,
+            Motion::PageUp => selected.saturating_sub(page),
+            Motion::Top => 0,
+            Motion::Bottom => last,
+        };
+        target.min(last)
+    }
+}
+
+/// The rows of a list of `len` rows that fit in `height`, scrolled from `offset` as little as
+/// needed to show `selected`. Only these are drawn, however long the list is.
+pub fn visible_rows(selected: usize, offset: usize, len: usize, height: usize) -> Range<usize> {
+    let height = height.max(1);
+    let offset = if selected < offset {
+        selected
+    } else if selected >= offset + height {
+        selected + 1 - height
+    } else {
+        offset
+    };
+    // A list that got shorter still fills the page
+    let offset = offset.min(len.saturating_sub(height));
+    offset..(offset + height).min(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_motions() {
+        let mut pending_g = false;
+        let none = KeyModifiers::NONE;
+        assert_eq!(
+            Motion::from_key(KeyCode::Char('g'), none, &mut pending_g),
+            None
+        );
+        assert_eq!(
+            Motion::from_key(KeyCode::Char('g'), none, &mut pending_g),
+            Some(Motion::Top)
+        );
+        // Another key in between cancels the first g
+        Motion::from_key(KeyCode::Char('g'), none, &mut pending_g);
+        Motion::from_key(KeyCode::Char('x'), none, &mut pending_g);
+        assert_eq!(
+            Motion::from_key(KeyCode::Char('g'), none, &mut pending_g),
+            None
+        );
+        assert_eq!(
+            Motion::from_key(KeyCode::Char('d'), KeyModifiers::CONTROL, &mut pending_g),
+            Some(Motion::HalfPageDown)
+        );
+        assert_eq!(
+            Motion::from_key(KeyCode::Char('d'), none, &mut pending_g),
+            None
+        );
+
+        // 900 secrets, 20 visible
+        assert_eq!(Motion::HalfPageDown.apply(0, 900, 20), 10);
+        assert_eq!(Motion::PageDown.apply(890, 900, 20), 899);
+        assert_eq!(Motion::PageUp.apply(5, 900, 20), 0);
+        assert_eq!(Motion::Bottom.apply(3, 900, 20), 899);
+        assert_eq!(Motion::Down.apply(0, 0, 20), 0);
+    }
+
+    #[test]
+    fn test_visible_rows() {
+        assert_eq!(visible_rows(0, 0, 5000, 20), 0..20);
+        assert_eq!(visible_rows(19, 0, 5000, 20), 0..20);
+        assert_eq!(visible_rows(20, 0, 5000, 20), 1..21);
+        assert_eq!(visible_rows(4999, 1, 5000, 20), 4980..5000);
+        assert_eq!(visible_rows(100, 4980, 5000, 20), 100..120);
+        // The list was filtered down below the old offset
+        assert_eq!(visible_rows(3, 4980, 12, 20), 0..12);
+        assert_eq!(visible_rows(0, 0, 0, 20), 0..0);
+    }
+}
@@ -0,0 +1,18 @@
+use notify_rust::Notification;
+use tracing::debug;
+
+/// Show a desktop notification from a separate thread, so a slow or missing notification
+/// daemon can't stall the UI. Failures only end up in the debug log.
+pub fn desktop(body: &str) {
+    let body = body.to_string();
+    std::thread::spawn(move || {
+        if let Err(e) = Notification::new()
+            .appname("akv")
+            .summary("Azure Key Vault TUI")
+            .body(&body)
+            .show()
+        {
+            debug!("Desktop notification failed: {}", e);
+        }
+    });
+}
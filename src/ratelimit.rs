@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use azure_core::http::policies::{Policy, PolicyResult};
+use azure_core::http::{Context, Request};
+
+use crate::cloud::cloud;
+use crate::config::RateLimitConfig;
+
+/// Token bucket allowing `rate` requests per second, with bursts of up to one second's worth.
+#[derive(Debug)]
+struct Bucket {
+    rate: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(rate: u32, now: Instant) -> Self {
+        Self {
+            rate: rate as f64,
+            tokens: rate as f64,
+            updated: now,
+        }
+    }
+
+    /// Refill for the time passed since the last call; how long until a token is available.
+    fn wait(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.rate)
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    global: Option<Bucket>,
+    vaults: HashMap<String, Bucket>, // created on a vault's first request
+}
+
+/// Request rates shared by everything that calls Key Vault (preload, refresh, exports, ...).
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_vault: u32,
+    global: u32,
+    vaults: HashMap<String, u32>, // lowercase vault name -> rate
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            per_vault: config.per_vault,
+            global: config.global,
+            vaults: config
+                .vaults
+                .iter()
+                .map(|(name, rate)| (name.to_lowercase(), *rate))
+                .collect(),
+            buckets: Mutex::default(),
+        }
+    }
+
+    /// How long a request to `vault` has to wait. A request only counts against the
+    /// limits (global and per vault) once it may go out under both.
+    fn reserve(&self, vault: &str, now: Instant) -> Duration {
+        let rate = self.vaults.get(vault).copied().unwrap_or(self.per_vault);
+        let mut buckets = self.buckets.lock().unwrap();
+        let Buckets { global, vaults } = &mut *buckets;
+        let mut limited = Vec::new();
+        if self.global > 0 {
+            limited.push(global.get_or_insert_with(|| Bucket::new(self.global, now)));
+        }
+        if rate > 0 {
+            limited.push(
+                vaults
+                    .entry(vault.to_string())
+                    .or_insert_with(|| Bucket::new(rate, now)),
+            );
+        }
+        let wait = limited
+            .iter_mut()
+            .map(|b| b.wait(now))
+            .max()
+            .unwrap_or_default();
+        if wait.is_zero() {
+            for bucket in limited {
+                bucket.tokens -= 1.0;
+            }
+        }
+        wait
+    }
+
+    /// Wait until a request to `vault` (lowercase name) is allowed.
+    pub async fn acquire(&self, vault: &str) {
+        loop {
+            let wait = self.reserve(vault, Instant::now());
+            if wait.is_zero() {
+                return;
+            }
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+
+/// Apply the `[rate_limit]` settings. Must be called before the first request.
+pub fn set_rate_limits(config: &RateLimitConfig) {
+    let _ = LIMITER.set(RateLimiter::new(config));
+}
+
+fn limiter() -> &'static RateLimiter {
+    LIMITER.get_or_init(|| RateLimiter::new(&RateLimitConfig::default()))
+}
+
+/// Wait for the rate limits before a request to `host`. Only Key Vault hosts are limited.
+pub async fn throttle(host: &str) {
+    let vault = host
+        .strip_suffix(cloud().vault_suffix.as_str())
+        .and_then(|h| h.strip_suffix('.'));
+    if let Some(vault) = vault {
+        limiter().acquire(&vault.to_lowercase()).await;
+    }
+}
+
+/// SDK pipeline policy that applies the rate limits to every try of a Key Vault request.
+#[derive(Debug)]
+pub struct RateLimitPolicy;
+
+#[async_trait]
+impl Policy for RateLimitPolicy {
+    async fn send(
+        &self,
+        ctx: &Context,
+        request: &mut Request,
+        next: &[Arc<dyn Policy>],
+    ) -> PolicyResult {
+        if let Some(host) = request.url().host_str() {
+            throttle(host).await;
+        }
+        next[0].send(ctx, request, &next[1..]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limits() {
+        let limiter = RateLimiter::new(&RateLimitConfig {
+            per_vault: 2,
+            global: 3,
+            vaults: HashMap::from([("KV-Slow".to_string(), 1)]),
+        });
+        let start = Instant::now();
+        assert!(limiter.reserve("kv-a", start).is_zero());
+        assert!(limiter.reserve("kv-a", start).is_zero());
+        assert_eq!(limiter.reserve("kv-a", start), Duration::from_millis(500));
+        // Another vault has its own bucket, but shares the global one
+        assert!(limiter.reserve("kv-slow", start).is_zero());
+        assert!(!limiter.reserve("kv-b", start).is_zero());
+        // A refused request doesn't use up a token: 1/3s later the global bucket allows one
+        let later = start + Duration::from_millis(340);
+        assert!(limiter.reserve("kv-b", later).is_zero());
+        // kv-slow only gets 1 request per second
+        assert!(limiter.reserve("kv-slow", later) > Duration::from_millis(600));
+
+        let unlimited = RateLimiter::new(&RateLimitConfig {
+            per_vault: 0,
+            ..Default::default()
+        });
+        assert!((0..1000).all(|_| unlimited.reserve("kv-a", start).is_zero()));
+    }
+}
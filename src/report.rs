@@ -0,0 +1,143 @@
+use std::path::Path;
+
+use azure_core::time::to_rfc3339;
+use serde_json::{Map, Value, json};
+use time::OffsetDateTime;
+
+use crate::model::SecretMeta;
+
+/// Format of a metadata report, picked by the file extension (`.json`, anything else CSV).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+impl ReportFormat {
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Csv,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Csv => "CSV",
+            Self::Json => "JSON",
+        }
+    }
+}
+
+const COLUMNS: [&str; 7] = [
+    "vault", "name", "enabled", "created", "updated", "expires", "tags",
+];
+
+/// Metadata (never values) of (vault, secret) rows for access reviews. Timestamps are
+/// RFC 3339; CSV tags are `key=value` pairs separated by `; `.
+pub fn render(rows: &[(String, SecretMeta)], format: ReportFormat) -> String {
+    let time = |t: Option<OffsetDateTime>| t.as_ref().map(to_rfc3339);
+    match format {
+        ReportFormat::Json => {
+            let records: Vec<Value> = rows
+                .iter()
+                .map(|(vault, meta)| {
+                    let tags: Map<String, Value> = meta
+                        .tags
+                        .iter()
+                        .map(|(k, v)| (k.clone(), json!(v)))
+                        .collect();
+                    json!({
+                        "vault": vault,
+                        "name": meta.name,
+                        "enabled": meta.enabled,
+                        "created": time(meta.created),
+                        "updated": time(meta.updated),
+                        "expires": time(meta.expires),
+                        "tags": tags,
+                    })
+                })
+                .collect();
+            let mut out = serde_json::to_string_pretty(&records).unwrap_or_default();
+            out.push('\n');
+            out
+        }
+        ReportFormat::Csv => {
+            let mut out = COLUMNS.join(",");
+            out.push('\n');
+            for (vault, meta) in rows {
+                let tags: Vec<String> = meta
+                    .tags
+                    .iter()
+                    .map(|(k, v)| format!("{}={}", k, v))
+                    .collect();
+                let fields = [
+                    vault.clone(),
+                    meta.name.clone(),
+                    meta.enabled.to_string(),
+                    time(meta.created).unwrap_or_default(),
+                    time(meta.updated).unwrap_or_default(),
+                    time(meta.expires).unwrap_or_default(),
+                    tags.join("; "),
+                ];
+                let fields: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+                out.push_str(&fields.join(","));
+                out.push('\n');
+            }
+            out
+        }
+    }
+}
+
+/// Quote a CSV field when needed (RFC 4180).
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_report() {
+        let rows = vec![(
+            "kv-prod".to_string(),
+            SecretMeta {
+                name: "db-password".into(),
+                enabled: true,
+                tags: vec![
+                    ("env".into(), "prod".into()),
+                    ("owner".into(), "data, ops".into()),
+                ],
+                expires: Some(OffsetDateTime::from_unix_timestamp(1_800_000_000).unwrap()),
+                ..Default::default()
+            },
+        )];
+
+        let csv = render(&rows, ReportFormat::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "vault,name,enabled,created,updated,expires,tags");
+        assert_eq!(
+            lines[1],
+            "kv-prod,db-password,true,,,2027-01-15T08:00:00Z,\"env=prod; owner=data, ops\""
+        );
+
+        let json: Value = serde_json::from_str(&render(&rows, ReportFormat::Json)).unwrap();
+        assert_eq!(json[0]["expires"], "2027-01-15T08:00:00Z");
+        assert_eq!(json[0]["created"], Value::Null);
+        assert_eq!(json[0]["tags"]["owner"], "data, ops");
+
+        assert_eq!(
+            ReportFormat::for_path(Path::new("review.JSON")),
+            ReportFormat::Json
+        );
+        assert_eq!(
+            ReportFormat::for_path(Path::new("review")),
+            ReportFormat::Csv
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use serde_json::{Value, json};
+
+use crate::model::SubmitState;
+use crate::textarea::TextArea;
+
+/// A key rotation policy. Durations are ISO 8601 (`P90D`, `P3M`, `P2Y`), as Key Vault uses.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RotationPolicy {
+    pub rotate_after_create: Option<String>,
+    pub rotate_before_expiry: Option<String>,
+    pub notify_before_expiry: Option<String>,
+    pub expiry_time: Option<String>, // lifetime of new key versions
+}
+
+impl RotationPolicy {
+    /// From a `GET /keys/{name}/rotationpolicy` response.
+    pub fn from_json(policy: &Value) -> Self {
+        let mut parsed = Self {
+            expiry_time: policy["attributes"]["expiryTime"]
+                .as_str()
+                .map(String::from),
+            ..Default::default()
+        };
+        for action in policy["lifetimeActions"].as_array().into_iter().flatten() {
+            let trigger = &action["trigger"];
+            let after_create = trigger["timeAfterCreate"].as_str().map(String::from);
+            let before_expiry = trigger["timeBeforeExpiry"].as_str().map(String::from);
+            match action["action"]["type"]
+                .as_str()
+                .map(str::to_lowercase)
+                .as_deref()
+            {
+                Some("rotate") => {
+                    parsed.rotate_after_create = after_create;
+                    parsed.rotate_before_expiry = before_expiry;
+                }
+                Some("notify") => parsed.notify_before_expiry = before_expiry,
+                _ => {}
+            }
+        }
+        parsed
+    }
+
+    /// Body for `PUT /keys/{name}/rotationpolicy`.
+    pub fn to_json(&self) -> Value {
+        let mut actions = Vec::new();
+        if let Some(d) = &self.rotate_after_create {
+            actions.push(
+                json!({ "trigger": { "timeAfterCreate": d }, "action": { "type": "Rotate" } }),
+            );
+        } else if let Some(d) = &self.rotate_before_expiry {
+            actions.push(
+                json!({ "trigger": { "timeBeforeExpiry": d }, "action": { "type": "Rotate" } }),
+            );
+        }
+        if let Some(d) = &self.notify_before_expiry {
+            actions.push(
+                json!({ "trigger": { "timeBeforeExpiry": d }, "action": { "type": "Notify" } }),
+            );
+        }
+        let mut body = json!({ "lifetimeActions": actions });
+        if let Some(expiry) = &self.expiry_time {
+            body["attributes"] = json!({ "expiryTime": expiry });
+        }
+        body
+    }
+
+    /// e.g. "rotate 90d after creation, notify 30d before expiry, versions expire after 1y";
+    /// None without any lifetime action.
+    pub fn summary(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(d) = &self.rotate_after_create {
+            parts.push(format!("rotate {} after creation", short_duration(d)));
+        }
+        if let Some(d) = &self.rotate_before_expiry {
+            parts.push(format!("rotate {} before expiry", short_duration(d)));
+        }
+        if let Some(d) = &self.notify_before_expiry {
+            parts.push(format!("notify {} before expiry", short_duration(d)));
+        }
+        if parts.is_empty() {
+            return None;
+        }
+        if let Some(d) = &self.expiry_time {
+            parts.push(format!("versions expire after {}", short_duration(d)));
+        }
+        Some(parts.join(", "))
+    }
+}
+
+/// `P90D` -> `90d`. Rotation policies only use days, months and years.
+pub fn short_duration(iso: &str) -> String {
+    iso.trim_start_matches('P').to_lowercase()
+}
+
+/// `90d`, `3m`, `2y` (or ISO 8601 `P90D`) -> `P90D`.
+pub fn parse_duration(input: &str) -> Result<String, String> {
+    let upper = input.trim().to_uppercase();
+    let body = upper.strip_prefix('P').unwrap_or(&upper);
+    let (number, unit) = body.split_at(body.len().saturating_sub(1));
+    match (number.parse::<u32>(), unit) {
+        (Ok(n), "D" | "M" | "Y") if n > 0 => Ok(format!("P{}{}", n, unit)),
+        _ => Err(format!(
+            "Invalid duration '{}' (e.g. 90d, 3m or 2y)",
+            input.trim()
+        )),
+    }
+}
+
+/// Rotation policy dialog (`P` on the keys screen). Empty fields are left out of the policy.
+#[derive(Debug, Clone)]
+pub struct RotationForm {
+    pub key: String,
+    pub fields: [TextArea; 4], // in the order of `LABELS`
+    pub focus: usize,
+    pub status: SubmitState,
+}
+
+impl RotationForm {
+    pub const LABELS: [&str; 4] = [
+        "Rotate after creation",
+        "Rotate before expiry",
+        "Notify before expiry",
+        "New versions expire after",
+    ];
+
+    pub fn new(key: String, policy: &RotationPolicy) -> Self {
+        let field = |d: &Option<String>| {
+            TextArea::from_text(&d.as_deref().map(short_duration).unwrap_or_default())
+        };
+        Self {
+            key,
+            fields: [
+                field(&policy.rotate_after_create),
+                field(&policy.rotate_before_expiry),
+                field(&policy.notify_before_expiry),
+                field(&policy.expiry_time),
+            ],
+            focus: 0,
+            status: SubmitState::default(),
+        }
+    }
+
+    /// The policy entered, validated.
+    pub fn policy(&self) -> Result<RotationPolicy, String> {
+        let [after_create, before_expiry, notify, expiry] = self.fields.each_ref().map(|f| {
+            let text = f.text();
+            (!text.trim().is_empty())
+                .then(|| parse_duration(&text))
+                .transpose()
+        });
+        let policy = RotationPolicy {
+            rotate_after_create: after_create?,
+            rotate_before_expiry: before_expiry?,
+            notify_before_expiry: notify?,
+            expiry_time: expiry?,
+        };
+        if policy.rotate_after_create.is_some() && policy.rotate_before_expiry.is_some() {
+            return Err("Rotate either after creation or before expiry, not both".into());
+        }
+        if (policy.rotate_before_expiry.is_some() || policy.notify_before_expiry.is_some())
+            && policy.expiry_time.is_none()
+        {
+            return Err("Triggers before expiry need an expiry for new versions".into());
+        }
+        Ok(policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_policy_round_trip() {
+        let policy = RotationPolicy::from_json(&json!({
+            "id": "https://kv.vault.azure.net/keys/signing/rotationpolicy",
+            "lifetimeActions": [
+                {"trigger": {"timeAfterCreate": "P90D"}, "action": {"type": "Rotate"}},
+                {"trigger": {"timeBeforeExpiry": "P30D"}, "action": {"type": "Notify"}}
+            ],
+            "attributes": {"expiryTime": "P1Y"}
+        }));
+        assert_eq!(
+            policy.summary().as_deref(),
+            Some("rotate 90d after creation, notify 30d before expiry, versions expire after 1y")
+        );
+        assert_eq!(RotationPolicy::from_json(&policy.to_json()), policy);
+        assert_eq!(RotationPolicy::default().summary(), None);
+
+        assert_eq!(parse_duration(" 90d"), Ok("P90D".into()));
+        assert_eq!(parse_duration("P2Y"), Ok("P2Y".into()));
+        assert!(parse_duration("0d").is_err());
+        assert!(parse_duration("12h").is_err());
+
+        // The form shows short durations and validates what is entered
+        let mut form = RotationForm::new("signing".into(), &policy);
+        assert_eq!(form.fields[0].text(), "90d");
+        assert_eq!(form.policy(), Ok(policy));
+        form.fields[1] = TextArea::from_text("7d");
+        assert!(form.policy().is_err()); // both rotate triggers
+        form.fields[0] = TextArea::default();
+        form.fields[3] = TextArea::default();
+        assert!(form.policy().is_err()); // before-expiry triggers without an expiry
+    }
+}
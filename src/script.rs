@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::buffer::Buffer;
+
+/// How long `expect` waits for its text before the script fails.
+const EXPECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Size of the screen a script runs on (there is no terminal to ask).
+pub const SCREEN_WIDTH: u16 = 120;
+pub const SCREEN_HEIGHT: u16 = 40;
+
+/// One line of a script.
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(KeyCode, KeyModifiers), // key Enter, key ctrl+d, key j
+    Type(String),               // type db-pass: one key per character
+    Wait(Duration),             // wait 500 (milliseconds)
+    Expect(String),             // expect Loaded: wait until the screen shows the text
+}
+
+/// Key events for `--script <file>`, fed to the app in place of the terminal, with waits and
+/// checks of what is drawn. Runs through the steps in order; the first failed `expect` ends it.
+pub struct Script {
+    steps: VecDeque<(usize, Step)>, // with their line numbers
+    since: Instant,                 // when the current step started
+    screen: String,                 // what was drawn last
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read script {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut steps = VecDeque::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (command, arg) = line.split_once(' ').unwrap_or((line, ""));
+            let arg = arg.trim();
+            let step = match command {
+                "key" => {
+                    let (code, modifiers) =
+                        parse_key(arg).map_err(|e| format!("line {}: {}", i + 1, e))?;
+                    Step::Key(code, modifiers)
+                }
+                "type" if !arg.is_empty() => Step::Type(arg.to_string()),
+                "wait" => {
+                    Step::Wait(Duration::from_millis(arg.parse().map_err(|_| {
+                        format!("line {}: invalid milliseconds '{}'", i + 1, arg)
+                    })?))
+                }
+                "expect" if !arg.is_empty() => Step::Expect(arg.to_string()),
+                _ => return Err(format!("line {}: cannot parse '{}'", i + 1, line)),
+            };
+            steps.push_back((i + 1, step));
+        }
+        Ok(Self {
+            steps,
+            since: Instant::now(),
+            screen: String::new(),
+        })
+    }
+
+    /// Remember what was drawn, for `expect`.
+    pub fn show(&mut self, buffer: &Buffer) {
+        let width = buffer.area.width.max(1) as usize;
+        self.screen = buffer
+            .content
+            .chunks(width)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .map(|row| row.trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    pub fn done(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// The next input event once it is due, or why the script failed.
+    pub fn next(&mut self, now: Instant) -> Result<Option<Event>, String> {
+        let Some((line, step)) = self.steps.front_mut() else {
+            return Ok(None);
+        };
+        let event = match step {
+            Step::Key(code, modifiers) => Some(Event::Key(KeyEvent::new(*code, *modifiers))),
+            Step::Type(text) => {
+                let event = Event::Key(KeyEvent::new(
+                    KeyCode::Char(text.remove(0)),
+                    KeyModifiers::NONE,
+                ));
+                // The step is done with its last character
+                if !text.is_empty() {
+                    return Ok(Some(event));
+                }
+                Some(event)
+            }
+            Step::Wait(duration) if now < self.since + *duration => return Ok(None),
+            Step::Wait(_) => None,
+            Step::Expect(text) if self.screen.contains(text.as_str()) => None,
+            Step::Expect(text) if now >= self.since + EXPECT_TIMEOUT => {
+                return Err(format!(
+                    "line {}: '{}' not on the screen after {}s; the screen was:\n{}",
+                    line,
+                    text,
+                    EXPECT_TIMEOUT.as_secs(),
+                    self.screen
+                ));
+            }
+            Step::Expect(_) => return Ok(None),
+        };
+        self.steps.pop_front();
+        self.since = now;
+        Ok(event)
+    }
+}
+
+/// A key as written in a script: a character, or a name such as `Enter` or `PageDown`, with
+/// optional `ctrl+`, `alt+` and `shift+` prefixes.
+fn parse_key(key: &str) -> Result<(KeyCode, KeyModifiers), String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = key;
+    while let Some((prefix, tail)) = rest.split_once('+').filter(|(_, tail)| !tail.is_empty()) {
+        modifiers |= match prefix.to_lowercase().as_str() {
+            "ctrl" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return Err(format!("unknown modifier '{}' in '{}'", prefix, key)),
+        };
+        rest = tail;
+    }
+    let mut chars = rest.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => match rest.to_lowercase().as_str() {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => return Err(format!("unknown key '{}'", key)),
+            },
+        },
+    };
+    Ok((code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("j"), Ok((KeyCode::Char('j'), KeyModifiers::NONE)));
+        assert_eq!(parse_key("+"), Ok((KeyCode::Char('+'), KeyModifiers::NONE)));
+        assert_eq!(
+            parse_key("ctrl+d"),
+            Ok((KeyCode::Char('d'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_key("Ctrl+Alt+Left"),
+            Ok((KeyCode::Left, KeyModifiers::CONTROL | KeyModifiers::ALT))
+        );
+        assert_eq!(parse_key("F5"), Ok((KeyCode::F(5), KeyModifiers::NONE)));
+        assert!(parse_key("hyper+x").is_err());
+        assert!(parse_key("F13").is_err());
+        assert!(parse_key("").is_err());
+    }
+
+    #[test]
+    fn test_script_steps() {
+        assert!(Script::parse("jump 3").is_err());
+        assert!(Script::parse("wait soon").is_err());
+
+        let mut script =
+            Script::parse("# open and search\nexpect kv-a\n\nkey Enter\ntype ab\nwait 100\nkey q")
+                .unwrap();
+        let start = Instant::now();
+        let key = |c| Some(Event::Key(KeyEvent::new(c, KeyModifiers::NONE)));
+        // Waits for the text to be drawn
+        assert_eq!(script.next(start), Ok(None));
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 10, 2));
+        buffer.set_string(0, 1, "kv-a", ratatui::style::Style::default());
+        script.show(&buffer);
+        assert_eq!(script.screen, "\nkv-a");
+        assert_eq!(script.next(start), Ok(None));
+        assert_eq!(script.next(start), Ok(key(KeyCode::Enter)));
+        assert_eq!(script.next(start), Ok(key(KeyCode::Char('a'))));
+        assert_eq!(script.next(start), Ok(key(KeyCode::Char('b'))));
+        assert_eq!(script.next(start), Ok(None));
+        assert_eq!(script.next(start + Duration::from_millis(50)), Ok(None));
+        assert_eq!(script.next(start + Duration::from_millis(100)), Ok(None)); // waited
+        assert_eq!(script.next(start), Ok(key(KeyCode::Char('q'))));
+        assert!(script.done());
+
+        let mut missing = Script::parse("expect Loaded").unwrap();
+        let later = Instant::now() + EXPECT_TIMEOUT;
+        assert!(
+            missing
+                .next(later)
+                .unwrap_err()
+                .starts_with("line 1: 'Loaded'")
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// UI state remembered between runs, kept apart from the hand-written config file in
+/// `$XDG_STATE_HOME/akv-tui/state.toml` (one file per profile).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    /// Favorite vaults starred with `f`. Once set, replaces `favorites` from the config.
+    pub favorites: Option<Vec<String>>,
+    /// Vaults hidden with `h`: left out of the selection list and background preload.
+    pub hidden: Vec<String>,
+    /// Recently opened vaults, most recent first.
+    pub recent: Vec<RecentVault>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentVault {
+    pub name: String,
+    pub uri: String,
+}
+
+/// How many recently opened vaults are remembered.
+const MAX_RECENT: usize = 10;
+
+impl State {
+    pub fn default_path(profile: Option<&str>) -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local").join("state"))
+            })
+            .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))?;
+        let file = match profile {
+            Some(profile) => format!("state-{}.toml", profile),
+            None => "state.toml".to_string(),
+        };
+        Some(base.join("akv-tui").join(file))
+    }
+
+    /// Move a vault to the front of the recently opened list.
+    pub fn record_recent(&mut self, name: &str, uri: &str) {
+        self.recent.retain(|r| r.name != name);
+        self.recent.insert(
+            0,
+            RecentVault {
+                name: name.to_string(),
+                uri: uri.to_string(),
+            },
+        );
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    /// Position in the recently opened list (lower = more recent).
+    pub fn recent_rank(&self, name: &str) -> Option<usize> {
+        self.recent.iter().position(|r| r.name == name)
+    }
+
+    /// Load the state file; a missing or unreadable file starts from scratch.
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).unwrap_or_else(|e| {
+                warn!("Ignoring invalid state file {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let text = toml::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| format!("Cannot save {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_round_trip() {
+        let path = std::env::temp_dir().join(format!("akv-state-{}.toml", std::process::id()));
+        assert_eq!(State::load(&path), State::default());
+
+        let state = State {
+            favorites: Some(vec!["kv-prod".to_string(), "kv-shared".to_string()]),
+            hidden: vec!["other-team-kv".to_string()],
+            recent: Vec::new(),
+        };
+        state.save(&path).unwrap();
+        assert_eq!(State::load(&path), state);
+
+        let mut state = state;
+        state.record_recent("kv-a", "https://kv-a.vault.azure.net/");
+        state.record_recent("kv-b", "https://kv-b.vault.azure.net/");
+        state.record_recent("kv-a", "https://kv-a.vault.azure.net/");
+        state.save(&path).unwrap();
+        let loaded = State::load(&path);
+        assert_eq!(loaded.recent.len(), 2);
+        assert_eq!(loaded.recent_rank("kv-a"), Some(0));
+        assert_eq!(loaded.recent_rank("kv-b"), Some(1));
+
+        std::fs::write(&path, "favorites = 3").unwrap();
+        assert_eq!(State::load(&path), State::default());
+        std::fs::remove_file(path).unwrap();
+    }
+}
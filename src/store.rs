@@ -0,0 +1,325 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use azure_core::credentials::TokenCredential;
+use time::OffsetDateTime;
+use tokio::sync::Semaphore;
+use tracing::debug;
+
+use crate::azure::AzureStore;
+use crate::error::AkvError;
+use crate::events::EventSender;
+use crate::model::{AppEvent, SecretMeta, SecretVersion};
+
+/// The secrets of one vault as the app reads and changes them. `AzureStore` implements it for
+/// Key Vault; the UI and its background tasks only go through this trait.
+#[async_trait]
+pub trait SecretStore: Send + Sync {
+    /// Every secret with its listing metadata, handed to `found` as the listing arrives.
+    async fn list(&self, found: &mut (dyn FnMut(SecretMeta) + Send)) -> Result<(), AkvError>;
+
+    /// The current value of a secret.
+    async fn get(&self, name: &str) -> Result<String, AkvError>;
+
+    /// Create a secret or add a new version with the given value.
+    async fn set(
+        &self,
+        name: &str,
+        value: String,
+        content_type: Option<String>,
+    ) -> Result<(), AkvError>;
+
+    /// Delete a secret (soft-delete when the vault has it enabled).
+    async fn delete(&self, name: &str) -> Result<(), AkvError>;
+
+    /// The versions of a secret, newest first.
+    async fn versions(&self, name: &str) -> Result<Vec<SecretVersion>, AkvError>;
+}
+
+/// Stores by vault URI, created on first use and shared by every request and background task
+/// for the vault. Cleared when the credential changes.
+#[derive(Default)]
+pub struct StorePool {
+    stores: HashMap<String, Arc<dyn SecretStore>>,
+    fixed: bool, // only the stores given to `fixed`, never Key Vault
+}
+
+impl StorePool {
+    /// A pool of just `stores` (demo mode): other vaults are an error instead of a Key Vault
+    /// client, and a new credential keeps them.
+    pub fn fixed(stores: impl IntoIterator<Item = (String, Arc<dyn SecretStore>)>) -> Self {
+        Self {
+            stores: stores.into_iter().collect(),
+            fixed: true,
+        }
+    }
+
+    pub fn get(
+        &mut self,
+        uri: &str,
+        credential: &Arc<dyn TokenCredential>,
+    ) -> azure_core::Result<Arc<dyn SecretStore>> {
+        if let Some(store) = self.stores.get(uri) {
+            return Ok(store.clone());
+        }
+        if self.fixed {
+            return Err(azure_core::Error::with_message(
+                azure_core::error::ErrorKind::Other,
+                format!("No vault at {}", uri),
+            ));
+        }
+        let store: Arc<dyn SecretStore> = Arc::new(AzureStore::new(uri, credential.clone())?);
+        self.stores.insert(uri.to_string(), store.clone());
+        Ok(store)
+    }
+
+    /// Serve `uri` from `store` instead of Key Vault.
+    #[cfg(test)]
+    pub fn insert(&mut self, uri: &str, store: Arc<dyn SecretStore>) {
+        self.stores.insert(uri.to_string(), store);
+    }
+
+    pub fn clear(&mut self) {
+        if !self.fixed {
+            self.stores.clear();
+        }
+    }
+}
+
+/// Least time between two batches of an incremental listing, so a large vault doesn't flood
+/// the UI with updates.
+const BATCH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Incrementally list secrets and send the names listed since the last batch back to the UI
+/// (`SecretsListed`). The full listing follows at the end, with CacheVaultSecrets (with
+/// metadata) for silent caching and indexing.
+pub async fn list_secrets_incremental(
+    store: Arc<dyn SecretStore>,
+    tx: EventSender,
+    vault_name: String,
+) -> Result<(), AkvError> {
+    debug!("Starting incremental list for vault '{}'", vault_name);
+    let mut secrets: Vec<SecretMeta> = Vec::new();
+    // Names listed since the last batch; the UI gets at most one batch per BATCH_INTERVAL
+    let mut batch: Vec<String> = Vec::new();
+    let mut last_batch: Option<Instant> = None;
+    store
+        .list(&mut |secret| {
+            batch.push(secret.name.clone());
+            secrets.push(secret);
+            if last_batch.is_none_or(|at| at.elapsed() >= BATCH_INTERVAL) {
+                let _ = tx.send(AppEvent::SecretsListed(
+                    vault_name.clone(),
+                    std::mem::take(&mut batch),
+                ));
+                last_batch = Some(Instant::now());
+            }
+        })
+        .await?;
+    // The whole listing once, for the cache and the search index
+    secrets.sort_by(|a, b| a.name.cmp(&b.name));
+    let names = secrets.iter().map(|s| s.name.clone()).collect();
+    let _ = tx.send(AppEvent::SecretsUpdated(
+        vault_name.clone(),
+        Arc::new(names),
+    ));
+    let _ = tx.send(AppEvent::CacheVaultSecrets(
+        vault_name.clone(),
+        Arc::new(secrets),
+    ));
+    let _ = tx.send(AppEvent::ListingComplete(vault_name.clone()));
+    debug!("Completed incremental list for vault '{}'", vault_name);
+    Ok(())
+}
+
+/// List secrets fully and update cache (used after write/delete to ensure cache is fresh).
+pub async fn list_secrets_and_cache(
+    store: Arc<dyn SecretStore>,
+    tx: EventSender,
+    vault_name: String,
+) -> Result<(), AkvError> {
+    debug!("Starting full list+cache for vault '{}'", vault_name);
+    let mut secrets: Vec<SecretMeta> = Vec::new();
+    store.list(&mut |secret| secrets.push(secret)).await?;
+    secrets.sort_by(|a, b| a.name.cmp(&b.name));
+    let names: Vec<String> = secrets.iter().map(|s| s.name.clone()).collect();
+    let _ = tx.send(AppEvent::CacheVaultSecrets(
+        vault_name.clone(),
+        Arc::new(secrets),
+    ));
+    let _ = tx.send(AppEvent::SecretsUpdated(
+        vault_name.clone(),
+        Arc::new(names),
+    ));
+    debug!("Completed full list+cache for vault '{}'", vault_name);
+    Ok(())
+}
+
+/// Preload one vault's secrets into the cache silently, waiting for a slot of `sem`
+/// (bounded concurrency across vaults).
+pub async fn preload_vault(
+    store: Arc<dyn SecretStore>,
+    tx: EventSender,
+    name: String,
+    sem: Arc<Semaphore>,
+) {
+    let _permit = sem.acquire_owned().await.expect("semaphore");
+    debug!("Preloading vault '{}'", name);
+    if let Err(e) = list_secrets_and_cache(store, tx, name.clone()).await {
+        debug!("Preload failed for {}: {}", name, e);
+    } else {
+        debug!("Preload succeeded for {}", name);
+    }
+}
+
+/// Secrets per page of a `MemoryStore` listing.
+const LIST_PAGE: usize = 25;
+
+/// Secrets kept in memory, each operation taking `latency` like a round trip would: the
+/// backend of demo mode, and of tests that drive the app without Key Vault.
+#[derive(Default)]
+pub struct MemoryStore {
+    secrets: Mutex<BTreeMap<String, (SecretMeta, String)>>,
+    latency: Duration,
+}
+
+impl MemoryStore {
+    pub fn new(secrets: Vec<(SecretMeta, String)>, latency: Duration) -> Self {
+        let secrets = secrets
+            .into_iter()
+            .map(|(meta, value)| (meta.name.clone(), (meta, value)))
+            .collect();
+        Self {
+            secrets: Mutex::new(secrets),
+            latency,
+        }
+    }
+
+    /// Enabled secrets without attributes, answering at once.
+    #[cfg(test)]
+    pub fn with(secrets: &[(&str, &str)]) -> Self {
+        let secrets = secrets
+            .iter()
+            .map(|(name, value)| {
+                let meta = SecretMeta {
+                    name: name.to_string(),
+                    enabled: true,
+                    ..Default::default()
+                };
+                (meta, value.to_string())
+            })
+            .collect();
+        Self::new(secrets, Duration::ZERO)
+    }
+
+    fn secrets(&self) -> MutexGuard<'_, BTreeMap<String, (SecretMeta, String)>> {
+        self.secrets.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn not_found(name: &str) -> AkvError {
+        AkvError::response(404, &format!("Secret '{}' not found", name))
+    }
+}
+
+#[async_trait]
+impl SecretStore for MemoryStore {
+    async fn list(&self, found: &mut (dyn FnMut(SecretMeta) + Send)) -> Result<(), AkvError> {
+        let secrets: Vec<SecretMeta> = self.secrets().values().map(|(m, _)| m.clone()).collect();
+        // One page per round trip, as Key Vault pages its listings
+        for page in secrets.chunks(LIST_PAGE) {
+            tokio::time::sleep(self.latency).await;
+            page.iter().cloned().for_each(&mut *found);
+        }
+        Ok(())
+    }
+
+    async fn get(&self, name: &str) -> Result<String, AkvError> {
+        tokio::time::sleep(self.latency).await;
+        let secrets = self.secrets();
+        match secrets.get(name) {
+            Some((meta, value)) if meta.enabled => Ok(value.clone()),
+            Some(_) => Err(AkvError::response(
+                403,
+                &format!("Secret '{}' is disabled", name),
+            )),
+            None => Err(Self::not_found(name)),
+        }
+    }
+
+    async fn set(
+        &self,
+        name: &str,
+        value: String,
+        content_type: Option<String>,
+    ) -> Result<(), AkvError> {
+        tokio::time::sleep(self.latency).await;
+        let now = OffsetDateTime::now_utc();
+        let mut secrets = self.secrets();
+        let (meta, old) = secrets.entry(name.to_string()).or_insert_with(|| {
+            let meta = SecretMeta {
+                name: name.to_string(),
+                enabled: true,
+                created: Some(now),
+                ..Default::default()
+            };
+            (meta, String::new())
+        });
+        meta.updated = Some(now);
+        meta.content_type = content_type;
+        *old = value;
+        Ok(())
+    }
+
+    async fn delete(&self, name: &str) -> Result<(), AkvError> {
+        tokio::time::sleep(self.latency).await;
+        match self.secrets().remove(name) {
+            Some(_) => Ok(()),
+            None => Err(Self::not_found(name)),
+        }
+    }
+
+    async fn versions(&self, name: &str) -> Result<Vec<SecretVersion>, AkvError> {
+        tokio::time::sleep(self.latency).await;
+        let secrets = self.secrets();
+        let (meta, _) = secrets.get(name).ok_or_else(|| Self::not_found(name))?;
+        // Only the current value is kept
+        Ok(vec![SecretVersion {
+            version: "current".into(),
+            enabled: meta.enabled,
+            created: meta.updated.or(meta.created),
+            updated: meta.updated,
+            expires: meta.expires,
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_pool() {
+        let credential: Arc<dyn TokenCredential> =
+            azure_identity::DeveloperToolsCredential::new(None).unwrap();
+        let mut pool = StorePool::default();
+        let a = pool
+            .get("https://kv-a.vault.azure.net/", &credential)
+            .unwrap();
+        let again = pool
+            .get("https://kv-a.vault.azure.net/", &credential)
+            .unwrap();
+        let b = pool
+            .get("https://kv-b.vault.azure.net/", &credential)
+            .unwrap();
+        assert!(Arc::ptr_eq(&a, &again));
+        assert!(!Arc::ptr_eq(&a, &b));
+        // A new credential gets new stores
+        pool.clear();
+        let fresh = pool
+            .get("https://kv-a.vault.azure.net/", &credential)
+            .unwrap();
+        assert!(!Arc::ptr_eq(&a, &fresh));
+    }
+}
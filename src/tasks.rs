@@ -0,0 +1,270 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinHandle;
+
+/// Finished tasks kept in the panel, newest first.
+const KEEP_FINISHED: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskStatus {
+    Running,
+    Done,
+    Cancelled,
+}
+
+/// A background operation shown in the task panel (`T`).
+#[derive(Debug)]
+pub struct Task {
+    pub id: u64,
+    pub label: String,         // e.g. "Preload kv-prod"
+    pub scope: Option<String>, // vault whose leaving aborts the task, None = runs on
+    pub writes: bool,          // changes a vault or a file: quitting waits for it
+    pub status: TaskStatus,
+    pub started: Instant,
+    pub finished: Option<Instant>,
+    handle: JoinHandle<()>,
+}
+
+impl Task {
+    /// Time the task has been running, or took.
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.finished
+            .unwrap_or(now)
+            .saturating_duration_since(self.started)
+    }
+}
+
+/// Background operations of the app: what runs, since when, and a way to cancel it.
+#[derive(Debug, Default)]
+pub struct TaskRegistry {
+    tasks: Vec<Task>, // in start order
+    next_id: u64,
+    quitting: Arc<AtomicBool>,
+}
+
+impl TaskRegistry {
+    /// Spawn `task` and track it under `label`.
+    pub fn spawn<F>(&mut self, label: impl Into<String>, scope: Option<String>, task: F) -> u64
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.push(label.into(), scope, false, task)
+    }
+
+    /// Spawn `task`, which writes to a vault or a file. It runs on when the vault is left, and
+    /// quitting waits for it rather than aborting it halfway.
+    pub fn spawn_write<F>(&mut self, label: impl Into<String>, task: F) -> u64
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.push(label.into(), None, true, task)
+    }
+
+    fn push<F>(&mut self, label: String, scope: Option<String>, writes: bool, task: F) -> u64
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tasks.push(Task {
+            id,
+            label,
+            scope,
+            writes,
+            status: TaskStatus::Running,
+            started: Instant::now(),
+            finished: None,
+            handle: tokio::spawn(task),
+        });
+        id
+    }
+
+    /// Mark tasks that ended as done and forget the oldest finished ones.
+    pub fn reap(&mut self, now: Instant) {
+        for task in &mut self.tasks {
+            if task.status == TaskStatus::Running && task.handle.is_finished() {
+                task.status = TaskStatus::Done;
+                task.finished = Some(now);
+            }
+        }
+        let finished = self
+            .tasks
+            .iter()
+            .filter(|t| t.status != TaskStatus::Running)
+            .count();
+        let mut excess = finished.saturating_sub(KEEP_FINISHED);
+        self.tasks.retain(|t| {
+            let drop = excess > 0 && t.status != TaskStatus::Running;
+            excess -= drop as usize;
+            !drop
+        });
+    }
+
+    /// Abort a running task; false when it had already ended.
+    pub fn cancel(&mut self, id: u64) -> bool {
+        match self
+            .tasks
+            .iter_mut()
+            .find(|t| t.id == id && t.status == TaskStatus::Running)
+        {
+            Some(task) => {
+                task.handle.abort();
+                task.status = TaskStatus::Cancelled;
+                task.finished = Some(Instant::now());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Abort the running tasks scoped to a vault (when leaving it).
+    pub fn cancel_scoped(&mut self) {
+        let ids: Vec<u64> = self
+            .tasks
+            .iter()
+            .filter(|t| t.scope.is_some())
+            .map(|t| t.id)
+            .collect();
+        for id in ids {
+            self.cancel(id);
+        }
+    }
+
+    /// Set once the app quits, for writes of several secrets to stop after the one in flight.
+    pub fn quitting(&self) -> Arc<AtomicBool> {
+        self.quitting.clone()
+    }
+
+    /// Writes still running.
+    pub fn writing(&self) -> usize {
+        self.tasks
+            .iter()
+            .filter(|t| t.writes && t.status == TaskStatus::Running)
+            .count()
+    }
+
+    /// Quit: abort the reads, signal the writes to stop and give them `timeout` to finish.
+    /// Returns the labels of the writes that were still running then (and got aborted).
+    pub async fn shutdown(&mut self, timeout: Duration) -> Vec<String> {
+        self.quitting.store(true, Ordering::Relaxed);
+        let ids: Vec<u64> = self
+            .tasks
+            .iter()
+            .filter(|t| !t.writes)
+            .map(|t| t.id)
+            .collect();
+        for id in ids {
+            self.cancel(id);
+        }
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut unfinished = Vec::new();
+        for task in &mut self.tasks {
+            if task.status != TaskStatus::Running {
+                continue;
+            }
+            if tokio::time::timeout_at(deadline, &mut task.handle)
+                .await
+                .is_ok()
+            {
+                task.status = TaskStatus::Done;
+            } else {
+                task.handle.abort();
+                task.status = TaskStatus::Cancelled;
+                unfinished.push(task.label.clone());
+            }
+            task.finished = Some(Instant::now());
+        }
+        unfinished
+    }
+
+    /// Running tasks first (oldest first), then finished ones (newest first).
+    pub fn list(&self) -> Vec<&Task> {
+        let (mut running, mut finished): (Vec<&Task>, Vec<&Task>) = self
+            .tasks
+            .iter()
+            .partition(|t| t.status == TaskStatus::Running);
+        finished.sort_by_key(|t| std::cmp::Reverse(t.finished));
+        running.append(&mut finished);
+        running
+    }
+
+    pub fn running(&self) -> usize {
+        self.tasks
+            .iter()
+            .filter(|t| t.status == TaskStatus::Running)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_task_registry() {
+        let mut tasks = TaskRegistry::default();
+        let quick = tasks.spawn("Export report", None, async {});
+        let slow = tasks.spawn("Preload kv-a", None, std::future::pending());
+        let listing = tasks.spawn("List kv-b", Some("kv-b".into()), std::future::pending());
+        tokio::task::yield_now().await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        tasks.reap(Instant::now());
+        assert_eq!(tasks.running(), 2);
+        let order: Vec<u64> = tasks.list().iter().map(|t| t.id).collect();
+        assert_eq!(order, [slow, listing, quick]);
+
+        // Leaving kv-b aborts its listing but not the preload
+        tasks.cancel_scoped();
+        assert_eq!(tasks.running(), 1);
+        assert!(!tasks.cancel(listing)); // already cancelled
+        assert!(tasks.cancel(slow));
+        let statuses: Vec<TaskStatus> = tasks.list().iter().map(|t| t.status).collect();
+        assert_eq!(
+            statuses
+                .iter()
+                .filter(|s| **s == TaskStatus::Cancelled)
+                .count(),
+            2
+        );
+
+        // Only the newest finished tasks are kept
+        for i in 0..KEEP_FINISHED {
+            tasks.spawn(format!("task {}", i), None, async {});
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        tasks.reap(Instant::now());
+        assert_eq!(tasks.list().len(), KEEP_FINISHED);
+        assert!(tasks.list().iter().all(|t| t.id != quick));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_waits_for_writes() {
+        let mut tasks = TaskRegistry::default();
+        tasks.spawn("Preload kv-a", None, std::future::pending());
+        tasks.spawn_write("Update 'db-password' in kv-a", async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        });
+        let quitting = tasks.quitting();
+        tasks.spawn_write("Delete 500 secrets from kv-a", async move {
+            // One secret at a time until told to stop
+            while !quitting.load(Ordering::Relaxed) {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        });
+        tasks.spawn_write("Export report", std::future::pending());
+        assert_eq!(tasks.writing(), 3);
+
+        let unfinished = tasks.shutdown(Duration::from_millis(100)).await;
+        assert_eq!(unfinished, ["Export report"]);
+        assert_eq!(tasks.running(), 0);
+        let done = tasks
+            .list()
+            .iter()
+            .filter(|t| t.status == TaskStatus::Done)
+            .count();
+        assert_eq!(done, 2);
+    }
+}
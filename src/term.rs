@@ -0,0 +1,139 @@
+use std::io::{self, Write};
+
+use crossterm::cursor::Show;
+use crossterm::event::{DisableBracketedPaste, DisableFocusChange, DisableMouseCapture};
+use crossterm::execute;
+use crossterm::terminal::{LeaveAlternateScreen, disable_raw_mode};
+use tracing::warn;
+
+use crate::events::EventSender;
+use crate::model::AppEvent;
+
+/// Leave the TUI: raw mode off, back on the normal screen with the cursor shown. Harmless when
+/// the terminal was already restored, so every way out can call it.
+pub fn restore(out: &mut impl Write) -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        out,
+        DisableBracketedPaste,
+        DisableFocusChange,
+        DisableMouseCapture,
+        LeaveAlternateScreen,
+        Show
+    )
+}
+
+/// Restores the terminal when dropped, so every way out of the TUI, including an error
+/// returned from the event loop, leaves the shell usable.
+pub struct RestoreOnDrop<W: Write>(W);
+
+impl RestoreOnDrop<Box<dyn Write>> {
+    pub fn new(pick: bool) -> Self {
+        Self(screen(pick))
+    }
+}
+
+impl<W: Write> Drop for RestoreOnDrop<W> {
+    fn drop(&mut self) {
+        if let Err(e) = restore(&mut self.0) {
+            warn!("Cannot restore the terminal: {}", e);
+        }
+    }
+}
+
+/// The stream the TUI draws on: stderr in pick mode, where stdout carries the result.
+fn screen(pick: bool) -> Box<dyn Write> {
+    if pick {
+        Box::new(io::stderr())
+    } else {
+        Box::new(io::stdout())
+    }
+}
+
+/// Restore the terminal before a panic is reported, so the message is readable and the shell
+/// isn't left in raw mode on the alternate screen.
+pub fn install_panic_hook(pick: bool) {
+    let report = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore(&mut screen(pick));
+        report(info);
+    }));
+}
+
+/// SIGINT and SIGTERM (Ctrl+C on Windows): the first one quits like `q`, so writes in flight
+/// finish and the clipboard is cleared; a second one restores the terminal and exits at once.
+pub fn handle_signals(tx: EventSender, pick: bool) {
+    let mut signals = match Signals::new() {
+        Ok(signals) => signals,
+        Err(e) => {
+            warn!("Cannot handle signals: {}", e);
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        signals.next().await;
+        let _ = tx.send(AppEvent::Quit);
+        let code = signals.next().await;
+        let _ = restore(&mut screen(pick));
+        std::process::exit(code);
+    });
+}
+
+#[cfg(unix)]
+struct Signals {
+    interrupt: tokio::signal::unix::Signal,
+    terminate: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    fn new() -> io::Result<Self> {
+        use tokio::signal::unix::{SignalKind, signal};
+        Ok(Self {
+            interrupt: signal(SignalKind::interrupt())?,
+            terminate: signal(SignalKind::terminate())?,
+        })
+    }
+
+    /// Wait for the next signal; the exit code it calls for (128 + its number).
+    async fn next(&mut self) -> i32 {
+        tokio::select! {
+            _ = self.interrupt.recv() => 130,
+            _ = self.terminate.recv() => 143,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    fn new() -> io::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn next(&mut self) -> i32 {
+        let _ = tokio::signal::ctrl_c().await;
+        130
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restored_on_early_return() {
+        fn event_loop(out: &mut Vec<u8>, listed: Result<(), String>) -> Result<(), String> {
+            let _restore = RestoreOnDrop(out);
+            listed?;
+            Ok(())
+        }
+        let mut out = Vec::new();
+        assert!(event_loop(&mut out, Err("Failed to list secrets".into())).is_err());
+        let out = String::from_utf8(out).unwrap();
+        // Back on the normal screen, with the cursor shown
+        assert!(out.contains("\x1b[?1049l") && out.ends_with("\x1b[?25h"));
+    }
+}
@@ -0,0 +1,302 @@
+/// Minimal multi-line text editor state used by the Add/Edit modals.
+/// Cursor positions are char indices so multi-byte input is handled correctly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextArea {
+    lines: Vec<String>,
+    row: usize,
+    col: usize,
+    masked: bool, // render every char as '•' (screen-sharing)
+}
+
+impl Default for TextArea {
+    fn default() -> Self {
+        Self {
+            lines: vec![String::new()],
+            row: 0,
+            col: 0,
+            masked: false,
+        }
+    }
+}
+
+impl TextArea {
+    /// Build from existing text, placing the cursor at the end.
+    pub fn from_text(text: &str) -> Self {
+        let lines: Vec<String> = text.split('\n').map(|l| l.to_string()).collect();
+        let row = lines.len() - 1;
+        let col = lines[row].chars().count();
+        Self {
+            lines,
+            row,
+            col,
+            masked: false,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Text as it should be drawn: masked values keep their shape (one '•' per char, same
+    /// line breaks) so the cursor position still lines up.
+    pub fn display_text(&self) -> String {
+        if self.masked {
+            self.lines
+                .iter()
+                .map(|l| "•".repeat(l.chars().count()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            self.text()
+        }
+    }
+
+    pub fn is_masked(&self) -> bool {
+        self.masked
+    }
+
+    pub fn set_masked(&mut self, masked: bool) {
+        self.masked = masked;
+    }
+
+    /// (row, col) of the cursor in chars
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn byte_idx(&self, row: usize, col: usize) -> usize {
+        self.lines[row]
+            .char_indices()
+            .nth(col)
+            .map(|(i, _)| i)
+            .unwrap_or(self.lines[row].len())
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        if c == '\n' {
+            self.insert_newline();
+            return;
+        }
+        let idx = self.byte_idx(self.row, self.col);
+        self.lines[self.row].insert(idx, c);
+        self.col += 1;
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.chars() {
+            self.insert_char(c);
+        }
+    }
+
+    pub fn insert_newline(&mut self) {
+        let idx = self.byte_idx(self.row, self.col);
+        let rest = self.lines[self.row].split_off(idx);
+        self.lines.insert(self.row + 1, rest);
+        self.row += 1;
+        self.col = 0;
+    }
+
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            let idx = self.byte_idx(self.row, self.col - 1);
+            self.lines[self.row].remove(idx);
+            self.col -= 1;
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len(self.row);
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        if self.col < self.line_len(self.row) {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+
+    pub fn move_down(&mut self) {
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = self.col.min(self.line_len(self.row));
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.col < self.line_len(self.row) {
+            let idx = self.byte_idx(self.row, self.col);
+            self.lines[self.row].remove(idx);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        }
+    }
+
+    pub fn home(&mut self) {
+        self.col = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.col = self.line_len(self.row);
+    }
+
+    /// Move to the start of the previous word (alphanumeric run), crossing lines.
+    pub fn word_left(&mut self) {
+        if self.col == 0 {
+            self.move_left();
+            return;
+        }
+        let chars: Vec<char> = self.lines[self.row].chars().collect();
+        let mut col = self.col;
+        while col > 0 && !chars[col - 1].is_alphanumeric() {
+            col -= 1;
+        }
+        while col > 0 && chars[col - 1].is_alphanumeric() {
+            col -= 1;
+        }
+        self.col = col;
+    }
+
+    /// Move past the end of the next word (alphanumeric run), crossing lines.
+    pub fn word_right(&mut self) {
+        let chars: Vec<char> = self.lines[self.row].chars().collect();
+        if self.col == chars.len() {
+            self.move_right();
+            return;
+        }
+        let mut col = self.col;
+        while col < chars.len() && !chars[col].is_alphanumeric() {
+            col += 1;
+        }
+        while col < chars.len() && chars[col].is_alphanumeric() {
+            col += 1;
+        }
+        self.col = col;
+    }
+
+    /// Delete from the cursor back to the start of the previous word.
+    pub fn delete_word_back(&mut self) {
+        let (row, end) = (self.row, self.col);
+        self.word_left();
+        if self.row != row {
+            // At the start of a line: behave like backspace and join the lines.
+            self.row = row;
+            self.col = end;
+            self.backspace();
+            return;
+        }
+        let start = self.byte_idx(row, self.col);
+        let stop = self.byte_idx(row, end);
+        self.lines[row].replace_range(start..stop, "");
+    }
+
+    /// Scroll offset (rows, cols) that keeps the cursor inside a viewport of the given size.
+    pub fn scroll_for(&self, height: u16, width: u16) -> (u16, u16) {
+        let height = height.max(1) as usize;
+        let width = width.max(1) as usize;
+        let top = (self.row + 1).saturating_sub(height);
+        let left = (self.col + 1).saturating_sub(width);
+        (top as u16, left as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multiline_editing() {
+        let mut ta = TextArea::default();
+        for c in "-----BEGIN".chars() {
+            ta.insert_char(c);
+        }
+        ta.insert_newline();
+        for c in "abc".chars() {
+            ta.insert_char(c);
+        }
+        assert_eq!(ta.text(), "-----BEGIN\nabc");
+        assert_eq!(ta.cursor(), (1, 3));
+
+        // Up clamps the column, insert happens mid-line
+        ta.move_up();
+        assert_eq!(ta.cursor(), (0, 3));
+        ta.insert_char('X');
+        assert_eq!(ta.text(), "---X--BEGIN\nabc");
+
+        // Backspace at the start of a line joins it with the previous one
+        ta.move_down();
+        for _ in 0..3 {
+            ta.move_left();
+        }
+        ta.backspace();
+        assert_eq!(ta.text(), "---X--BEGINabc");
+        assert_eq!(ta.cursor(), (0, 11));
+    }
+
+    #[test]
+    fn test_cursor_movement_and_word_editing() {
+        let mut ta = TextArea::from_text("Server=tcp:db;Password=oops");
+        ta.home();
+        ta.word_right();
+        assert_eq!(ta.cursor(), (0, 6));
+        ta.word_right();
+        assert_eq!(ta.cursor(), (0, 10));
+        ta.delete();
+        assert_eq!(ta.text(), "Server=tcpdb;Password=oops");
+
+        ta.end();
+        ta.word_left();
+        assert_eq!(ta.cursor(), (0, 22));
+        ta.end();
+        ta.delete_word_back();
+        assert_eq!(ta.text(), "Server=tcpdb;Password=");
+
+        // Delete at end of line joins the next line
+        let mut ta = TextArea::from_text("ab\ncd");
+        ta.move_up();
+        ta.end();
+        ta.delete();
+        assert_eq!(ta.text(), "abcd");
+    }
+
+    #[test]
+    fn test_masked_display() {
+        let mut ta = TextArea::from_text("ab\ncde");
+        ta.set_masked(true);
+        assert_eq!(ta.display_text(), "••\n•••");
+        assert_eq!(ta.text(), "ab\ncde");
+        ta.set_masked(false);
+        assert_eq!(ta.display_text(), "ab\ncde");
+    }
+
+    #[test]
+    fn test_from_text_and_scroll() {
+        let ta = TextArea::from_text("a\nb\nc\nd\né");
+        assert_eq!(ta.cursor(), (4, 1));
+        assert_eq!(ta.scroll_for(3, 10), (2, 0));
+        assert_eq!(ta.text(), "a\nb\nc\nd\né");
+    }
+}
@@ -0,0 +1,131 @@
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use ratatui::style::Color;
+
+/// Colors of the UI by role, so a preset can restyle every screen at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub accent: Color,        // screen titles, selection bars, info toasts
+    pub highlight: Color,     // focused fields, selected rows, progress
+    pub text: Color,          // unfocused fields
+    pub muted: Color,         // hints, table headers, secondary details
+    pub dim: Color,           // hidden vaults, disabled secrets
+    pub success: Color,       // finished operations, added secrets
+    pub warning: Color,       // expiring soon, read-only, offline
+    pub error: Color,         // failures, expired, deletes
+    pub marked: Color,        // secrets marked for a bulk operation
+    pub background: Color,    // dialogs, and text on accent or warning bars
+    pub syntax: &'static str, // syntect theme of the value preview unless `theme.syntax` is set
+    pub monochrome: bool,     // NO_COLOR / --no-color: colors are stripped after drawing
+    pub ascii: bool,          // `theme.ascii`: no emoji, braille or box drawing
+}
+
+impl Theme {
+    /// For dark terminals; the colors akv-tui always had.
+    pub fn dark() -> Self {
+        Self {
+            accent: Color::Cyan,
+            highlight: Color::Yellow,
+            text: Color::White,
+            muted: Color::Gray,
+            dim: Color::DarkGray,
+            success: Color::Green,
+            warning: Color::Yellow,
+            error: Color::Red,
+            marked: Color::Magenta,
+            background: Color::Black,
+            syntax: "base16-ocean.dark",
+            monochrome: false,
+            ascii: false,
+        }
+    }
+
+    /// For light terminals: no yellow or cyan text, which is unreadable on white.
+    pub fn light() -> Self {
+        Self {
+            accent: Color::Blue,
+            highlight: Color::Rgb(0x87, 0x00, 0xaf),
+            text: Color::Black,
+            muted: Color::DarkGray,
+            dim: Color::Gray,
+            success: Color::Rgb(0x00, 0x87, 0x00),
+            warning: Color::Rgb(0xaf, 0x5f, 0x00),
+            error: Color::Rgb(0xd7, 0x00, 0x00),
+            marked: Color::Magenta,
+            background: Color::White,
+            syntax: "InspiredGitHub",
+            monochrome: false,
+            ascii: false,
+        }
+    }
+
+    /// Bright colors on black, with hints as readable as the rest.
+    pub fn high_contrast() -> Self {
+        Self {
+            accent: Color::LightCyan,
+            highlight: Color::LightYellow,
+            text: Color::White,
+            muted: Color::White,
+            dim: Color::Gray,
+            success: Color::LightGreen,
+            warning: Color::LightYellow,
+            error: Color::LightRed,
+            marked: Color::LightMagenta,
+            background: Color::Black,
+            syntax: "base16-eighties.dark",
+            monochrome: false,
+            ascii: false,
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "dark" => Ok(Self::dark()),
+            "light" => Ok(Self::light()),
+            "high-contrast" | "high_contrast" => Ok(Self::high_contrast()),
+            other => Err(format!(
+                "Unknown theme '{}' (expected dark, light or high-contrast)",
+                other
+            )),
+        }
+    }
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Select the theme for this run; must be called before the first frame is drawn.
+pub fn set_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// The selected theme (dark unless `set_theme` said otherwise).
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::dark)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_presets() {
+        assert_eq!("dark".parse::<Theme>().unwrap(), Theme::dark());
+        assert_eq!(
+            "High-Contrast".parse::<Theme>().unwrap(),
+            Theme::high_contrast()
+        );
+        let light: Theme = "light".parse().unwrap();
+        assert_ne!(light.text, light.background);
+        assert!("solarized".parse::<Theme>().is_err());
+        // Every preset's preview theme ships with syntect
+        let syntax = syntect::highlighting::ThemeSet::load_defaults();
+        for theme in [Theme::dark(), Theme::light(), Theme::high_contrast()] {
+            assert!(syntax.themes.contains_key(theme.syntax), "{}", theme.syntax);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Toasts shown at once; older ones are dropped first.
+const MAX_TOASTS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,    // status and progress, e.g. "Loading secrets..."
+    Success, // an operation finished
+    Warning, // not possible right now, e.g. offline or read-only
+    Error,
+}
+
+impl Severity {
+    /// How long a toast stays up. Errors stay longest, as they often say what to do next.
+    fn timeout(self) -> Duration {
+        Duration::from_secs(match self {
+            Severity::Info => 5,
+            Severity::Success => 6,
+            Severity::Warning => 10,
+            Severity::Error => 20,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toast {
+    pub severity: Severity,
+    pub text: String,
+    pub expires: Instant,
+}
+
+/// Messages stacked in the bottom-right corner, each dismissed after its timeout.
+#[derive(Debug, Default)]
+pub struct Toasts {
+    queue: VecDeque<Toast>, // oldest first
+}
+
+impl Toasts {
+    /// Show `text`. Info toasts are status lines: the next toast replaces the newest one,
+    /// so "Loading..." doesn't linger next to what it turned into.
+    pub fn push(&mut self, severity: Severity, text: String, now: Instant) {
+        if self
+            .queue
+            .back()
+            .is_some_and(|t| t.severity == Severity::Info)
+        {
+            self.queue.pop_back();
+        }
+        self.queue.retain(|t| t.text != text);
+        if self.queue.len() == MAX_TOASTS {
+            self.queue.pop_front();
+        }
+        self.queue.push_back(Toast {
+            severity,
+            text,
+            expires: now + severity.timeout(),
+        });
+    }
+
+    /// Drop toasts whose timeout has passed; true when any were dropped.
+    pub fn expire(&mut self, now: Instant) -> bool {
+        let before = self.queue.len();
+        self.queue.retain(|t| t.expires > now);
+        self.queue.len() != before
+    }
+
+    /// Drop the status line (an operation's "Loading..." once its result is on screen).
+    pub fn dismiss_info(&mut self) {
+        self.queue.retain(|t| t.severity != Severity::Info);
+    }
+
+    /// Oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Toast> {
+        self.queue.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toast_queue() {
+        let now = Instant::now();
+        let mut toasts = Toasts::default();
+        toasts.push(Severity::Info, "Loading secrets...".into(), now);
+        toasts.push(Severity::Error, "Failed to get secret".into(), now);
+        toasts.push(Severity::Info, "Loading keys...".into(), now);
+        toasts.push(Severity::Success, "Loaded 3 keys".into(), now);
+        // The status lines were replaced; the error is still there
+        let texts: Vec<&str> = toasts.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(texts, ["Failed to get secret", "Loaded 3 keys"]);
+
+        assert!(toasts.expire(now + Duration::from_secs(10)));
+        assert!(!toasts.expire(now + Duration::from_secs(11)));
+        assert_eq!(toasts.iter().next_back().unwrap().severity, Severity::Error);
+        toasts.expire(now + Duration::from_secs(30));
+        assert!(toasts.iter().next_back().is_none());
+
+        for i in 0..MAX_TOASTS + 2 {
+            toasts.push(Severity::Warning, format!("warning {}", i), now);
+        }
+        assert_eq!(toasts.iter().count(), MAX_TOASTS);
+        assert_eq!(toasts.iter().next().unwrap().text, "warning 2");
+        toasts.push(Severity::Info, "Loading...".into(), now);
+        toasts.dismiss_info();
+        assert_eq!(toasts.iter().next_back().unwrap().text, "warning 6");
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::BTreeSet;
+
+/// A row of the grouped secrets list (`z`): a group of secrets sharing a name prefix, or a
+/// secret shown by the rest of its name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeRow {
+    Group {
+        path: String,  // the prefix up to and including the delimiter, e.g. "payments--"
+        label: String, // e.g. "payments"
+        depth: usize,
+        count: usize, // secrets in the group and the groups inside it
+        expanded: bool,
+    },
+    Secret {
+        name: String,
+        label: String, // name without the prefixes of its groups
+        depth: usize,
+    },
+}
+
+impl TreeRow {
+    pub fn secret(&self) -> Option<&str> {
+        match self {
+            TreeRow::Secret { name, .. } => Some(name),
+            TreeRow::Group { .. } => None,
+        }
+    }
+
+    /// What the row stands for: the secret's name or the group's path.
+    pub fn key(&self) -> &str {
+        match self {
+            TreeRow::Secret { name, .. } => name,
+            TreeRow::Group { path, .. } => path,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        match self {
+            TreeRow::Secret { depth, .. } | TreeRow::Group { depth, .. } => *depth,
+        }
+    }
+}
+
+/// Paths of the groups `name` is in, outermost first ("a--b--c" is in "a--" and "a--b--").
+/// Empty parts (a leading or doubled delimiter) don't make a group.
+pub fn group_paths<'a>(name: &'a str, delimiter: &str) -> Vec<&'a str> {
+    let mut start = 0;
+    let mut paths = Vec::new();
+    for (i, _) in name.match_indices(delimiter) {
+        let end = i + delimiter.len();
+        if i > start && end < name.len() {
+            paths.push(&name[..end]);
+            start = end;
+        }
+    }
+    paths
+}
+
+#[derive(Default)]
+struct Node<'a> {
+    groups: Vec<(&'a str, Node<'a>)>, // by path, in order of their first secret
+    secrets: Vec<&'a str>,
+    count: usize,
+}
+
+impl<'a> Node<'a> {
+    fn insert(&mut self, paths: &[&'a str], name: &'a str) {
+        self.count += 1;
+        match paths.split_first() {
+            None => self.secrets.push(name),
+            Some((path, rest)) => {
+                let i = match self.groups.iter().position(|(p, _)| p == path) {
+                    Some(i) => i,
+                    None => {
+                        self.groups.push((path, Node::default()));
+                        self.groups.len() - 1
+                    }
+                };
+                self.groups[i].1.insert(rest, name);
+            }
+        }
+    }
+
+    fn flatten(&self, depth: usize, prefix: usize, ctx: &Flatten, rows: &mut Vec<TreeRow>) {
+        for (path, node) in &self.groups {
+            let expanded = ctx.expanded.contains(*path);
+            rows.push(TreeRow::Group {
+                path: path.to_string(),
+                label: path[prefix..path.len() - ctx.delimiter.len()].to_string(),
+                depth,
+                count: node.count,
+                expanded,
+            });
+            if expanded {
+                node.flatten(depth + 1, path.len(), ctx, rows);
+            }
+        }
+        rows.extend(self.secrets.iter().map(|name| TreeRow::Secret {
+            name: name.to_string(),
+            label: name[prefix..].to_string(),
+            depth,
+        }));
+    }
+}
+
+struct Flatten<'a> {
+    delimiter: &'a str,
+    expanded: &'a BTreeSet<String>,
+}
+
+/// Paths of every group in `names`, e.g. to open them all.
+pub fn all_group_paths(names: &[String], delimiter: &str) -> BTreeSet<String> {
+    names
+        .iter()
+        .flat_map(|name| group_paths(name, delimiter))
+        .map(String::from)
+        .collect()
+}
+
+/// The rows of `names` grouped on `delimiter`, like a file tree: groups before secrets on
+/// every level, both in the order of `names`. Only the groups in `expanded` show what's in them.
+pub fn tree_rows(names: &[String], delimiter: &str, expanded: &BTreeSet<String>) -> Vec<TreeRow> {
+    let mut root = Node::default();
+    for name in names {
+        root.insert(&group_paths(name, delimiter), name);
+    }
+    let mut rows = Vec::new();
+    let ctx = Flatten {
+        delimiter,
+        expanded,
+    };
+    root.flatten(0, 0, &ctx, &mut rows);
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_rows() {
+        assert_eq!(
+            group_paths("payments--db--password", "--"),
+            ["payments--", "payments--db--"]
+        );
+        assert!(group_paths("--odd--", "--").is_empty());
+
+        let names: Vec<String> = [
+            "api-key",
+            "payments--db--password",
+            "payments--api-key",
+            "payments--db--user",
+            "shop--token",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let show = |rows: Vec<TreeRow>| -> Vec<String> {
+            rows.iter()
+                .map(|r| match r {
+                    TreeRow::Group {
+                        label,
+                        depth,
+                        count,
+                        ..
+                    } => format!("{}{}/ ({})", " ".repeat(*depth), label, count),
+                    TreeRow::Secret { label, depth, .. } => {
+                        format!("{}{}", " ".repeat(*depth), label)
+                    }
+                })
+                .collect()
+        };
+
+        let collapsed = tree_rows(&names, "--", &BTreeSet::new());
+        assert_eq!(show(collapsed), ["payments/ (3)", "shop/ (1)", "api-key"]);
+
+        let expanded = all_group_paths(&names, "--");
+        assert_eq!(expanded.len(), 3);
+        let rows = tree_rows(&names, "--", &expanded);
+        assert_eq!(
+            show(rows.clone()),
+            [
+                "payments/ (3)",
+                " db/ (2)",
+                "  password",
+                "  user",
+                " api-key",
+                "shop/ (1)",
+                " token",
+                "api-key"
+            ]
+        );
+        assert_eq!(rows[2].secret(), Some("payments--db--password"));
+    }
+}
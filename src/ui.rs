@@ -1,26 +1,664 @@
+use std::time::{Duration, Instant};
+
 use ratatui::{
     Frame,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
-    text::Span,
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Cell, List, ListItem, ListState, Paragraph, Row, Table, TableState, Wrap,
+    },
+};
+use throbber_widgets_tui::{ASCII, BRAILLE_SIX, Set, Throbber, WhichUse};
+use time::OffsetDateTime;
+
+use crate::api_log::{self, ApiCall};
+use crate::app::{App, MESSAGE_HISTORY, expires_in, format_age, validate_secret_name};
+use crate::audit::Issue;
+use crate::columns::Column;
+use crate::connstr::ConnStringPicker;
+use crate::crypto::{CryptoField, CryptoForm, CryptoOp, algorithm};
+use crate::encoding::expand_path;
+use crate::generator::GeneratorOptions;
+use crate::json_view::JsonViewer;
+use crate::model::{
+    Access, AddInputMode, AppScreen, BulkItemStatus, Failure, ListingChanges, Modal, SecretMeta,
+    SignInState, SortKey, SubmitState, Tenant, Vault,
 };
-use throbber_widgets_tui::{BRAILLE_SIX, Throbber, WhichUse};
+use crate::nav::visible_rows;
+use crate::report::ReportFormat;
+use crate::rotation::RotationForm;
+use crate::tasks::TaskStatus;
+use crate::textarea::TextArea;
+use crate::theme::{Theme, theme};
+use crate::toast::Severity;
+use crate::tree::TreeRow;
 
-use crate::app::App;
-use crate::model::{AddInputMode, AppScreen, Modal};
+/// Smallest terminal the screens are laid out for.
+const MIN_WIDTH: u16 = 80;
+const MIN_HEIGHT: u16 = 24;
 
 /// Draw router
 pub fn draw_ui(f: &mut Frame<'_>, app: &mut App) {
+    let area = f.area();
+    if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+        draw_too_small(f);
+        return;
+    }
     match app.screen {
-        AppScreen::Welcome => draw_welcome_screen(f),
+        AppScreen::Welcome => draw_welcome_screen(f, app),
         AppScreen::VaultSelection => draw_vault_selection_screen(f, app),
         AppScreen::Secrets => draw_secrets_screen(f, app),
+        AppScreen::Expiring => draw_expiring_screen(f, app),
+        AppScreen::Audit => draw_audit_screen(f, app),
+        AppScreen::Keys => draw_keys_screen(f, app),
+        AppScreen::History => draw_history_screen(f, app),
+    }
+    // Help is available on every screen, so it's drawn here rather than by the screens
+    if let Some(Modal::Help { lines, scroll }) = &app.modal {
+        draw_help(f, lines, *scroll, f.area());
+    }
+    if app.api_overlay {
+        draw_api_overlay(f, &api_log::recent(), Instant::now());
+    }
+    draw_toasts(f, app);
+    if theme().monochrome {
+        strip_colors(f.buffer_mut(), theme());
+    }
+    if theme().ascii {
+        to_ascii(f.buffer_mut());
+    }
+}
+
+/// An emoji for a title, dropped in ASCII mode.
+fn icon(emoji: &'static str) -> &'static str {
+    if theme().ascii { "" } else { emoji }
+}
+
+fn throbber_set() -> Set {
+    if theme().ascii { ASCII } else { BRAILLE_SIX }
+}
+
+/// ASCII mode: replace box drawing and the symbols the UI uses with look-alikes, for
+/// consoles and fonts that render them as boxes. Anything else (e.g. a secret's name)
+/// is left alone.
+fn to_ascii(buf: &mut Buffer) {
+    for cell in buf.content.iter_mut() {
+        let mut chars = cell.symbol().chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            continue;
+        };
+        let ascii = match c {
+            '─' | '━' | '═' => "-",
+            '│' | '┃' | '║' => "|",
+            '\u{2500}'..='\u{257f}' => "+",
+            '—' | '·' => "-",
+            'ℹ' => "i",
+            '✓' => "+",
+            '✗' => "x",
+            '⚠' => "!",
+            '★' | '●' | '•' => "*",
+            '⟳' => "~",
+            '←' => "<",
+            '→' | '▸' => ">",
+            '↑' => "^",
+            '↓' | '▾' => "v",
+            '…' => ".",
+            _ => continue,
+        };
+        cell.set_symbol(ascii);
+    }
+}
+
+/// Monochrome mode: drop every color but keep what it set apart, so selections, focused
+/// fields and status bars stay visible with the terminal's default colors.
+fn strip_colors(buf: &mut Buffer, theme: &Theme) {
+    for cell in buf.content.iter_mut() {
+        let mut modifier = cell.modifier;
+        if cell.bg != Color::Reset && cell.bg != theme.background {
+            modifier |= Modifier::REVERSED;
+        }
+        if [theme.accent, theme.highlight, theme.warning, theme.error].contains(&cell.fg) {
+            modifier |= Modifier::BOLD;
+        } else if cell.fg == theme.dim {
+            modifier |= Modifier::DIM;
+        }
+        cell.set_style(Style::reset().add_modifier(modifier));
+    }
+}
+
+/// Toasts stacked in the bottom-right corner, newest at the bottom, on top of everything.
+fn draw_toasts(f: &mut Frame<'_>, app: &App) {
+    let area = f.area();
+    let width = (area.width / 2).clamp(area.width.min(30), 60);
+    let text_width = width.saturating_sub(2).max(1) as usize;
+    let mut bottom = area.bottom();
+    for toast in app.toasts.iter().rev() {
+        let (color, icon) = match toast.severity {
+            Severity::Info => (theme().accent, "ℹ"),
+            Severity::Success => (theme().success, "✓"),
+            Severity::Warning => (theme().warning, "⚠"),
+            Severity::Error => (theme().error, "✗"),
+        };
+        // Long messages (e.g. the remedy for a denied request) wrap onto up to six lines
+        let lines = toast.text.chars().count().div_ceil(text_width).clamp(1, 6) as u16;
+        if bottom < area.y + lines + 2 {
+            break;
+        }
+        bottom -= lines + 2;
+        let toast_area = Rect {
+            x: area.right() - width,
+            y: bottom,
+            width,
+            height: lines + 2,
+        };
+        let p = Paragraph::new(toast.text.clone())
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(color))
+                    .title(Span::styled(
+                        format!(" {} ", icon),
+                        Style::default().fg(color),
+                    )),
+            );
+        f.render_widget(ratatui::widgets::Clear, toast_area);
+        f.render_widget(p, toast_area);
+    }
+}
+
+/// Calls slower than this are highlighted in the API overlay.
+const SLOW_CALL: Duration = Duration::from_secs(1);
+
+/// Recent ARM and Key Vault calls (`F12`) along the bottom, newest first, drawn over the
+/// screen without taking the keys.
+fn draw_api_overlay(f: &mut Frame<'_>, calls: &[ApiCall], now: Instant) {
+    let area = f.area();
+    let height = area.height.min(14);
+    let area = Rect {
+        y: area.bottom() - height,
+        height,
+        ..area
+    };
+    f.render_widget(ratatui::widgets::Clear, area);
+    let slowest = calls.iter().map(|c| c.duration).max().unwrap_or_default();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme().accent))
+        .title(format!(
+            "API Calls (last {}, slowest {} ms) [F12: close]",
+            calls.len(),
+            slowest.as_millis()
+        ))
+        .style(Style::default().bg(theme().background));
+    if calls.is_empty() {
+        let p = Paragraph::new("No calls yet")
+            .style(Style::default().fg(theme().muted))
+            .block(block);
+        f.render_widget(p, area);
+        return;
+    }
+    let warn = |on: bool| {
+        if on {
+            Style::default().fg(theme().warning)
+        } else {
+            Style::default()
+        }
+    };
+    let rows: Vec<Row> = calls
+        .iter()
+        .map(|c| {
+            let age = c.at.map(|at| format_age(now.saturating_duration_since(at)));
+            let status = match c.status {
+                Some(status) => Span::styled(
+                    status.to_string(),
+                    match status {
+                        429 => Style::default().fg(theme().warning),
+                        400.. => Style::default().fg(theme().error),
+                        _ => Style::default().fg(theme().success),
+                    },
+                ),
+                None => Span::styled("failed", Style::default().fg(theme().error)),
+            };
+            Row::new([
+                Cell::from(age.unwrap_or_default()),
+                Cell::from(c.method.clone()),
+                Cell::from(status),
+                Cell::from(Span::styled(
+                    format!("{} ms", c.duration.as_millis()),
+                    warn(c.duration >= SLOW_CALL),
+                )),
+                Cell::from(Span::styled(c.retries.to_string(), warn(c.retries > 0))),
+                Cell::from(format!("{}{}", c.host, c.path)),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(4),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(9),
+            Constraint::Length(7),
+            Constraint::Min(20),
+        ],
+    )
+    .header(
+        Row::new(["Age", "Method", "Status", "Time", "Retries", "Request"])
+            .style(Style::default().fg(theme().muted)),
+    )
+    .block(block);
+    f.render_widget(table, area);
+}
+
+/// Past toasts, newest first, with their time (UTC) and age.
+fn draw_history_screen(f: &mut Frame<'_>, app: &mut App) {
+    let area = f.area();
+    let block = Block::default()
+        .title(format!(
+            "{}Messages ({} of the last {}) [j/k: scroll] [Esc: back]",
+            icon("📜 "),
+            app.history.len(),
+            MESSAGE_HISTORY
+        ))
+        .borders(Borders::ALL)
+        .title_alignment(Alignment::Center);
+
+    let now = OffsetDateTime::now_utc();
+    let lines: Vec<Line> = if app.history.is_empty() {
+        vec![Line::from("No messages yet")]
+    } else {
+        app.history
+            .iter()
+            .rev()
+            .map(|(at, message)| {
+                let age = (now - *at).try_into().unwrap_or_default();
+                Line::from(vec![
+                    Span::styled(
+                        format!(
+                            "{:02}:{:02}:{:02} ({:>3} ago)  ",
+                            at.hour(),
+                            at.minute(),
+                            at.second(),
+                            format_age(age)
+                        ),
+                        Style::default().fg(theme().muted),
+                    ),
+                    Span::raw(message.clone()),
+                ])
+            })
+            .collect()
+    };
+    let p = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((app.history_scroll, 0));
+    f.render_widget(p, area);
+}
+
+/// Secrets of all cached vaults expiring within the configured window, soonest first.
+/// Red once expired or within a week, yellow otherwise.
+fn draw_expiring_screen(f: &mut Frame<'_>, app: &mut App) {
+    let area = f.area();
+    let mut block = Block::default()
+        .title(format!(
+            "{}Expiring within {} days ({} cached vaults) [Enter: open] [?: help] [Esc: back]",
+            icon("⏳ "),
+            app.expiry_window.as_secs() / 86400,
+            app.vault_secret_cache.len()
+        ))
+        .borders(Borders::ALL)
+        .title_alignment(Alignment::Center);
+    if app.offline {
+        block = block.title(offline_badge());
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let rows: Vec<Row> = if app.expiring.is_empty() {
+        vec![Row::new([Cell::from(
+            "No cached secret expires within the window",
+        )])]
+    } else {
+        app.expiring
+            .iter()
+            .map(|e| {
+                let color =
+                    expiry_color(e.expires, now, app.expiry_window).unwrap_or(theme().warning);
+                Row::new([
+                    Cell::from(e.expires.date().to_string()),
+                    Cell::from(expires_in(e.expires, now)),
+                    Cell::from(e.vault.clone()),
+                    Cell::from(e.name.clone()),
+                ])
+                .style(Style::default().fg(color))
+            })
+            .collect()
+    };
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(12),
+            Constraint::Length(16),
+            Constraint::Percentage(30),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(
+        Row::new(["Expires", "", "Vault", "Secret"]).style(
+            Style::default()
+                .fg(theme().muted)
+                .add_modifier(Modifier::BOLD),
+        ),
+    )
+    .block(block)
+    .row_highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED));
+    f.render_stateful_widget(table, area, &mut app.expiring_state);
+}
+
+/// Audit findings, one row per flagged secret. Disabled-but-referenced secrets are red.
+fn draw_audit_screen(f: &mut Frame<'_>, app: &mut App) {
+    let area = f.area();
+    let scope = match &app.audit_vault {
+        Some(vault) => vault.clone(),
+        None => format!("{} cached vaults", app.vault_secret_cache.len()),
+    };
+    let mut block = Block::default()
+        .title(format!(
+            "{}Audit of {} ({} flagged) [Enter: open] [?: help] [Esc: back]",
+            icon("🛡 "),
+            scope,
+            app.audit.len()
+        ))
+        .borders(Borders::ALL)
+        .title_alignment(Alignment::Center);
+    if app.offline {
+        block = block.title(offline_badge());
+    }
+
+    let rows: Vec<Row> = if app.audit.is_empty() {
+        vec![Row::new([Cell::from("Nothing flagged")])]
+    } else {
+        app.audit
+            .iter()
+            .map(|finding| {
+                let issues: Vec<&str> = finding.issues.iter().map(|i| i.label()).collect();
+                let color = if finding.issues.contains(&Issue::DisabledReferenced) {
+                    theme().error
+                } else {
+                    theme().warning
+                };
+                Row::new([
+                    Cell::from(finding.vault.clone()),
+                    Cell::from(finding.name.clone()),
+                    Cell::from(issues.join(", ")),
+                ])
+                .style(Style::default().fg(color))
+            })
+            .collect()
+    };
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(35),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(
+        Row::new(["Vault", "Secret", "Issues"]).style(
+            Style::default()
+                .fg(theme().muted)
+                .add_modifier(Modifier::BOLD),
+        ),
+    )
+    .block(block)
+    .row_highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED));
+    f.render_stateful_widget(table, area, &mut app.audit_state);
+}
+
+/// Keys of the current vault. Disabled keys are dimmed.
+fn draw_keys_screen(f: &mut Frame<'_>, app: &mut App) {
+    let area = f.area();
+    let vault = app
+        .current_vault
+        .as_ref()
+        .map(|(name, _)| name.as_str())
+        .unwrap_or_default();
+    let block = Block::default()
+        .title(format!(
+            "{}Keys of {} [Enter: crypto] [P: rotation policy] [?: help] [Esc: secrets]",
+            icon("🗝 "),
+            vault
+        ))
+        .borders(Borders::ALL)
+        .title_alignment(Alignment::Center);
+    let inner = block.inner(area);
+
+    let rows: Vec<Row> = if app.keys.is_empty() && !app.loading {
+        vec![Row::new([Cell::from("No keys in this vault")])]
+    } else {
+        app.keys
+            .iter()
+            .map(|key| {
+                let size = match (key.size, &key.curve) {
+                    (Some(bits), _) => bits.to_string(),
+                    (None, Some(curve)) => curve.clone(),
+                    (None, None) => String::new(),
+                };
+                let row = Row::new([
+                    Cell::from(key.name.clone()),
+                    Cell::from(key.kty.clone()),
+                    Cell::from(size),
+                    Cell::from(if key.enabled { "yes" } else { "no" }),
+                    Cell::from(key.ops.join(", ")),
+                    Cell::from(key.rotation.clone().unwrap_or_else(|| "—".into())),
+                ]);
+                if key.enabled {
+                    row
+                } else {
+                    row.style(Style::default().fg(theme().dim))
+                }
+            })
+            .collect()
+    };
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(22),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Percentage(25),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(
+        Row::new(["Key", "Type", "Size", "Enabled", "Operations", "Rotation"]).style(
+            Style::default()
+                .fg(theme().muted)
+                .add_modifier(Modifier::BOLD),
+        ),
+    )
+    .block(block)
+    .row_highlight_style(
+        Style::default()
+            .fg(theme().accent)
+            .add_modifier(Modifier::BOLD),
+    );
+    f.render_stateful_widget(table, area, &mut app.keys_state);
+
+    if app.loading {
+        let throbber = Throbber::default()
+            .label(" Loading keys...")
+            .style(Style::default().fg(theme().highlight))
+            .throbber_set(throbber_set())
+            .use_type(WhichUse::Spin);
+        let spinner_area = Rect {
+            x: inner.x + 2,
+            y: inner.bottom().saturating_sub(1),
+            width: 20,
+            height: 1,
+        };
+        let mut ts = app.throbber_state.clone();
+        f.render_stateful_widget(throbber, spinner_area, &mut ts);
+    }
+
+    match &app.modal {
+        Some(Modal::Crypto(form)) => draw_crypto(f, form, centered_rect(70, 60, area)),
+        Some(Modal::Rotation(form)) => draw_rotation(f, form, area),
+        Some(Modal::ErrorDetails { failure, scroll }) => {
+            draw_error_details(f, failure, *scroll, area)
+        }
+        _ => {}
+    }
+}
+
+/// Crypto dialog: operation, input source (file or clipboard), signature for verify, result.
+fn draw_crypto(f: &mut Frame<'_>, form: &CryptoForm, area: Rect) {
+    f.render_widget(ratatui::widgets::Clear, area);
+    let key = &form.key;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "Key '{}' ({}{})",
+            key.name,
+            key.kty,
+            algorithm(key, form.op())
+                .map(|alg| format!(", {}", alg))
+                .unwrap_or_default()
+        ))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let verify = form.op() == CryptoOp::Verify;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Length(if verify { 3 } else { 0 }),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let focus_style = |field: CryptoField| {
+        if form.focus == field {
+            Style::default().fg(theme().highlight)
+        } else {
+            Style::default().fg(theme().text)
+        }
+    };
+    let mut ops = vec![Span::styled(
+        "Operation: ",
+        focus_style(CryptoField::Operation),
+    )];
+    for (i, op) in form.ops.iter().enumerate() {
+        let style = if i == form.selected {
+            Style::default()
+                .fg(theme().background)
+                .bg(theme().accent)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme().muted)
+        };
+        ops.push(Span::styled(format!(" {} ", op.name()), style));
+        ops.push(Span::raw(" "));
+    }
+    f.render_widget(Paragraph::new(Line::from(ops)), chunks[0]);
+
+    let input_title = if form.op().takes_base64() {
+        "Base64 input file (empty: clipboard)"
+    } else {
+        "Input file (empty: clipboard)"
+    };
+    draw_text_area(
+        f,
+        &form.input,
+        Block::default().borders(Borders::ALL).title(input_title),
+        focus_style(CryptoField::Input),
+        chunks[1],
+        form.focus == CryptoField::Input,
+    );
+    if verify {
+        draw_text_area(
+            f,
+            &form.signature,
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Signature (base64)"),
+            focus_style(CryptoField::Signature),
+            chunks[2],
+            form.focus == CryptoField::Signature,
+        );
+    }
+
+    let result = match (&form.result, form.pending) {
+        (_, true) => Paragraph::new("Running...").style(Style::default().fg(theme().highlight)),
+        (Some(Ok(output)), _) => {
+            Paragraph::new(output.as_str()).style(Style::default().fg(theme().success))
+        }
+        (Some(Err(e)), _) => Paragraph::new(e.as_str()).style(Style::default().fg(theme().error)),
+        (None, _) => Paragraph::new(""),
+    };
+    f.render_widget(
+        result
+            .wrap(Wrap { trim: false })
+            .block(Block::default().borders(Borders::TOP).title("Result")),
+        chunks[3],
+    );
+    let help = Paragraph::new(
+        "Tab: next field | ←/→: operation | Enter: run | Ctrl+Y: copy result | Esc: close",
+    )
+    .style(Style::default().fg(theme().muted))
+    .alignment(Alignment::Center);
+    f.render_widget(help, chunks[4]);
+}
+
+/// Red once expired or within a week, yellow within `window`.
+fn expiry_color(expires: OffsetDateTime, now: OffsetDateTime, window: Duration) -> Option<Color> {
+    let left = expires - now;
+    if left <= time::Duration::days(7) {
+        Some(theme().error)
+    } else if left <= window {
+        Some(theme().warning)
+    } else {
+        None
     }
 }
 
-/// Welcome ASCII art screen (centered)
-fn draw_welcome_screen(f: &mut Frame<'_>) {
+/// Placeholder instead of layouts that don't fit (and would be cut off or overlap).
+fn draw_too_small(f: &mut Frame<'_>) {
+    let area = f.area();
+    let text = format!(
+        "Terminal too small (min {}x{}, now {}x{})",
+        MIN_WIDTH, MIN_HEIGHT, area.width, area.height
+    );
+    let p = Paragraph::new(text)
+        .style(Style::default().fg(theme().warning))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    // Vertically centered when there is room for it
+    let y = area.y + area.height.saturating_sub(1) / 2;
+    f.render_widget(
+        p,
+        Rect {
+            y,
+            height: area.bottom() - y,
+            ..area
+        },
+    );
+}
+
+/// Welcome ASCII art screen (centered), offering to resume the last opened vault
+fn draw_welcome_screen(f: &mut Frame<'_>, app: &App) {
     let area = f.area();
     let art = r#"
      e      888  /   Y88b      / 
@@ -36,11 +674,23 @@ fn draw_welcome_screen(f: &mut Frame<'_>) {
         .title("Azure KeyVault TUI")
         .title_alignment(Alignment::Center);
 
-    let paragraph = Paragraph::new(art)
+    let mut lines: Vec<Line> = art.lines().map(Line::from).collect();
+    if let Some(last) = app.state.recent.first() {
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            format!(
+                "Press Enter to resume {} — any other key for the vault list",
+                app.vault_label(&last.name)
+            ),
+            Style::default().fg(theme().highlight),
+        ));
+    }
+
+    let paragraph = Paragraph::new(lines)
         .alignment(Alignment::Center)
         .style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme().accent)
                 .add_modifier(Modifier::BOLD),
         )
         .block(block);
@@ -53,49 +703,122 @@ fn draw_vault_selection_screen(f: &mut Frame<'_>, app: &mut App) {
     let area = f.area();
 
     let title = if app.vault_search_mode {
-        format!("🔐 Select Vault (Search: {}_ )", app.vault_search_query)
+        format!(
+            "{}Select Vault (Search: {}_ )",
+            icon("🔐 "),
+            app.vault_search_query
+        )
     } else if !app.vault_search_query.is_empty() {
-        format!("🔐 Select Vault (Filter: {})", app.vault_search_query)
+        format!(
+            "{}Select Vault (Filter: {})",
+            icon("🔐 "),
+            app.vault_search_query
+        )
     } else {
-        "🔐 Select an Azure Key Vault (Press '/' to filter)".to_string()
+        format!(
+            "{}Select an Azure Key Vault [/: filter] [?: help]",
+            icon("🔐 ")
+        )
+    };
+    let title = match &app.profile {
+        Some(profile) => format!("{} · {}", title, profile),
+        None => title,
     };
 
-    let block = Block::default()
+    let mut block = Block::default()
         .title(title)
         .borders(Borders::ALL)
         .title_alignment(Alignment::Center);
+    if app.offline {
+        block = block.title(offline_badge());
+    }
+    if let Some(badge) = tasks_badge(app) {
+        block = block.title(badge);
+    }
 
     let inner = block.inner(area);
 
-    let items: Vec<ListItem> = if app.displayed_vaults.is_empty() {
-        if app.vaults.is_empty() {
-            vec![ListItem::new("No vaults found yet...")]
+    let placeholder = if app.displayed_vaults.is_empty() {
+        Some(if app.vaults.is_empty() {
+            "No vaults found yet..."
         } else {
-            vec![ListItem::new("No matching vaults...")]
-        }
+            "No matching vaults..."
+        })
     } else {
-        app.displayed_vaults
+        None
+    };
+
+    let rows: Vec<Row> = match placeholder {
+        Some(text) => vec![Row::new([Cell::from(text)])],
+        None => app
+            .displayed_vaults
             .iter()
-            .map(|(n, _)| ListItem::new(n.clone()))
-            .collect()
+            .map(|v| {
+                let star = if app.favorites.contains(&v.name) {
+                    "★ "
+                } else {
+                    ""
+                };
+                let name = match &v.alias {
+                    Some(alias) => Line::from(vec![
+                        Span::raw(format!("{}{} ", star, alias)),
+                        Span::styled(format!("({})", v.name), Style::default().fg(theme().dim)),
+                    ]),
+                    None => Line::from(format!("{}{}", star, v.name)),
+                };
+                let row = Row::new([
+                    Cell::from(name),
+                    Cell::from(v.resource_group.clone().unwrap_or_default()),
+                    Cell::from(v.location.clone().unwrap_or_default()),
+                ]);
+                if app.is_hidden(&v.name) {
+                    row.style(Style::default().fg(theme().dim))
+                } else {
+                    row
+                }
+            })
+            .collect(),
     };
 
-    let list = List::new(items).block(block).highlight_style(
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(45),
+            Constraint::Percentage(35),
+            Constraint::Percentage(20),
+        ],
+    )
+    .header(
+        Row::new(["Vault", "Resource group", "Region"]).style(
+            Style::default()
+                .fg(theme().muted)
+                .add_modifier(Modifier::BOLD),
+        ),
+    )
+    .block(block)
+    .row_highlight_style(
         Style::default()
-            .fg(Color::Cyan)
+            .fg(theme().accent)
             .add_modifier(Modifier::BOLD),
     );
-    f.render_stateful_widget(list, inner, &mut app.vault_list_state);
+    // Rows start below the border and the header
+    app.list_rows = Rect {
+        x: inner.x + 1,
+        y: inner.y + 2,
+        width: inner.width.saturating_sub(2),
+        height: inner.height.saturating_sub(3),
+    };
+    f.render_stateful_widget(table, inner, &mut app.vault_list_state);
 
     if app.loading {
         let throbber = Throbber::default()
             .label(" Discovering vaults...")
-            .style(Style::default().fg(Color::Yellow))
-            .throbber_set(BRAILLE_SIX)
+            .style(Style::default().fg(theme().highlight))
+            .throbber_set(throbber_set())
             .use_type(WhichUse::Spin);
         let spinner_area = Rect {
             x: inner.x + 2,
-            y: inner.bottom() - 2,
+            y: inner.bottom().saturating_sub(2),
             width: 28,
             height: 1,
         };
@@ -103,32 +826,79 @@ fn draw_vault_selection_screen(f: &mut Frame<'_>, app: &mut App) {
         f.render_stateful_widget(throbber, spinner_area, &mut ts);
     }
 
-    let footer = Paragraph::new(app.message.clone().unwrap_or_default())
-        .block(Block::default().borders(Borders::ALL).title("Message"))
-        .style(Style::default().fg(Color::Cyan));
-    let footer_area = Rect {
-        x: area.x,
-        y: area.bottom() - 3,
-        width: area.width,
-        height: 3,
-    };
-    f.render_widget(footer, footer_area);
+    match &app.modal {
+        Some(Modal::SignIn(state)) => draw_sign_in(f, state, centered_rect(70, 60, area)),
+        Some(Modal::TenantPicker { tenants, selected }) => {
+            draw_tenant_picker(f, tenants, *selected, app.tenant.as_deref(), area)
+        }
+        Some(Modal::Export { vault, names, path }) => {
+            draw_export(f, vault.as_deref(), names.as_deref(), path, area)
+        }
+        Some(Modal::VaultInfo(vault)) => draw_vault_info(f, vault, area),
+        Some(Modal::Tasks { selected }) => draw_tasks(f, app, *selected, area),
+        Some(Modal::ErrorDetails { failure, scroll }) => {
+            draw_error_details(f, failure, *scroll, area)
+        }
+        _ => {}
+    }
 }
 
 fn draw_secrets_screen(f: &mut Frame<'_>, app: &mut App) {
     let area = f.area();
-    let outer_block = Block::default().borders(Borders::ALL).title(Span::styled(
-        "Azure Key Vault Manager",
+    let title = match &app.profile {
+        Some(profile) => format!("Azure Key Vault Manager · {}", profile),
+        None => "Azure Key Vault Manager".to_string(),
+    };
+    let mut outer_block = Block::default().borders(Borders::ALL).title(Span::styled(
+        title,
         Style::default()
-            .fg(Color::Cyan)
+            .fg(theme().accent)
             .add_modifier(Modifier::BOLD),
     ));
+    // Who we are signed in as, so prod isn't touched with the wrong account by accident
+    if let Some(identity) = &app.identity {
+        let label = match &identity.tenant {
+            Some(tenant) => format!(
+                " {}{} · tenant {} ",
+                icon("👤 "),
+                identity.principal,
+                tenant
+            ),
+            None => format!(" {}{} ", icon("👤 "), identity.principal),
+        };
+        outer_block = outer_block.title(
+            Line::from(Span::styled(label, Style::default().fg(theme().success))).right_aligned(),
+        );
+    }
+    // Permission preflight: whether the vault's secrets can be changed
+    if let Some(access) = app.access {
+        let (label, color) = match access {
+            Access::ReadOnly => (" RO ", theme().warning),
+            Access::ReadWrite => (" RW ", theme().success),
+        };
+        outer_block = outer_block.title(
+            Line::from(Span::styled(
+                label,
+                Style::default()
+                    .fg(theme().background)
+                    .bg(color)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .right_aligned(),
+        );
+    }
+    if app.offline {
+        outer_block = outer_block.title(offline_badge());
+    }
+    if let Some(badge) = tasks_badge(app) {
+        outer_block = outer_block.title(badge);
+    }
     f.render_widget(outer_block, area);
     let inner = Rect {
         x: area.x + 1,
         y: area.y + 1,
-        width: area.width - 2,
-        height: area.height - 2,
+        width: area.width.saturating_sub(2),
+        height: area.height.saturating_sub(2),
     };
 
     let chunks = Layout::default()
@@ -138,84 +908,232 @@ fn draw_secrets_screen(f: &mut Frame<'_>, app: &mut App) {
             Constraint::Length(3),
             Constraint::Min(4),
             Constraint::Length(3),
-            Constraint::Length(3),
         ])
         .split(inner);
 
     let vault_label = app
         .current_vault
         .as_ref()
-        .map(|(n, _)| format!(" (Vault: {})", n))
+        .map(|(n, _)| match app.cache_age(n) {
+            Some(age) => format!(
+                " (Vault: {} · {} · cache: {} old)",
+                app.vault_label(n),
+                app.secret_count(),
+                format_age(age)
+            ),
+            None => format!(" (Vault: {} · {})", app.vault_label(n), app.secret_count()),
+        })
         .unwrap_or_default();
-    let header_text = if app.search_mode {
-        format!("🔍 Search: {}_", app.search_query)
+    let watch_label = match app.watch_next {
+        Some(_) => format!(" (watching every {}s)", app.watch_interval.as_secs()),
+        None => String::new(),
+    };
+    let marked_label = if app.marked.is_empty() {
+        String::new()
     } else {
+        format!(" ({} marked)", app.marked.len())
+    };
+    let header_text = if app.search_mode {
+        format!(
+            "{}Search: {}_  ({})",
+            icon("🔍 "),
+            app.search_query,
+            app.secret_count()
+        )
+    } else if app.pick_name_only.is_some() {
         format!(
-            "🔑 Azure Key Vault TUI{} — [q: quit] [v: vault] [/: search] [a: add] [e: edit] [d: delete] [r: refresh] [Enter: copy]",
+            "{}Pick a secret{} — [Enter: pick] [/: search] [v: vault] [q: cancel]",
+            icon("🔑 "),
             vault_label
         )
+    } else {
+        let write_hints = if app.offline || app.access == Some(Access::ReadOnly) {
+            ""
+        } else {
+            "[a: add] [e: edit] [d: delete] "
+        };
+        format!(
+            "{}Azure Key Vault TUI{}{}{} — [?: help] [Enter: copy] [/: search] {}[v: vault] [q: quit]",
+            icon("🔑 "),
+            vault_label,
+            watch_label,
+            marked_label,
+            write_hints
+        )
     };
 
     let header = Paragraph::new(header_text)
         .style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme().accent)
                 .add_modifier(Modifier::BOLD),
         )
         .block(Block::default().borders(Borders::ALL).title("Header"));
     f.render_widget(header, chunks[0]);
 
-    let items: Vec<ListItem> = app
-        .displayed_secrets
-        .iter()
-        .map(|s| ListItem::new(s.clone()))
+    // While watching, the last changes found in this vault are highlighted
+    let no_changes = ListingChanges::default();
+    let changes = app
+        .current_vault
+        .as_ref()
+        .filter(|_| app.watch_next.is_some())
+        .and_then(|(name, _)| app.changes.get(name))
+        .unwrap_or(&no_changes);
+    // The rest are colored by their cached metadata: expiring soon (see `expiry_color`) or
+    // disabled (dimmed)
+    let vault = app.current_vault.as_ref().map(|(name, _)| name.as_str());
+    let now = OffsetDateTime::now_utc();
+    // The marker goes in front of the name (indented in the tree), wherever the name column is
+    let row = |marker: &str, label: &str, name: &str, meta: Option<&SecretMeta>| {
+        Row::new(app.columns.iter().map(|c| match c {
+            Column::Name => format!("{} {}", marker, label),
+            c => c.text(name, meta),
+        }))
+    };
+    let secret_row = |label: &str, s: &String| {
+        let meta = vault.and_then(|v| app.index.meta(v, s));
+        if app.marked.contains(s) {
+            row("●", label, s, meta).style(Style::default().fg(theme().marked))
+        } else if changes.added.binary_search(s).is_ok() {
+            row("+", label, s, meta).style(Style::default().fg(theme().success))
+        } else if changes.changed.binary_search(s).is_ok() {
+            row("~", label, s, meta).style(Style::default().fg(theme().warning))
+        } else {
+            let color = meta.and_then(|m| {
+                if !m.enabled {
+                    Some(theme().dim)
+                } else {
+                    expiry_color(m.expires?, now, app.expiry_window)
+                }
+            });
+            let row = row(" ", label, s, meta);
+            match color {
+                Some(color) => row.style(Style::default().fg(color)),
+                None => row,
+            }
+        }
+    };
+    let tree_row = |r: &TreeRow| match r {
+        TreeRow::Group {
+            label,
+            depth,
+            count,
+            expanded,
+            ..
+        } => {
+            let arrow = if *expanded { "▾" } else { "▸" };
+            let label = format!(
+                "{}{} {}{} ({})",
+                "  ".repeat(*depth),
+                arrow,
+                label,
+                app.group_delimiter,
+                count
+            );
+            row(" ", &label, "", None).style(Style::default().fg(theme().accent))
+        }
+        TreeRow::Secret { name, label, depth } => {
+            secret_row(&format!("{}{}", "  ".repeat(*depth), label), name)
+        }
+    };
+    // Rows start below the border and the header
+    let list_inner = chunks[1].inner(Margin::new(1, 1));
+    app.list_rows = Rect {
+        y: list_inner.y + 1,
+        height: list_inner.height.saturating_sub(1),
+        ..list_inner
+    };
+    // Only the visible rows are built, so long lists cost no more per frame than short ones.
+    // Removed secrets stay listed (below, not selectable) until the next change.
+    let shown = app.secret_rows();
+    let window = visible_rows(
+        app.selected,
+        app.list_state.offset(),
+        shown + changes.removed.len(),
+        app.list_rows.height as usize,
+    );
+    let rows: Vec<Row> = window
+        .clone()
+        .map(|i| {
+            if i >= shown {
+                let s = &changes.removed[i - shown];
+                row("✗", s, s, None).style(
+                    Style::default()
+                        .fg(theme().error)
+                        .add_modifier(Modifier::CROSSED_OUT),
+                )
+            } else if app.grouped {
+                tree_row(&app.tree[i])
+            } else {
+                secret_row(&app.displayed_secrets[i], &app.displayed_secrets[i])
+            }
+        })
         .collect();
-    let mut list_state = app.list_state.clone();
-    if app.displayed_secrets.is_empty() {
-        list_state.select(None);
+    let selected = (shown > 0).then_some(app.selected);
+    let mut list_state = TableState::default().with_selected(selected.map(|s| s - window.start));
+    let mut list_title = if app.offline {
+        "Secrets (cached, read-only)".to_string()
     } else {
-        list_state.select(Some(app.selected));
+        "Secrets".to_string()
+    };
+    if app.sort != SortKey::Name || app.sort_descending {
+        let arrow = if app.sort_descending { "↓" } else { "↑" };
+        list_title.push_str(&format!(" · by {} {}", app.sort.label(), arrow));
     }
-    let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("Secrets"))
-        .highlight_style(
+    if app.value_matches.is_some() {
+        list_title.push_str(" · value search (Esc: clear)");
+    }
+    let table = Table::new(rows, app.columns.iter().map(|c| c.width()))
+        .header(
+            Row::new(app.columns.iter().map(|c| match c {
+                Column::Name => format!("  {}", c.title()),
+                c => c.title().to_string(),
+            }))
+            .style(
+                Style::default()
+                    .fg(theme().muted)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        )
+        .block(Block::default().borders(Borders::ALL).title(list_title))
+        .row_highlight_style(
             Style::default()
-                .fg(Color::Yellow)
+                .fg(theme().highlight)
                 .add_modifier(Modifier::BOLD),
         );
-    f.render_stateful_widget(list, chunks[1], &mut list_state);
-    app.list_state = list_state;
-
-    let footer_style = Style::default().fg(Color::Cyan);
-    let footer = Paragraph::new(app.message.clone().unwrap_or_default())
-        .style(footer_style)
-        .block(Block::default().borders(Borders::ALL).title("Message"));
-    f.render_widget(footer, chunks[2]);
+    f.render_stateful_widget(table, chunks[1], &mut list_state);
+    app.list_state = TableState::default()
+        .with_offset(window.start)
+        .with_selected(selected);
 
     if app.loading {
         let throbber = Throbber::default()
             .label(" Processing...")
-            .style(Style::default().fg(Color::Yellow))
-            .throbber_set(BRAILLE_SIX)
+            .style(Style::default().fg(theme().highlight))
+            .throbber_set(throbber_set())
             .use_type(WhichUse::Spin);
-        f.render_stateful_widget(throbber, chunks[3], &mut app.throbber_state);
+        f.render_stateful_widget(throbber, chunks[2], &mut app.throbber_state);
     }
 
     if let Some(modal) = &app.modal {
         let area = f.area();
-        let area_modal = centered_rect(60, 40, area);
+        let area_modal = centered_rect(70, 60, area);
         f.render_widget(ratatui::widgets::Clear, area_modal);
 
         let block = Block::default()
             .borders(Borders::ALL)
             .title_alignment(Alignment::Center)
-            .style(Style::default().bg(Color::Black));
+            .style(Style::default().bg(theme().background));
 
         match modal {
             Modal::Add {
                 name,
                 value,
                 input_mode,
+                generator,
+                file_path,
+                encode_base64,
+                status,
             } => {
                 f.render_widget(block.title("Add Secret"), area_modal);
 
@@ -224,42 +1142,83 @@ fn draw_secrets_screen(f: &mut Frame<'_>, app: &mut App) {
                     .margin(2)
                     .constraints([
                         Constraint::Length(3), // Name label + input
-                        Constraint::Length(3), // Value label + input
-                        Constraint::Min(1),    // Help text
+                        Constraint::Min(5),    // Value text area
+                        Constraint::Length(2), // Submit status / error
+                        Constraint::Length(1), // Help text
                     ])
                     .split(area_modal);
 
                 let name_style = if *input_mode == AddInputMode::Name {
-                    Style::default().fg(Color::Yellow)
+                    Style::default().fg(theme().highlight)
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(theme().text)
                 };
                 let value_style = if *input_mode == AddInputMode::Value {
-                    Style::default().fg(Color::Yellow)
+                    Style::default().fg(theme().highlight)
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(theme().text)
                 };
 
-                let name_block = Block::default().borders(Borders::ALL).title("Name");
-                let value_block = Block::default().borders(Borders::ALL).title("Value");
+                // Live validation: show the violation in the field title once something is typed
+                let name_text = name.text();
+                let name_block = match validate_secret_name(&name_text) {
+                    Err(e) if !name_text.is_empty() => Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme().error))
+                        .title(Span::styled(
+                            format!("Name — {}", e),
+                            Style::default().fg(theme().error),
+                        )),
+                    _ => Block::default().borders(Borders::ALL).title("Name"),
+                };
+                let value_block = Block::default()
+                    .borders(Borders::ALL)
+                    .title(value_title(value, *encode_base64));
 
-                let p_name = Paragraph::new(name.as_str())
-                    .block(name_block)
-                    .style(name_style);
-                let p_value = Paragraph::new(value.as_str())
-                    .block(value_block)
-                    .style(value_style);
+                draw_text_area(
+                    f,
+                    name,
+                    name_block,
+                    name_style,
+                    chunks[0],
+                    *input_mode == AddInputMode::Name && generator.is_none(),
+                );
+                match file_path {
+                    Some(path) => draw_file_input(
+                        f,
+                        path,
+                        *encode_base64,
+                        *input_mode == AddInputMode::File,
+                        chunks[1],
+                    ),
+                    None => draw_text_area(
+                        f,
+                        value,
+                        value_block,
+                        value_style,
+                        chunks[1],
+                        *input_mode == AddInputMode::Value && generator.is_none(),
+                    ),
+                }
 
-                f.render_widget(p_name, chunks[0]);
-                f.render_widget(p_value, chunks[1]);
+                draw_submit_status(f, status, chunks[2]);
 
-                let help_text = "Tab: Switch field | Enter: Submit | Esc: Cancel";
+                let help_text = "Tab: Switch field | Ctrl+G: Generate | Ctrl+O: From file | Ctrl+V: Paste | Ctrl+R: Hide/Reveal | Ctrl+B: Base64 | Enter (name) / Ctrl+S: Submit | Esc: Cancel";
                 let p_help = Paragraph::new(help_text)
-                    .style(Style::default().fg(Color::Gray))
+                    .style(Style::default().fg(theme().muted))
                     .alignment(Alignment::Center);
-                f.render_widget(p_help, chunks[2]);
+                f.render_widget(p_help, chunks[3]);
+
+                if let Some(opts) = generator {
+                    draw_generator_popup(f, opts, area);
+                }
             }
-            Modal::Edit { name, value } => {
+            Modal::Edit {
+                name,
+                value,
+                encode_base64,
+                status,
+            } => {
                 f.render_widget(block.title("Edit Secret"), area_modal);
 
                 let chunks = Layout::default()
@@ -267,62 +1226,1171 @@ fn draw_secrets_screen(f: &mut Frame<'_>, app: &mut App) {
                     .margin(2)
                     .constraints([
                         Constraint::Length(3), // Name (read-only)
-                        Constraint::Length(3), // Value (editable)
-                        Constraint::Min(1),    // Help text
+                        Constraint::Min(5),    // Value text area (editable)
+                        Constraint::Length(2), // Submit status / error
+                        Constraint::Length(1), // Help text
                     ])
                     .split(area_modal);
 
                 let name_block = Block::default()
                     .borders(Borders::ALL)
                     .title("Name (Read-only)");
-                let value_block = Block::default().borders(Borders::ALL).title("Value");
+                let value_block = Block::default()
+                    .borders(Borders::ALL)
+                    .title(value_title(value, *encode_base64));
 
                 let p_name = Paragraph::new(name.as_str())
                     .block(name_block)
-                    .style(Style::default().fg(Color::DarkGray));
-                let p_value = Paragraph::new(value.as_str())
-                    .block(value_block)
-                    .style(Style::default().fg(Color::Yellow));
-
+                    .style(Style::default().fg(theme().dim));
                 f.render_widget(p_name, chunks[0]);
-                f.render_widget(p_value, chunks[1]);
+                draw_text_area(
+                    f,
+                    value,
+                    value_block,
+                    Style::default().fg(theme().highlight),
+                    chunks[1],
+                    true,
+                );
+
+                draw_submit_status(f, status, chunks[2]);
 
-                let help_text = "Enter: Save | Esc: Cancel";
+                let help_text = "Enter: New line | Ctrl+V: Paste | Ctrl+R: Hide/Reveal | Ctrl+B: Base64 | Ctrl+S: Save | Esc: Cancel";
                 let p_help = Paragraph::new(help_text)
-                    .style(Style::default().fg(Color::Gray))
+                    .style(Style::default().fg(theme().muted))
                     .alignment(Alignment::Center);
-                f.render_widget(p_help, chunks[2]);
+                f.render_widget(p_help, chunks[3]);
             }
-            Modal::ConfirmDelete { name } => {
+            Modal::ConfirmDelete { name, status } => {
                 let area_confirm = centered_rect(40, 20, area);
                 f.render_widget(ratatui::widgets::Clear, area_confirm);
                 let block = Block::default()
                     .borders(Borders::ALL)
                     .title("Confirm Delete")
-                    .style(Style::default().bg(Color::Red));
-                let text = format!(
+                    .style(Style::default().bg(theme().error));
+                let mut text = format!(
                     "\nAre you sure you want to delete\n'{}'?\n\n(y) Yes / (n) No",
                     name
                 );
+                if status.pending {
+                    text.push_str("\n\nDeleting...");
+                } else if let Some(err) = &status.error {
+                    text.push_str(&format!("\n\n{}", err));
+                }
                 let p = Paragraph::new(text)
                     .block(block)
                     .alignment(Alignment::Center)
+                    .wrap(Wrap { trim: true })
                     .style(
                         Style::default()
-                            .fg(Color::White)
+                            .fg(theme().text)
                             .add_modifier(Modifier::BOLD),
                     );
                 f.render_widget(p, area_confirm);
             }
-        }
-    }
-}
-
-/// Helper to center a rect
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
+            Modal::JsonViewer(viewer) => {
+                draw_json_viewer(f, viewer, area);
+            }
+            Modal::ConnString(picker) => {
+                draw_conn_string_picker(f, picker, area);
+            }
+            Modal::Changes { lines, scroll } => {
+                let popup = centered_rect(70, 70, area);
+                f.render_widget(ratatui::widgets::Clear, popup);
+                let block = Block::default()
+                    .borders(Borders::ALL)
+                    .title("Changes since the cached listings")
+                    .title_alignment(Alignment::Center)
+                    .style(Style::default().bg(theme().background));
+                let inner = block.inner(popup);
+                f.render_widget(block, popup);
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(1), Constraint::Length(1)])
+                    .split(inner);
+                let p = Paragraph::new(lines.clone()).scroll((*scroll, 0));
+                f.render_widget(p, chunks[0]);
+                let p_help =
+                    Paragraph::new("+ added | - removed | ~ updated | j/k: Scroll | Esc: Close")
+                        .style(Style::default().fg(theme().muted))
+                        .alignment(Alignment::Center);
+                f.render_widget(p_help, chunks[1]);
+            }
+            Modal::Activity {
+                name,
+                lines,
+                scroll,
+            } => {
+                let popup = centered_rect(90, 70, area);
+                f.render_widget(ratatui::widgets::Clear, popup);
+                let block = Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Activity of '{}' (last 30 days)", name))
+                    .title_alignment(Alignment::Center)
+                    .style(Style::default().bg(theme().background));
+                let inner = block.inner(popup);
+                f.render_widget(block, popup);
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(1), Constraint::Length(1)])
+                    .split(inner);
+                let p = Paragraph::new(lines.clone()).scroll((*scroll, 0));
+                f.render_widget(p, chunks[0]);
+                let p_help = Paragraph::new("j/k: Scroll | g/G: Top/Bottom | Esc: Close")
+                    .style(Style::default().fg(theme().muted))
+                    .alignment(Alignment::Center);
+                f.render_widget(p_help, chunks[1]);
+            }
+            Modal::Preview {
+                name,
+                format,
+                lines,
+                scroll,
+            } => {
+                let popup = centered_rect(80, 80, area);
+                f.render_widget(ratatui::widgets::Clear, popup);
+                let block = Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Preview: {} ({})", name, format.label()))
+                    .title_alignment(Alignment::Center)
+                    .style(Style::default().bg(theme().background));
+                let inner = block.inner(popup);
+                f.render_widget(block, popup);
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(1), Constraint::Length(1)])
+                    .split(inner);
+                let p = Paragraph::new(lines.clone()).scroll((*scroll, 0));
+                f.render_widget(p, chunks[0]);
+                let p_help = Paragraph::new(format!(
+                    "Line {}/{} | j/k: Scroll | PgUp/PgDn | g/G: Top/Bottom | Esc: Close",
+                    (*scroll as usize + 1).min(lines.len()),
+                    lines.len()
+                ))
+                .style(Style::default().fg(theme().muted))
+                .alignment(Alignment::Center);
+                f.render_widget(p_help, chunks[1]);
+            }
+            Modal::BulkDelete {
+                names,
+                statuses,
+                confirm_input,
+                started,
+                selected,
+            } => {
+                let area_bulk = centered_rect(60, 60, area);
+                f.render_widget(ratatui::widgets::Clear, area_bulk);
+                let block = Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Bulk Delete ({} secrets)", names.len()))
+                    .title_alignment(Alignment::Center)
+                    .style(Style::default().bg(theme().background));
+                f.render_widget(block, area_bulk);
+
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(2)
+                    .constraints([
+                        Constraint::Min(3),    // Secret list with statuses
+                        Constraint::Length(3), // Confirmation input
+                        Constraint::Length(1), // Help text
+                    ])
+                    .split(area_bulk);
+
+                let items: Vec<ListItem> = names
+                    .iter()
+                    .zip(statuses.iter())
+                    .map(|(n, st)| match st {
+                        BulkItemStatus::Pending => ListItem::new(format!("  {}", n))
+                            .style(Style::default().fg(theme().text)),
+                        BulkItemStatus::Done => ListItem::new(format!("✓ {}", n))
+                            .style(Style::default().fg(theme().success)),
+                        BulkItemStatus::Failed(e) => ListItem::new(format!("✗ {} — {}", n, e))
+                            .style(Style::default().fg(theme().error)),
+                    })
+                    .collect();
+                let list = List::new(items)
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title("Marked secrets"),
+                    )
+                    .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+                // Keeps the secret being deleted in view, unless scrolled elsewhere
+                let mut state = ListState::default();
+                state.select(Some(
+                    selected.unwrap_or_else(|| BulkItemStatus::progress(statuses)),
+                ));
+                f.render_stateful_widget(list, chunks[0], &mut state);
+
+                let (input_text, help_text) = if *started {
+                    let done = statuses
+                        .iter()
+                        .filter(|st| **st != BulkItemStatus::Pending)
+                        .count();
+                    (
+                        format!("Deleting... {}/{}", done, names.len()),
+                        "j/k: Scroll | Esc/Enter: Close (deletion continues in background)",
+                    )
+                } else {
+                    (
+                        format!("{}_", confirm_input),
+                        "Type 'yes' and press Enter to delete | ↑/↓: Scroll | Esc: Cancel",
+                    )
+                };
+                let p_input = Paragraph::new(input_text)
+                    .block(Block::default().borders(Borders::ALL).title("Confirm"))
+                    .style(
+                        Style::default()
+                            .fg(theme().error)
+                            .add_modifier(Modifier::BOLD),
+                    );
+                f.render_widget(p_input, chunks[1]);
+
+                let p_help = Paragraph::new(help_text)
+                    .style(Style::default().fg(theme().muted))
+                    .alignment(Alignment::Center);
+                f.render_widget(p_help, chunks[2]);
+            }
+            Modal::SignIn(state) => draw_sign_in(f, state, area_modal),
+            Modal::TenantPicker { tenants, selected } => {
+                draw_tenant_picker(f, tenants, *selected, app.tenant.as_deref(), area)
+            }
+            Modal::Export { vault, names, path } => {
+                draw_export(f, vault.as_deref(), names.as_deref(), path, area)
+            }
+            Modal::ValueSearch {
+                needle,
+                names,
+                confirm,
+            } => draw_value_search(f, needle, names.len(), *confirm, area),
+            Modal::Crypto(form) => draw_crypto(f, form, centered_rect(70, 60, area)),
+            Modal::Rotation(form) => draw_rotation(f, form, area),
+            Modal::VaultInfo(vault) => draw_vault_info(f, vault, area),
+            Modal::Tasks { selected } => draw_tasks(f, app, *selected, area),
+            Modal::ErrorDetails { failure, scroll } => {
+                draw_error_details(f, failure, *scroll, area)
+            }
+            Modal::Help { .. } => {}
+            Modal::VaultSwitcher {
+                query,
+                matches,
+                selected,
+                copy,
+            } => draw_vault_switcher(f, app, query, matches, *selected, copy.as_deref(), area),
+        }
+    }
+}
+
+/// Key bindings (`?`), the current screen's first.
+fn draw_help(f: &mut Frame<'_>, lines: &[Line<'static>], scroll: u16, area: Rect) {
+    let popup = centered_rect(80, 80, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Help")
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    let p = Paragraph::new(lines.to_vec()).scroll((scroll, 0));
+    f.render_widget(p, chunks[0]);
+    let p_help = Paragraph::new("j/k: Scroll | PgUp/PgDn | g/G: Top/Bottom | Esc: Close")
+        .style(Style::default().fg(theme().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(p_help, chunks[1]);
+}
+
+/// Device-code sign-in dialog, shown on whichever screen discovery failed.
+fn draw_sign_in(f: &mut Frame<'_>, state: &SignInState, area: Rect) {
+    f.render_widget(ratatui::widgets::Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Sign In")
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(inner);
+
+    let (body, help) = match state {
+        SignInState::Offer { reason } => (
+            vec![
+                Line::from("No Azure CLI or Azure Developer CLI login was found:"),
+                Line::from(""),
+                Line::styled(reason.clone(), Style::default().fg(theme().error)),
+                Line::from(""),
+                Line::from("Sign in with a device code in your browser instead?"),
+            ],
+            "Enter/y: Sign in | Esc: Cancel",
+        ),
+        SignInState::Requesting => (
+            vec![Line::from("Requesting a sign-in code...")],
+            "Esc: Cancel",
+        ),
+        SignInState::Waiting(code) => (
+            vec![
+                Line::from("To sign in, open"),
+                Line::from(""),
+                Line::styled(
+                    code.verification_uri.clone(),
+                    Style::default().fg(theme().accent),
+                ),
+                Line::from(""),
+                Line::from("and enter the code"),
+                Line::from(""),
+                Line::styled(
+                    code.user_code.clone(),
+                    Style::default()
+                        .fg(theme().highlight)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Line::from(""),
+                Line::styled(
+                    format!(
+                        "Waiting for sign-in (code expires in {} min)...",
+                        code.expires_in / 60
+                    ),
+                    Style::default().fg(theme().muted),
+                ),
+            ],
+            "c: Copy code | Esc: Cancel",
+        ),
+        SignInState::Failed(e) => (
+            vec![Line::styled(e.clone(), Style::default().fg(theme().error))],
+            "Enter: Try again | Esc: Close",
+        ),
+    };
+    let p_body = Paragraph::new(body)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(p_body, chunks[0]);
+
+    let p_help = Paragraph::new(help)
+        .style(Style::default().fg(theme().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(p_help, chunks[1]);
+}
+
+/// Count of running background tasks, shown while there are any (`T` lists them).
+fn tasks_badge(app: &App) -> Option<Line<'static>> {
+    let running = app.tasks.running();
+    (running > 0).then(|| {
+        Line::from(Span::styled(
+            format!(" ⟳ {} task(s) · T ", running),
+            Style::default().fg(theme().highlight),
+        ))
+        .right_aligned()
+    })
+}
+
+/// Title badge marking cached, read-only data while offline.
+fn offline_badge() -> Line<'static> {
+    Line::from(Span::styled(
+        " ⚠ OFFLINE · cached data ",
+        Style::default()
+            .fg(theme().background)
+            .bg(theme().warning)
+            .add_modifier(Modifier::BOLD),
+    ))
+    .right_aligned()
+}
+
+/// Ctrl+P vault switcher: a filter line over the fuzzy-ranked vaults.
+fn draw_vault_switcher(
+    f: &mut Frame<'_>,
+    app: &App,
+    query: &str,
+    matches: &[Vault],
+    selected: usize,
+    copy: Option<&[String]>,
+    area: Rect,
+) {
+    let area = centered_rect(60, 50, area);
+    f.render_widget(ratatui::widgets::Clear, area);
+    let title = match copy {
+        Some([name]) => format!("Copy '{}' to Vault", name),
+        Some(names) => format!("Copy {} Secrets to Vault", names.len()),
+        None => "Switch Vault".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let p_query = Paragraph::new(format!("{}_", query))
+        .block(Block::default().borders(Borders::ALL).title("Filter"))
+        .style(Style::default().fg(theme().highlight));
+    f.render_widget(p_query, chunks[0]);
+
+    let current = app.current_vault.as_ref().map(|(name, _)| name.as_str());
+    let items: Vec<ListItem> = if matches.is_empty() {
+        vec![ListItem::new("No matching vaults...")]
+    } else {
+        matches
+            .iter()
+            .map(|v| {
+                let marker = if Some(v.name.as_str()) == current {
+                    "● "
+                } else {
+                    "  "
+                };
+                let mut spans = vec![Span::raw(format!("{}{}", marker, app.vault_label(&v.name)))];
+                if v.alias.is_some() {
+                    spans.push(Span::styled(
+                        format!(" ({})", v.name),
+                        Style::default().fg(theme().dim),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
+    };
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .fg(theme().accent)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut state = ListState::default();
+    state.select((!matches.is_empty()).then_some(selected));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+
+    let help = if copy.is_some() {
+        "Type to filter | ↑/↓: Move | Enter: Copy (new versions where the name exists) | Esc: Cancel"
+    } else {
+        "Type to filter | ↑/↓: Move | Enter: Open | Esc: Cancel"
+    };
+    let p_help = Paragraph::new(help)
+        .style(Style::default().fg(theme().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(p_help, chunks[2]);
+}
+
+/// Vault properties popup: SKU, soft delete, purge protection, access model, network ACLs.
+fn draw_vault_info(f: &mut Frame<'_>, vault: &Vault, area: Rect) {
+    let popup = centered_rect(70, 60, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Vault '{}'", vault.name))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+
+    let label =
+        |text: &str| Span::styled(format!("{:<22}", text), Style::default().fg(theme().muted));
+    let row = |name: &str, value: String| Line::from(vec![label(name), Span::raw(value)]);
+    let or_dash = |value: &Option<String>| value.clone().unwrap_or_else(|| "—".into());
+    let mut lines = vec![
+        row("URI", vault.uri.clone()),
+        row("Subscription", or_dash(&vault.subscription)),
+        row("Resource group", or_dash(&vault.resource_group)),
+        row("Location", or_dash(&vault.location)),
+        Line::from(""),
+    ];
+    match &vault.properties {
+        None => lines.push(Line::from(Span::styled(
+            "Properties unavailable: the vault was not discovered through Azure Resource Manager",
+            Style::default().fg(theme().warning),
+        ))),
+        Some(props) => {
+            let on_off = |on: bool| if on { "on" } else { "off" };
+            let soft_delete = match (props.soft_delete, props.retention_days) {
+                (true, Some(days)) => format!("on, kept for {} days", days),
+                (on, _) => on_off(on).to_string(),
+            };
+            let purge = if props.purge_protection {
+                Span::styled(
+                    "on (deleted items cannot be purged before retention ends)",
+                    Style::default().fg(theme().success),
+                )
+            } else {
+                Span::styled(
+                    "off (deleted items can be purged for good)",
+                    Style::default().fg(theme().warning),
+                )
+            };
+            let network = match (&props.default_action, &props.bypass) {
+                (Some(action), Some(bypass)) => format!("default {}, bypass {}", action, bypass),
+                (Some(action), None) => format!("default {}", action),
+                (None, _) => "none".to_string(),
+            };
+            let list = |items: &[String]| {
+                if items.is_empty() {
+                    "—".to_string()
+                } else {
+                    items.join(", ")
+                }
+            };
+            lines.extend([
+                row("SKU", or_dash(&props.sku)),
+                row("Soft delete", soft_delete),
+                Line::from(vec![label("Purge protection"), purge]),
+                row(
+                    "Permission model",
+                    if props.rbac {
+                        "Azure RBAC".into()
+                    } else {
+                        "Access policies".into()
+                    },
+                ),
+                row("Public network access", or_dash(&props.public_access)),
+                row("Network ACLs", network),
+                row("IP rules", list(&props.ip_rules)),
+                row("Subnets", list(&props.subnets)),
+            ]);
+        }
+    }
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), chunks[0]);
+    let help = Paragraph::new("Esc: close")
+        .style(Style::default().fg(theme().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(help, chunks[1]);
+}
+
+/// Tenant picker; the active tenant is marked with a dot.
+/// The last failed operation: what was attempted, the toast summary and the whole error chain.
+fn draw_error_details(f: &mut Frame<'_>, failure: &Failure, scroll: u16, area: Rect) {
+    let popup = centered_rect(80, 60, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Error Details")
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+
+    let gray = Style::default().fg(theme().muted);
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Operation: ", gray),
+            Span::styled(
+                failure.operation.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(Span::styled(
+            failure.message.clone(),
+            Style::default().fg(theme().warning),
+        )),
+        Line::from(""),
+    ];
+    for (i, e) in failure.chain.iter().enumerate() {
+        let (prefix, style) = if i == 0 {
+            ("Error: ", Style::default().fg(theme().error))
+        } else {
+            ("Caused by: ", Style::default().fg(theme().text))
+        };
+        lines.push(Line::from(vec![
+            Span::styled(prefix, gray),
+            Span::styled(e.clone(), style),
+        ]));
+    }
+    let p = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0));
+    f.render_widget(p, chunks[0]);
+
+    let help = if failure.retry.is_some() {
+        "r: Retry | c: Copy details | j/k: Scroll | Esc: Close"
+    } else {
+        "c: Copy details | j/k: Scroll | Esc: Close"
+    };
+    let p_help = Paragraph::new(help)
+        .style(gray)
+        .alignment(Alignment::Center);
+    f.render_widget(p_help, chunks[1]);
+}
+
+/// Background operations: running ones first, with how long they have been going.
+fn draw_tasks(f: &mut Frame<'_>, app: &App, selected: usize, area: Rect) {
+    let area = centered_rect(70, 60, area);
+    f.render_widget(ratatui::widgets::Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "Background Tasks ({} running)",
+            app.tasks.running()
+        ))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+
+    let now = Instant::now();
+    let tasks = app.tasks.list();
+    if tasks.is_empty() {
+        f.render_widget(
+            Paragraph::new("No background tasks").style(Style::default().fg(theme().muted)),
+            chunks[0],
+        );
+    }
+    let rows: Vec<Row> = tasks
+        .iter()
+        .map(|t| {
+            let (status, color) = match t.status {
+                TaskStatus::Running => ("⟳ running", theme().highlight),
+                TaskStatus::Done => ("✓ done", theme().success),
+                TaskStatus::Cancelled => ("✗ cancelled", theme().muted),
+            };
+            Row::new([
+                Cell::from(Span::styled(status, Style::default().fg(color))),
+                Cell::from(t.label.clone()),
+                Cell::from(format_age(t.elapsed(now))),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(12),
+            Constraint::Min(20),
+            Constraint::Length(6),
+        ],
+    )
+    .header(Row::new(["Status", "Task", "Time"]).style(Style::default().fg(theme().muted)))
+    .row_highlight_style(
+        Style::default()
+            .fg(theme().accent)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut state = TableState::default();
+    state.select((!tasks.is_empty()).then_some(selected.min(tasks.len() - 1)));
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    let p_help = Paragraph::new("j/k: Move | c: Cancel task | Esc: Close")
+        .style(Style::default().fg(theme().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(p_help, chunks[1]);
+}
+
+fn draw_tenant_picker(
+    f: &mut Frame<'_>,
+    tenants: &[Tenant],
+    selected: usize,
+    current: Option<&str>,
+    area: Rect,
+) {
+    let area = centered_rect(60, 50, area);
+    f.render_widget(ratatui::widgets::Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Switch Tenant")
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+
+    let items: Vec<ListItem> = tenants
+        .iter()
+        .map(|t| {
+            let marker = if Some(t.id.as_str()) == current {
+                "● "
+            } else {
+                "  "
+            };
+            let detail = t.domain.as_deref().unwrap_or(&t.id);
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{}{}", marker, t.name)),
+                Span::styled(format!("  {}", detail), Style::default().fg(theme().muted)),
+            ]))
+        })
+        .collect();
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .fg(theme().accent)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut state = ListState::default();
+    state.select(Some(selected));
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let p_help = Paragraph::new("j/k: Move | Enter: Switch | Esc: Cancel")
+        .style(Style::default().fg(theme().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(p_help, chunks[1]);
+}
+
+fn value_title(value: &TextArea, encode_base64: bool) -> String {
+    let mut title = String::from("Value");
+    if value.is_masked() {
+        title.push_str(" (hidden — Ctrl+R to reveal)");
+    }
+    if encode_base64 {
+        title.push_str(" [base64-encoded on save]");
+    }
+    title
+}
+
+/// Path input for uploading a file as the secret value, with a summary of what will be stored.
+fn draw_file_input(
+    f: &mut Frame<'_>,
+    path: &TextArea,
+    encode_base64: bool,
+    focused: bool,
+    area: Rect,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(area);
+    let style = if focused {
+        Style::default().fg(theme().highlight)
+    } else {
+        Style::default().fg(theme().text)
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("File path (Ctrl+O: type a value instead)");
+    draw_text_area(f, path, block, style, chunks[0], focused);
+
+    let path_text = path.text();
+    let info = if path_text.trim().is_empty() {
+        Span::styled(
+            "Enter the path of a certificate, kubeconfig, license file, ...",
+            Style::default().fg(theme().dim),
+        )
+    } else {
+        // Only stat the file here; it is read once on submit
+        let path = expand_path(&path_text);
+        match std::fs::metadata(&path) {
+            Ok(meta) if meta.is_file() => Span::styled(
+                format!(
+                    "{} bytes{}",
+                    meta.len(),
+                    if encode_base64 {
+                        " — will be stored base64-encoded"
+                    } else {
+                        " — binary content is stored base64-encoded"
+                    }
+                ),
+                Style::default().fg(theme().success),
+            ),
+            Ok(_) => Span::styled("Not a regular file", Style::default().fg(theme().error)),
+            Err(e) => Span::styled(e.to_string(), Style::default().fg(theme().error)),
+        }
+    };
+    f.render_widget(Paragraph::new(info), chunks[1]);
+}
+
+/// Drill-down tree for JSON secret values
+fn draw_json_viewer(f: &mut Frame<'_>, viewer: &JsonViewer, area: Rect) {
+    let popup = centered_rect(70, 70, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("JSON: {}", viewer.secret))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(inner);
+
+    let items: Vec<ListItem> = viewer
+        .rows()
+        .into_iter()
+        .map(|row| {
+            let indent = "  ".repeat(row.depth);
+            let marker = match (row.is_container, row.expanded) {
+                (true, true) => "▾ ",
+                (true, false) => "▸ ",
+                (false, _) => "  ",
+            };
+            let text = if row.expanded {
+                format!("{}{}{}", indent, marker, row.key)
+            } else {
+                format!("{}{}{}: {}", indent, marker, row.key, row.summary)
+            };
+            let style = if row.is_container {
+                Style::default().fg(theme().accent)
+            } else {
+                Style::default().fg(theme().text)
+            };
+            ListItem::new(text).style(style)
+        })
+        .collect();
+    let mut state = ListState::default();
+    state.select(Some(viewer.selected));
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .fg(theme().highlight)
+            .add_modifier(Modifier::BOLD),
+    );
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let p_help =
+        Paragraph::new("j/k: Move | l/→: Expand | h/←: Collapse | Enter/y: Copy | Esc: Close")
+            .style(Style::default().fg(theme().muted))
+            .alignment(Alignment::Center);
+    f.render_widget(p_help, chunks[1]);
+}
+
+/// Component picker for connection-string secrets; sensitive values stay masked
+fn draw_conn_string_picker(f: &mut Frame<'_>, picker: &ConnStringPicker, area: Rect) {
+    let popup = centered_rect(70, 50, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "{} connection string: {}",
+            picker.kind.label(),
+            picker.secret
+        ))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(inner);
+
+    let key_width = picker
+        .parts
+        .iter()
+        .map(|p| p.key.chars().count())
+        .max()
+        .unwrap_or(0);
+    let items: Vec<ListItem> = picker
+        .parts
+        .iter()
+        .map(|part| {
+            let value = if part.is_sensitive() {
+                "•".repeat(part.value.chars().count().min(16))
+            } else {
+                part.value.clone()
+            };
+            let style = if part.derived {
+                Style::default().fg(theme().accent)
+            } else {
+                Style::default().fg(theme().text)
+            };
+            ListItem::new(format!(
+                "{:<width$}  {}",
+                part.key,
+                value,
+                width = key_width
+            ))
+            .style(style)
+        })
+        .collect();
+    let mut state = ListState::default();
+    state.select(Some(picker.selected));
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .fg(theme().highlight)
+            .add_modifier(Modifier::BOLD),
+    );
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let p_help = Paragraph::new("j/k: Move | Enter/y: Copy component | Esc: Close")
+        .style(Style::default().fg(theme().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(p_help, chunks[1]);
+}
+
+/// In-flight indicator or inline error for a modal submit
+fn draw_submit_status(f: &mut Frame<'_>, status: &SubmitState, area: Rect) {
+    let p = if status.pending {
+        Paragraph::new("Saving...").style(Style::default().fg(theme().highlight))
+    } else if let Some(err) = &status.error {
+        Paragraph::new(err.as_str())
+            .style(Style::default().fg(theme().error))
+            .wrap(Wrap { trim: true })
+    } else {
+        return;
+    };
+    f.render_widget(p, area);
+}
+
+/// File path prompt for the metadata report; the format follows the extension.
+fn draw_value_search(
+    f: &mut Frame<'_>,
+    needle: &TextArea,
+    count: usize,
+    confirm: bool,
+    area: Rect,
+) {
+    let popup = centered_rect(60, 30, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("{}Search inside the values", icon("🔎 ")))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let field = Block::default().borders(Borders::ALL).title("Text to find");
+    draw_text_area(
+        f,
+        needle,
+        field,
+        Style::default().fg(theme().highlight),
+        chunks[0],
+        !confirm,
+    );
+    let (info, color, help) = if confirm {
+        (
+            format!(
+                "This fetches the values of {} listed secret(s), one request each. Continue?",
+                count
+            ),
+            theme().warning,
+            "y: Search | n / Esc: Cancel",
+        )
+    } else {
+        (
+            format!(
+                "Looks through the values of the {} listed secret(s), ignoring case. Narrow the list with / first to fetch fewer.",
+                count
+            ),
+            theme().muted,
+            "Enter: Continue | Esc: Cancel",
+        )
+    };
+    f.render_widget(
+        Paragraph::new(info)
+            .style(Style::default().fg(color))
+            .wrap(Wrap { trim: true }),
+        chunks[1],
+    );
+    let help = Paragraph::new(help)
+        .style(Style::default().fg(theme().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(help, chunks[2]);
+}
+
+fn draw_export(
+    f: &mut Frame<'_>,
+    vault: Option<&str>,
+    names: Option<&[String]>,
+    path: &TextArea,
+    area: Rect,
+) {
+    let popup = centered_rect(60, 30, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let scope = match (vault, names) {
+        (Some(vault), Some(names)) => format!("{} marked secrets of '{}'", names.len(), vault),
+        (Some(vault), None) => format!("'{}'", vault),
+        (None, _) => "all cached vaults".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Export metadata of {} (no values)", scope))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let field = Block::default().borders(Borders::ALL).title("File path");
+    draw_text_area(
+        f,
+        path,
+        field,
+        Style::default().fg(theme().highlight),
+        chunks[0],
+        true,
+    );
+    let format = ReportFormat::for_path(&expand_path(path.text().trim()));
+    let info = format!(
+        "Format: {} (use .json for JSON, anything else is CSV)",
+        format.name()
+    );
+    f.render_widget(
+        Paragraph::new(info).style(Style::default().fg(theme().muted)),
+        chunks[1],
+    );
+    let help = Paragraph::new("Enter: Export | Esc: Cancel")
+        .style(Style::default().fg(theme().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(help, chunks[2]);
+}
+
+/// Rotation policy editor: one duration field per lifetime action, plus the version expiry.
+fn draw_rotation(f: &mut Frame<'_>, form: &RotationForm, area: Rect) {
+    let popup = centered_rect(60, 60, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Rotation policy of '{}'", form.key))
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+    let mut constraints = vec![Constraint::Length(3); RotationForm::LABELS.len()];
+    constraints.extend([
+        Constraint::Length(1),
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ]);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(inner);
+
+    for (i, (field, label)) in form.fields.iter().zip(RotationForm::LABELS).enumerate() {
+        let focused = form.focus == i;
+        let style = if focused {
+            Style::default().fg(theme().highlight)
+        } else {
+            Style::default().fg(theme().text)
+        };
+        let block = Block::default().borders(Borders::ALL).title(label);
+        draw_text_area(f, field, block, style, chunks[i], focused);
+    }
+    let n = RotationForm::LABELS.len();
+    f.render_widget(
+        Paragraph::new("Durations like 90d, 3m or 2y; leave empty for none")
+            .style(Style::default().fg(theme().muted)),
+        chunks[n],
+    );
+    draw_submit_status(f, &form.status, chunks[n + 1]);
+    let help = Paragraph::new("Tab/↑/↓: field | Ctrl+S: save | Esc: cancel")
+        .style(Style::default().fg(theme().muted))
+        .alignment(Alignment::Center);
+    f.render_widget(help, chunks[n + 2]);
+}
+
+/// Render an editable text area inside a block, scrolled so the cursor stays visible
+fn draw_text_area(
+    f: &mut Frame<'_>,
+    ta: &TextArea,
+    block: Block<'_>,
+    style: Style,
+    area: Rect,
+    focused: bool,
+) {
+    let inner = block.inner(area);
+    let (top, left) = ta.scroll_for(inner.height, inner.width);
+    let p = Paragraph::new(ta.display_text())
+        .block(block)
+        .style(style)
+        .scroll((top, left));
+    f.render_widget(p, area);
+    if focused {
+        let (row, col) = ta.cursor();
+        f.set_cursor_position((
+            inner.x + (col as u16).saturating_sub(left),
+            inner.y + (row as u16).saturating_sub(top),
+        ));
+    }
+}
+
+/// Options popup for the random value generator (drawn over the Add modal)
+fn draw_generator_popup(f: &mut Frame<'_>, opts: &GeneratorOptions, area: Rect) {
+    let popup = centered_rect(40, 40, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+
+    let check = |on: bool| if on { "[x]" } else { "[ ]" };
+    let rows = [
+        format!("Length: < {} >", opts.length),
+        format!("{} Lowercase (a-z)", check(opts.lowercase)),
+        format!("{} Uppercase (A-Z)", check(opts.uppercase)),
+        format!("{} Digits (0-9)", check(opts.digits)),
+        format!("{} Symbols (!@#...)", check(opts.symbols)),
+    ];
+    let items: Vec<ListItem> = rows
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            let style = if i == opts.focus {
+                Style::default()
+                    .fg(theme().highlight)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme().text)
+            };
+            ListItem::new(r.clone()).style(style)
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Generate Value")
+        .title_alignment(Alignment::Center)
+        .style(Style::default().bg(theme().background));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(2)])
+        .split(inner);
+    f.render_widget(List::new(items), chunks[0]);
+    let p_help =
+        Paragraph::new("↑/↓: Select | ←/→: Length | Space: Toggle\nEnter: Generate | Esc: Back")
+            .style(Style::default().fg(theme().muted))
+            .alignment(Alignment::Center);
+    f.render_widget(p_help, chunks[1]);
+}
+
+/// Helper to center a rect
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
             Constraint::Percentage((100 - percent_y) / 2),
             Constraint::Percentage(percent_y),
             Constraint::Percentage((100 - percent_y) / 2),
@@ -338,3 +2406,138 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use ratatui::widgets::Widget;
+
+    use super::*;
+
+    fn render(app: &mut App, width: u16, height: u16) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let frame = terminal.draw(|f| draw_ui(f, app)).unwrap();
+        frame.buffer.content().iter().map(|c| c.symbol()).collect()
+    }
+
+    #[test]
+    fn test_api_overlay() {
+        let now = Instant::now();
+        let calls = [ApiCall {
+            at: Some(now - Duration::from_secs(3)),
+            method: "GET".into(),
+            host: "kv-slow.vault.azure.net".into(),
+            path: "/secrets".into(),
+            status: Some(429),
+            duration: Duration::from_millis(2400),
+            retries: 3,
+        }];
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        let frame = terminal.draw(|f| draw_api_overlay(f, &calls, now)).unwrap();
+        let screen: String = frame.buffer.content().iter().map(|c| c.symbol()).collect();
+        assert!(screen.contains("API Calls (last 1, slowest 2400 ms)"));
+        let row = screen
+            .split('│')
+            .find(|line| line.contains("kv-slow"))
+            .unwrap();
+        let cells: Vec<&str> = row.split_whitespace().collect();
+        assert_eq!(
+            cells,
+            [
+                "3s",
+                "GET",
+                "429",
+                "2400",
+                "ms",
+                "3",
+                "kv-slow.vault.azure.net/secrets"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tiny_terminal() {
+        let credential = azure_identity::DeveloperToolsCredential::new(None).unwrap();
+        let mut app = App::new(credential);
+        app.screen = AppScreen::VaultSelection;
+        render(&mut app, 1, 1);
+        for (width, height) in [(20, 2), (79, 40), (120, 10)] {
+            assert!(render(&mut app, width, height).contains("Terminal too small"));
+        }
+        assert!(render(&mut app, MIN_WIDTH, MIN_HEIGHT).contains("Select an Azure Key Vault"));
+    }
+
+    #[test]
+    fn test_bulk_delete_follows_progress() {
+        let credential = azure_identity::DeveloperToolsCredential::new(None).unwrap();
+        let mut app = App::new(credential);
+        app.screen = AppScreen::Secrets;
+        let names: Vec<String> = (0..60).map(|n| format!("tmp-{:02}", n)).collect();
+        let mut modal = Modal::bulk_delete(names);
+        if let Modal::BulkDelete {
+            statuses, started, ..
+        } = &mut modal
+        {
+            *started = true;
+            statuses[..45].fill(BulkItemStatus::Done);
+        }
+        app.modal = Some(modal);
+        let screen = render(&mut app, 120, 40);
+        assert!(screen.contains("tmp-45") && !screen.contains("tmp-00"));
+
+        // Scrolled back up, the list stays there
+        if let Some(Modal::BulkDelete { selected, .. }) = &mut app.modal {
+            *selected = Some(0);
+        }
+        let screen = render(&mut app, 120, 40);
+        assert!(screen.contains("✓ tmp-00") && !screen.contains("tmp-45"));
+    }
+
+    #[test]
+    fn test_ascii_mode() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 3));
+        Block::default()
+            .borders(Borders::ALL)
+            .render(Rect::new(0, 0, 3, 3), &mut buf);
+        buf.set_string(3, 1, "✓ä→", Style::default());
+        to_ascii(&mut buf);
+        let rows: Vec<String> = buf
+            .content
+            .chunks(6)
+            .map(|row| row.iter().map(|c| c.symbol()).collect())
+            .collect();
+        assert_eq!(rows, ["+-+   ", "| |+ä>", "+-+   "]);
+    }
+
+    #[test]
+    fn test_strip_colors() {
+        let theme = Theme::dark();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 1));
+        buf[(0, 0)].set_style(Style::default().fg(theme.background).bg(theme.accent));
+        buf[(1, 0)].set_style(Style::default().fg(theme.highlight));
+        buf[(2, 0)].set_style(Style::default().fg(theme.dim).bg(theme.background));
+        buf[(3, 0)].set_style(
+            Style::default()
+                .fg(theme.muted)
+                .add_modifier(Modifier::ITALIC),
+        );
+        strip_colors(&mut buf, &theme);
+
+        let modifiers: Vec<Modifier> = buf.content.iter().map(|c| c.modifier).collect();
+        assert_eq!(
+            modifiers,
+            [
+                Modifier::REVERSED,
+                Modifier::BOLD,
+                Modifier::DIM,
+                Modifier::ITALIC
+            ]
+        );
+        assert!(
+            buf.content
+                .iter()
+                .all(|c| c.fg == Color::Reset && c.bg == Color::Reset)
+        );
+    }
+}